@@ -0,0 +1,10 @@
+-- Your SQL goes here
+CREATE TYPE "TimeInForce_Type" AS ENUM (
+    'ImmediateOrCancel',
+    'FillOrKill',
+    'GoodTillCancelled',
+    'GoodTillDate'
+);
+
+ALTER TABLE "orders"
+    ADD COLUMN "time_in_force" "TimeInForce_Type" NOT NULL DEFAULT 'GoodTillDate';
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE "positions"
+    DROP COLUMN "stop_loss",
+    DROP COLUMN "take_profit";
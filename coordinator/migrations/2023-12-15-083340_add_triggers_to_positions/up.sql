@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE "positions"
+    ADD COLUMN "stop_loss" REAL,
+    ADD COLUMN "take_profit" REAL;
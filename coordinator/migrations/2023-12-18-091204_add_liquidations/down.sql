@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS "liquidations";
+
+-- Postgres does not allow removing enum type values. One can only re-create an enum type with fewer values and replace the references.
+-- However, there is no proper way to replace the values to be removed where they are used (i.e. referenced in `positions` and `orders` table)
+-- We opt to NOT remove enum values that were added at a later point.
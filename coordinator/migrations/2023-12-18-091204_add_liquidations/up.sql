@@ -0,0 +1,22 @@
+-- Your SQL goes here
+-- Note that the `IF NOT EXISTS` is essential because there is no `down` migration for removing these values because it is not really feasible to remove enum values!
+-- In order to allow re-running this migration we thus have to make sure to only add the values if they do not exist yet.
+ALTER TYPE "PositionState_Type"
+ADD
+    VALUE IF NOT EXISTS 'Liquidated';
+
+ALTER TYPE "OrderReason_Type"
+ADD
+    VALUE IF NOT EXISTS 'Liquidated';
+
+CREATE TABLE "liquidations"
+(
+    id                SERIAL PRIMARY KEY       NOT NULL,
+    position_id       INTEGER                  NOT NULL REFERENCES positions (id),
+    trader_pubkey     TEXT                     NOT NULL,
+    order_id          UUID                     NOT NULL REFERENCES orders (trader_order_id),
+    liquidation_price REAL                     NOT NULL,
+    index_price       REAL                     NOT NULL,
+    price_source      TEXT                     NOT NULL,
+    timestamp         timestamp WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
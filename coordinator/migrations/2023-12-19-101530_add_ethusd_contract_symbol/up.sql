@@ -0,0 +1,6 @@
+-- Your SQL goes here
+-- Note that the `IF NOT EXISTS` is essential because there is no `down` migration for removing these values because it is not really feasible to remove enum values!
+-- In order to allow re-running this migration we thus have to make sure to only add the values if they do not exist yet.
+ALTER TYPE "ContractSymbol_Type"
+ADD
+    VALUE IF NOT EXISTS 'EthUsd';
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE "candles";
+
+DROP TYPE "CandleResolution_Type";
@@ -0,0 +1,21 @@
+-- Your SQL goes here
+CREATE TYPE "CandleResolution_Type" AS ENUM (
+    'OneMinute',
+    'FiveMinutes',
+    'OneHour',
+    'OneDay'
+);
+
+CREATE TABLE "candles"
+(
+    contract_symbol "ContractSymbol_Type"    NOT NULL,
+    resolution      "CandleResolution_Type"  NOT NULL,
+    start_time      timestamp WITH TIME ZONE NOT NULL,
+    open            REAL                     NOT NULL,
+    high            REAL                     NOT NULL,
+    low             REAL                     NOT NULL,
+    close           REAL                     NOT NULL,
+    volume          REAL                     NOT NULL,
+    updated_at      timestamp WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (contract_symbol, resolution, start_time)
+);
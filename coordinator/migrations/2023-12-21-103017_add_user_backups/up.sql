@@ -0,0 +1,8 @@
+-- Your SQL goes here
+CREATE TABLE "user_backups"
+(
+    tree  TEXT  NOT NULL,
+    key   BYTEA NOT NULL,
+    value BYTEA NOT NULL,
+    PRIMARY KEY (tree, key)
+);
@@ -0,0 +1,21 @@
+-- Your SQL goes here
+CREATE TABLE "admin_tokens"
+(
+    id         SERIAL PRIMARY KEY       NOT NULL,
+    name       TEXT UNIQUE              NOT NULL,
+    token_hash TEXT UNIQUE              NOT NULL,
+    scopes     TEXT[]                   NOT NULL,
+    created_at timestamp WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    revoked_at timestamp WITH TIME ZONE
+);
+
+CREATE TABLE "admin_audit_log"
+(
+    id          SERIAL PRIMARY KEY       NOT NULL,
+    token_name  TEXT                     NOT NULL,
+    scope       TEXT                     NOT NULL,
+    method      TEXT                     NOT NULL,
+    path        TEXT                     NOT NULL,
+    status_code INTEGER                  NOT NULL,
+    timestamp   timestamp WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+CREATE TABLE "backup_versions"
+(
+    node_id   TEXT                     NOT NULL,
+    version   BIGINT                   NOT NULL,
+    key       TEXT                     NOT NULL,
+    timestamp timestamp WITH TIME ZONE NOT NULL,
+    deleted   BOOLEAN                  NOT NULL,
+    pruned    BOOLEAN                  NOT NULL DEFAULT false,
+    PRIMARY KEY (node_id, version)
+);
+
+CREATE INDEX backup_versions_node_id_key ON backup_versions (node_id, key, version);
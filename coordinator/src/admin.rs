@@ -1,6 +1,8 @@
 use crate::collaborative_revert;
 use crate::db;
+use crate::db::admin::AuditEntry;
 use crate::parse_channel_id;
+use crate::position::models::Liquidation;
 use crate::routes::AppState;
 use crate::AppError;
 use anyhow::Context;
@@ -28,6 +30,7 @@ use std::sync::Arc;
 use time::OffsetDateTime;
 use tokio::task::spawn_blocking;
 use tracing::instrument;
+use uuid::Uuid;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Balance {
@@ -403,3 +406,75 @@ pub async fn is_connected(
     })?;
     Ok(Json(state.node.is_connected(&target)))
 }
+
+#[derive(Serialize)]
+pub struct LiquidationDetails {
+    pub position_id: i32,
+    pub trader_pubkey: PublicKey,
+    pub order_id: Uuid,
+    pub liquidation_price: f32,
+    pub index_price: f32,
+    pub price_source: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+}
+
+impl From<Liquidation> for LiquidationDetails {
+    fn from(value: Liquidation) -> Self {
+        LiquidationDetails {
+            position_id: value.position_id,
+            trader_pubkey: value.trader_pubkey,
+            order_id: value.order_id,
+            liquidation_price: value.liquidation_price,
+            index_price: value.index_price,
+            price_source: value.price_source,
+            timestamp: value.timestamp,
+        }
+    }
+}
+
+#[instrument(skip_all, err(Debug))]
+pub async fn list_liquidations(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<LiquidationDetails>>, AppError> {
+    let mut conn =
+        state.pool.clone().get().map_err(|e| {
+            AppError::InternalServerError(format!("Failed to acquire db lock: {e:#}"))
+        })?;
+
+    let liquidations = db::liquidations::get_all(&mut conn)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to load liquidations: {e:#}")))?
+        .into_iter()
+        .map(LiquidationDetails::from)
+        .collect();
+
+    Ok(Json(liquidations))
+}
+
+#[derive(Deserialize)]
+pub struct AuditLogParams {
+    /// The maximum number of entries to return, capped at [`MAX_AUDIT_LOG_ENTRIES`].
+    limit: Option<i64>,
+}
+
+const MAX_AUDIT_LOG_ENTRIES: i64 = 1_000;
+
+#[instrument(skip_all, err(Debug))]
+pub async fn list_audit_log(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<AuditLogParams>,
+) -> Result<Json<Vec<AuditEntry>>, AppError> {
+    let mut conn =
+        state.pool.clone().get().map_err(|e| {
+            AppError::InternalServerError(format!("Failed to acquire db lock: {e:#}"))
+        })?;
+
+    let limit = params
+        .limit
+        .unwrap_or(MAX_AUDIT_LOG_ENTRIES)
+        .clamp(0, MAX_AUDIT_LOG_ENTRIES);
+    let entries = db::admin::get_audit_log(&mut conn, limit)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to load audit log: {e:#}")))?;
+
+    Ok(Json(entries))
+}
@@ -1,55 +1,766 @@
+use crate::backup::filesystem_store::FilesystemStore;
+use crate::backup::postgres_store::PostgresStore;
+use crate::backup::s3_store::S3Config;
+use crate::backup::s3_store::S3Store;
+use crate::backup::sled_store::SledStore;
+use crate::db;
+use anyhow::ensure;
+use anyhow::Context;
 use anyhow::Result;
+use async_trait::async_trait;
 use bitcoin::secp256k1::PublicKey;
 use commons::Backup;
+use commons::BackupVersion;
 use commons::DeleteBackup;
 use commons::Restore;
-use sled::Db;
+use commons::RestoreParams;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::PgConnection;
+use diesel::QueryResult;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::RwLock;
+use time::Duration;
+use time::OffsetDateTime;
+use tokio::sync::Mutex;
+use tokio::task::spawn_blocking;
 
-const BACKUPS_DIRECTORY: &str = "user_backups";
+pub mod filesystem_store;
+pub mod postgres_store;
+pub mod s3_store;
+pub mod sled_store;
 
-/// Holds the user backups in a sled database
+/// A key-value store holding the user backups.
 ///
-/// TODO(holzeis): This is fine for now, once we grow we should consider moving that into a dedicate
-/// KV database, potentially to a managed service.
-pub struct SledBackup {
-    db: Db,
+/// Entries are grouped into trees, e.g. a tree per user. Keys and values are arbitrary bytes.
+#[async_trait]
+pub trait BackupStore: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Returns the names of all trees.
+    async fn trees(&self) -> Result<Vec<String>>;
+
+    /// Returns all entries of the tree, ordered by key. A tree which does not exist is empty.
+    async fn entries(&self, tree: &str) -> Result<Vec<(Vec<u8>, Vec<u8>)>>;
+
+    /// Returns the value of the entry with the given key, if it exists.
+    async fn get(&self, tree: &str, key: &[u8]) -> Result<Option<Vec<u8>>>;
+
+    /// Inserts the entry, replacing the value of an existing entry with the same key.
+    async fn insert(&self, tree: &str, key: &[u8], value: &[u8]) -> Result<()>;
+
+    /// Removes the entry with the given key, if it exists.
+    async fn remove(&self, tree: &str, key: &[u8]) -> Result<()>;
 }
 
-impl SledBackup {
-    pub fn new(data_dir: String) -> Self {
-        SledBackup {
-            db: sled::open(format!("{data_dir}/{BACKUPS_DIRECTORY}")).expect("valid path"),
+/// The storage backend of the user backups.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Default, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupStoreKind {
+    /// A sled database in the data directory.
+    #[default]
+    Sled,
+    /// Plain files in the data directory.
+    Filesystem,
+    /// The coordinator database.
+    Postgres,
+    /// An S3-compatible object storage.
+    S3,
+}
+
+/// Creates the [`BackupStore`] for the given [`BackupStoreKind`].
+///
+/// The S3 config is only required by the [`BackupStoreKind::S3`] store.
+pub fn new_backup_store(
+    kind: BackupStoreKind,
+    data_dir: &Path,
+    pool: Pool<ConnectionManager<PgConnection>>,
+    s3_config: Option<S3Config>,
+) -> Result<Box<dyn BackupStore>> {
+    let store: Box<dyn BackupStore> = match kind {
+        BackupStoreKind::Sled => Box::new(SledStore::new(data_dir)?),
+        BackupStoreKind::Filesystem => Box::new(FilesystemStore::new(data_dir)),
+        BackupStoreKind::Postgres => Box::new(PostgresStore::new(pool)),
+        BackupStoreKind::S3 => {
+            let config = s3_config.context("Missing configuration of S3 backup store")?;
+            Box::new(S3Store::new(config))
         }
+    };
+
+    Ok(store)
+}
+
+/// Copies all trees of the user backups from one store to another, including all versions.
+///
+/// Entries which already exist in the target store are overwritten. Returns the number of copied
+/// entries.
+pub async fn copy_backups(from: &dyn BackupStore, to: &dyn BackupStore) -> Result<usize> {
+    let mut copied = 0;
+    for tree in from.trees().await? {
+        let entries = from.entries(&tree).await?;
+        for (key, value) in entries.iter() {
+            to.insert(&tree, key, value).await?;
+        }
+
+        tracing::debug!(%tree, entries = entries.len(), "Copied backup tree");
+        copied += entries.len();
     }
 
-    pub fn restore(&self, node_id: PublicKey) -> Result<Vec<Restore>> {
-        tracing::debug!(%node_id, "Restoring backup");
-        let tree = self.db.open_tree(node_id.to_string())?;
+    tracing::info!(
+        from = from.name(),
+        to = to.name(),
+        copied,
+        "Copied user backups"
+    );
+
+    Ok(copied)
+}
 
-        let mut backup = vec![];
-        for entry in tree.into_iter() {
-            let entry = entry?;
-            let key = String::from_utf8(entry.0.to_vec())?;
-            let value = entry.1.to_vec();
-            backup.push(Restore { key, value });
+/// Defines which versions of a backed up key are kept.
+///
+/// The latest version of every key is always kept.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub struct BackupRetention {
+    /// The number of versions to keep per key, including the latest one.
+    pub max_versions: usize,
+    /// Versions older than this are removed.
+    pub max_age_days: Option<u64>,
+}
+
+impl Default for BackupRetention {
+    fn default() -> Self {
+        Self {
+            max_versions: 10,
+            max_age_days: None,
+        }
+    }
+}
+
+/// Holds the user backups in a [`BackupStore`].
+///
+/// The latest value of every key is stored in a tree per user. In addition, every backup is
+/// stored as a new version in a separate tree per user, so that older versions can be restored.
+/// The versions are indexed in the coordinator database, so that they can be listed and pruned
+/// without reading their values from the store.
+pub struct UserBackup {
+    store: Box<dyn BackupStore>,
+    pool: Pool<ConnectionManager<PgConnection>>,
+    retention: RwLock<BackupRetention>,
+    /// Serializes the writes of every user, as the number of a new version is derived from the
+    /// latest version of the user.
+    write_locks: std::sync::Mutex<HashMap<PublicKey, Arc<Mutex<()>>>>,
+}
+
+impl UserBackup {
+    pub fn new(
+        store: Box<dyn BackupStore>,
+        pool: Pool<ConnectionManager<PgConnection>>,
+        retention: BackupRetention,
+    ) -> Self {
+        tracing::info!(store = store.name(), "Storing user backups");
+        UserBackup {
+            store,
+            pool,
+            retention: RwLock::new(retention),
+            write_locks: std::sync::Mutex::new(HashMap::new()),
         }
+    }
 
-        Ok(backup)
+    pub fn update_retention(&self, retention: BackupRetention) {
+        tracing::info!(?retention, "Updating backup retention");
+        *self.retention.write().expect("RwLock to not be poisoned") = retention;
+    }
+
+    /// Restores the latest backup, or the backup as of the version or point in time given in
+    /// `params`.
+    ///
+    /// Fails if the version of a key to restore has been pruned, rather than restoring an
+    /// incomplete backup.
+    pub async fn restore(&self, node_id: PublicKey, params: RestoreParams) -> Result<Vec<Restore>> {
+        tracing::debug!(%node_id, ?params, "Restoring backup");
+
+        let mut backup = BTreeMap::new();
+        for (key, value) in self.store.entries(&node_id.to_string()).await? {
+            let key = String::from_utf8(key)?;
+            backup.insert(key, value);
+        }
+
+        if params.version.is_none() && params.at.is_none() {
+            return Ok(backup
+                .into_iter()
+                .map(|(key, value)| Restore { key, value })
+                .collect());
+        }
+
+        let versions = self
+            .run_query(move |conn| db::backup_versions::get_all(conn, &node_id.to_string()))
+            .await?;
+
+        // The versions are ordered by key and version, hence the last included version of a key
+        // wins. Keys which have been backed up before backups got versioned don't have any
+        // versions, hence we restore their latest value.
+        let mut included_versions = BTreeMap::new();
+        for version in versions {
+            backup.remove(&version.key);
+
+            let is_included = params.version.map_or(true, |v| version.version as u64 <= v)
+                && params.at.map_or(true, |at| version.timestamp <= at);
+            if is_included {
+                included_versions.insert(version.key.clone(), version);
+            }
+        }
+
+        let tree = versions_tree(node_id);
+        for (key, version) in included_versions {
+            ensure!(
+                !version.pruned,
+                "The backup of {key} as of {params:?} has been pruned"
+            );
+
+            if version.deleted {
+                continue;
+            }
+
+            let stored_version = self
+                .store
+                .get(&tree, &version_key(&key, version.version as u64))
+                .await?
+                .with_context(|| format!("Missing version {} of {key}", version.version))?;
+
+            if let Some(value) = StoredVersion::decode(&stored_version)?.value {
+                backup.insert(key, value);
+            }
+        }
+
+        Ok(backup
+            .into_iter()
+            .map(|(key, value)| Restore { key, value })
+            .collect())
+    }
+
+    /// Lists all stored versions of the user's backup, ordered by key and version.
+    pub async fn list_versions(&self, node_id: PublicKey) -> Result<Vec<BackupVersion>> {
+        let versions = self
+            .run_query(move |conn| db::backup_versions::get_all(conn, &node_id.to_string()))
+            .await?;
+
+        Ok(versions
+            .into_iter()
+            .filter(|version| !version.pruned)
+            .map(|version| BackupVersion {
+                key: version.key,
+                version: version.version as u64,
+                timestamp: version.timestamp,
+                deleted: version.deleted,
+            })
+            .collect())
     }
 
     pub async fn back_up(&self, node_id: PublicKey, backup: Backup) -> Result<()> {
         tracing::debug!(%node_id, backup.key, "Create user backup");
-        let tree = self.db.open_tree(node_id.to_string())?;
-        tree.insert(backup.key, backup.value)?;
-        tree.flush()?;
+        let write_lock = self.write_lock(node_id);
+        let _guard = write_lock.lock().await;
+        self.add_version(node_id, &backup.key, Some(backup.value.clone()))
+            .await?;
+        self.store
+            .insert(&node_id.to_string(), backup.key.as_bytes(), &backup.value)
+            .await?;
         Ok(())
     }
 
-    pub fn delete(&self, node_id: PublicKey, backup: DeleteBackup) -> Result<()> {
+    pub async fn delete(&self, node_id: PublicKey, backup: DeleteBackup) -> Result<()> {
         tracing::debug!(%node_id, key=backup.key, "Deleting user backup");
-        let tree = self.db.open_tree(node_id.to_string())?;
-        tree.remove(backup.key)?;
-        tree.flush()?;
+        let write_lock = self.write_lock(node_id);
+        let _guard = write_lock.lock().await;
+        self.add_version(node_id, &backup.key, None).await?;
+        self.store
+            .remove(&node_id.to_string(), backup.key.as_bytes())
+            .await?;
+        Ok(())
+    }
+
+    fn write_lock(&self, node_id: PublicKey) -> Arc<Mutex<()>> {
+        self.write_locks
+            .lock()
+            .expect("Mutex to not be poisoned")
+            .entry(node_id)
+            .or_default()
+            .clone()
+    }
+
+    /// Stores a new version of the key and removes the versions which are not retained anymore.
+    ///
+    /// A value of `None` marks the key as deleted. The oldest pruned version of the key stays in
+    /// the index, so that restoring the backup as of a pruned version fails.
+    async fn add_version(
+        &self,
+        node_id: PublicKey,
+        key: &str,
+        value: Option<Vec<u8>>,
+    ) -> Result<()> {
+        let tree = versions_tree(node_id);
+
+        let latest_version = self
+            .run_query(move |conn| {
+                db::backup_versions::get_latest_version(conn, &node_id.to_string())
+            })
+            .await?;
+        let version = latest_version.unwrap_or(0) + 1;
+
+        let now = OffsetDateTime::now_utc();
+        let deleted = value.is_none();
+        self.store
+            .insert(
+                &tree,
+                &version_key(key, version as u64),
+                &StoredVersion {
+                    timestamp: now,
+                    value,
+                }
+                .encode(),
+            )
+            .await?;
+
+        let key = key.to_string();
+        let key_versions = self
+            .run_query(move |conn| {
+                let node_id = node_id.to_string();
+                db::backup_versions::insert(
+                    conn,
+                    db::backup_versions::NewBackupVersion {
+                        node_id: &node_id,
+                        version,
+                        key: &key,
+                        timestamp: now,
+                        deleted,
+                    },
+                )?;
+                db::backup_versions::get_by_key(conn, &node_id, &key)
+            })
+            .await?;
+
+        let retention = *self.retention.read().expect("RwLock to not be poisoned");
+        let mut has_pruned_version = key_versions.iter().any(|version| version.pruned);
+        let retained_versions = key_versions
+            .into_iter()
+            .filter(|version| !version.pruned)
+            .collect::<Vec<_>>();
+        let latest = retained_versions.len() - 1;
+        for (i, version) in retained_versions.into_iter().enumerate() {
+            if i == latest {
+                break;
+            }
+
+            let is_outdated = match retention.max_age_days {
+                Some(max_age_days) => version.timestamp + Duration::days(max_age_days as i64) < now,
+                None => false,
+            };
+
+            if latest - i < retention.max_versions && !is_outdated {
+                continue;
+            }
+
+            self.store
+                .remove(&tree, &version_key(&version.key, version.version as u64))
+                .await?;
+
+            let mark_pruned = !has_pruned_version;
+            self.run_query(move |conn| {
+                if mark_pruned {
+                    db::backup_versions::set_pruned(conn, &version.node_id, version.version)
+                } else {
+                    db::backup_versions::delete(conn, &version.node_id, version.version)
+                }
+            })
+            .await?;
+            has_pruned_version = true;
+        }
+
         Ok(())
     }
+
+    async fn run_query<T, F>(&self, query: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut PgConnection) -> QueryResult<T> + Send + 'static,
+    {
+        let pool = self.pool.clone();
+        spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            let result = query(&mut conn)?;
+            anyhow::Ok(result)
+        })
+        .await
+        .expect("task to complete")
+    }
+}
+
+/// The tree holding all versions of the user's backup.
+fn versions_tree(node_id: PublicKey) -> String {
+    format!("{node_id}/versions")
+}
+
+/// A version of a backed up key. A value of `None` marks the key as deleted.
+struct StoredVersion {
+    timestamp: OffsetDateTime,
+    value: Option<Vec<u8>>,
+}
+
+impl StoredVersion {
+    /// Encodes the version as the timestamp in nanoseconds, a flag whether the key has been
+    /// deleted and the value.
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = self.timestamp.unix_timestamp_nanos().to_be_bytes().to_vec();
+        match &self.value {
+            Some(value) => {
+                bytes.push(0);
+                bytes.extend_from_slice(value);
+            }
+            None => bytes.push(1),
+        }
+
+        bytes
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        ensure!(bytes.len() > 16, "Stored backup version is too short");
+
+        let (timestamp, bytes) = bytes.split_at(16);
+        let timestamp = i128::from_be_bytes(timestamp.try_into().expect("16 bytes"));
+        let timestamp = OffsetDateTime::from_unix_timestamp_nanos(timestamp)
+            .context("Invalid timestamp of stored backup version")?;
+
+        let value = match bytes[0] {
+            0 => Some(bytes[1..].to_vec()),
+            _ => None,
+        };
+
+        Ok(Self { timestamp, value })
+    }
+}
+
+/// The prefix of all versions of the key.
+///
+/// The key is terminated with a zero byte, so that the versions of a key are not mixed up with
+/// the versions of other keys sharing the same prefix.
+fn version_key_prefix(key: &str) -> Vec<u8> {
+    let mut prefix = key.as_bytes().to_vec();
+    prefix.push(0);
+    prefix
+}
+
+/// The key of a version, which is the key followed by the big-endian sequence number of the
+/// version, so that the versions of a key are ordered by their sequence number.
+fn version_key(key: &str, version: u64) -> Vec<u8> {
+    let mut version_key = version_key_prefix(key);
+    version_key.extend_from_slice(&version.to_be_bytes());
+    version_key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::run_migration;
+    use bitcoin::secp256k1::Secp256k1;
+    use bitcoin::secp256k1::SecretKey;
+    use testcontainers::clients::Cli;
+    use testcontainers::images::generic::GenericImage;
+    use testcontainers::Container;
+
+    #[tokio::test]
+    async fn restore_latest_backup_by_default() {
+        let docker = Cli::default();
+        let (_container, pool) = start_postgres(&docker);
+        let (backup, node_id, secret_key) = setup(pool, BackupRetention::default());
+
+        backup
+            .back_up(node_id, dummy_backup(&secret_key, "foo", b"1"))
+            .await
+            .unwrap();
+        backup
+            .back_up(node_id, dummy_backup(&secret_key, "foo", b"2"))
+            .await
+            .unwrap();
+
+        let restored = backup
+            .restore(node_id, RestoreParams::default())
+            .await
+            .unwrap();
+
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].value, b"2".to_vec());
+    }
+
+    #[tokio::test]
+    async fn restore_backup_as_of_version() {
+        let docker = Cli::default();
+        let (_container, pool) = start_postgres(&docker);
+        let (backup, node_id, secret_key) = setup(pool, BackupRetention::default());
+
+        backup
+            .back_up(node_id, dummy_backup(&secret_key, "foo", b"1"))
+            .await
+            .unwrap();
+        backup
+            .back_up(node_id, dummy_backup(&secret_key, "bar", b"1"))
+            .await
+            .unwrap();
+        backup
+            .back_up(node_id, dummy_backup(&secret_key, "foo", b"2"))
+            .await
+            .unwrap();
+        backup
+            .delete(
+                node_id,
+                DeleteBackup {
+                    key: "bar".to_string(),
+                    signature: Secp256k1::new().sign_ecdsa(
+                        &commons::create_sign_message(node_id.to_string().as_bytes().to_vec()),
+                        &secret_key,
+                    ),
+                },
+            )
+            .await
+            .unwrap();
+
+        let versions = backup.list_versions(node_id).await.unwrap();
+        assert_eq!(versions.len(), 4);
+        let bar_version = versions
+            .iter()
+            .find(|v| v.key == "bar" && !v.deleted)
+            .unwrap();
+
+        let restored = backup
+            .restore(
+                node_id,
+                RestoreParams {
+                    version: Some(bar_version.version),
+                    at: None,
+                },
+            )
+            .await
+            .unwrap();
+        let restored = restored
+            .into_iter()
+            .map(|r| (r.key, r.value))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            restored,
+            vec![
+                ("bar".to_string(), b"1".to_vec()),
+                ("foo".to_string(), b"1".to_vec())
+            ]
+        );
+
+        let restored = backup
+            .restore(node_id, RestoreParams::default())
+            .await
+            .unwrap();
+        let restored = restored
+            .into_iter()
+            .map(|r| (r.key, r.value))
+            .collect::<Vec<_>>();
+        assert_eq!(restored, vec![("foo".to_string(), b"2".to_vec())]);
+    }
+
+    #[tokio::test]
+    async fn only_retained_versions_are_kept() {
+        let docker = Cli::default();
+        let (_container, pool) = start_postgres(&docker);
+        let (backup, node_id, secret_key) = setup(
+            pool,
+            BackupRetention {
+                max_versions: 2,
+                max_age_days: None,
+            },
+        );
+
+        for value in [b"1", b"2", b"3"] {
+            backup
+                .back_up(node_id, dummy_backup(&secret_key, "foo", value))
+                .await
+                .unwrap();
+        }
+
+        let versions = backup.list_versions(node_id).await.unwrap();
+        assert_eq!(versions.len(), 2);
+
+        let restored = backup
+            .restore(
+                node_id,
+                RestoreParams {
+                    version: Some(versions[0].version),
+                    at: None,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(restored[0].value, b"2".to_vec());
+    }
+
+    #[tokio::test]
+    async fn restoring_pruned_version_fails() {
+        let docker = Cli::default();
+        let (_container, pool) = start_postgres(&docker);
+        let (backup, node_id, secret_key) = setup(
+            pool,
+            BackupRetention {
+                max_versions: 1,
+                max_age_days: None,
+            },
+        );
+
+        backup
+            .back_up(node_id, dummy_backup(&secret_key, "foo", b"1"))
+            .await
+            .unwrap();
+        backup
+            .back_up(node_id, dummy_backup(&secret_key, "bar", b"1"))
+            .await
+            .unwrap();
+        backup
+            .back_up(node_id, dummy_backup(&secret_key, "foo", b"2"))
+            .await
+            .unwrap();
+
+        // The first version of foo has been pruned.
+        let result = backup
+            .restore(
+                node_id,
+                RestoreParams {
+                    version: Some(2),
+                    at: None,
+                },
+            )
+            .await;
+        assert!(result.is_err());
+
+        // Before its first version, foo did not exist.
+        let restored = backup
+            .restore(
+                node_id,
+                RestoreParams {
+                    version: Some(0),
+                    at: None,
+                },
+            )
+            .await
+            .unwrap();
+        assert!(restored.is_empty());
+    }
+
+    #[tokio::test]
+    async fn copy_backups_between_stores() {
+        let from = temporary_sled_store();
+        let to = temporary_sled_store();
+        from.insert("foo", b"a", b"1").await.unwrap();
+        from.insert("foo", b"b", b"2").await.unwrap();
+        from.insert("bar", b"a", b"3").await.unwrap();
+        to.insert("foo", b"a", b"outdated").await.unwrap();
+
+        let copied = copy_backups(&from, &to).await.unwrap();
+
+        assert_eq!(copied, 3);
+        assert_eq!(
+            to.entries("foo").await.unwrap(),
+            vec![
+                (b"a".to_vec(), b"1".to_vec()),
+                (b"b".to_vec(), b"2".to_vec())
+            ]
+        );
+        assert_eq!(
+            to.entries("bar").await.unwrap(),
+            vec![(b"a".to_vec(), b"3".to_vec())]
+        );
+    }
+
+    /// Asserts the behaviour all [`BackupStore`] implementations have to provide.
+    pub(crate) async fn assert_store_behaviour(store: &dyn BackupStore) {
+        assert!(store.trees().await.unwrap().is_empty());
+        assert!(store.entries("foo").await.unwrap().is_empty());
+
+        store.insert("foo", &[2], b"2").await.unwrap();
+        store.insert("foo", &[1, 0], b"1").await.unwrap();
+        store.insert("foo", &[1, 1], b"outdated").await.unwrap();
+        store.insert("foo", &[1, 1], b"3").await.unwrap();
+        store.insert("foo/versions", &[1], b"4").await.unwrap();
+
+        let mut trees = store.trees().await.unwrap();
+        trees.sort();
+        assert_eq!(trees, vec!["foo".to_string(), "foo/versions".to_string()]);
+
+        assert_eq!(
+            store.entries("foo").await.unwrap(),
+            vec![
+                (vec![1, 0], b"1".to_vec()),
+                (vec![1, 1], b"3".to_vec()),
+                (vec![2], b"2".to_vec()),
+            ]
+        );
+        assert_eq!(
+            store.get("foo", &[1, 1]).await.unwrap(),
+            Some(b"3".to_vec())
+        );
+        assert_eq!(store.get("foo", &[1]).await.unwrap(), None);
+
+        store.remove("foo", &[1, 0]).await.unwrap();
+        // Removing a key which does not exist is not an error.
+        store.remove("foo", &[1, 0]).await.unwrap();
+
+        assert_eq!(
+            store.entries("foo").await.unwrap(),
+            vec![(vec![1, 1], b"3".to_vec()), (vec![2], b"2".to_vec())]
+        );
+        assert_eq!(
+            store.entries("foo/versions").await.unwrap(),
+            vec![(vec![1], b"4".to_vec())]
+        );
+    }
+
+    fn temporary_sled_store() -> SledStore {
+        SledStore::from(sled::Config::new().temporary(true).open().unwrap())
+    }
+
+    /// Starts a Postgres container with the migrated coordinator database.
+    pub(crate) fn start_postgres(
+        docker: &Cli,
+    ) -> (
+        Container<GenericImage>,
+        Pool<ConnectionManager<PgConnection>>,
+    ) {
+        let (container, db_url) = crate::orderbook::tests::start_postgres(docker).unwrap();
+
+        let pool = Pool::builder()
+            .build(ConnectionManager::<PgConnection>::new(db_url))
+            .unwrap();
+        run_migration(&mut pool.get().unwrap());
+
+        (container, pool)
+    }
+
+    fn setup(
+        pool: Pool<ConnectionManager<PgConnection>>,
+        retention: BackupRetention,
+    ) -> (UserBackup, PublicKey, SecretKey) {
+        let backup = UserBackup::new(Box::new(temporary_sled_store()), pool, retention);
+
+        let secret_key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let node_id = secret_key.public_key(&Secp256k1::new());
+
+        (backup, node_id, secret_key)
+    }
+
+    fn dummy_backup(secret_key: &SecretKey, key: &str, value: &[u8]) -> Backup {
+        Backup {
+            key: key.to_string(),
+            value: value.to_vec(),
+            signature: Secp256k1::new()
+                .sign_ecdsa(&commons::create_sign_message(value.to_vec()), secret_key),
+        }
+    }
 }
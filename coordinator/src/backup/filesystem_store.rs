@@ -0,0 +1,138 @@
+use crate::backup::BackupStore;
+use anyhow::Context;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+use tokio::fs;
+
+const BACKUPS_DIRECTORY: &str = "user_backups_fs";
+
+/// Suffix of files which are being written and have not been moved to their final location yet.
+const TEMPORARY_FILE_SUFFIX: &str = ".tmp";
+
+/// Stores the user backups as plain files, using a directory per tree and a file per key.
+///
+/// Tree names and keys are hex encoded, so that they are valid file names on every file system.
+pub struct FilesystemStore {
+    root: PathBuf,
+}
+
+impl FilesystemStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            root: data_dir.join(BACKUPS_DIRECTORY),
+        }
+    }
+
+    fn tree_path(&self, tree: &str) -> PathBuf {
+        self.root.join(hex::encode(tree))
+    }
+}
+
+#[async_trait]
+impl BackupStore for FilesystemStore {
+    fn name(&self) -> &'static str {
+        "filesystem"
+    }
+
+    async fn trees(&self) -> Result<Vec<String>> {
+        let mut dir = match fs::read_dir(&self.root).await {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut trees = vec![];
+        while let Some(entry) = dir.next_entry().await? {
+            if !entry.file_type().await?.is_dir() {
+                continue;
+            }
+
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let tree = hex::decode(name.as_bytes())
+                .with_context(|| format!("Invalid backup tree directory {name}"))?;
+            trees.push(String::from_utf8(tree)?);
+        }
+
+        trees.sort();
+
+        Ok(trees)
+    }
+
+    async fn entries(&self, tree: &str) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let mut dir = match fs::read_dir(self.tree_path(tree)).await {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut entries = vec![];
+        while let Some(entry) = dir.next_entry().await? {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.ends_with(TEMPORARY_FILE_SUFFIX) {
+                continue;
+            }
+
+            let key = hex::decode(name.as_bytes())
+                .with_context(|| format!("Invalid backup file {name}"))?;
+            let value = fs::read(entry.path()).await?;
+            entries.push((key, value));
+        }
+
+        entries.sort();
+
+        Ok(entries)
+    }
+
+    async fn get(&self, tree: &str, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let path = self.tree_path(tree).join(hex::encode(key));
+        match fs::read(path).await {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn insert(&self, tree: &str, key: &[u8], value: &[u8]) -> Result<()> {
+        let tree_path = self.tree_path(tree);
+        fs::create_dir_all(&tree_path).await?;
+
+        // Write to a temporary file first, so that a crash never leaves a partially written
+        // backup behind.
+        let path = tree_path.join(hex::encode(key));
+        let temporary_path = tree_path.join(format!("{}{TEMPORARY_FILE_SUFFIX}", hex::encode(key)));
+        fs::write(&temporary_path, value).await?;
+        fs::rename(&temporary_path, &path).await?;
+
+        Ok(())
+    }
+
+    async fn remove(&self, tree: &str, key: &[u8]) -> Result<()> {
+        let path = self.tree_path(tree).join(hex::encode(key));
+        match fs::remove_file(path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backup::filesystem_store::FilesystemStore;
+    use crate::backup::tests::assert_store_behaviour;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn filesystem_store_behaves_like_a_backup_store() {
+        let data_dir = std::env::temp_dir().join(Uuid::new_v4().to_string());
+
+        assert_store_behaviour(&FilesystemStore::new(&data_dir)).await;
+
+        std::fs::remove_dir_all(data_dir).unwrap();
+    }
+}
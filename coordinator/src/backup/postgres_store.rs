@@ -0,0 +1,101 @@
+use crate::backup::BackupStore;
+use crate::db;
+use anyhow::Result;
+use async_trait::async_trait;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::PgConnection;
+use tokio::task::spawn_blocking;
+
+/// Stores the user backups in the `user_backups` table of the coordinator database.
+pub struct PostgresStore {
+    pool: Pool<ConnectionManager<PgConnection>>,
+}
+
+impl PostgresStore {
+    pub fn new(pool: Pool<ConnectionManager<PgConnection>>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl BackupStore for PostgresStore {
+    fn name(&self) -> &'static str {
+        "postgres"
+    }
+
+    async fn trees(&self) -> Result<Vec<String>> {
+        let pool = self.pool.clone();
+        spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            let trees = db::user_backups::get_trees(&mut conn)?;
+            anyhow::Ok(trees)
+        })
+        .await
+        .expect("task to complete")
+    }
+
+    async fn entries(&self, tree: &str) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let pool = self.pool.clone();
+        let tree = tree.to_string();
+        spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            let entries = db::user_backups::get_entries(&mut conn, &tree)?;
+            anyhow::Ok(entries)
+        })
+        .await
+        .expect("task to complete")
+    }
+
+    async fn get(&self, tree: &str, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let pool = self.pool.clone();
+        let (tree, key) = (tree.to_string(), key.to_vec());
+        spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            let value = db::user_backups::get(&mut conn, &tree, &key)?;
+            anyhow::Ok(value)
+        })
+        .await
+        .expect("task to complete")
+    }
+
+    async fn insert(&self, tree: &str, key: &[u8], value: &[u8]) -> Result<()> {
+        let pool = self.pool.clone();
+        let (tree, key, value) = (tree.to_string(), key.to_vec(), value.to_vec());
+        spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            db::user_backups::upsert(&mut conn, &tree, &key, &value)?;
+            anyhow::Ok(())
+        })
+        .await
+        .expect("task to complete")
+    }
+
+    async fn remove(&self, tree: &str, key: &[u8]) -> Result<()> {
+        let pool = self.pool.clone();
+        let (tree, key) = (tree.to_string(), key.to_vec());
+        spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            db::user_backups::delete(&mut conn, &tree, &key)?;
+            anyhow::Ok(())
+        })
+        .await
+        .expect("task to complete")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backup::postgres_store::PostgresStore;
+    use crate::backup::tests::assert_store_behaviour;
+    use crate::backup::tests::start_postgres;
+    use testcontainers::clients::Cli;
+
+    #[tokio::test]
+    async fn postgres_store_behaves_like_a_backup_store() {
+        let docker = Cli::default();
+        let (_container, pool) = start_postgres(&docker);
+
+        assert_store_behaviour(&PostgresStore::new(pool)).await;
+    }
+}
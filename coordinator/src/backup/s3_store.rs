@@ -0,0 +1,570 @@
+use crate::backup::BackupStore;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use async_trait::async_trait;
+use hmac::Hmac;
+use hmac::Mac;
+use reqwest::Method;
+use reqwest::Response;
+use reqwest::StatusCode;
+use reqwest::Url;
+use sha2::Digest;
+use sha2::Sha256;
+use time::macros::format_description;
+use time::OffsetDateTime;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The delimiter between the tree and the key of an object.
+const DELIMITER: &str = "/";
+
+/// The connection details of an S3-compatible object storage, e.g. AWS S3 or MinIO.
+#[derive(Clone)]
+pub struct S3Config {
+    /// The base URL of the object storage, e.g. `https://s3.eu-central-1.amazonaws.com`.
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+/// Stores the user backups in an S3-compatible bucket, using an object per key.
+///
+/// The object name is the hex encoded tree name and the hex encoded key, separated by a `/`.
+/// Requests are authenticated using AWS signature version 4 and use path-style URLs, which are
+/// supported by all S3-compatible object storages.
+pub struct S3Store {
+    client: reqwest::Client,
+    config: S3Config,
+}
+
+impl S3Store {
+    pub fn new(config: S3Config) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    fn object_path(&self, tree: &str, key: &[u8]) -> String {
+        format!(
+            "/{}/{}{DELIMITER}{}",
+            self.config.bucket,
+            hex::encode(tree),
+            hex::encode(key)
+        )
+    }
+
+    /// Lists the objects and common prefixes starting with `prefix`, following continuation
+    /// tokens until all pages have been fetched.
+    async fn list(&self, prefix: &str, delimiter: Option<&str>) -> Result<ListResult> {
+        let bucket_path = format!("/{}", self.config.bucket);
+
+        let mut result = ListResult::default();
+        let mut continuation_token = None;
+        loop {
+            let mut query = vec![
+                ("list-type", "2".to_string()),
+                ("prefix", prefix.to_string()),
+            ];
+            if let Some(delimiter) = delimiter {
+                query.push(("delimiter", delimiter.to_string()));
+            }
+            if let Some(token) = continuation_token.take() {
+                query.push(("continuation-token", token));
+            }
+
+            let response = self.send(Method::GET, &bucket_path, query, vec![]).await?;
+            let xml = ensure_success(response).await?.text().await?;
+
+            for contents in xml_values(&xml, "Contents") {
+                result
+                    .keys
+                    .extend(xml_values(contents, "Key").into_iter().map(unescape));
+            }
+            for common_prefixes in xml_values(&xml, "CommonPrefixes") {
+                result.common_prefixes.extend(
+                    xml_values(common_prefixes, "Prefix")
+                        .into_iter()
+                        .map(unescape),
+                );
+            }
+
+            let is_truncated = xml_values(&xml, "IsTruncated").first() == Some(&"true");
+            continuation_token = xml_values(&xml, "NextContinuationToken")
+                .first()
+                .map(|token| unescape(token));
+
+            if !is_truncated || continuation_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(result)
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        mut query: Vec<(&str, String)>,
+        body: Vec<u8>,
+    ) -> Result<Response> {
+        // The query parameters have to be sorted and encoded for the canonical request, hence we
+        // build the query string ourselves to send exactly what has been signed.
+        query.sort();
+        let query = query
+            .iter()
+            .map(|(name, value)| format!("{}={}", uri_encode(name), uri_encode(value)))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let mut url = format!("{}{path}", self.config.endpoint.trim_end_matches('/'));
+        if !query.is_empty() {
+            url = format!("{url}?{query}");
+        }
+        let url = Url::parse(&url).context("Invalid S3 URL")?;
+
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let now = OffsetDateTime::now_utc();
+        let signed = sign(&self.config, method.as_str(), &url, &payload_hash, now)?;
+
+        let response = self
+            .client
+            .request(method, url)
+            .header("x-amz-date", signed.amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("authorization", signed.authorization)
+            .body(body)
+            .send()
+            .await?;
+
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl BackupStore for S3Store {
+    fn name(&self) -> &'static str {
+        "s3"
+    }
+
+    async fn trees(&self) -> Result<Vec<String>> {
+        let result = self.list("", Some(DELIMITER)).await?;
+
+        let mut trees = vec![];
+        for prefix in result.common_prefixes {
+            let tree = hex::decode(prefix.trim_end_matches(DELIMITER))
+                .with_context(|| format!("Invalid backup tree prefix {prefix}"))?;
+            trees.push(String::from_utf8(tree)?);
+        }
+
+        Ok(trees)
+    }
+
+    async fn entries(&self, tree: &str) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let prefix = format!("{}{DELIMITER}", hex::encode(tree));
+        let result = self.list(&prefix, None).await?;
+
+        let mut entries = vec![];
+        for object in result.keys {
+            let key = hex::decode(&object[prefix.len()..])
+                .with_context(|| format!("Invalid backup object {object}"))?;
+
+            let response = self
+                .send(Method::GET, &self.object_path(tree, &key), vec![], vec![])
+                .await?;
+
+            // The object may have been removed since listing the bucket.
+            if response.status() == StatusCode::NOT_FOUND {
+                continue;
+            }
+
+            let value = ensure_success(response).await?.bytes().await?;
+            entries.push((key, value.to_vec()));
+        }
+
+        entries.sort();
+
+        Ok(entries)
+    }
+
+    async fn get(&self, tree: &str, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let response = self
+            .send(Method::GET, &self.object_path(tree, key), vec![], vec![])
+            .await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let value = ensure_success(response).await?.bytes().await?;
+        Ok(Some(value.to_vec()))
+    }
+
+    async fn insert(&self, tree: &str, key: &[u8], value: &[u8]) -> Result<()> {
+        let response = self
+            .send(
+                Method::PUT,
+                &self.object_path(tree, key),
+                vec![],
+                value.to_vec(),
+            )
+            .await?;
+        ensure_success(response).await?;
+
+        Ok(())
+    }
+
+    async fn remove(&self, tree: &str, key: &[u8]) -> Result<()> {
+        let response = self
+            .send(Method::DELETE, &self.object_path(tree, key), vec![], vec![])
+            .await?;
+        ensure_success(response).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct ListResult {
+    keys: Vec<String>,
+    common_prefixes: Vec<String>,
+}
+
+struct SignedRequest {
+    amz_date: String,
+    authorization: String,
+}
+
+/// Signs the request according to AWS signature version 4, signing the `host`,
+/// `x-amz-content-sha256` and `x-amz-date` headers.
+fn sign(
+    config: &S3Config,
+    method: &str,
+    url: &Url,
+    payload_hash: &str,
+    now: OffsetDateTime,
+) -> Result<SignedRequest> {
+    let amz_date = now.format(format_description!(
+        "[year][month][day]T[hour][minute][second]Z"
+    ))?;
+    let date = now.format(format_description!("[year][month][day]"))?;
+
+    let host = match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{host}:{port}"),
+        (Some(host), None) => host.to_string(),
+        (None, _) => bail!("S3 URL without host"),
+    };
+
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+        "{method}\n{}\n{}\nhost:{host}\nx-amz-content-sha256:{payload_hash}\nx-amz-date:{amz_date}\n\n{signed_headers}\n{payload_hash}",
+        url.path(),
+        url.query().unwrap_or_default(),
+    );
+
+    let scope = format!("{date}/{}/s3/aws4_request", config.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let signing_key = [config.region.as_bytes(), b"s3", b"aws4_request"]
+        .into_iter()
+        .fold(
+            hmac(
+                format!("AWS4{}", config.secret_key).as_bytes(),
+                date.as_bytes(),
+            ),
+            |key, data| hmac(&key, data),
+        );
+    let signature = hex::encode(hmac(&signing_key, string_to_sign.as_bytes()));
+
+    Ok(SignedRequest {
+        amz_date,
+        authorization: format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            config.access_key
+        ),
+    })
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC to accept keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent-encodes everything but the unreserved characters, as required by AWS signature
+/// version 4.
+fn uri_encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    encoded
+}
+
+async fn ensure_success(response: Response) -> Result<Response> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        bail!("S3 request failed with status {status}: {body}");
+    }
+
+    Ok(response)
+}
+
+/// Returns the contents of all elements with the given tag, without any nesting.
+///
+/// This is sufficient for the responses of the S3 API we rely on, which saves us from pulling in
+/// a full XML parser.
+fn xml_values<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{tag}>");
+    let close = format!("</{tag}>");
+
+    let mut values = vec![];
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        match rest.find(&close) {
+            Some(end) => {
+                values.push(&rest[..end]);
+                rest = &rest[end + close.len()..];
+            }
+            None => break,
+        }
+    }
+
+    values
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backup::s3_store::sign;
+    use crate::backup::s3_store::S3Config;
+    use crate::backup::s3_store::S3Store;
+    use crate::backup::tests::assert_store_behaviour;
+    use axum::body::Bytes;
+    use axum::extract::Path;
+    use axum::extract::Query;
+    use axum::extract::State;
+    use axum::http::HeaderMap;
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use axum::Router;
+    use reqwest::Url;
+    use std::collections::BTreeMap;
+    use std::collections::BTreeSet;
+    use std::collections::HashMap;
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use time::macros::datetime;
+
+    /// The number of objects returned per page by the stand-in, small enough to exercise
+    /// pagination.
+    const PAGE_SIZE: usize = 2;
+
+    type Objects = Arc<Mutex<BTreeMap<String, Vec<u8>>>>;
+
+    #[tokio::test]
+    async fn s3_store_behaves_like_a_backup_store() {
+        let endpoint = start_s3_stand_in();
+
+        let store = S3Store::new(S3Config {
+            endpoint,
+            bucket: "backups".to_string(),
+            region: "us-east-1".to_string(),
+            access_key: "access-key".to_string(),
+            secret_key: "secret-key".to_string(),
+        });
+
+        assert_store_behaviour(&store).await;
+    }
+
+    #[test]
+    fn signature_only_depends_on_request() {
+        let config = S3Config {
+            endpoint: "http://localhost:9000".to_string(),
+            bucket: "backups".to_string(),
+            region: "us-east-1".to_string(),
+            access_key: "access-key".to_string(),
+            secret_key: "secret-key".to_string(),
+        };
+        let url = Url::parse("http://localhost:9000/backups/00ff").unwrap();
+        let now = datetime!(2023-12-21 10:30:00 UTC);
+
+        let signed = sign(&config, "PUT", &url, "hash", now).unwrap();
+        let signed_again = sign(&config, "PUT", &url, "hash", now).unwrap();
+        let other_method = sign(&config, "GET", &url, "hash", now).unwrap();
+
+        assert_eq!(signed.amz_date, "20231221T103000Z");
+        assert!(signed.authorization.starts_with(
+            "AWS4-HMAC-SHA256 Credential=access-key/20231221/us-east-1/s3/aws4_request, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature="
+        ));
+        assert_eq!(signed.authorization, signed_again.authorization);
+        assert_ne!(signed.authorization, other_method.authorization);
+    }
+
+    /// Starts a minimal in-memory stand-in for an S3-compatible object storage, supporting the
+    /// requests used by the [`S3Store`].
+    fn start_s3_stand_in() -> String {
+        let objects = Objects::default();
+        let app = Router::new()
+            .route("/:bucket", get(list_objects))
+            .route(
+                "/:bucket/*object",
+                get(get_object).put(put_object).delete(delete_object),
+            )
+            .with_state(objects);
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+
+        format!("http://{address}")
+    }
+
+    fn is_signed(headers: &HeaderMap) -> bool {
+        headers
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .map_or(false, |value| value.starts_with("AWS4-HMAC-SHA256 "))
+            && headers.contains_key("x-amz-date")
+            && headers.contains_key("x-amz-content-sha256")
+    }
+
+    async fn list_objects(
+        Path(bucket): Path<String>,
+        Query(params): Query<HashMap<String, String>>,
+        State(objects): State<Objects>,
+        headers: HeaderMap,
+    ) -> Result<String, StatusCode> {
+        if !is_signed(&headers) {
+            return Err(StatusCode::FORBIDDEN);
+        }
+
+        let prefix = format!(
+            "{bucket}/{}",
+            params.get("prefix").cloned().unwrap_or_default()
+        );
+        let objects = objects.lock().unwrap();
+        let names = objects
+            .keys()
+            .filter(|name| name.starts_with(&prefix))
+            .map(|name| name[bucket.len() + 1..].to_string());
+
+        let mut xml = String::from("<ListBucketResult>");
+        match params.get("delimiter") {
+            Some(delimiter) => {
+                let common_prefixes = names
+                    .filter_map(|name| {
+                        name.find(delimiter.as_str())
+                            .map(|i| name[..i + delimiter.len()].to_string())
+                    })
+                    .collect::<BTreeSet<_>>();
+                for common_prefix in common_prefixes {
+                    xml.push_str(&format!(
+                        "<CommonPrefixes><Prefix>{common_prefix}</Prefix></CommonPrefixes>"
+                    ));
+                }
+                xml.push_str("<IsTruncated>false</IsTruncated>");
+            }
+            None => {
+                let token = params.get("continuation-token");
+                let names = names
+                    .filter(|name| token.map_or(true, |token| name > token))
+                    .collect::<Vec<_>>();
+                for name in names.iter().take(PAGE_SIZE) {
+                    xml.push_str(&format!("<Contents><Key>{name}</Key></Contents>"));
+                }
+                if names.len() > PAGE_SIZE {
+                    xml.push_str(&format!(
+                        "<IsTruncated>true</IsTruncated><NextContinuationToken>{}</NextContinuationToken>",
+                        names[PAGE_SIZE - 1]
+                    ));
+                } else {
+                    xml.push_str("<IsTruncated>false</IsTruncated>");
+                }
+            }
+        }
+        xml.push_str("</ListBucketResult>");
+
+        Ok(xml)
+    }
+
+    async fn get_object(
+        Path((bucket, object)): Path<(String, String)>,
+        State(objects): State<Objects>,
+        headers: HeaderMap,
+    ) -> Result<Vec<u8>, StatusCode> {
+        if !is_signed(&headers) {
+            return Err(StatusCode::FORBIDDEN);
+        }
+
+        objects
+            .lock()
+            .unwrap()
+            .get(&format!("{bucket}/{object}"))
+            .cloned()
+            .ok_or(StatusCode::NOT_FOUND)
+    }
+
+    async fn put_object(
+        Path((bucket, object)): Path<(String, String)>,
+        State(objects): State<Objects>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> StatusCode {
+        if !is_signed(&headers) {
+            return StatusCode::FORBIDDEN;
+        }
+
+        objects
+            .lock()
+            .unwrap()
+            .insert(format!("{bucket}/{object}"), body.to_vec());
+
+        StatusCode::OK
+    }
+
+    async fn delete_object(
+        Path((bucket, object)): Path<(String, String)>,
+        State(objects): State<Objects>,
+        headers: HeaderMap,
+    ) -> StatusCode {
+        if !is_signed(&headers) {
+            return StatusCode::FORBIDDEN;
+        }
+
+        objects
+            .lock()
+            .unwrap()
+            .remove(&format!("{bucket}/{object}"));
+
+        StatusCode::NO_CONTENT
+    }
+}
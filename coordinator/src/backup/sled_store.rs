@@ -0,0 +1,93 @@
+use crate::backup::BackupStore;
+use anyhow::Result;
+use async_trait::async_trait;
+use sled::Db;
+use std::path::Path;
+
+const BACKUPS_DIRECTORY: &str = "user_backups";
+
+/// The name of the tree sled creates by default, which is not used for backups.
+const DEFAULT_TREE: &[u8] = b"__sled__default";
+
+/// Stores the user backups in a sled database, using a sled tree per backup tree.
+pub struct SledStore {
+    db: Db,
+}
+
+impl SledStore {
+    pub fn new(data_dir: &Path) -> Result<Self> {
+        let db = sled::open(data_dir.join(BACKUPS_DIRECTORY))?;
+        Ok(Self { db })
+    }
+}
+
+impl From<Db> for SledStore {
+    fn from(db: Db) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl BackupStore for SledStore {
+    fn name(&self) -> &'static str {
+        "sled"
+    }
+
+    async fn trees(&self) -> Result<Vec<String>> {
+        let mut trees = vec![];
+        for name in self.db.tree_names() {
+            if name == DEFAULT_TREE {
+                continue;
+            }
+
+            trees.push(String::from_utf8(name.to_vec())?);
+        }
+
+        Ok(trees)
+    }
+
+    async fn entries(&self, tree: &str) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let tree = self.db.open_tree(tree)?;
+
+        let mut entries = vec![];
+        for entry in tree.iter() {
+            let (key, value) = entry?;
+            entries.push((key.to_vec(), value.to_vec()));
+        }
+
+        Ok(entries)
+    }
+
+    async fn get(&self, tree: &str, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let tree = self.db.open_tree(tree)?;
+        let value = tree.get(key)?;
+        Ok(value.map(|value| value.to_vec()))
+    }
+
+    async fn insert(&self, tree: &str, key: &[u8], value: &[u8]) -> Result<()> {
+        let tree = self.db.open_tree(tree)?;
+        tree.insert(key, value)?;
+        tree.flush()?;
+        Ok(())
+    }
+
+    async fn remove(&self, tree: &str, key: &[u8]) -> Result<()> {
+        let tree = self.db.open_tree(tree)?;
+        tree.remove(key)?;
+        tree.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backup::sled_store::SledStore;
+    use crate::backup::tests::assert_store_behaviour;
+
+    #[tokio::test]
+    async fn sled_store_behaves_like_a_backup_store() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+
+        assert_store_behaviour(&SledStore::from(db)).await;
+    }
+}
@@ -1,8 +1,13 @@
 use anyhow::Context;
 use anyhow::Result;
 use bitcoin::XOnlyPublicKey;
-use coordinator::backup::SledBackup;
+use coordinator::admin_auth;
+use coordinator::backup::copy_backups;
+use coordinator::backup::new_backup_store;
+use coordinator::backup::UserBackup;
+use coordinator::cli::Command;
 use coordinator::cli::Opts;
+use coordinator::db;
 use coordinator::logger;
 use coordinator::message::spawn_delivering_messages_to_authenticated_users;
 use coordinator::message::NewUserMessage;
@@ -11,19 +16,27 @@ use coordinator::metrics::init_meter;
 use coordinator::node;
 use coordinator::node::connection;
 use coordinator::node::expired_positions;
+use coordinator::node::liquidation;
+use coordinator::node::position_triggers;
 use coordinator::node::rollover;
 use coordinator::node::storage::NodeStorage;
 use coordinator::node::unrealized_pnl;
 use coordinator::node::Node;
 use coordinator::notifications::NotificationService;
 use coordinator::orderbook::async_match;
+use coordinator::orderbook::candles;
 use coordinator::orderbook::collaborative_revert;
+use coordinator::orderbook::db::orders;
+use coordinator::orderbook::feed;
+use coordinator::orderbook::feed::OrderbookFeed;
 use coordinator::orderbook::trading;
+use coordinator::rate_limit::RateLimiter;
 use coordinator::routes::router;
 use coordinator::run_migration;
 use coordinator::scheduler::NotificationScheduler;
 use coordinator::settings::Settings;
 use coordinator::storage::CoordinatorTenTenOneStorage;
+use coordinator::trader_auth::NonceCache;
 use diesel::r2d2;
 use diesel::r2d2::ConnectionManager;
 use diesel::PgConnection;
@@ -40,6 +53,7 @@ use std::net::SocketAddr;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
+use time::OffsetDateTime;
 use tokio::sync::broadcast;
 use tokio::sync::watch;
 use tokio::task::spawn_blocking;
@@ -49,7 +63,9 @@ const PROCESS_PROMETHEUS_METRICS: Duration = Duration::from_secs(10);
 const PROCESS_INCOMING_DLC_MESSAGES_INTERVAL: Duration = Duration::from_millis(200);
 const EXPIRED_POSITION_SYNC_INTERVAL: Duration = Duration::from_secs(5 * 60);
 const UNREALIZED_PNL_SYNC_INTERVAL: Duration = Duration::from_secs(10 * 60);
+const LIQUIDATION_CHECK_INTERVAL: Duration = Duration::from_secs(60);
 const CONNECTION_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const RATE_LIMIT_PRUNE_INTERVAL: Duration = Duration::from_secs(60);
 
 const NODE_ALIAS: &str = "10101.finance";
 
@@ -103,6 +119,25 @@ async fn main() -> Result<()> {
     let mut conn = pool.get()?;
     run_migration(&mut conn);
 
+    match opts.command.clone() {
+        Some(Command::MigrateBackups { from, to }) => {
+            let from = new_backup_store(from, &data_dir, pool.clone(), opts.backup_s3_config()?)?;
+            let to = new_backup_store(to, &data_dir, pool.clone(), opts.backup_s3_config()?)?;
+            copy_backups(from.as_ref(), to.as_ref()).await?;
+            return Ok(());
+        }
+        Some(Command::CreateAdminToken { name, scopes }) => {
+            let token = admin_auth::create_token(&mut conn, &name, &scopes)?;
+            println!("{token}");
+            return Ok(());
+        }
+        Some(Command::RevokeAdminToken { name }) => {
+            db::admin::revoke_token(&mut conn, &name)?;
+            return Ok(());
+        }
+        None => {}
+    }
+
     let (node_event_sender, mut node_event_receiver) = watch::channel::<Option<Event>>(None);
 
     let storage = CoordinatorTenTenOneStorage::new(data_dir.to_string_lossy().to_string());
@@ -133,7 +168,15 @@ async fn main() -> Result<()> {
 
     let event_handler = CoordinatorEventHandler::new(node.clone(), Some(node_event_sender));
     let running = node.start(event_handler, false)?;
-    let node = Node::new(node, running, pool.clone(), settings.to_node_settings());
+    let (tx_price_feed, _rx) = broadcast::channel(100);
+
+    let node = Node::new(
+        node,
+        running,
+        pool.clone(),
+        settings.to_node_settings(),
+        tx_price_feed.clone(),
+    );
 
     // TODO: Pass the tokio metrics into Prometheus
     if let Some(interval) = opts.tokio_metrics_interval_seconds {
@@ -209,8 +252,6 @@ async fn main() -> Result<()> {
 
     let (tx_user_feed, _rx) = broadcast::channel::<NewUserMessage>(100);
 
-    let (tx_price_feed, _rx) = broadcast::channel(100);
-
     let notification_service = NotificationService::new(opts.fcm_api_key.clone());
 
     let (_handle, auth_users_notifier) = spawn_delivering_messages_to_authenticated_users(
@@ -219,12 +260,36 @@ async fn main() -> Result<()> {
         tx_user_feed.clone(),
     );
 
+    // The orderbook feed has to be set up before any order can be placed, so that no order is
+    // missing from it.
+    let orderbook_feed = {
+        let mut conn = pool.get()?;
+        Arc::new(OrderbookFeed::new(orders::all_limit_orders(&mut conn)?))
+    };
+    let _handle = feed::monitor(pool.clone(), orderbook_feed.clone(), tx_price_feed.clone());
+
+    let rate_limiter = Arc::new(RateLimiter::new(settings.rate_limits));
+    let nonces = Arc::new(NonceCache::default());
+
+    tokio::spawn({
+        let rate_limiter = rate_limiter.clone();
+        let nonces = nonces.clone();
+        async move {
+            loop {
+                tokio::time::sleep(RATE_LIMIT_PRUNE_INTERVAL).await;
+                rate_limiter.prune();
+                nonces.prune(OffsetDateTime::now_utc());
+            }
+        }
+    });
+
     let (_handle, trading_sender) = trading::start(
         pool.clone(),
         tx_price_feed.clone(),
         auth_users_notifier.clone(),
         network,
         node.inner.oracle_pubkey,
+        rate_limiter.clone(),
     );
     let _handle = async_match::monitor(
         pool.clone(),
@@ -246,6 +311,11 @@ async fn main() -> Result<()> {
         auth_users_notifier.clone(),
     );
 
+    let _handle = candles::monitor(pool.clone(), tx_price_feed.clone());
+
+    let _handle =
+        position_triggers::monitor(node.clone(), tx_price_feed.clone(), trading_sender.clone());
+
     tokio::spawn({
         let node = node.clone();
         let trading_sender = trading_sender.clone();
@@ -260,14 +330,33 @@ async fn main() -> Result<()> {
         }
     });
 
+    tokio::spawn({
+        let node = node.clone();
+        let trading_sender = trading_sender.clone();
+        async move {
+            loop {
+                tokio::time::sleep(LIQUIDATION_CHECK_INTERVAL).await;
+                if let Err(e) = liquidation::liquidate(node.clone(), trading_sender.clone()).await {
+                    tracing::error!("Failed to liquidate positions! Error: {e:#}");
+                }
+            }
+        }
+    });
+
     tokio::spawn({
         let node = node.clone();
         connection::keep_public_channel_peers_connected(node.inner, CONNECTION_CHECK_INTERVAL)
     });
 
-    let user_backup = SledBackup::new(data_dir.to_string_lossy().to_string());
+    let backup_store = new_backup_store(
+        settings.backup_store,
+        &data_dir,
+        pool.clone(),
+        opts.backup_s3_config()?,
+    )?;
+    let user_backup = UserBackup::new(backup_store, pool.clone(), settings.backup_retention);
 
-    let app = router(
+    let (app, admin_app) = router(
         node.clone(),
         pool.clone(),
         settings.clone(),
@@ -279,8 +368,34 @@ async fn main() -> Result<()> {
         tx_user_feed,
         auth_users_notifier.clone(),
         user_backup,
+        orderbook_feed,
+        rate_limiter,
+        nonces,
+        opts.insecure_admin_api,
     );
 
+    if opts.insecure_admin_api {
+        tracing::warn!("Serving the admin API without authentication. Do not use for production!");
+    }
+
+    let app = match opts.admin_http_address {
+        Some(admin_http_address) => {
+            tracing::debug!("Listening for admin requests on http://{admin_http_address}");
+
+            tokio::spawn(async move {
+                if let Err(e) = axum::Server::bind(&admin_http_address)
+                    .serve(admin_app.into_make_service())
+                    .await
+                {
+                    tracing::error!("Admin HTTP server stopped running: {e:#}");
+                }
+            });
+
+            app
+        }
+        None => app.merge(admin_app),
+    };
+
     let sender = notification_service.get_sender();
     let notification_scheduler =
         NotificationScheduler::new(sender, settings, network, node, auth_users_notifier);
@@ -314,7 +429,7 @@ async fn main() -> Result<()> {
     tracing::debug!("Listening on http://{}", http_address);
 
     match axum::Server::bind(&http_address)
-        .serve(app.into_make_service())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
         .await
     {
         Ok(_) => {
@@ -1,15 +1,24 @@
+use crate::admin_auth::AdminScope;
+use crate::backup::s3_store::S3Config;
+use crate::backup::BackupStoreKind;
+use anyhow::Context;
 use anyhow::Result;
 use bitcoin::XOnlyPublicKey;
 use clap::Parser;
 use lightning::ln::msgs::SocketAddress;
 use ln_dlc_node::node::OracleInfo;
 use local_ip_address::local_ip;
+use std::env;
 use std::env::current_dir;
+use std::fs;
 use std::net::IpAddr;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::str::FromStr;
 
+/// The environment variable holding the secret key of the S3 backup store.
+const BACKUP_S3_SECRET_KEY_ENV: &str = "BACKUP_S3_SECRET_KEY";
+
 #[derive(Parser)]
 pub struct Opts {
     /// The address to listen on for the lightning and dlc peer2peer API.
@@ -20,6 +29,15 @@ pub struct Opts {
     #[clap(long, default_value = "0.0.0.0:8000")]
     pub http_address: SocketAddr,
 
+    /// The address to listen on for the admin API. If not set, the admin API is served on the
+    /// `http_address` alongside the public API.
+    #[clap(long)]
+    pub admin_http_address: Option<SocketAddr>,
+
+    /// Serves the admin API without requiring an admin token. Only meant for local development.
+    #[clap(long)]
+    pub insecure_admin_api: bool,
+
     /// Where to permanently store data, defaults to the current working directory.
     #[clap(long)]
     data_dir: Option<PathBuf>,
@@ -78,6 +96,55 @@ pub struct Opts {
         default_value = "16f88cf7d21e6c0f46bcbc983a4e3b19726c6c98858cc31c83551a88fde171c0"
     )]
     pub oracle_pubkey: String,
+
+    /// The endpoint of the S3-compatible object storage, if user backups are stored in S3.
+    #[clap(long)]
+    backup_s3_endpoint: Option<String>,
+
+    /// The bucket holding the user backups in the S3-compatible object storage.
+    #[clap(long)]
+    backup_s3_bucket: Option<String>,
+
+    /// The region of the S3-compatible object storage.
+    #[clap(long, default_value = "us-east-1")]
+    backup_s3_region: String,
+
+    /// The access key of the S3-compatible object storage.
+    #[clap(long)]
+    backup_s3_access_key: Option<String>,
+
+    /// A file holding the secret key of the S3-compatible object storage.
+    ///
+    /// Alternatively, the secret key can be set with the `BACKUP_S3_SECRET_KEY` environment
+    /// variable.
+    #[clap(long)]
+    backup_s3_secret_key_file: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum Command {
+    /// Copies all user backups from one storage backend to another and exits.
+    MigrateBackups {
+        #[clap(long, value_enum)]
+        from: BackupStoreKind,
+        #[clap(long, value_enum)]
+        to: BackupStoreKind,
+    },
+    /// Creates a token for the admin API with the given scopes, prints it and exits.
+    CreateAdminToken {
+        #[clap(long)]
+        name: String,
+        #[clap(long, value_enum, value_delimiter = ',', required = true)]
+        scopes: Vec<AdminScope>,
+    },
+    /// Revokes the admin API token with the given name and exits.
+    RevokeAdminToken {
+        #[clap(long)]
+        name: String,
+    },
 }
 
 #[derive(Debug, Clone, Copy, clap::ValueEnum)]
@@ -125,6 +192,39 @@ impl Opts {
             .collect()
     }
 
+    /// Returns the configuration of the S3 backup store, if all connection details are given.
+    ///
+    /// The secret key is not accepted as an argument, as it would show up in the process list.
+    pub fn backup_s3_config(&self) -> Result<Option<S3Config>> {
+        let (Some(endpoint), Some(bucket), Some(access_key)) = (
+            self.backup_s3_endpoint.clone(),
+            self.backup_s3_bucket.clone(),
+            self.backup_s3_access_key.clone(),
+        ) else {
+            return Ok(None);
+        };
+
+        let secret_key = match (
+            env::var(BACKUP_S3_SECRET_KEY_ENV).ok(),
+            &self.backup_s3_secret_key_file,
+        ) {
+            (Some(secret_key), _) => secret_key,
+            (None, Some(path)) => fs::read_to_string(path)
+                .with_context(|| format!("Failed to read S3 secret key from {path:?}"))?
+                .trim()
+                .to_string(),
+            (None, None) => return Ok(None),
+        };
+
+        Ok(Some(S3Config {
+            endpoint,
+            bucket,
+            region: self.backup_s3_region.clone(),
+            access_key,
+            secret_key,
+        }))
+    }
+
     pub fn data_dir(&self) -> Result<PathBuf> {
         let data_dir = match self.data_dir.clone() {
             None => current_dir()?.join("data"),
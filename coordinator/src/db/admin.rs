@@ -0,0 +1,108 @@
+use crate::schema::admin_audit_log;
+use crate::schema::admin_tokens;
+use anyhow::ensure;
+use anyhow::Result;
+use diesel::prelude::*;
+use serde::Serialize;
+use time::OffsetDateTime;
+
+#[derive(Queryable, Debug, Clone)]
+#[diesel(table_name = admin_tokens)]
+pub struct AdminToken {
+    pub id: i32,
+    pub name: String,
+    pub token_hash: String,
+    pub scopes: Vec<String>,
+    pub created_at: OffsetDateTime,
+    pub revoked_at: Option<OffsetDateTime>,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = admin_tokens)]
+struct NewAdminToken<'a> {
+    name: &'a str,
+    token_hash: &'a str,
+    scopes: Vec<String>,
+}
+
+#[derive(Queryable, Serialize, Debug, Clone)]
+#[diesel(table_name = admin_audit_log)]
+pub struct AuditEntry {
+    pub id: i32,
+    pub token_name: String,
+    pub scope: String,
+    pub method: String,
+    pub path: String,
+    pub status_code: i32,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = admin_audit_log)]
+pub struct NewAuditEntry {
+    pub token_name: String,
+    pub scope: String,
+    pub method: String,
+    pub path: String,
+    pub status_code: i32,
+}
+
+pub fn insert_token(
+    conn: &mut PgConnection,
+    name: &str,
+    token_hash: &str,
+    scopes: Vec<String>,
+) -> Result<()> {
+    let affected_rows = diesel::insert_into(admin_tokens::table)
+        .values(NewAdminToken {
+            name,
+            token_hash,
+            scopes,
+        })
+        .execute(conn)?;
+
+    ensure!(affected_rows > 0, "Could not insert admin token");
+
+    Ok(())
+}
+
+/// Returns the token with the given hash, unless it has been revoked.
+pub fn get_active_token(
+    conn: &mut PgConnection,
+    token_hash: &str,
+) -> QueryResult<Option<AdminToken>> {
+    admin_tokens::table
+        .filter(admin_tokens::token_hash.eq(token_hash))
+        .filter(admin_tokens::revoked_at.is_null())
+        .first(conn)
+        .optional()
+}
+
+pub fn revoke_token(conn: &mut PgConnection, name: &str) -> Result<()> {
+    let affected_rows = diesel::update(admin_tokens::table)
+        .filter(admin_tokens::name.eq(name))
+        .filter(admin_tokens::revoked_at.is_null())
+        .set(admin_tokens::revoked_at.eq(OffsetDateTime::now_utc()))
+        .execute(conn)?;
+
+    ensure!(affected_rows > 0, "No active admin token named {name}");
+
+    Ok(())
+}
+
+pub fn insert_audit_entry(conn: &mut PgConnection, entry: NewAuditEntry) -> QueryResult<()> {
+    diesel::insert_into(admin_audit_log::table)
+        .values(entry)
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Returns the latest entries of the audit log, most recent first.
+pub fn get_audit_log(conn: &mut PgConnection, limit: i64) -> QueryResult<Vec<AuditEntry>> {
+    admin_audit_log::table
+        .order_by(admin_audit_log::id.desc())
+        .limit(limit)
+        .load(conn)
+}
@@ -0,0 +1,85 @@
+use crate::schema::backup_versions;
+use diesel::prelude::*;
+use time::OffsetDateTime;
+
+/// An entry of the index of the stored versions of the user backups.
+///
+/// The values of the versions are held by the backup store.
+#[derive(Queryable, Debug, Clone, PartialEq)]
+#[diesel(table_name = backup_versions)]
+pub struct BackupVersion {
+    pub node_id: String,
+    pub version: i64,
+    pub key: String,
+    pub timestamp: OffsetDateTime,
+    /// Whether the version marks the key as deleted.
+    pub deleted: bool,
+    /// Whether the value of the version has been removed from the backup store.
+    pub pruned: bool,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = backup_versions)]
+pub struct NewBackupVersion<'a> {
+    pub node_id: &'a str,
+    pub version: i64,
+    pub key: &'a str,
+    pub timestamp: OffsetDateTime,
+    pub deleted: bool,
+}
+
+pub fn insert(conn: &mut PgConnection, version: NewBackupVersion) -> QueryResult<()> {
+    diesel::insert_into(backup_versions::table)
+        .values(version)
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Returns the sequence number of the latest version of the user's backup.
+pub fn get_latest_version(conn: &mut PgConnection, node_id: &str) -> QueryResult<Option<i64>> {
+    backup_versions::table
+        .filter(backup_versions::node_id.eq(node_id))
+        .select(diesel::dsl::max(backup_versions::version))
+        .first(conn)
+}
+
+/// Returns all versions of the user's backup, ordered by key and version.
+pub fn get_all(conn: &mut PgConnection, node_id: &str) -> QueryResult<Vec<BackupVersion>> {
+    backup_versions::table
+        .filter(backup_versions::node_id.eq(node_id))
+        .order_by((backup_versions::key, backup_versions::version))
+        .load(conn)
+}
+
+/// Returns all versions of the key, ordered by version.
+pub fn get_by_key(
+    conn: &mut PgConnection,
+    node_id: &str,
+    key: &str,
+) -> QueryResult<Vec<BackupVersion>> {
+    backup_versions::table
+        .filter(backup_versions::node_id.eq(node_id))
+        .filter(backup_versions::key.eq(key))
+        .order_by(backup_versions::version)
+        .load(conn)
+}
+
+pub fn set_pruned(conn: &mut PgConnection, node_id: &str, version: i64) -> QueryResult<()> {
+    diesel::update(backup_versions::table)
+        .filter(backup_versions::node_id.eq(node_id))
+        .filter(backup_versions::version.eq(version))
+        .set(backup_versions::pruned.eq(true))
+        .execute(conn)?;
+
+    Ok(())
+}
+
+pub fn delete(conn: &mut PgConnection, node_id: &str, version: i64) -> QueryResult<()> {
+    diesel::delete(backup_versions::table)
+        .filter(backup_versions::node_id.eq(node_id))
+        .filter(backup_versions::version.eq(version))
+        .execute(conn)?;
+
+    Ok(())
+}
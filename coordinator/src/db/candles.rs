@@ -0,0 +1,193 @@
+use crate::db::positions::ContractSymbol;
+use crate::decimal_from_f32;
+use crate::f32_from_decimal;
+use crate::schema::candles;
+use crate::schema::sql_types::CandleResolutionType;
+use commons::CandleFilter;
+use diesel::prelude::*;
+use diesel::query_builder::QueryId;
+use diesel::upsert::excluded;
+use diesel::AsExpression;
+use diesel::FromSqlRow;
+use std::any::TypeId;
+use time::OffsetDateTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, FromSqlRow, AsExpression)]
+#[diesel(sql_type = CandleResolutionType)]
+pub enum CandleResolution {
+    OneMinute,
+    FiveMinutes,
+    OneHour,
+    OneDay,
+}
+
+impl QueryId for CandleResolutionType {
+    type QueryId = CandleResolutionType;
+    const HAS_STATIC_QUERY_ID: bool = false;
+
+    fn query_id() -> Option<TypeId> {
+        None
+    }
+}
+
+#[derive(Insertable, Queryable, Debug, Clone)]
+#[diesel(table_name = candles)]
+struct Candle {
+    contract_symbol: ContractSymbol,
+    resolution: CandleResolution,
+    start_time: OffsetDateTime,
+    open: f32,
+    high: f32,
+    low: f32,
+    close: f32,
+    volume: f32,
+    updated_at: OffsetDateTime,
+}
+
+/// Inserts the candle or replaces the candle with the same contract symbol, resolution and start.
+pub fn upsert(conn: &mut PgConnection, candle: &commons::Candle) -> QueryResult<()> {
+    diesel::insert_into(candles::table)
+        .values(Candle::from(candle))
+        .on_conflict((
+            candles::contract_symbol,
+            candles::resolution,
+            candles::start_time,
+        ))
+        .do_update()
+        .set((
+            candles::open.eq(excluded(candles::open)),
+            candles::high.eq(excluded(candles::high)),
+            candles::low.eq(excluded(candles::low)),
+            candles::close.eq(excluded(candles::close)),
+            candles::volume.eq(excluded(candles::volume)),
+            candles::updated_at.eq(excluded(candles::updated_at)),
+        ))
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Inserts the candles which do not exist yet, leaving existing candles untouched.
+///
+/// Returns the number of inserted candles.
+pub fn insert_missing(conn: &mut PgConnection, candles: &[commons::Candle]) -> QueryResult<usize> {
+    let mut inserted = 0;
+    // Stay well below the maximum number of bind parameters of a single statement.
+    for chunk in candles.chunks(1_000) {
+        let chunk = chunk.iter().map(Candle::from).collect::<Vec<_>>();
+
+        inserted += diesel::insert_into(candles::table)
+            .values(chunk)
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+    }
+
+    Ok(inserted)
+}
+
+/// Returns the candles matching the filter, oldest first.
+///
+/// If there are more candles than the limit of the filter, the most recent ones are returned.
+pub fn get(conn: &mut PgConnection, filter: &CandleFilter) -> QueryResult<Vec<commons::Candle>> {
+    let mut query = candles::table
+        .filter(candles::contract_symbol.eq(ContractSymbol::from(filter.contract_symbol)))
+        .filter(candles::resolution.eq(CandleResolution::from(filter.resolution)))
+        .into_boxed();
+
+    if let Some(from) = filter.from {
+        query = query.filter(candles::start_time.ge(from));
+    }
+    if let Some(to) = filter.to {
+        query = query.filter(candles::start_time.lt(to));
+    }
+
+    let candles = query
+        .order_by(candles::start_time.desc())
+        .limit(filter.limit())
+        .load::<Candle>(conn)?;
+
+    Ok(candles
+        .into_iter()
+        .rev()
+        .map(commons::Candle::from)
+        .collect())
+}
+
+/// Returns the most recent candle of every contract symbol and resolution.
+pub fn get_latest(conn: &mut PgConnection) -> QueryResult<Vec<commons::Candle>> {
+    let mut latest = vec![];
+    for contract_symbol in trade::ContractSymbol::ALL {
+        for resolution in commons::Resolution::ALL {
+            let candle = candles::table
+                .filter(candles::contract_symbol.eq(ContractSymbol::from(contract_symbol)))
+                .filter(candles::resolution.eq(CandleResolution::from(resolution)))
+                .order_by(candles::start_time.desc())
+                .first::<Candle>(conn)
+                .optional()?;
+
+            latest.extend(candle.map(commons::Candle::from));
+        }
+    }
+
+    Ok(latest)
+}
+
+/// Returns the start of the most recent candle of any contract symbol and resolution.
+pub fn get_latest_start(conn: &mut PgConnection) -> QueryResult<Option<OffsetDateTime>> {
+    candles::table
+        .select(diesel::dsl::max(candles::start_time))
+        .first::<Option<OffsetDateTime>>(conn)
+}
+
+impl From<&commons::Candle> for Candle {
+    fn from(value: &commons::Candle) -> Self {
+        Candle {
+            contract_symbol: value.contract_symbol.into(),
+            resolution: value.resolution.into(),
+            start_time: value.start,
+            open: f32_from_decimal(value.open),
+            high: f32_from_decimal(value.high),
+            low: f32_from_decimal(value.low),
+            close: f32_from_decimal(value.close),
+            volume: f32_from_decimal(value.volume),
+            updated_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
+impl From<Candle> for commons::Candle {
+    fn from(value: Candle) -> Self {
+        commons::Candle {
+            contract_symbol: value.contract_symbol.into(),
+            resolution: value.resolution.into(),
+            start: value.start_time,
+            open: decimal_from_f32(value.open),
+            high: decimal_from_f32(value.high),
+            low: decimal_from_f32(value.low),
+            close: decimal_from_f32(value.close),
+            volume: decimal_from_f32(value.volume),
+        }
+    }
+}
+
+impl From<commons::Resolution> for CandleResolution {
+    fn from(value: commons::Resolution) -> Self {
+        match value {
+            commons::Resolution::OneMinute => CandleResolution::OneMinute,
+            commons::Resolution::FiveMinutes => CandleResolution::FiveMinutes,
+            commons::Resolution::OneHour => CandleResolution::OneHour,
+            commons::Resolution::OneDay => CandleResolution::OneDay,
+        }
+    }
+}
+
+impl From<CandleResolution> for commons::Resolution {
+    fn from(value: CandleResolution) -> Self {
+        match value {
+            CandleResolution::OneMinute => commons::Resolution::OneMinute,
+            CandleResolution::FiveMinutes => commons::Resolution::FiveMinutes,
+            CandleResolution::OneHour => commons::Resolution::OneHour,
+            CandleResolution::OneDay => commons::Resolution::OneDay,
+        }
+    }
+}
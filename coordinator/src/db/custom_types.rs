@@ -1,8 +1,10 @@
+use crate::db::candles::CandleResolution;
 use crate::db::channels::ChannelState;
 use crate::db::payments::HtlcStatus;
 use crate::db::payments::PaymentFlow;
 use crate::db::positions::ContractSymbol;
 use crate::db::positions::PositionState;
+use crate::schema::sql_types::CandleResolutionType;
 use crate::schema::sql_types::ChannelStateType;
 use crate::schema::sql_types::ContractSymbolType;
 use crate::schema::sql_types::DirectionType;
@@ -24,6 +26,7 @@ impl ToSql<ContractSymbolType, Pg> for ContractSymbol {
     fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
         match *self {
             ContractSymbol::BtcUsd => out.write_all(b"BtcUsd")?,
+            ContractSymbol::EthUsd => out.write_all(b"EthUsd")?,
         }
         Ok(IsNull::No)
     }
@@ -33,6 +36,31 @@ impl FromSql<ContractSymbolType, Pg> for ContractSymbol {
     fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
         match bytes.as_bytes() {
             b"BtcUsd" => Ok(ContractSymbol::BtcUsd),
+            b"EthUsd" => Ok(ContractSymbol::EthUsd),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+impl ToSql<CandleResolutionType, Pg> for CandleResolution {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        match *self {
+            CandleResolution::OneMinute => out.write_all(b"OneMinute")?,
+            CandleResolution::FiveMinutes => out.write_all(b"FiveMinutes")?,
+            CandleResolution::OneHour => out.write_all(b"OneHour")?,
+            CandleResolution::OneDay => out.write_all(b"OneDay")?,
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<CandleResolutionType, Pg> for CandleResolution {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"OneMinute" => Ok(CandleResolution::OneMinute),
+            b"FiveMinutes" => Ok(CandleResolution::FiveMinutes),
+            b"OneHour" => Ok(CandleResolution::OneHour),
+            b"OneDay" => Ok(CandleResolution::OneDay),
             _ => Err("Unrecognized enum variant".into()),
         }
     }
@@ -49,6 +77,7 @@ impl ToSql<PositionStateType, Pg> for PositionState {
             PositionState::Proposed => out.write_all(b"Proposed")?,
             PositionState::Failed => out.write_all(b"Failed")?,
             PositionState::ResizeProposed => out.write_all(b"ResizeProposed")?,
+            PositionState::Liquidated => out.write_all(b"Liquidated")?,
         }
         Ok(IsNull::No)
     }
@@ -65,6 +94,7 @@ impl FromSql<PositionStateType, Pg> for PositionState {
             b"Proposed" => Ok(PositionState::Proposed),
             b"Failed" => Ok(PositionState::Failed),
             b"ResizeProposed" => Ok(PositionState::ResizeProposed),
+            b"Liquidated" => Ok(PositionState::Liquidated),
             _ => Err("Unrecognized enum variant".into()),
         }
     }
@@ -0,0 +1,99 @@
+use crate::position;
+use crate::schema::liquidations;
+use anyhow::ensure;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use diesel::prelude::*;
+use std::str::FromStr;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Queryable, Debug, Clone)]
+#[diesel(table_name = liquidations)]
+struct Liquidation {
+    id: i32,
+    position_id: i32,
+    trader_pubkey: String,
+    order_id: Uuid,
+    liquidation_price: f32,
+    index_price: f32,
+    price_source: String,
+    timestamp: OffsetDateTime,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = liquidations)]
+struct NewLiquidation {
+    position_id: i32,
+    trader_pubkey: String,
+    order_id: Uuid,
+    liquidation_price: f32,
+    index_price: f32,
+    price_source: String,
+}
+
+pub fn insert(
+    conn: &mut PgConnection,
+    liquidation: position::models::NewLiquidation,
+) -> Result<()> {
+    let affected_rows = diesel::insert_into(liquidations::table)
+        .values(NewLiquidation::from(liquidation))
+        .execute(conn)?;
+
+    ensure!(affected_rows > 0, "Could not insert liquidation");
+
+    Ok(())
+}
+
+/// Returns the latest liquidation of the given position, if the position has been liquidated.
+pub fn get_by_position_id(
+    conn: &mut PgConnection,
+    position_id: i32,
+) -> Result<Option<position::models::Liquidation>> {
+    liquidations::table
+        .filter(liquidations::position_id.eq(position_id))
+        .order_by(liquidations::timestamp.desc())
+        .first::<Liquidation>(conn)
+        .optional()?
+        .map(position::models::Liquidation::try_from)
+        .transpose()
+}
+
+pub fn get_all(conn: &mut PgConnection) -> Result<Vec<position::models::Liquidation>> {
+    liquidations::table
+        .order_by(liquidations::timestamp.desc())
+        .load::<Liquidation>(conn)?
+        .into_iter()
+        .map(position::models::Liquidation::try_from)
+        .collect()
+}
+
+impl From<position::models::NewLiquidation> for NewLiquidation {
+    fn from(value: position::models::NewLiquidation) -> Self {
+        NewLiquidation {
+            position_id: value.position_id,
+            trader_pubkey: value.trader_pubkey.to_string(),
+            order_id: value.order_id,
+            liquidation_price: value.liquidation_price,
+            index_price: value.index_price,
+            price_source: value.price_source,
+        }
+    }
+}
+
+impl TryFrom<Liquidation> for position::models::Liquidation {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Liquidation) -> Result<Self> {
+        Ok(position::models::Liquidation {
+            id: value.id,
+            position_id: value.position_id,
+            trader_pubkey: PublicKey::from_str(value.trader_pubkey.as_str())?,
+            order_id: value.order_id,
+            liquidation_price: value.liquidation_price,
+            index_price: value.index_price,
+            price_source: value.price_source,
+            timestamp: value.timestamp,
+        })
+    }
+}
@@ -1,6 +1,10 @@
+pub mod admin;
+pub mod backup_versions;
+pub mod candles;
 pub mod channels;
 pub mod collaborative_reverts;
 pub mod custom_types;
+pub mod liquidations;
 pub mod liquidity;
 pub mod liquidity_options;
 pub mod payments;
@@ -11,3 +15,4 @@ pub mod spendable_outputs;
 pub mod trades;
 pub mod transactions;
 pub mod user;
+pub mod user_backups;
@@ -39,6 +39,8 @@ pub struct Position {
     pub coordinator_leverage: f32,
     pub trader_margin: i64,
     pub stable: bool,
+    pub stop_loss: Option<f32>,
+    pub take_profit: Option<f32>,
 }
 
 impl Position {
@@ -98,6 +100,27 @@ impl Position {
         Ok(positions)
     }
 
+    /// Returns all open positions which have a stop loss or a take profit set.
+    pub fn get_all_open_positions_with_triggers(
+        conn: &mut PgConnection,
+    ) -> QueryResult<Vec<crate::position::models::Position>> {
+        let positions = positions::table
+            .filter(positions::position_state.eq(PositionState::Open))
+            .filter(
+                positions::stop_loss
+                    .is_not_null()
+                    .or(positions::take_profit.is_not_null()),
+            )
+            .load::<Position>(conn)?;
+
+        let positions = positions
+            .into_iter()
+            .map(crate::position::models::Position::from)
+            .collect();
+
+        Ok(positions)
+    }
+
     pub fn get_all_open_or_closing_positions(
         conn: &mut PgConnection,
     ) -> QueryResult<Vec<crate::position::models::Position>> {
@@ -251,6 +274,29 @@ impl Position {
         Ok(())
     }
 
+    pub fn set_position_to_liquidated_with_pnl(
+        conn: &mut PgConnection,
+        id: i32,
+        pnl: i64,
+    ) -> Result<()> {
+        let affected_rows = diesel::update(positions::table)
+            .filter(positions::id.eq(id))
+            .set((
+                positions::position_state.eq(PositionState::Liquidated),
+                positions::realized_pnl_sat.eq(Some(pnl)),
+                positions::update_timestamp.eq(OffsetDateTime::now_utc()),
+            ))
+            .execute(conn)?;
+
+        if affected_rows == 0 {
+            bail!(
+                "Could not update position to Liquidated with realized pnl {pnl} for position {id}"
+            )
+        }
+
+        Ok(())
+    }
+
     pub fn set_position_to_closed(conn: &mut PgConnection, id: i32) -> Result<()> {
         let affected_rows = diesel::update(positions::table)
             .filter(positions::id.eq(id))
@@ -291,6 +337,31 @@ impl Position {
         Ok(())
     }
 
+    /// Sets the stop loss and take profit of the open position of the given trader.
+    pub fn set_position_triggers(
+        conn: &mut PgConnection,
+        trader_pubkey: String,
+        stop_loss: Option<f32>,
+        take_profit: Option<f32>,
+    ) -> Result<()> {
+        let affected_rows = diesel::update(positions::table)
+            .filter(positions::trader_pubkey.eq(trader_pubkey.clone()))
+            .filter(positions::position_state.eq(PositionState::Open))
+            .set((
+                positions::stop_loss.eq(stop_loss),
+                positions::take_profit.eq(take_profit),
+                positions::update_timestamp.eq(OffsetDateTime::now_utc()),
+            ))
+            .execute(conn)?;
+
+        ensure!(
+            affected_rows > 0,
+            "Could not set triggers for position of trader {trader_pubkey}"
+        );
+
+        Ok(())
+    }
+
     pub fn update_unrealized_pnl(conn: &mut PgConnection, id: i32, pnl: i64) -> Result<()> {
         let affected_rows = diesel::update(positions::table)
             .filter(positions::id.eq(id))
@@ -353,6 +424,7 @@ impl From<crate::position::models::PositionState> for PositionState {
             }
             crate::position::models::PositionState::Proposed => PositionState::Proposed,
             crate::position::models::PositionState::Failed => PositionState::Failed,
+            crate::position::models::PositionState::Liquidated { .. } => PositionState::Liquidated,
         }
     }
 }
@@ -384,6 +456,8 @@ impl From<Position> for crate::position::models::Position {
             coordinator_leverage: value.coordinator_leverage,
             trader_margin: value.trader_margin,
             stable: value.stable,
+            stop_loss: value.stop_loss,
+            take_profit: value.take_profit,
         }
     }
 }
@@ -439,6 +513,7 @@ pub enum PositionState {
     Failed,
     Resizing,
     ResizeProposed,
+    Liquidated,
 }
 
 impl QueryId for PositionStateType {
@@ -473,6 +548,9 @@ impl From<(PositionState, Option<i64>, Option<f32>)> for crate::position::models
             PositionState::ResizeProposed => {
                 crate::position::models::PositionState::ResizeOpeningSubchannelProposed
             }
+            PositionState::Liquidated => crate::position::models::PositionState::Liquidated {
+                pnl: realized_pnl.unwrap_or(0),
+            },
         }
     }
 }
@@ -481,6 +559,7 @@ impl From<(PositionState, Option<i64>, Option<f32>)> for crate::position::models
 #[diesel(sql_type = ContractSymbolType)]
 pub enum ContractSymbol {
     BtcUsd,
+    EthUsd,
 }
 
 impl QueryId for ContractSymbolType {
@@ -496,6 +575,7 @@ impl From<ContractSymbol> for trade::ContractSymbol {
     fn from(value: ContractSymbol) -> Self {
         match value {
             ContractSymbol::BtcUsd => trade::ContractSymbol::BtcUsd,
+            ContractSymbol::EthUsd => trade::ContractSymbol::EthUsd,
         }
     }
 }
@@ -504,6 +584,7 @@ impl From<trade::ContractSymbol> for ContractSymbol {
     fn from(value: trade::ContractSymbol) -> Self {
         match value {
             trade::ContractSymbol::BtcUsd => ContractSymbol::BtcUsd,
+            trade::ContractSymbol::EthUsd => ContractSymbol::EthUsd,
         }
     }
 }
@@ -4,6 +4,7 @@ use crate::schema::trades;
 use anyhow::Result;
 use bitcoin::hashes::hex::ToHex;
 use bitcoin::secp256k1::PublicKey;
+use commons::HistoryFilter;
 use diesel::prelude::*;
 use hex::FromHex;
 use lightning::ln::PaymentHash;
@@ -65,6 +66,61 @@ pub fn get_latest_for_position(
     Ok(trade.map(crate::trade::models::Trade::from))
 }
 
+/// Returns the trades of all traders matching the filter, most recent first.
+pub fn get_all(
+    conn: &mut PgConnection,
+    filter: &HistoryFilter,
+) -> Result<Vec<crate::trade::models::Trade>> {
+    let mut query = trades::table.into_boxed();
+
+    if let Some(contract_symbol) = filter.contract_symbol {
+        query = query.filter(trades::contract_symbol.eq(ContractSymbol::from(contract_symbol)));
+    }
+    if let Some(direction) = filter.direction {
+        query = query.filter(trades::direction.eq(Direction::from(direction)));
+    }
+    if let Some(from) = filter.from {
+        query = query.filter(trades::timestamp.ge(from));
+    }
+    if let Some(to) = filter.to {
+        query = query.filter(trades::timestamp.lt(to));
+    }
+
+    let trades = query
+        .order_by(trades::timestamp.desc())
+        .then_order_by(trades::id.desc())
+        .limit(filter.limit())
+        .offset(filter.offset())
+        .load::<Trade>(conn)?;
+
+    Ok(trades
+        .into_iter()
+        .map(crate::trade::models::Trade::from)
+        .collect())
+}
+
+/// Returns all trades executed at or after the given time, oldest first.
+pub fn get_all_since(
+    conn: &mut PgConnection,
+    since: Option<OffsetDateTime>,
+) -> Result<Vec<crate::trade::models::Trade>> {
+    let mut query = trades::table.into_boxed();
+
+    if let Some(since) = since {
+        query = query.filter(trades::timestamp.ge(since));
+    }
+
+    let trades = query
+        .order_by(trades::timestamp.asc())
+        .then_order_by(trades::id.asc())
+        .load::<Trade>(conn)?;
+
+    Ok(trades
+        .into_iter()
+        .map(crate::trade::models::Trade::from)
+        .collect())
+}
+
 /// Returns the position by trader pub key
 pub fn is_payment_hash_registered_as_trade_fee(
     conn: &mut PgConnection,
@@ -0,0 +1,58 @@
+use crate::schema::user_backups;
+use diesel::prelude::*;
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = user_backups)]
+struct UserBackupEntry<'a> {
+    tree: &'a str,
+    key: &'a [u8],
+    value: &'a [u8],
+}
+
+/// Returns the names of all trees holding at least one entry.
+pub fn get_trees(conn: &mut PgConnection) -> QueryResult<Vec<String>> {
+    user_backups::table
+        .select(user_backups::tree)
+        .distinct()
+        .order_by(user_backups::tree)
+        .load(conn)
+}
+
+/// Returns all entries of the tree, ordered by key.
+pub fn get_entries(conn: &mut PgConnection, tree: &str) -> QueryResult<Vec<(Vec<u8>, Vec<u8>)>> {
+    user_backups::table
+        .filter(user_backups::tree.eq(tree))
+        .select((user_backups::key, user_backups::value))
+        .order_by(user_backups::key)
+        .load(conn)
+}
+
+pub fn get(conn: &mut PgConnection, tree: &str, key: &[u8]) -> QueryResult<Option<Vec<u8>>> {
+    user_backups::table
+        .filter(user_backups::tree.eq(tree))
+        .filter(user_backups::key.eq(key))
+        .select(user_backups::value)
+        .first(conn)
+        .optional()
+}
+
+/// Inserts the entry or replaces the value of an existing entry with the same key.
+pub fn upsert(conn: &mut PgConnection, tree: &str, key: &[u8], value: &[u8]) -> QueryResult<()> {
+    diesel::insert_into(user_backups::table)
+        .values(UserBackupEntry { tree, key, value })
+        .on_conflict((user_backups::tree, user_backups::key))
+        .do_update()
+        .set(user_backups::value.eq(value))
+        .execute(conn)?;
+
+    Ok(())
+}
+
+pub fn delete(conn: &mut PgConnection, tree: &str, key: &[u8]) -> QueryResult<()> {
+    diesel::delete(user_backups::table)
+        .filter(user_backups::tree.eq(tree))
+        .filter(user_backups::key.eq(key))
+        .execute(conn)?;
+
+    Ok(())
+}
@@ -19,6 +19,7 @@ mod collaborative_revert;
 mod payout_curve;
 
 pub mod admin;
+pub mod admin_auth;
 pub mod backup;
 pub mod cli;
 pub mod db;
@@ -29,6 +30,8 @@ pub mod node;
 pub mod notifications;
 pub mod orderbook;
 pub mod position;
+pub mod price_source;
+pub mod rate_limit;
 pub mod routes;
 pub mod routing_fee;
 pub mod scheduler;
@@ -36,6 +39,7 @@ pub mod schema;
 pub mod settings;
 pub mod storage;
 pub mod trade;
+pub mod trader_auth;
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
 
@@ -52,7 +56,10 @@ pub enum AppError {
     NoMatchFound(String),
     InvalidOrder(String),
     ServiceUnavailable(String),
+    TooManyRequests(String),
     Unauthorized,
+    Forbidden(String),
+    Conflict(String),
 }
 
 impl IntoResponse for AppError {
@@ -63,7 +70,10 @@ impl IntoResponse for AppError {
             AppError::NoMatchFound(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
             AppError::InvalidOrder(msg) => (StatusCode::BAD_REQUEST, msg),
             AppError::ServiceUnavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
+            AppError::TooManyRequests(msg) => (StatusCode::TOO_MANY_REQUESTS, msg),
             AppError::Unauthorized => (StatusCode::UNAUTHORIZED, "".to_string()),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg),
         };
 
         let body = Json(json!({
@@ -6,6 +6,7 @@ use dlc_manager::subchannel::SubChannelState;
 use lazy_static::lazy_static;
 use lightning::ln::channelmanager::ChannelDetails;
 use opentelemetry::global;
+use opentelemetry::metrics::Counter;
 use opentelemetry::metrics::Meter;
 use opentelemetry::metrics::ObservableGauge;
 use opentelemetry::sdk::export::metrics::aggregation;
@@ -68,6 +69,12 @@ lazy_static! {
         .i64_observable_gauge("position_margin_sats")
         .with_description("Current open position margin in sats")
         .init();
+
+    // abuse protection metrics
+    pub static ref REJECTED_REQUESTS: Counter<u64> = METER
+        .u64_counter("rejected_requests_total")
+        .with_description("Number of requests rejected for exceeding a rate or size limit")
+        .init();
 }
 
 pub fn init_meter() -> PrometheusExporter {
@@ -145,66 +152,67 @@ fn position_metrics(cx: &Context, node: &Node) {
         }
     };
 
-    let mut margin_long = 0;
-    let mut margin_short = 0;
-    let mut quantity_long = 0.0;
-    let mut quantity_short = 0.0;
+    for contract_symbol in ContractSymbol::ALL {
+        let mut margin_long = 0;
+        let mut margin_short = 0;
+        let mut quantity_long = 0.0;
+        let mut quantity_short = 0.0;
 
-    // Note: we should filter positions here by BTCUSD once we have multiple contract symbols
-
-    for position in positions {
-        debug_assert!(
-            position.contract_symbol == ContractSymbol::BtcUsd,
-            "We should filter positions here by BTCUSD once we have multiple contract symbols"
-        );
-        match position.direction {
-            Direction::Long => {
-                // TODO: fix me: this was meant to be the traders margin
-                margin_long += position.coordinator_margin;
-                quantity_long += position.quantity;
-            }
-            Direction::Short => {
-                margin_short += position.coordinator_margin;
-                quantity_short += position.quantity;
+        for position in positions
+            .iter()
+            .filter(|position| position.contract_symbol == contract_symbol)
+        {
+            match position.direction {
+                Direction::Long => {
+                    // TODO: fix me: this was meant to be the traders margin
+                    margin_long += position.coordinator_margin;
+                    quantity_long += position.quantity;
+                }
+                Direction::Short => {
+                    margin_short += position.coordinator_margin;
+                    quantity_short += position.quantity;
+                }
             }
         }
+
+        let symbol = contract_symbol.to_string().to_uppercase();
+        POSITION_QUANTITY.observe(
+            cx,
+            quantity_long as f64,
+            &[
+                KeyValue::new("symbol", symbol.clone()),
+                KeyValue::new("status", "open"),
+                KeyValue::new("direction", "long"),
+            ],
+        );
+        POSITION_QUANTITY.observe(
+            cx,
+            quantity_short as f64,
+            &[
+                KeyValue::new("symbol", symbol.clone()),
+                KeyValue::new("status", "open"),
+                KeyValue::new("direction", "short"),
+            ],
+        );
+        POSITION_MARGIN.observe(
+            cx,
+            margin_long,
+            &[
+                KeyValue::new("symbol", symbol.clone()),
+                KeyValue::new("status", "open"),
+                KeyValue::new("direction", "long"),
+            ],
+        );
+        POSITION_MARGIN.observe(
+            cx,
+            margin_short,
+            &[
+                KeyValue::new("symbol", symbol),
+                KeyValue::new("status", "open"),
+                KeyValue::new("direction", "short"),
+            ],
+        );
     }
-    POSITION_QUANTITY.observe(
-        cx,
-        quantity_long as f64,
-        &[
-            KeyValue::new("symbol", "BTCUSD"),
-            KeyValue::new("status", "open"),
-            KeyValue::new("direction", "long"),
-        ],
-    );
-    POSITION_QUANTITY.observe(
-        cx,
-        quantity_short as f64,
-        &[
-            KeyValue::new("symbol", "BTCUSD"),
-            KeyValue::new("status", "open"),
-            KeyValue::new("direction", "short"),
-        ],
-    );
-    POSITION_MARGIN.observe(
-        cx,
-        margin_long,
-        &[
-            KeyValue::new("symbol", "BTCUSD"),
-            KeyValue::new("status", "open"),
-            KeyValue::new("direction", "long"),
-        ],
-    );
-    POSITION_MARGIN.observe(
-        cx,
-        margin_short,
-        &[
-            KeyValue::new("symbol", "BTCUSD"),
-            KeyValue::new("status", "open"),
-            KeyValue::new("direction", "short"),
-        ],
-    );
 }
 
 fn channel_metrics(cx: &Context, channels: Vec<ChannelDetails>) {
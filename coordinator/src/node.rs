@@ -9,6 +9,8 @@ use crate::payout_curve::create_rounding_interval;
 use crate::position::models::NewPosition;
 use crate::position::models::Position;
 use crate::position::models::PositionState;
+use crate::price_source::new_price_source;
+use crate::price_source::PriceSourceKind;
 use crate::storage::CoordinatorTenTenOneStorage;
 use crate::trade::models::NewTrade;
 use anyhow::anyhow;
@@ -20,7 +22,9 @@ use bitcoin::hashes::hex::ToHex;
 use bitcoin::secp256k1::PublicKey;
 use commons::order_matching_fee_taker;
 use commons::MatchState;
+use commons::OrderReason;
 use commons::OrderState;
+use commons::TimeInForce;
 use commons::TradeParams;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::Pool;
@@ -46,16 +50,20 @@ use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use std::sync::Arc;
 use time::OffsetDateTime;
+use tokio::sync::broadcast;
 use tokio::sync::RwLock;
 use tracing::instrument;
 use trade::cfd::calculate_long_liquidation_price;
 use trade::cfd::calculate_margin;
 use trade::cfd::calculate_short_liquidation_price;
+use trade::price_source::PriceSource;
 use trade::Direction;
 use uuid::Uuid;
 
 pub mod connection;
 pub mod expired_positions;
+pub mod liquidation;
+pub mod position_triggers;
 pub mod resize;
 pub mod rollover;
 pub mod routing_fees;
@@ -70,6 +78,7 @@ pub struct NodeSettings {
     pub max_allowed_tx_fee_rate_when_opening_channel: Option<u32>,
     /// Defines the sats/vbyte to be used for all transactions within the sub-channel
     pub contract_tx_fee_rate: u64,
+    pub price_source: PriceSourceKind,
 }
 
 impl NodeSettings {
@@ -87,6 +96,7 @@ pub struct Node {
     _running: Arc<RunningNode>,
     pub pool: Pool<ConnectionManager<PgConnection>>,
     settings: Arc<RwLock<NodeSettings>>,
+    tx_price_feed: broadcast::Sender<commons::Message>,
 }
 
 impl Node {
@@ -95,12 +105,14 @@ impl Node {
         running: RunningNode,
         pool: Pool<ConnectionManager<PgConnection>>,
         settings: NodeSettings,
+        tx_price_feed: broadcast::Sender<commons::Message>,
     ) -> Self {
         Self {
             inner,
             pool,
             settings: Arc::new(RwLock::new(settings)),
             _running: Arc::new(running),
+            tx_price_feed,
         }
     }
 
@@ -116,6 +128,12 @@ impl Node {
             .await;
     }
 
+    /// Creates the [`PriceSource`] configured in the node settings.
+    pub async fn price_source(&self) -> Box<dyn PriceSource> {
+        let kind = self.settings.read().await.price_source;
+        new_price_source(kind, self.pool.clone(), self.inner.network)
+    }
+
     pub fn update_ldk_settings(&self, ldk_config: UserConfig) {
         self.inner.update_ldk_settings(ldk_config)
     }
@@ -187,7 +205,8 @@ impl Node {
         })?;
 
         ensure!(
-            order.expiry > OffsetDateTime::now_utc(),
+            order.time_in_force == TimeInForce::GoodTillCancelled
+                || order.expiry > OffsetDateTime::now_utc(),
             "Can't execute a trade on an expired order"
         );
         ensure!(
@@ -297,7 +316,10 @@ impl Node {
         )
         .context("Could not build contract descriptor")?;
 
-        let contract_symbol = trade_params.contract_symbol.label();
+        let event_id_prefix = trade_params
+            .contract_symbol
+            .metadata()
+            .oracle_event_id_prefix;
         let maturity_time = trade_params.filled_with.expiry_timestamp;
         let maturity_time = maturity_time.unix_timestamp();
 
@@ -305,7 +327,7 @@ impl Node {
 
         // The contract input to be used for setting up the trade between the trader and the
         // coordinator
-        let event_id = format!("{contract_symbol}{maturity_time}");
+        let event_id = format!("{event_id_prefix}{maturity_time}");
         tracing::debug!(event_id, oracle=%trade_params.filled_with.oracle_pk, "Proposing dlc channel");
         let contract_input = ContractInput {
             offer_collateral: margin_coordinator - fee,
@@ -349,6 +371,23 @@ impl Node {
         )
     }
 
+    /// Persists the trade and publishes it on the public trade tape.
+    pub(crate) fn insert_trade(&self, conn: &mut PgConnection, new_trade: NewTrade) -> Result<()> {
+        let trade = db::trades::insert(conn, new_trade)?;
+
+        if let Err(e) = self
+            .tx_price_feed
+            .send(commons::Message::Trade((&trade).into()))
+        {
+            tracing::debug!(
+                trade_id = trade.id,
+                "Nobody is subscribed to new trades: {e:#}"
+            );
+        }
+
+        Ok(())
+    }
+
     // Creates a position and a trade from the trade params
     fn persist_position_and_trade(
         &self,
@@ -386,7 +425,7 @@ impl Node {
 
         let position = db::positions::Position::insert(connection, new_position.clone())?;
 
-        db::trades::insert(
+        self.insert_trade(
             connection,
             NewTrade {
                 position_id: position.id,
@@ -426,7 +465,7 @@ impl Node {
             .propose_dlc_channel_collaborative_settlement(channel_id, accept_settlement_amount)
             .await?;
 
-        db::trades::insert(
+        self.insert_trade(
             conn,
             NewTrade {
                 position_id: position.id,
@@ -487,14 +526,29 @@ impl Node {
             "Setting position to closed to match the contract state."
         );
 
-        if let Err(e) = db::positions::Position::set_position_to_closed_with_pnl(
-            conn,
-            position.id,
-            contract.pnl,
-        ) {
+        // The position has only been liquidated if it got closed by the liquidation order, not if
+        // the liquidation order failed and the trader closed the position themselves.
+        let is_liquidated = orders::get_last_executed_by_trader_id(conn, position.trader)?
+            .map_or(false, |order| order.order_reason == OrderReason::Liquidated);
+        let result = if is_liquidated {
+            db::positions::Position::set_position_to_liquidated_with_pnl(
+                conn,
+                position.id,
+                contract.pnl,
+            )
+        } else {
+            db::positions::Position::set_position_to_closed_with_pnl(
+                conn,
+                position.id,
+                contract.pnl,
+            )
+        };
+
+        if let Err(e) = result {
             tracing::error!(
                 temporary_contract_id=%temporary_contract_id.to_hex(),
                 pnl=contract.pnl,
+                is_liquidated,
                 "Failed to set position to closed: {e:#}"
             )
         }
@@ -534,6 +588,15 @@ impl Node {
         )?
         .with_context(|| format!("Failed to find open position with peer {trader_peer_id}"))?;
 
+        // A DLC channel can only hold a single contract, hence the trader can only trade the
+        // contract symbol of their open position until it is closed.
+        ensure!(
+            position.contract_symbol == trade_params.contract_symbol,
+            "Can't trade {} while having an open {} position",
+            trade_params.contract_symbol,
+            position.contract_symbol
+        );
+
         let position_contracts = {
             let contracts = decimal_from_f32(position.quantity);
 
@@ -776,6 +839,7 @@ pub enum TradeAction {
 
 fn margin_trader(trade_params: &TradeParams) -> u64 {
     calculate_margin(
+        trade_params.contract_symbol,
         trade_params.average_execution_price(),
         trade_params.quantity,
         trade_params.leverage,
@@ -784,6 +848,7 @@ fn margin_trader(trade_params: &TradeParams) -> u64 {
 
 fn margin_coordinator(trade_params: &TradeParams, coordinator_leverage: f32) -> u64 {
     calculate_margin(
+        trade_params.contract_symbol,
         trade_params.average_execution_price(),
         trade_params.quantity,
         coordinator_leverage,
@@ -795,8 +860,12 @@ fn liquidation_price(trade_params: &TradeParams) -> f32 {
     let leverage = Decimal::try_from(trade_params.leverage).expect("to fit into decimal");
 
     match trade_params.direction {
-        Direction::Long => calculate_long_liquidation_price(leverage, price),
-        Direction::Short => calculate_short_liquidation_price(leverage, price),
+        Direction::Long => {
+            calculate_long_liquidation_price(trade_params.contract_symbol, leverage, price)
+        }
+        Direction::Short => {
+            calculate_short_liquidation_price(trade_params.contract_symbol, leverage, price)
+        }
     }
     .to_f32()
     .expect("to fit into f32")
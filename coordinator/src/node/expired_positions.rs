@@ -2,6 +2,7 @@ use crate::db;
 use crate::node::Node;
 use crate::orderbook;
 use crate::orderbook::trading::NewOrderMessage;
+use crate::orderbook::trading::TradingMessage;
 use crate::position::models::Position;
 use crate::position::models::PositionState;
 use anyhow::Context;
@@ -14,6 +15,7 @@ use commons::Order;
 use commons::OrderReason;
 use commons::OrderState;
 use commons::OrderType;
+use commons::TimeInForce;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use std::ops::Add;
@@ -25,7 +27,7 @@ use tokio::sync::mpsc;
 /// not be larger than our refund transaction time lock.
 pub const EXPIRED_POSITION_TIMEOUT: Duration = Duration::days(7);
 
-pub async fn close(node: Node, trading_sender: mpsc::Sender<NewOrderMessage>) -> Result<()> {
+pub async fn close(node: Node, trading_sender: mpsc::Sender<TradingMessage>) -> Result<()> {
     let mut conn = node.pool.get()?;
 
     let positions = db::positions::Position::get_all_open_positions(&mut conn)
@@ -94,6 +96,7 @@ pub async fn close(node: Node, trading_sender: mpsc::Sender<NewOrderMessage>) ->
             // close.
             expiry: OffsetDateTime::now_utc().add(EXPIRED_POSITION_TIMEOUT),
             stable: position.stable,
+            time_in_force: TimeInForce::FillOrKill,
         };
 
         let (sender, mut receiver) = mpsc::channel::<Result<Order>>(1);
@@ -103,7 +106,7 @@ pub async fn close(node: Node, trading_sender: mpsc::Sender<NewOrderMessage>) ->
             sender,
         };
 
-        if let Err(e) = trading_sender.send(message).await {
+        if let Err(e) = trading_sender.send(TradingMessage::NewOrder(message)).await {
             tracing::error!(order_id=%new_order.id, trader_id=%new_order.trader_id, "Failed to submit new order for closing expired position. Error: {e:#}");
             continue;
         }
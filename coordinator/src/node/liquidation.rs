@@ -0,0 +1,245 @@
+use crate::db;
+use crate::node::expired_positions::EXPIRED_POSITION_TIMEOUT;
+use crate::node::Node;
+use crate::orderbook;
+use crate::orderbook::trading::NewOrderMessage;
+use crate::orderbook::trading::TradingMessage;
+use crate::position::models::NewLiquidation;
+use crate::position::models::Position;
+use anyhow::Context;
+use anyhow::Result;
+use commons::NewOrder;
+use commons::Order;
+use commons::OrderReason;
+use commons::OrderState;
+use commons::OrderType;
+use commons::TimeInForce;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::ops::Add;
+use time::Duration;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+use trade::price_source::PriceSource;
+use trade::Price;
+
+/// The time we wait before trying to liquidate a position again after its liquidation order did
+/// not find a match.
+const LIQUIDATION_RETRY_INTERVAL: Duration = Duration::minutes(5);
+
+/// Closes all open positions whose liquidation price has been crossed by the index price.
+///
+/// The position is closed with a fill-or-kill market order, collaboratively at the price it got
+/// matched at once the trader comes online to execute the match. If the order does not find a
+/// match, the position is only retried after a back-off.
+pub async fn liquidate(node: Node, trading_sender: mpsc::Sender<TradingMessage>) -> Result<()> {
+    let mut conn = node.pool.get()?;
+
+    let positions = db::positions::Position::get_all_open_positions(&mut conn)
+        .context("Failed to fetch open positions")?;
+
+    if positions.is_empty() {
+        return Ok(());
+    }
+
+    let price_source = node.price_source().await;
+
+    for (position, price) in liquidated_positions(price_source.as_ref(), positions).await {
+        if let Some(order) = orderbook::db::orders::get_by_trader_id_and_state(
+            &mut conn,
+            position.trader,
+            OrderState::Matched,
+        )? {
+            tracing::trace!(trader_id=%position.trader, order_id=%order.id, "Skipping liquidated position as match has already been found. Waiting for trader to come online to execute the trade.");
+            continue;
+        }
+
+        if orderbook::db::orders::has_failed_since(
+            &mut conn,
+            position.trader,
+            OrderReason::Liquidated,
+            OffsetDateTime::now_utc() - LIQUIDATION_RETRY_INTERVAL,
+        )? {
+            tracing::trace!(trader_id=%position.trader, position_id=position.id, "Skipping liquidated position as its last liquidation order failed recently.");
+            continue;
+        }
+
+        let index_price = price.get_price_for_direction(position.direction.opposite());
+
+        tracing::info!(
+            trader_pk=%position.trader,
+            position_id=position.id,
+            liquidation_price=position.liquidation_price,
+            %index_price,
+            price_source=price_source.name(),
+            "Attempting to close liquidated position"
+        );
+
+        let new_order = NewOrder {
+            id: uuid::Uuid::new_v4(),
+            contract_symbol: position.contract_symbol,
+            price: Decimal::ZERO,
+            quantity: Decimal::try_from(position.quantity).expect("to fit into decimal"),
+            trader_id: position.trader,
+            direction: position.direction.opposite(),
+            leverage: position.trader_leverage,
+            order_type: OrderType::Market,
+            // Same as for expired positions, the trader has to come back online to execute the
+            // trade.
+            expiry: OffsetDateTime::now_utc().add(EXPIRED_POSITION_TIMEOUT),
+            stable: position.stable,
+            time_in_force: TimeInForce::FillOrKill,
+        };
+
+        let (sender, mut receiver) = mpsc::channel::<Result<Order>>(1);
+        let message = NewOrderMessage {
+            new_order: new_order.clone(),
+            order_reason: OrderReason::Liquidated,
+            sender,
+        };
+
+        if let Err(e) = trading_sender.send(TradingMessage::NewOrder(message)).await {
+            tracing::error!(order_id=%new_order.id, trader_id=%new_order.trader_id, "Failed to submit new order for closing liquidated position. Error: {e:#}");
+            continue;
+        }
+
+        let order = match receiver.recv().await {
+            Some(Ok(order)) => order,
+            Some(Err(e)) => {
+                tracing::error!(order_id=%new_order.id, trader_id=%new_order.trader_id, "Failed to submit new order for closing liquidated position. Error: {e:#}");
+                continue;
+            }
+            None => {
+                tracing::error!(order_id=%new_order.id, trader_id=%new_order.trader_id, "Failed to receive response from trading.");
+                continue;
+            }
+        };
+
+        db::liquidations::insert(
+            &mut conn,
+            NewLiquidation {
+                position_id: position.id,
+                trader_pubkey: position.trader,
+                order_id: order.id,
+                liquidation_price: position.liquidation_price,
+                index_price: index_price.to_f32().expect("to fit into f32"),
+                price_source: price_source.name().to_string(),
+            },
+        )
+        .context("Failed to record liquidation")?;
+    }
+
+    Ok(())
+}
+
+/// Returns the positions whose liquidation price has been crossed by the index price, together
+/// with the index price.
+///
+/// Positions in a contract symbol for which the price source fails to provide a price are skipped,
+/// so that they don't prevent the liquidation of positions in other contract symbols.
+async fn liquidated_positions(
+    price_source: &dyn PriceSource,
+    positions: Vec<Position>,
+) -> Vec<(Position, Price)> {
+    let mut prices = HashMap::new();
+    let mut liquidated = vec![];
+
+    for position in positions.into_iter() {
+        let price = match prices.get(&position.contract_symbol) {
+            Some(price) => *price,
+            None => {
+                let price = match price_source.get_price(position.contract_symbol).await {
+                    Ok(price) => Some(price),
+                    Err(e) => {
+                        tracing::warn!(
+                            contract_symbol=%position.contract_symbol,
+                            price_source=price_source.name(),
+                            "Failed to fetch price, skipping liquidation of positions in this contract symbol: {e:#}"
+                        );
+                        None
+                    }
+                };
+                prices.insert(position.contract_symbol, price);
+                price
+            }
+        };
+
+        let price = match price {
+            Some(price) => price,
+            None => continue,
+        };
+
+        if position.is_liquidated(price) {
+            liquidated.push((position, price));
+        }
+    }
+
+    liquidated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::ensure;
+    use async_trait::async_trait;
+    use rust_decimal_macros::dec;
+    use trade::ContractSymbol;
+
+    #[tokio::test]
+    async fn liquidate_btcusd_positions_without_ethusd_price() {
+        let price_source = BtcUsdPriceSource(Price {
+            bid: dec!(19_000),
+            ask: dec!(19_010),
+        });
+
+        let positions = vec![
+            Position {
+                id: 1,
+                contract_symbol: ContractSymbol::EthUsd,
+                liquidation_price: 2_000.0,
+                ..Position::dummy()
+            },
+            Position {
+                id: 2,
+                contract_symbol: ContractSymbol::BtcUsd,
+                liquidation_price: 20_000.0,
+                ..Position::dummy()
+            },
+            Position {
+                id: 3,
+                contract_symbol: ContractSymbol::BtcUsd,
+                liquidation_price: 15_000.0,
+                ..Position::dummy()
+            },
+        ];
+
+        let liquidated = liquidated_positions(&price_source, positions).await;
+
+        let liquidated = liquidated
+            .iter()
+            .map(|(position, _)| position.id)
+            .collect::<Vec<_>>();
+        assert_eq!(liquidated, vec![2]);
+    }
+
+    /// Price source which, like the BitMEX price source, only supports BTCUSD.
+    struct BtcUsdPriceSource(Price);
+
+    #[async_trait]
+    impl PriceSource for BtcUsdPriceSource {
+        fn name(&self) -> &'static str {
+            "btcusd"
+        }
+
+        async fn get_price(&self, contract_symbol: ContractSymbol) -> Result<Price> {
+            ensure!(
+                contract_symbol == ContractSymbol::BtcUsd,
+                "Only {} is supported",
+                ContractSymbol::BtcUsd
+            );
+
+            Ok(self.0)
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use crate::db;
+use crate::node::expired_positions::EXPIRED_POSITION_TIMEOUT;
+use crate::node::Node;
+use crate::orderbook;
+use crate::orderbook::trading::NewOrderMessage;
+use crate::orderbook::trading::TradingMessage;
+use crate::position::models::Position;
+use anyhow::Context;
+use anyhow::Result;
+use commons::best_current_price;
+use commons::Message;
+use commons::NewOrder;
+use commons::Order;
+use commons::OrderReason;
+use commons::OrderState;
+use commons::OrderType;
+use commons::TimeInForce;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::PgConnection;
+use futures::future::RemoteHandle;
+use futures::FutureExt;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::ops::Add;
+use time::Duration;
+use time::OffsetDateTime;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio::task::spawn_blocking;
+use trade::Direction;
+
+/// The time we wait before submitting another closing order for the same triggered position.
+///
+/// Without it, a closing order which does not find a match would be resubmitted (and stored as
+/// failed) on every update of the orderbook.
+const TRIGGER_RETRY_INTERVAL: Duration = Duration::minutes(1);
+
+/// Watches the orderbook price feed and closes all open positions whose stop loss or take profit
+/// has been hit.
+pub fn monitor(
+    node: Node,
+    tx_price_feed: broadcast::Sender<Message>,
+    trading_sender: mpsc::Sender<TradingMessage>,
+) -> RemoteHandle<()> {
+    let mut price_feed = tx_price_feed.subscribe();
+    let (fut, remote_handle) = async move {
+        // The last time we submitted a closing order per position id.
+        let mut attempts = HashMap::new();
+        loop {
+            match price_feed.recv().await {
+                Ok(
+                    Message::AllOrders(_)
+                    | Message::NewOrder(_)
+                    | Message::DeleteOrder(_)
+                    | Message::Update(_),
+                ) => {
+                    if let Err(e) = close(&node, &trading_sender, &mut attempts).await {
+                        tracing::error!("Failed to close triggered positions! Error: {e:#}");
+                    }
+                }
+                Ok(_) => {}
+                Err(RecvError::Closed) => {
+                    tracing::error!("Price feed sender died! Channel closed.");
+                    break;
+                }
+                Err(RecvError::Lagged(skip)) => {
+                    tracing::warn!(%skip, "Lagging behind on price feed.")
+                }
+            }
+        }
+    }
+    .remote_handle();
+
+    tokio::spawn(fut);
+
+    remote_handle
+}
+
+async fn close(
+    node: &Node,
+    trading_sender: &mpsc::Sender<TradingMessage>,
+    attempts: &mut HashMap<i32, OffsetDateTime>,
+) -> Result<()> {
+    let triggered = spawn_blocking({
+        let pool = node.pool.clone();
+        move || triggered_positions(&pool)
+    })
+    .await
+    .expect("task to complete")?;
+
+    let now = OffsetDateTime::now_utc();
+    attempts.retain(|_, attempted_at| now - *attempted_at < TRIGGER_RETRY_INTERVAL);
+
+    for (position, closing_price, order_reason) in triggered.into_iter() {
+        if let Some(attempted_at) = attempts.get(&position.id) {
+            tracing::trace!(trader_id=%position.trader, position_id=position.id, %attempted_at, ?order_reason, "Skipping triggered position as a closing order has been submitted recently.");
+            continue;
+        }
+        attempts.insert(position.id, now);
+
+        tracing::info!(trader_pk=%position.trader, %closing_price, ?order_reason, "Attempting to close triggered position");
+
+        let new_order = NewOrder {
+            id: uuid::Uuid::new_v4(),
+            contract_symbol: position.contract_symbol,
+            price: Decimal::ZERO,
+            quantity: Decimal::try_from(position.quantity).expect("to fit into decimal"),
+            trader_id: position.trader,
+            direction: position.direction.opposite(),
+            leverage: position.trader_leverage,
+            order_type: OrderType::Market,
+            // Same as for expired positions, the trader has to come back online to execute the
+            // trade.
+            expiry: OffsetDateTime::now_utc().add(EXPIRED_POSITION_TIMEOUT),
+            stable: position.stable,
+            time_in_force: TimeInForce::FillOrKill,
+        };
+
+        let (sender, mut receiver) = mpsc::channel::<Result<Order>>(1);
+        let message = NewOrderMessage {
+            new_order: new_order.clone(),
+            order_reason,
+            sender,
+        };
+
+        if let Err(e) = trading_sender.send(TradingMessage::NewOrder(message)).await {
+            tracing::error!(order_id=%new_order.id, trader_id=%new_order.trader_id, "Failed to submit new order for closing triggered position. Error: {e:#}");
+            continue;
+        }
+
+        match receiver.recv().await {
+            Some(Ok(order)) => order,
+            Some(Err(e)) => {
+                tracing::error!(order_id=%new_order.id, trader_id=%new_order.trader_id, "Failed to submit new order for closing triggered position. Error: {e:#}");
+                continue;
+            }
+            None => {
+                tracing::error!(order_id=%new_order.id, trader_id=%new_order.trader_id, "Failed to receive response from trading.");
+                continue;
+            }
+        };
+    }
+
+    Ok(())
+}
+
+/// Loads all open positions whose stop loss or take profit has been hit by the best price in the
+/// orderbook, together with their closing price and the reason for closing them.
+///
+/// Positions which already have a match waiting for the trader to execute it are skipped.
+fn triggered_positions(
+    pool: &Pool<ConnectionManager<PgConnection>>,
+) -> Result<Vec<(Position, Decimal, OrderReason)>> {
+    let mut conn = pool.get()?;
+
+    let positions = db::positions::Position::get_all_open_positions_with_triggers(&mut conn)
+        .context("Failed to fetch open positions with triggers")?;
+
+    if positions.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let orders = orderbook::db::orders::all_limit_orders(&mut conn)?;
+    let prices = best_current_price(&orders);
+
+    let mut triggered = vec![];
+    for position in positions.into_iter() {
+        let price = match prices.get(&position.contract_symbol) {
+            Some(price) => price,
+            None => continue,
+        };
+
+        // The position gets closed with a market order in the opposite direction, i.e. a long
+        // position is closed at the best bid and a short position at the best ask.
+        let closing_price = match position.direction {
+            Direction::Long => price.bid,
+            Direction::Short => price.ask,
+        };
+
+        let (closing_price, order_reason) = match closing_price.and_then(|closing_price| {
+            position
+                .triggered_order_reason(closing_price)
+                .map(|order_reason| (closing_price, order_reason))
+        }) {
+            Some(triggered) => triggered,
+            None => continue,
+        };
+
+        if let Some(order) = orderbook::db::orders::get_by_trader_id_and_state(
+            &mut conn,
+            position.trader,
+            OrderState::Matched,
+        )? {
+            tracing::trace!(trader_id=%position.trader, order_id=%order.id, ?order_reason, "Skipping triggered position as match has already been found. Waiting for trader to come online to execute the trade.");
+            continue;
+        }
+
+        triggered.push((position, closing_price, order_reason));
+    }
+
+    Ok(triggered)
+}
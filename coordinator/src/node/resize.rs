@@ -28,6 +28,8 @@ use tokio::task::block_in_place;
 use trade::cfd::calculate_long_liquidation_price;
 use trade::cfd::calculate_margin;
 use trade::cfd::calculate_short_liquidation_price;
+use trade::ContractKind;
+use trade::ContractSymbol;
 use trade::Direction;
 
 impl Node {
@@ -131,7 +133,7 @@ impl Node {
             margin_coordinator(trade_params, leverage_coordinator) as i64
         };
 
-        db::trades::insert(
+        self.insert_trade(
             tx,
             NewTrade {
                 position_id: position.id,
@@ -206,7 +208,10 @@ impl Node {
             (total_contracts, direction)
         };
 
+        let contract_symbol = old_position.contract_symbol;
+
         let average_execution_price = compute_average_execution_price(
+            contract_symbol,
             old_position.average_entry_price,
             trade.average_price,
             old_position.quantity,
@@ -220,15 +225,24 @@ impl Node {
         let leverage_coordinator = decimal_from_f32(old_position.coordinator_leverage);
 
         let margin_coordinator = compute_margin(
+            contract_symbol,
             total_contracts,
             leverage_coordinator,
             average_execution_price,
         );
-        let margin_trader =
-            compute_margin(total_contracts, leverage_trader, average_execution_price);
+        let margin_trader = compute_margin(
+            contract_symbol,
+            total_contracts,
+            leverage_trader,
+            average_execution_price,
+        );
 
-        let liquidation_price_trader =
-            compute_liquidation_price(leverage_trader, average_execution_price, &direction);
+        let liquidation_price_trader = compute_liquidation_price(
+            contract_symbol,
+            leverage_trader,
+            average_execution_price,
+            &direction,
+        );
         let expiry_timestamp = trade
             .dlc_expiry_timestamp
             .context("No expiry timestamp for resizing trade")?;
@@ -240,9 +254,9 @@ impl Node {
         let contract_input = {
             let fee_rate = self.settings.blocking_read().contract_tx_fee_rate;
 
-            let contract_symbol = old_position.contract_symbol;
             let maturity_time = expiry_timestamp.unix_timestamp();
-            let event_id = format!("{contract_symbol}{maturity_time}");
+            let event_id_prefix = contract_symbol.metadata().oracle_event_id_prefix;
+            let event_id = format!("{event_id_prefix}{maturity_time}");
 
             let total_collateral = margin_coordinator + margin_trader;
 
@@ -353,6 +367,7 @@ impl Node {
 
 fn margin_coordinator(trade_params: &TradeParams, coordinator_leverage: f32) -> u64 {
     calculate_margin(
+        trade_params.contract_symbol,
         trade_params.average_execution_price(),
         trade_params.quantity,
         coordinator_leverage,
@@ -360,11 +375,17 @@ fn margin_coordinator(trade_params: &TradeParams, coordinator_leverage: f32) ->
 }
 
 fn compute_margin(
+    contract_symbol: ContractSymbol,
     total_contracts: Decimal,
     leverage: Decimal,
     average_execution_price: Decimal,
 ) -> u64 {
-    let margin_btc = total_contracts / (leverage * average_execution_price);
+    let margin_btc = match contract_symbol.metadata().kind {
+        ContractKind::Inverse => total_contracts / (leverage * average_execution_price),
+        ContractKind::Quanto { multiplier } => {
+            total_contracts * average_execution_price * multiplier / leverage
+        }
+    };
 
     let margin_btc = margin_btc
         .abs()
@@ -378,6 +399,7 @@ fn compute_margin(
 }
 
 fn compute_average_execution_price(
+    contract_symbol: ContractSymbol,
     starting_average_execution_price: f32,
     trade_execution_price: f32,
     starting_contracts: f32,
@@ -408,9 +430,18 @@ fn compute_average_execution_price(
     else if starting_contracts_relative.signum() == total_contracts_relative.signum()
         && starting_contracts < total_contracts_relative.abs()
     {
-        let price = total_contracts_relative
-            / (starting_contracts_relative / starting_average_execution_price
-                + trade_contracts_relative / trade_execution_price);
+        let price = match contract_symbol.metadata().kind {
+            ContractKind::Inverse => {
+                total_contracts_relative
+                    / (starting_contracts_relative / starting_average_execution_price
+                        + trade_contracts_relative / trade_execution_price)
+            }
+            ContractKind::Quanto { .. } => {
+                (starting_contracts_relative * starting_average_execution_price
+                    + trade_contracts_relative * trade_execution_price)
+                    / total_contracts_relative
+            }
+        };
 
         price.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
     }
@@ -427,10 +458,15 @@ fn compute_average_execution_price(
     }
 }
 
-fn compute_liquidation_price(leverage: Decimal, price: Decimal, direction: &Direction) -> Decimal {
+fn compute_liquidation_price(
+    contract_symbol: ContractSymbol,
+    leverage: Decimal,
+    price: Decimal,
+    direction: &Direction,
+) -> Decimal {
     match direction {
-        Direction::Long => calculate_long_liquidation_price(leverage, price),
-        Direction::Short => calculate_short_liquidation_price(leverage, price),
+        Direction::Long => calculate_long_liquidation_price(contract_symbol, leverage, price),
+        Direction::Short => calculate_short_liquidation_price(contract_symbol, leverage, price),
     }
 }
 
@@ -442,6 +478,7 @@ mod tests {
     #[test]
     fn average_execution_price_extend_position() {
         let price = compute_average_execution_price(
+            ContractSymbol::BtcUsd,
             10_000.0,
             30_000.0,
             10.0,
@@ -456,6 +493,7 @@ mod tests {
     #[test]
     fn average_execution_price_reduce_position() {
         let price = compute_average_execution_price(
+            ContractSymbol::BtcUsd,
             10_000.0,
             20_000.0,
             10.0,
@@ -470,6 +508,7 @@ mod tests {
     #[test]
     fn average_execution_price_change_position_direction() {
         let price = compute_average_execution_price(
+            ContractSymbol::BtcUsd,
             10_000.0,
             20_000.0,
             10.0,
@@ -144,7 +144,8 @@ impl Rollover {
 
     pub fn event_id(&self) -> String {
         let maturity_time = self.maturity_time().unix_timestamp();
-        format!("{}{maturity_time}", self.contract_symbol)
+        let event_id_prefix = self.contract_symbol.metadata().oracle_event_id_prefix;
+        format!("{event_id_prefix}{maturity_time}")
     }
 
     /// Calculates the maturity time based on the current expiry timestamp.
@@ -6,23 +6,44 @@ use anyhow::Result;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::PooledConnection;
 use diesel::PgConnection;
-use time::OffsetDateTime;
-use trade::bitmex_client::BitmexClient;
-use trade::bitmex_client::Quote;
+use trade::ContractSymbol;
+use trade::Price;
 
 pub async fn sync(node: Node) -> Result<()> {
     let mut conn = node.pool.get()?;
 
     let positions = db::positions::Position::get_all_open_or_closing_positions(&mut conn)?;
 
-    // TODO(holzeis): we should not use the bitmex quote here, but rather our own orderbook.
-    let current_quote = BitmexClient::get_quote(&node.inner.network, &OffsetDateTime::now_utc())
-        .await
-        .context("Failed to fetch quote from BitMEX")?;
+    let price_source = node.price_source().await;
 
-    for position in positions.iter() {
-        if let Err(e) = sync_position(&mut conn, position, current_quote.clone()) {
-            tracing::error!(position_id=%position.id, ?current_quote, "Failed to update position's unrealized pnl in database: {e:#}")
+    for contract_symbol in ContractSymbol::ALL {
+        let positions = positions
+            .iter()
+            .filter(|position| position.contract_symbol == contract_symbol)
+            .collect::<Vec<_>>();
+
+        if positions.is_empty() {
+            continue;
+        }
+
+        // A contract symbol which is not supported by the price source must not prevent updating
+        // the positions in the other contract symbols.
+        let current_price = match price_source.get_price(contract_symbol).await {
+            Ok(price) => price,
+            Err(e) => {
+                tracing::warn!(
+                    %contract_symbol,
+                    price_source=price_source.name(),
+                    "Failed to fetch price, skipping unrealized pnl of positions in this contract symbol: {e:#}"
+                );
+                continue;
+            }
+        };
+
+        for position in positions {
+            if let Err(e) = sync_position(&mut conn, position, current_price) {
+                tracing::error!(position_id=%position.id, ?current_price, "Failed to update position's unrealized pnl in database: {e:#}")
+            }
         }
     }
 
@@ -32,9 +53,9 @@ pub async fn sync(node: Node) -> Result<()> {
 fn sync_position(
     conn: &mut PooledConnection<ConnectionManager<PgConnection>>,
     position: &Position,
-    quote: Quote,
+    price: Price,
 ) -> Result<()> {
-    let pnl = position.calculate_coordinator_pnl(quote)?;
+    let pnl = position.calculate_coordinator_pnl(price)?;
     db::positions::Position::update_unrealized_pnl(conn, position.id, pnl)
         .context("Failed to update unrealized pnl in db")?;
 
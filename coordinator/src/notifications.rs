@@ -12,6 +12,10 @@ pub enum NotificationKind {
     PositionSoonToExpire,
     PositionExpired,
     CollaborativeRevert,
+    LimitOrderFilled,
+    StopLossTriggered,
+    TakeProfitTriggered,
+    PositionLiquidated,
 }
 
 impl Display for NotificationKind {
@@ -21,6 +25,10 @@ impl Display for NotificationKind {
             NotificationKind::PositionExpired => write!(f, "PositionExpired"),
             NotificationKind::RolloverWindowOpen => write!(f, "RolloverWindowOpen"),
             NotificationKind::CollaborativeRevert => write!(f, "CollaborativeRevertPending"),
+            NotificationKind::LimitOrderFilled => write!(f, "LimitOrderFilled"),
+            NotificationKind::StopLossTriggered => write!(f, "StopLossTriggered"),
+            NotificationKind::TakeProfitTriggered => write!(f, "TakeProfitTriggered"),
+            NotificationKind::PositionLiquidated => write!(f, "PositionLiquidated"),
         }
     }
 }
@@ -113,6 +121,22 @@ fn build_notification<'a>(kind: NotificationKind) -> fcm::Notification<'a> {
             notification_builder.title("Error detected");
             notification_builder.body("Please open your app to recover your funds.");
         }
+        NotificationKind::LimitOrderFilled => {
+            notification_builder.title("Your limit order has been filled");
+            notification_builder.body("Open your app to execute the trade.");
+        }
+        NotificationKind::StopLossTriggered => {
+            notification_builder.title("Your stop loss has been triggered");
+            notification_builder.body("Open your app to close your position.");
+        }
+        NotificationKind::TakeProfitTriggered => {
+            notification_builder.title("Your take profit has been triggered");
+            notification_builder.body("Open your app to close your position.");
+        }
+        NotificationKind::PositionLiquidated => {
+            notification_builder.title("Your position has been liquidated");
+            notification_builder.body("Open your app to close your position.");
+        }
     }
     notification_builder.finalize()
 }
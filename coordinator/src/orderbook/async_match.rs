@@ -13,6 +13,7 @@ use commons::Matches;
 use commons::Message;
 use commons::OrderReason;
 use commons::OrderState;
+use commons::OrderType;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::Pool;
 use diesel::PgConnection;
@@ -98,9 +99,18 @@ async fn process_pending_match(
         let matches = matches::get_matches_by_order_id(&mut conn, order.id)?;
         let filled_with = get_filled_with_from_matches(matches, network, oracle_pk)?;
 
-        let message = match order.order_reason {
-            OrderReason::Manual => Message::Match(filled_with),
-            OrderReason::Expired => Message::AsyncMatch { order, filled_with },
+        let message = match (order.order_type, &order.order_reason) {
+            (OrderType::Market, OrderReason::Manual) => Message::Match(filled_with),
+            // A limit order might have been matched while the trader was offline, hence we can't
+            // assume that the trader knows about the order.
+            (OrderType::Limit, _)
+            | (
+                _,
+                OrderReason::Expired
+                | OrderReason::StopLoss
+                | OrderReason::TakeProfit
+                | OrderReason::Liquidated,
+            ) => Message::AsyncMatch { order, filled_with },
         };
 
         // Sending no optional push notification as this is only executed if the user just
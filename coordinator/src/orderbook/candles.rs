@@ -0,0 +1,340 @@
+use crate::db;
+use crate::orderbook::db::orders;
+use anyhow::Result;
+use commons::best_current_price;
+use commons::Candle;
+use commons::Message;
+use commons::Price;
+use commons::PublicTrade;
+use commons::Resolution;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::PgConnection;
+use futures::future::RemoteHandle;
+use futures::FutureExt;
+use rust_decimal::Decimal;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use time::OffsetDateTime;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::spawn_blocking;
+use trade::ContractSymbol;
+
+/// Keeps the current candle of every contract symbol and resolution.
+#[derive(Default)]
+pub struct CandleAggregator {
+    candles: HashMap<(ContractSymbol, Resolution), Candle>,
+}
+
+impl CandleAggregator {
+    pub fn new(candles: Vec<Candle>) -> Self {
+        let candles = candles
+            .into_iter()
+            .map(|candle| ((candle.contract_symbol, candle.resolution), candle))
+            .collect();
+
+        Self { candles }
+    }
+
+    /// Adds the price at the given time to the candles of all resolutions.
+    ///
+    /// Returns the candles which changed. Prices older than the current candle are ignored.
+    pub fn update(
+        &mut self,
+        contract_symbol: ContractSymbol,
+        timestamp: OffsetDateTime,
+        price: Decimal,
+        volume: Decimal,
+    ) -> Vec<Candle> {
+        let mut updated = vec![];
+        for resolution in Resolution::ALL {
+            match self.candles.entry((contract_symbol, resolution)) {
+                Entry::Occupied(mut entry) => {
+                    let candle = entry.get_mut();
+                    if candle.contains(timestamp) {
+                        if candle.update(price, volume) {
+                            updated.push(candle.clone());
+                        }
+                    } else if timestamp > candle.start {
+                        *candle =
+                            Candle::new(contract_symbol, resolution, timestamp, price, volume);
+                        updated.push(candle.clone());
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    let candle = Candle::new(contract_symbol, resolution, timestamp, price, volume);
+                    updated.push(entry.insert(candle).clone());
+                }
+            }
+        }
+
+        updated
+    }
+
+    fn add_trade(&mut self, trade: &PublicTrade) -> Vec<Candle> {
+        self.update(
+            trade.contract_symbol,
+            trade.timestamp,
+            trade.price,
+            trade.quantity,
+        )
+    }
+}
+
+/// Aggregates executed trades and the best bid and ask of the orderbook into candles. Every
+/// updated candle is persisted and published on the price feed.
+///
+/// On startup, the candles are backfilled from the trade history.
+pub fn monitor(
+    pool: Pool<ConnectionManager<PgConnection>>,
+    tx_price_feed: broadcast::Sender<Message>,
+) -> RemoteHandle<()> {
+    let mut price_feed = tx_price_feed.subscribe();
+    let (fut, remote_handle) = async move {
+        let latest_candles = spawn_blocking({
+            let pool = pool.clone();
+            move || {
+                let mut conn = pool.get()?;
+                backfill(&mut conn)?;
+                let latest_candles = db::candles::get_latest(&mut conn)?;
+                anyhow::Ok(latest_candles)
+            }
+        })
+        .await
+        .expect("task to complete")
+        .unwrap_or_else(|e| {
+            tracing::error!("Failed to backfill candles. Error: {e:#}");
+            vec![]
+        });
+
+        let mut aggregator = CandleAggregator::new(latest_candles);
+
+        loop {
+            let updated = match price_feed.recv().await {
+                Ok(Message::Trade(trade)) => aggregator.add_trade(&trade),
+                Ok(
+                    Message::AllOrders(_)
+                    | Message::NewOrder(_)
+                    | Message::DeleteOrder(_)
+                    | Message::Update(_),
+                ) => match get_best_prices(pool.clone()).await {
+                    Ok(prices) => {
+                        let now = OffsetDateTime::now_utc();
+                        prices
+                            .into_iter()
+                            .filter_map(|(contract_symbol, price)| {
+                                mid_price(&price).map(|price| (contract_symbol, price))
+                            })
+                            .flat_map(|(contract_symbol, price)| {
+                                aggregator.update(contract_symbol, now, price, Decimal::ZERO)
+                            })
+                            .collect()
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to get best prices for candles. Error: {e:#}");
+                        continue;
+                    }
+                },
+                Ok(_) => continue,
+                Err(RecvError::Closed) => {
+                    tracing::error!("Price feed sender died! Channel closed.");
+                    break;
+                }
+                Err(RecvError::Lagged(skip)) => {
+                    tracing::warn!(%skip, "Lagging behind on price feed.");
+                    continue;
+                }
+            };
+
+            if updated.is_empty() {
+                continue;
+            }
+
+            if let Err(e) = persist(pool.clone(), updated.clone()).await {
+                tracing::error!("Failed to persist candles. Error: {e:#}");
+            }
+
+            for candle in updated {
+                if let Err(e) = tx_price_feed.send(Message::Candle(candle)) {
+                    tracing::debug!("Nobody is subscribed to candles: {e:#}");
+                }
+            }
+        }
+    }
+    .remote_handle();
+
+    tokio::spawn(fut);
+
+    remote_handle
+}
+
+/// Aggregates the trades executed since the start of the most recent persisted candle.
+///
+/// Candles which already exist are left untouched, as they have been aggregated live, including
+/// the best bid and ask of the orderbook.
+pub fn backfill(conn: &mut PgConnection) -> Result<()> {
+    let since = db::candles::get_latest_start(conn)?;
+    let trades = db::trades::get_all_since(conn, since)?
+        .iter()
+        .map(PublicTrade::from)
+        .collect::<Vec<_>>();
+
+    let candles = aggregate(&trades);
+    let inserted = db::candles::insert_missing(conn, &candles)?;
+
+    tracing::info!(
+        trades = trades.len(),
+        inserted,
+        "Backfilled candles from trade history"
+    );
+
+    Ok(())
+}
+
+/// Aggregates the trades, ordered from oldest to newest, into candles.
+fn aggregate(trades: &[PublicTrade]) -> Vec<Candle> {
+    let mut aggregator = CandleAggregator::default();
+
+    let mut candles = HashMap::new();
+    for trade in trades {
+        for candle in aggregator.add_trade(trade) {
+            candles.insert(
+                (candle.contract_symbol, candle.resolution, candle.start),
+                candle,
+            );
+        }
+    }
+
+    candles.into_values().collect()
+}
+
+async fn get_best_prices(
+    pool: Pool<ConnectionManager<PgConnection>>,
+) -> Result<HashMap<ContractSymbol, Price>> {
+    let orders = spawn_blocking(move || {
+        let mut conn = pool.get()?;
+        let orders = orders::all_limit_orders(&mut conn)?;
+        anyhow::Ok(orders)
+    })
+    .await
+    .expect("task to complete")?;
+
+    Ok(best_current_price(&orders))
+}
+
+async fn persist(pool: Pool<ConnectionManager<PgConnection>>, candles: Vec<Candle>) -> Result<()> {
+    spawn_blocking(move || {
+        let mut conn = pool.get()?;
+        for candle in candles.iter() {
+            db::candles::upsert(&mut conn, candle)?;
+        }
+        anyhow::Ok(())
+    })
+    .await
+    .expect("task to complete")
+}
+
+fn mid_price(price: &Price) -> Option<Decimal> {
+    match (price.bid, price.ask) {
+        (Some(bid), Some(ask)) => Some((bid + ask) / Decimal::TWO),
+        (Some(price), None) | (None, Some(price)) => Some(price),
+        (None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::candles::aggregate;
+    use crate::orderbook::candles::CandleAggregator;
+    use commons::PublicTrade;
+    use commons::Resolution;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+    use time::macros::datetime;
+    use time::OffsetDateTime;
+    use trade::ContractSymbol;
+    use trade::Direction;
+
+    #[test]
+    fn given_trades_in_same_minute_then_one_candle_per_resolution() {
+        let trades = vec![
+            dummy_trade(datetime!(2023-12-19 10:17:01 UTC), dec!(40_000), dec!(100)),
+            dummy_trade(datetime!(2023-12-19 10:17:20 UTC), dec!(41_000), dec!(50)),
+            dummy_trade(datetime!(2023-12-19 10:17:40 UTC), dec!(39_500), dec!(10)),
+        ];
+
+        let candles = aggregate(&trades);
+
+        assert_eq!(candles.len(), Resolution::ALL.len());
+        for candle in candles {
+            assert_eq!(candle.open, dec!(40_000));
+            assert_eq!(candle.high, dec!(41_000));
+            assert_eq!(candle.low, dec!(39_500));
+            assert_eq!(candle.close, dec!(39_500));
+            assert_eq!(candle.volume, dec!(160));
+        }
+    }
+
+    #[test]
+    fn given_trades_in_different_minutes_then_new_minute_candles() {
+        let trades = vec![
+            dummy_trade(datetime!(2023-12-19 10:17:01 UTC), dec!(40_000), dec!(100)),
+            dummy_trade(datetime!(2023-12-19 10:18:01 UTC), dec!(41_000), dec!(50)),
+        ];
+
+        let candles = aggregate(&trades);
+
+        let mut minute_candles = candles
+            .iter()
+            .filter(|candle| candle.resolution == Resolution::OneMinute)
+            .collect::<Vec<_>>();
+        minute_candles.sort_by_key(|candle| candle.start);
+        assert_eq!(minute_candles.len(), 2);
+        assert_eq!(minute_candles[0].start, datetime!(2023-12-19 10:17:00 UTC));
+        assert_eq!(minute_candles[0].close, dec!(40_000));
+        assert_eq!(minute_candles[1].start, datetime!(2023-12-19 10:18:00 UTC));
+        assert_eq!(minute_candles[1].open, dec!(41_000));
+
+        let hour_candles = candles
+            .iter()
+            .filter(|candle| candle.resolution == Resolution::OneHour)
+            .collect::<Vec<_>>();
+        assert_eq!(hour_candles.len(), 1);
+        assert_eq!(hour_candles[0].volume, dec!(150));
+    }
+
+    #[test]
+    fn given_outdated_price_then_candle_is_not_updated() {
+        let mut aggregator = CandleAggregator::default();
+
+        aggregator.update(
+            ContractSymbol::BtcUsd,
+            datetime!(2023-12-19 10:18:01 UTC),
+            dec!(40_000),
+            Decimal::ZERO,
+        );
+        let updated = aggregator.update(
+            ContractSymbol::BtcUsd,
+            datetime!(2023-12-19 10:17:01 UTC),
+            dec!(41_000),
+            Decimal::ZERO,
+        );
+
+        // Only the candles of the resolutions whose current candle contains the outdated price
+        // are updated.
+        assert!(updated
+            .iter()
+            .all(|candle| candle.resolution != Resolution::OneMinute));
+    }
+
+    fn dummy_trade(timestamp: OffsetDateTime, price: Decimal, quantity: Decimal) -> PublicTrade {
+        PublicTrade {
+            contract_symbol: ContractSymbol::BtcUsd,
+            price,
+            quantity,
+            direction: Direction::Long,
+            timestamp,
+        }
+    }
+}
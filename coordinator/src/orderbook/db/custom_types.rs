@@ -3,6 +3,7 @@ use crate::schema::sql_types::MatchStateType;
 use crate::schema::sql_types::OrderReasonType;
 use crate::schema::sql_types::OrderStateType;
 use crate::schema::sql_types::OrderTypeType;
+use crate::schema::sql_types::TimeInForceType;
 use diesel::deserialize;
 use diesel::deserialize::FromSql;
 use diesel::pg::Pg;
@@ -142,6 +143,13 @@ pub(crate) enum OrderReason {
     Manual,
     /// The order has been create automatically as the position expired.
     Expired,
+    /// The order has been created automatically as the stop loss of the position was triggered.
+    StopLoss,
+    /// The order has been created automatically as the take profit of the position was
+    /// triggered.
+    TakeProfit,
+    /// The order has been created automatically as the position crossed its liquidation price.
+    Liquidated,
 }
 
 impl QueryId for OrderReasonType {
@@ -158,6 +166,9 @@ impl ToSql<OrderReasonType, Pg> for OrderReason {
         match *self {
             OrderReason::Manual => out.write_all(b"Manual")?,
             OrderReason::Expired => out.write_all(b"Expired")?,
+            OrderReason::StopLoss => out.write_all(b"StopLoss")?,
+            OrderReason::TakeProfit => out.write_all(b"TakeProfit")?,
+            OrderReason::Liquidated => out.write_all(b"Liquidated")?,
         }
         Ok(IsNull::No)
     }
@@ -168,6 +179,51 @@ impl FromSql<OrderReasonType, Pg> for OrderReason {
         match bytes.as_bytes() {
             b"Manual" => Ok(OrderReason::Manual),
             b"Expired" => Ok(OrderReason::Expired),
+            b"StopLoss" => Ok(OrderReason::StopLoss),
+            b"TakeProfit" => Ok(OrderReason::TakeProfit),
+            b"Liquidated" => Ok(OrderReason::Liquidated),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, FromSqlRow, AsExpression)]
+#[diesel(sql_type = TimeInForceType)]
+pub(crate) enum TimeInForce {
+    ImmediateOrCancel,
+    FillOrKill,
+    GoodTillCancelled,
+    GoodTillDate,
+}
+
+impl QueryId for TimeInForceType {
+    type QueryId = TimeInForceType;
+    const HAS_STATIC_QUERY_ID: bool = false;
+
+    fn query_id() -> Option<TypeId> {
+        None
+    }
+}
+
+impl ToSql<TimeInForceType, Pg> for TimeInForce {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        match *self {
+            TimeInForce::ImmediateOrCancel => out.write_all(b"ImmediateOrCancel")?,
+            TimeInForce::FillOrKill => out.write_all(b"FillOrKill")?,
+            TimeInForce::GoodTillCancelled => out.write_all(b"GoodTillCancelled")?,
+            TimeInForce::GoodTillDate => out.write_all(b"GoodTillDate")?,
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<TimeInForceType, Pg> for TimeInForce {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"ImmediateOrCancel" => Ok(TimeInForce::ImmediateOrCancel),
+            b"FillOrKill" => Ok(TimeInForce::FillOrKill),
+            b"GoodTillCancelled" => Ok(TimeInForce::GoodTillCancelled),
+            b"GoodTillDate" => Ok(TimeInForce::GoodTillDate),
             _ => Err("Unrecognized enum variant".into()),
         }
     }
@@ -1,11 +1,18 @@
+use crate::db::positions::ContractSymbol;
+use crate::orderbook::db::custom_types::Direction;
 use crate::orderbook::db::custom_types::MatchState;
 use crate::orderbook::trading::TraderMatchParams;
 use crate::schema::matches;
+use crate::schema::orders;
 use anyhow::ensure;
 use anyhow::Result;
 use bitcoin::secp256k1::PublicKey;
+use commons::Fill;
+use commons::HistoryFilter;
+use diesel::BoolExpressionMethods;
 use diesel::ExpressionMethods;
 use diesel::Insertable;
+use diesel::JoinOnDsl;
 use diesel::PgConnection;
 use diesel::QueryDsl;
 use diesel::QueryResult;
@@ -72,6 +79,113 @@ pub fn get_matches_by_order_id(
     Ok(matches)
 }
 
+/// Returns the fills of the given trader matching the filter, most recent first.
+///
+/// Only the match of the taker is set to `Filled` once the trade has been executed. Hence, the
+/// fills of the trader's limit orders are looked up through the corresponding taker matches.
+pub fn get_fills_by_trader_id(
+    conn: &mut PgConnection,
+    trader_id: PublicKey,
+    filter: &HistoryFilter,
+) -> QueryResult<Vec<Fill>> {
+    let trader_id = trader_id.to_string();
+
+    let mut query = matches::table
+        // The order of the taker.
+        .inner_join(orders::table.on(orders::trader_order_id.eq(matches::order_id)))
+        .filter(matches::match_state.eq(MatchState::Filled))
+        .select((
+            matches::id,
+            matches::trader_id,
+            matches::order_id,
+            matches::match_order_id,
+            orders::contract_symbol,
+            orders::direction,
+            matches::execution_price,
+            matches::quantity,
+            matches::created_at,
+        ))
+        .into_boxed();
+
+    query = match filter.direction {
+        Some(direction) => query.filter(
+            matches::trader_id
+                .eq(trader_id.clone())
+                .and(orders::direction.eq(Direction::from(direction)))
+                .or(matches::match_trader_id
+                    .eq(trader_id.clone())
+                    .and(orders::direction.eq(Direction::from(direction.opposite())))),
+        ),
+        None => query.filter(
+            matches::trader_id
+                .eq(trader_id.clone())
+                .or(matches::match_trader_id.eq(trader_id.clone())),
+        ),
+    };
+    if let Some(contract_symbol) = filter.contract_symbol {
+        query = query.filter(orders::contract_symbol.eq(ContractSymbol::from(contract_symbol)));
+    }
+    if let Some(from) = filter.from {
+        query = query.filter(matches::created_at.ge(from));
+    }
+    if let Some(to) = filter.to {
+        query = query.filter(matches::created_at.lt(to));
+    }
+
+    let fills = query
+        .order_by(matches::created_at.desc())
+        .then_order_by(matches::id.desc())
+        .limit(filter.limit())
+        .offset(filter.offset())
+        .load::<(
+            Uuid,
+            String,
+            Uuid,
+            Uuid,
+            ContractSymbol,
+            Direction,
+            f32,
+            f32,
+            OffsetDateTime,
+        )>(conn)?;
+
+    let fills = fills
+        .into_iter()
+        .map(
+            |(
+                match_id,
+                taker_id,
+                taker_order_id,
+                maker_order_id,
+                contract_symbol,
+                taker_direction,
+                execution_price,
+                quantity,
+                created_at,
+            )| {
+                let taker_direction = trade::Direction::from(taker_direction);
+                let (order_id, direction) = if taker_id == trader_id {
+                    (taker_order_id, taker_direction)
+                } else {
+                    (maker_order_id, taker_direction.opposite())
+                };
+
+                Fill {
+                    match_id,
+                    order_id,
+                    contract_symbol: contract_symbol.into(),
+                    direction,
+                    price: Decimal::from_f32(execution_price).expect("to fit into decimal"),
+                    quantity: Decimal::from_f32(quantity).expect("to fit into decimal"),
+                    timestamp: created_at,
+                }
+            },
+        )
+        .collect();
+
+    Ok(fills)
+}
+
 pub fn set_match_state_by_order_id(
     conn: &mut PgConnection,
     order_id: Uuid,
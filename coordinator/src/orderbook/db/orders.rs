@@ -4,14 +4,20 @@ use crate::orderbook::db::custom_types::MatchState;
 use crate::orderbook::db::custom_types::OrderReason;
 use crate::orderbook::db::custom_types::OrderState;
 use crate::orderbook::db::custom_types::OrderType;
+use crate::orderbook::db::custom_types::TimeInForce;
 use crate::schema::matches;
 use crate::schema::orders;
+use anyhow::ensure;
+use anyhow::Context;
+use anyhow::Result;
 use bitcoin::secp256k1::PublicKey;
+use commons::HistoryFilter;
 use commons::NewOrder as OrderbookNewOrder;
 use commons::Order as OrderbookOrder;
 use commons::OrderReason as OrderBookOrderReason;
 use commons::OrderState as OrderBookOrderState;
 use commons::OrderType as OrderBookOrderType;
+use commons::TimeInForce as OrderBookTimeInForce;
 use diesel::prelude::*;
 use diesel::result::QueryResult;
 use diesel::PgConnection;
@@ -80,6 +86,28 @@ impl From<OrderBookOrderState> for OrderState {
     }
 }
 
+impl From<TimeInForce> for OrderBookTimeInForce {
+    fn from(value: TimeInForce) -> Self {
+        match value {
+            TimeInForce::ImmediateOrCancel => OrderBookTimeInForce::ImmediateOrCancel,
+            TimeInForce::FillOrKill => OrderBookTimeInForce::FillOrKill,
+            TimeInForce::GoodTillCancelled => OrderBookTimeInForce::GoodTillCancelled,
+            TimeInForce::GoodTillDate => OrderBookTimeInForce::GoodTillDate,
+        }
+    }
+}
+
+impl From<OrderBookTimeInForce> for TimeInForce {
+    fn from(value: OrderBookTimeInForce) -> Self {
+        match value {
+            OrderBookTimeInForce::ImmediateOrCancel => TimeInForce::ImmediateOrCancel,
+            OrderBookTimeInForce::FillOrKill => TimeInForce::FillOrKill,
+            OrderBookTimeInForce::GoodTillCancelled => TimeInForce::GoodTillCancelled,
+            OrderBookTimeInForce::GoodTillDate => TimeInForce::GoodTillDate,
+        }
+    }
+}
+
 #[derive(Queryable, Debug, Clone)]
 struct Order {
     // this id is only internally but needs to be here or diesel complains
@@ -98,6 +126,7 @@ struct Order {
     pub leverage: f32,
     pub order_reason: OrderReason,
     pub stable: bool,
+    pub time_in_force: TimeInForce,
 }
 
 impl From<Order> for OrderbookOrder {
@@ -117,6 +146,7 @@ impl From<Order> for OrderbookOrder {
             order_state: value.order_state.into(),
             order_reason: value.order_reason.into(),
             stable: value.stable,
+            time_in_force: value.time_in_force.into(),
         }
     }
 }
@@ -126,6 +156,9 @@ impl From<OrderReason> for OrderBookOrderReason {
         match value {
             OrderReason::Manual => OrderBookOrderReason::Manual,
             OrderReason::Expired => OrderBookOrderReason::Expired,
+            OrderReason::StopLoss => OrderBookOrderReason::StopLoss,
+            OrderReason::TakeProfit => OrderBookOrderReason::TakeProfit,
+            OrderReason::Liquidated => OrderBookOrderReason::Liquidated,
         }
     }
 }
@@ -135,6 +168,9 @@ impl From<OrderBookOrderReason> for OrderReason {
         match value {
             OrderBookOrderReason::Manual => OrderReason::Manual,
             OrderBookOrderReason::Expired => OrderReason::Expired,
+            OrderBookOrderReason::StopLoss => OrderReason::StopLoss,
+            OrderBookOrderReason::TakeProfit => OrderReason::TakeProfit,
+            OrderBookOrderReason::Liquidated => OrderReason::Liquidated,
         }
     }
 }
@@ -153,6 +189,7 @@ struct NewOrder {
     pub contract_symbol: ContractSymbol,
     pub leverage: f32,
     pub stable: bool,
+    pub time_in_force: TimeInForce,
 }
 
 impl From<OrderbookNewOrder> for NewOrder {
@@ -177,6 +214,7 @@ impl From<OrderbookNewOrder> for NewOrder {
             contract_symbol: value.contract_symbol.into(),
             leverage: value.leverage,
             stable: value.stable,
+            time_in_force: value.time_in_force.into(),
         }
     }
 }
@@ -184,7 +222,11 @@ impl From<OrderbookNewOrder> for NewOrder {
 pub fn all_limit_orders(conn: &mut PgConnection) -> QueryResult<Vec<OrderbookOrder>> {
     let orders = orders::table
         .filter(orders::order_type.eq(OrderType::Limit))
-        .filter(orders::expiry.gt(OffsetDateTime::now_utc()))
+        .filter(
+            orders::expiry
+                .gt(OffsetDateTime::now_utc())
+                .or(orders::time_in_force.eq(TimeInForce::GoodTillCancelled)),
+        )
         .filter(orders::order_state.ne(OrderState::Failed))
         .load::<Order>(conn)?;
 
@@ -205,7 +247,11 @@ pub fn all_by_direction_and_type(
 
     let orders: Vec<Order> = if filter_expired {
         filters
-            .filter(orders::expiry.gt(OffsetDateTime::now_utc()))
+            .filter(
+                orders::expiry
+                    .gt(OffsetDateTime::now_utc())
+                    .or(orders::time_in_force.eq(TimeInForce::GoodTillCancelled)),
+            )
             .load::<Order>(conn)?
     } else {
         filters.load::<Order>(conn)?
@@ -225,7 +271,11 @@ pub fn get_all_orders(
         .filter(orders::order_type.eq(OrderType::from(order_type)));
     let orders: Vec<Order> = if filter_expired {
         filters
-            .filter(orders::expiry.gt(OffsetDateTime::now_utc()))
+            .filter(
+                orders::expiry
+                    .gt(OffsetDateTime::now_utc())
+                    .or(orders::time_in_force.eq(TimeInForce::GoodTillCancelled)),
+            )
             .load::<Order>(conn)?
     } else {
         filters.load::<Order>(conn)?
@@ -251,17 +301,52 @@ pub fn insert(
     Ok(OrderbookOrder::from(order))
 }
 
-/// Returns the number of affected rows: 1.
-pub fn set_is_taken(
+#[derive(AsChangeset)]
+#[diesel(table_name = orders)]
+struct OpenOrderUpdate {
+    price: Option<f32>,
+    quantity: Option<f32>,
+    expiry: Option<OffsetDateTime>,
+    order_state: Option<OrderState>,
+}
+
+/// Changes the price, quantity and expiry of the open order, leaving those which are `None` as
+/// they are, and takes it out of the orderbook if `taken` is set.
+///
+/// The order is only updated while it is open, hence a filled or cancelled order is never
+/// amended nor reopened. Returns `None` if the order is not open.
+pub fn update_open_order(
     conn: &mut PgConnection,
     id: Uuid,
-    is_taken: bool,
-) -> QueryResult<OrderbookOrder> {
-    if is_taken {
-        set_order_state(conn, id, commons::OrderState::Taken)
-    } else {
-        set_order_state(conn, id, commons::OrderState::Open)
-    }
+    price: Option<Decimal>,
+    quantity: Option<Decimal>,
+    expiry: Option<OffsetDateTime>,
+    taken: bool,
+) -> Result<Option<OrderbookOrder>> {
+    let to_f32 = |value: Decimal| {
+        value
+            .round_dp(2)
+            .to_f32()
+            .with_context(|| format!("Cannot convert {value} to f32"))
+    };
+
+    let update = OpenOrderUpdate {
+        price: price.map(to_f32).transpose()?,
+        quantity: quantity.map(to_f32).transpose()?,
+        expiry,
+        order_state: taken.then_some(OrderState::Taken),
+    };
+
+    let order = conn.transaction(|conn| {
+        diesel::update(orders::table)
+            .filter(orders::trader_order_id.eq(id))
+            .filter(orders::order_state.eq(OrderState::Open))
+            .set(update)
+            .get_result::<Order>(conn)
+            .optional()
+    })?;
+
+    Ok(order.map(OrderbookOrder::from))
 }
 
 /// Returns the number of affected rows: 1.
@@ -278,6 +363,73 @@ pub fn set_order_state(
     Ok(OrderbookOrder::from(order))
 }
 
+/// Splits `quantity` off the order with the given id into a new order with `filled_order_id`.
+///
+/// This is used if an order gets only partially filled: the new order represents the filled part
+/// and is used to execute the trade, while the original order remains in the orderbook with the
+/// remaining quantity.
+///
+/// The original order is locked while splitting, as it may be matched by several orders at the
+/// same time. Fails if the order is not open anymore or if less than `quantity` remains.
+pub fn split_order(
+    conn: &mut PgConnection,
+    order_id: Uuid,
+    filled_order_id: Uuid,
+    quantity: Decimal,
+) -> Result<OrderbookOrder> {
+    conn.transaction(|conn| {
+        let order: Order = orders::table
+            .filter(orders::trader_order_id.eq(order_id))
+            .filter(orders::order_state.eq(OrderState::Open))
+            .for_update()
+            .first(conn)
+            .optional()?
+            .with_context(|| format!("Order {order_id} is not open"))?;
+
+        let remaining_quantity = Decimal::from_f32(order.quantity)
+            .context("Order quantity does not fit into Decimal")?
+            - quantity;
+
+        ensure!(
+            remaining_quantity > Decimal::ZERO,
+            "Order {order_id} has only {} contracts left, cannot fill {quantity}",
+            remaining_quantity + quantity
+        );
+
+        diesel::update(orders::table)
+            .filter(orders::trader_order_id.eq(order_id))
+            .set(
+                orders::quantity.eq(remaining_quantity
+                    .round_dp(2)
+                    .to_f32()
+                    .context("Remaining quantity does not fit into f32")?),
+            )
+            .execute(conn)?;
+
+        let filled_order: Order = diesel::insert_into(orders::table)
+            .values(NewOrder {
+                trader_order_id: filled_order_id,
+                price: order.price,
+                trader_id: order.trader_id,
+                direction: order.direction,
+                quantity: quantity
+                    .round_dp(2)
+                    .to_f32()
+                    .context("Filled quantity does not fit into f32")?,
+                order_type: order.order_type,
+                expiry: order.expiry,
+                order_reason: order.order_reason,
+                contract_symbol: order.contract_symbol,
+                leverage: order.leverage,
+                stable: order.stable,
+                time_in_force: order.time_in_force,
+            })
+            .get_result(conn)?;
+
+        Ok(OrderbookOrder::from(filled_order))
+    })
+}
+
 pub fn set_expired_limit_orders_to_failed(
     conn: &mut PgConnection,
 ) -> QueryResult<Vec<OrderbookOrder>> {
@@ -285,6 +437,8 @@ pub fn set_expired_limit_orders_to_failed(
         .filter(orders::order_state.eq(OrderState::Open))
         .filter(orders::order_type.eq(OrderType::Limit))
         .filter(orders::expiry.lt(OffsetDateTime::now_utc()))
+        // Good-till-cancelled orders remain in the orderbook regardless of their expiry.
+        .filter(orders::time_in_force.ne(TimeInForce::GoodTillCancelled))
         .set(orders::order_state.eq(OrderState::Failed))
         .get_results(conn)?;
 
@@ -317,6 +471,95 @@ pub fn get_by_trader_id_and_state(
         .optional()
 }
 
+/// Returns the most recent order of the trader whose match has been executed, i.e. the order
+/// which changed the trader's position last.
+///
+/// The order state alone does not tell whether an order has been executed, as cancelled orders
+/// are taken out of the orderbook as well.
+pub fn get_last_executed_by_trader_id(
+    conn: &mut PgConnection,
+    trader_id: PublicKey,
+) -> QueryResult<Option<OrderbookOrder>> {
+    let executed_order_ids = matches::table
+        .filter(matches::trader_id.eq(trader_id.to_string()))
+        .filter(matches::match_state.eq(MatchState::Filled))
+        .select(matches::order_id);
+
+    orders::table
+        .filter(orders::trader_order_id.eq_any(executed_order_ids))
+        .order_by(orders::timestamp.desc())
+        .first::<Order>(conn)
+        .map(OrderbookOrder::from)
+        .optional()
+}
+
+/// Returns whether an order of the trader with the given reason has failed since the given time,
+/// e.g. because it did not find a match.
+pub fn has_failed_since(
+    conn: &mut PgConnection,
+    trader_id: PublicKey,
+    order_reason: OrderBookOrderReason,
+    since: OffsetDateTime,
+) -> QueryResult<bool> {
+    let failed_orders: i64 = orders::table
+        .filter(orders::trader_id.eq(trader_id.to_string()))
+        .filter(orders::order_reason.eq(OrderReason::from(order_reason)))
+        .filter(orders::order_state.eq(OrderState::Failed))
+        .filter(orders::timestamp.gt(since))
+        .count()
+        .get_result(conn)?;
+
+    Ok(failed_orders > 0)
+}
+
+/// Returns the number of limit orders the trader has in the orderbook.
+pub fn count_open_limit_orders(conn: &mut PgConnection, trader_id: PublicKey) -> QueryResult<i64> {
+    orders::table
+        .filter(orders::trader_id.eq(trader_id.to_string()))
+        .filter(orders::order_type.eq(OrderType::Limit))
+        .filter(orders::order_state.eq(OrderState::Open))
+        .filter(
+            orders::expiry
+                .gt(OffsetDateTime::now_utc())
+                .or(orders::time_in_force.eq(TimeInForce::GoodTillCancelled)),
+        )
+        .count()
+        .get_result(conn)
+}
+
+/// Returns the orders of the given trader matching the filter, most recent first.
+pub fn get_by_trader_id(
+    conn: &mut PgConnection,
+    trader_id: PublicKey,
+    filter: &HistoryFilter,
+) -> QueryResult<Vec<OrderbookOrder>> {
+    let mut query = orders::table
+        .filter(orders::trader_id.eq(trader_id.to_string()))
+        .into_boxed();
+
+    if let Some(contract_symbol) = filter.contract_symbol {
+        query = query.filter(orders::contract_symbol.eq(ContractSymbol::from(contract_symbol)));
+    }
+    if let Some(direction) = filter.direction {
+        query = query.filter(orders::direction.eq(Direction::from(direction)));
+    }
+    if let Some(from) = filter.from {
+        query = query.filter(orders::timestamp.ge(from));
+    }
+    if let Some(to) = filter.to {
+        query = query.filter(orders::timestamp.lt(to));
+    }
+
+    let orders = query
+        .order_by(orders::timestamp.desc())
+        .then_order_by(orders::id.desc())
+        .limit(filter.limit())
+        .offset(filter.offset())
+        .load::<Order>(conn)?;
+
+    Ok(orders.into_iter().map(OrderbookOrder::from).collect())
+}
+
 /// Get all the filled matches for all the limit orders generated by `trader_id`.
 ///
 /// This can be used to calculate the implicit position of the maker, assuming that all the filled
@@ -0,0 +1,248 @@
+use commons::Depth;
+use commons::DepthFilter;
+use commons::Order;
+use commons::PriceLevel;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use trade::ContractSymbol;
+use trade::Direction;
+
+/// The open orders of a contract symbol aggregated by price, updated with every added or removed
+/// order.
+#[derive(Default)]
+pub struct DepthBook {
+    bids: BTreeMap<Decimal, Level>,
+    asks: BTreeMap<Decimal, Level>,
+}
+
+#[derive(Default, Clone, Copy)]
+struct Level {
+    quantity: Decimal,
+    orders: usize,
+}
+
+impl DepthBook {
+    pub fn add(&mut self, order: &Order) {
+        let level = self
+            .side_mut(order.direction)
+            .entry(order.price)
+            .or_default();
+        level.quantity += order.quantity;
+        level.orders += 1;
+    }
+
+    pub fn remove(&mut self, order: &Order) {
+        let side = self.side_mut(order.direction);
+        if let Some(level) = side.get_mut(&order.price) {
+            level.quantity -= order.quantity;
+            level.orders = level.orders.saturating_sub(1);
+
+            if level.orders == 0 {
+                side.remove(&order.price);
+            }
+        }
+    }
+
+    /// The highest price of the long orders.
+    pub fn best_bid(&self) -> Option<Decimal> {
+        self.bids.keys().next_back().copied()
+    }
+
+    /// The lowest price of the short orders.
+    pub fn best_ask(&self) -> Option<Decimal> {
+        self.asks.keys().next().copied()
+    }
+
+    pub fn depth(&self, contract_symbol: ContractSymbol, filter: &DepthFilter) -> Depth {
+        let levels = filter.levels();
+        let tick = filter.tick();
+
+        Depth {
+            contract_symbol,
+            bids: aggregate(self.bids.iter().rev(), levels, tick, Rounding::Down),
+            asks: aggregate(self.asks.iter(), levels, tick, Rounding::Up),
+        }
+    }
+
+    fn side_mut(&mut self, direction: Direction) -> &mut BTreeMap<Decimal, Level> {
+        match direction {
+            Direction::Long => &mut self.bids,
+            Direction::Short => &mut self.asks,
+        }
+    }
+}
+
+/// How prices are rounded to the tick, so that a grouped price level is never better than the
+/// orders it contains.
+#[derive(Clone, Copy)]
+enum Rounding {
+    Down,
+    Up,
+}
+
+/// Aggregates the price levels, given best first, into at most `levels` price levels.
+fn aggregate<'a>(
+    side: impl Iterator<Item = (&'a Decimal, &'a Level)>,
+    levels: usize,
+    tick: Option<Decimal>,
+    rounding: Rounding,
+) -> Vec<PriceLevel> {
+    let mut aggregated: Vec<PriceLevel> = vec![];
+    for (price, level) in side {
+        let price = match tick {
+            Some(tick) => match rounding {
+                Rounding::Down => (price / tick).floor() * tick,
+                Rounding::Up => (price / tick).ceil() * tick,
+            },
+            None => *price,
+        };
+
+        match aggregated.last_mut() {
+            Some(last) if last.price == price => {
+                last.quantity += level.quantity;
+                last.orders += level.orders;
+            }
+            _ => {
+                if aggregated.len() == levels {
+                    break;
+                }
+
+                aggregated.push(PriceLevel {
+                    price,
+                    quantity: level.quantity,
+                    orders: level.orders,
+                });
+            }
+        }
+    }
+
+    aggregated
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::depth::DepthBook;
+    use bitcoin::secp256k1::PublicKey;
+    use commons::DepthFilter;
+    use commons::Order;
+    use commons::OrderReason;
+    use commons::OrderState;
+    use commons::OrderType;
+    use commons::PriceLevel;
+    use commons::TimeInForce;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+    use std::str::FromStr;
+    use time::Duration;
+    use time::OffsetDateTime;
+    use trade::ContractSymbol;
+    use trade::Direction;
+    use uuid::Uuid;
+
+    #[test]
+    fn orders_at_the_same_price_are_aggregated() {
+        let mut book = DepthBook::default();
+        book.add(&dummy_order(Direction::Long, dec!(40_000), dec!(100)));
+        book.add(&dummy_order(Direction::Long, dec!(40_000), dec!(50)));
+        book.add(&dummy_order(Direction::Long, dec!(39_000), dec!(10)));
+        book.add(&dummy_order(Direction::Short, dec!(41_000), dec!(20)));
+
+        let depth = book.depth(ContractSymbol::BtcUsd, &DepthFilter::default());
+
+        assert_eq!(
+            depth.bids,
+            vec![
+                PriceLevel {
+                    price: dec!(40_000),
+                    quantity: dec!(150),
+                    orders: 2
+                },
+                PriceLevel {
+                    price: dec!(39_000),
+                    quantity: dec!(10),
+                    orders: 1
+                },
+            ]
+        );
+        assert_eq!(
+            depth.asks,
+            vec![PriceLevel {
+                price: dec!(41_000),
+                quantity: dec!(20),
+                orders: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn removed_order_empties_price_level() {
+        let order = dummy_order(Direction::Short, dec!(41_000), dec!(20));
+        let mut book = DepthBook::default();
+        book.add(&order);
+
+        book.remove(&order);
+
+        assert_eq!(book.best_ask(), None);
+        assert!(book
+            .depth(ContractSymbol::BtcUsd, &DepthFilter::default())
+            .asks
+            .is_empty());
+    }
+
+    #[test]
+    fn prices_are_grouped_by_tick_towards_the_spread() {
+        let mut book = DepthBook::default();
+        book.add(&dummy_order(Direction::Long, dec!(40_005), dec!(10)));
+        book.add(&dummy_order(Direction::Long, dec!(40_001), dec!(10)));
+        book.add(&dummy_order(Direction::Long, dec!(39_999), dec!(10)));
+        book.add(&dummy_order(Direction::Short, dec!(40_011), dec!(10)));
+        book.add(&dummy_order(Direction::Short, dec!(40_019), dec!(10)));
+
+        let depth = book.depth(
+            ContractSymbol::BtcUsd,
+            &DepthFilter {
+                levels: Some(1),
+                tick: Some(dec!(10)),
+            },
+        );
+
+        assert_eq!(
+            depth.bids,
+            vec![PriceLevel {
+                price: dec!(40_000),
+                quantity: dec!(20),
+                orders: 2
+            }]
+        );
+        assert_eq!(
+            depth.asks,
+            vec![PriceLevel {
+                price: dec!(40_020),
+                quantity: dec!(20),
+                orders: 2
+            }]
+        );
+    }
+
+    fn dummy_order(direction: Direction, price: Decimal, quantity: Decimal) -> Order {
+        Order {
+            id: Uuid::new_v4(),
+            price,
+            trader_id: PublicKey::from_str(
+                "027f31ebc5462c1fdce1b737ecff52d37d75dea43ce11c74d25aa297165faa2007",
+            )
+            .unwrap(),
+            direction,
+            leverage: 1.0,
+            contract_symbol: ContractSymbol::BtcUsd,
+            quantity,
+            order_type: OrderType::Limit,
+            timestamp: OffsetDateTime::now_utc(),
+            expiry: OffsetDateTime::now_utc() + Duration::minutes(1),
+            order_state: OrderState::Open,
+            order_reason: OrderReason::Manual,
+            stable: false,
+            time_in_force: TimeInForce::GoodTillDate,
+        }
+    }
+}
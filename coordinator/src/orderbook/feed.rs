@@ -0,0 +1,438 @@
+use crate::orderbook::db::orders;
+use crate::orderbook::depth::DepthBook;
+use commons::Depth;
+use commons::DepthFilter;
+use commons::Message;
+use commons::Order;
+use commons::OrderState;
+use commons::OrderbookDelta;
+use commons::Price;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::PgConnection;
+use futures::future::RemoteHandle;
+use futures::FutureExt;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::spawn_blocking;
+use trade::ContractSymbol;
+use uuid::Uuid;
+
+/// The number of sequenced messages a slow websocket connection may lag behind before it misses
+/// deltas and has to resync.
+const ORDERBOOK_FEED_BUFFER_SIZE: usize = 1000;
+
+/// Keeps the open limit orders of every contract symbol in memory and publishes their changes as
+/// sequenced deltas.
+pub struct OrderbookFeed {
+    books: RwLock<HashMap<ContractSymbol, Book>>,
+    /// Publishes [`Message::OrderbookDelta`]s and [`Message::TopOfBook`]s.
+    tx: broadcast::Sender<Message>,
+}
+
+#[derive(Default)]
+struct Book {
+    /// The sequence number of the latest delta.
+    seq: u64,
+    orders: HashMap<Uuid, Order>,
+    depth: DepthBook,
+    price: Price,
+}
+
+impl Book {
+    fn orders(&self) -> Vec<Order> {
+        let mut orders = self.orders.values().cloned().collect::<Vec<_>>();
+        orders.sort_by_key(|order| order.timestamp);
+        orders
+    }
+
+    fn apply(&mut self, delta: &OrderbookDelta) {
+        let order_id = match delta {
+            OrderbookDelta::New(order) | OrderbookDelta::Update(order) => order.id,
+            OrderbookDelta::Delete(order_id) => *order_id,
+        };
+
+        if let Some(order) = self.orders.remove(&order_id) {
+            self.depth.remove(&order);
+        }
+
+        match delta {
+            OrderbookDelta::New(order) | OrderbookDelta::Update(order)
+                if order.order_state == OrderState::Open =>
+            {
+                self.depth.add(order);
+                self.orders.insert(order.id, order.clone());
+            }
+            _ => {}
+        }
+    }
+
+    fn best_price(&self) -> Price {
+        Price {
+            bid: self.depth.best_bid(),
+            ask: self.depth.best_ask(),
+        }
+    }
+}
+
+impl OrderbookFeed {
+    /// Creates the feed from the current limit orders of the orderbook.
+    pub fn new(orders: Vec<Order>) -> Self {
+        let mut books = HashMap::<ContractSymbol, Book>::new();
+        for order in orders {
+            books
+                .entry(order.contract_symbol)
+                .or_default()
+                .apply(&OrderbookDelta::New(order));
+        }
+
+        for book in books.values_mut() {
+            book.price = book.best_price();
+        }
+
+        let (tx, _) = broadcast::channel(ORDERBOOK_FEED_BUFFER_SIZE);
+
+        Self {
+            books: RwLock::new(books),
+            tx,
+        }
+    }
+
+    /// Subscribes to the sequenced deltas and top of book updates of all contract symbols.
+    ///
+    /// Subscribe before taking a [`OrderbookFeed::snapshot`] so that no delta following the
+    /// snapshot is missed.
+    pub fn subscribe(&self) -> broadcast::Receiver<Message> {
+        self.tx.subscribe()
+    }
+
+    pub fn snapshot(&self, contract_symbol: ContractSymbol) -> Message {
+        let books = self.books.read();
+        let (seq, orders) = books
+            .get(&contract_symbol)
+            .map(|book| (book.seq, book.orders()))
+            .unwrap_or_default();
+
+        Message::OrderbookSnapshot {
+            contract_symbol,
+            seq,
+            orders,
+        }
+    }
+
+    pub fn top_of_book(&self, contract_symbol: ContractSymbol) -> Message {
+        let price = self
+            .books
+            .read()
+            .get(&contract_symbol)
+            .map(|book| book.price.clone())
+            .unwrap_or_default();
+
+        Message::TopOfBook {
+            contract_symbol,
+            price,
+        }
+    }
+
+    /// The orderbook of the contract symbol aggregated by price.
+    pub fn depth(&self, contract_symbol: ContractSymbol, filter: &DepthFilter) -> Depth {
+        match self.books.read().get(&contract_symbol) {
+            Some(book) => book.depth.depth(contract_symbol, filter),
+            None => DepthBook::default().depth(contract_symbol, filter),
+        }
+    }
+
+    /// Replaces all orders, e.g. after having missed some of their updates.
+    ///
+    /// The sequence numbers are increased without publishing a delta, so that clients notice the
+    /// gap with the next delta and resync.
+    fn reload(&self, orders: Vec<Order>) {
+        let mut reloaded = Self::new(orders).books.into_inner();
+
+        let mut books = self.books.write();
+        for (contract_symbol, book) in books.iter() {
+            let reloaded_book = reloaded.entry(*contract_symbol).or_default();
+            reloaded_book.seq = book.seq + 1;
+
+            if reloaded_book.price != book.price {
+                let _ = self.tx.send(Message::TopOfBook {
+                    contract_symbol: *contract_symbol,
+                    price: reloaded_book.price.clone(),
+                });
+            }
+        }
+
+        *books = reloaded;
+    }
+
+    /// Applies an orderbook message of the price feed and publishes the resulting delta.
+    fn apply(&self, message: &Message) {
+        let mut books = self.books.write();
+
+        let (contract_symbol, delta) = match message {
+            Message::NewOrder(order) => (order.contract_symbol, OrderbookDelta::New(order.clone())),
+            Message::Update(order) => {
+                (order.contract_symbol, OrderbookDelta::Update(order.clone()))
+            }
+            Message::DeleteOrder(order_id) => {
+                match books
+                    .iter()
+                    .find(|(_, book)| book.orders.contains_key(order_id))
+                {
+                    Some((contract_symbol, _)) => {
+                        (*contract_symbol, OrderbookDelta::Delete(*order_id))
+                    }
+                    // The order is not part of the orderbook, e.g. because it has already been
+                    // filled.
+                    None => return,
+                }
+            }
+            _ => return,
+        };
+
+        let book = books.entry(contract_symbol).or_default();
+        book.apply(&delta);
+        book.seq += 1;
+
+        // The messages are published while holding the lock, so that the sequence numbers are
+        // published in order and a snapshot is always consistent with the following deltas.
+        let _ = self.tx.send(Message::OrderbookDelta {
+            contract_symbol,
+            seq: book.seq,
+            delta,
+        });
+
+        let price = book.best_price();
+        if price != book.price {
+            book.price = price.clone();
+            let _ = self.tx.send(Message::TopOfBook {
+                contract_symbol,
+                price,
+            });
+        }
+    }
+}
+
+/// Keeps the [`OrderbookFeed`] up to date with the orders published on the price feed.
+pub fn monitor(
+    pool: Pool<ConnectionManager<PgConnection>>,
+    feed: Arc<OrderbookFeed>,
+    tx_price_feed: broadcast::Sender<Message>,
+) -> RemoteHandle<()> {
+    let mut price_feed = tx_price_feed.subscribe();
+    let (fut, remote_handle) = async move {
+        loop {
+            match price_feed.recv().await {
+                Ok(message) => feed.apply(&message),
+                Err(RecvError::Closed) => {
+                    tracing::error!("Price feed sender died! Channel closed.");
+                    break;
+                }
+                Err(RecvError::Lagged(skip)) => {
+                    tracing::warn!(%skip, "Lagging behind on price feed. Reloading orderbook.");
+
+                    let orders = spawn_blocking({
+                        let pool = pool.clone();
+                        move || {
+                            let mut conn = pool.get()?;
+                            let orders = orders::all_limit_orders(&mut conn)?;
+                            anyhow::Ok(orders)
+                        }
+                    })
+                    .await
+                    .expect("task to complete");
+
+                    match orders {
+                        Ok(orders) => feed.reload(orders),
+                        Err(e) => tracing::error!("Failed to reload orderbook. Error: {e:#}"),
+                    }
+                }
+            }
+        }
+    }
+    .remote_handle();
+
+    tokio::spawn(fut);
+
+    remote_handle
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::feed::OrderbookFeed;
+    use bitcoin::secp256k1::PublicKey;
+    use commons::DepthFilter;
+    use commons::Message;
+    use commons::Order;
+    use commons::OrderReason;
+    use commons::OrderState;
+    use commons::OrderType;
+    use commons::OrderbookDelta;
+    use commons::Price;
+    use commons::TimeInForce;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+    use std::str::FromStr;
+    use time::Duration;
+    use time::OffsetDateTime;
+    use trade::ContractSymbol;
+    use trade::Direction;
+    use uuid::Uuid;
+
+    #[test]
+    fn deltas_are_sequenced_per_contract_symbol() {
+        let feed = OrderbookFeed::new(vec![]);
+        let mut receiver = feed.subscribe();
+
+        feed.apply(&Message::NewOrder(dummy_order(
+            Direction::Long,
+            dec!(40_000),
+        )));
+        feed.apply(&Message::NewOrder(dummy_order(
+            Direction::Short,
+            dec!(41_000),
+        )));
+
+        let mut seqs = vec![];
+        while let Ok(message) = receiver.try_recv() {
+            if let Message::OrderbookDelta { seq, .. } = message {
+                seqs.push(seq);
+            }
+        }
+
+        assert_eq!(seqs, vec![1, 2]);
+        match feed.snapshot(ContractSymbol::BtcUsd) {
+            Message::OrderbookSnapshot { seq, orders, .. } => {
+                assert_eq!(seq, 2);
+                assert_eq!(orders.len(), 2);
+            }
+            message => panic!("Unexpected message {message}"),
+        }
+    }
+
+    #[test]
+    fn filled_order_is_removed_from_book() {
+        let order = dummy_order(Direction::Long, dec!(40_000));
+        let feed = OrderbookFeed::new(vec![order.clone()]);
+        let mut receiver = feed.subscribe();
+
+        feed.apply(&Message::Update(Order {
+            order_state: OrderState::Taken,
+            ..order.clone()
+        }));
+
+        assert!(matches!(
+            receiver.try_recv().unwrap(),
+            Message::OrderbookDelta {
+                seq: 1,
+                delta: OrderbookDelta::Update(_),
+                ..
+            }
+        ));
+        assert!(matches!(
+            receiver.try_recv().unwrap(),
+            Message::TopOfBook {
+                price: Price {
+                    bid: None,
+                    ask: None
+                },
+                ..
+            }
+        ));
+        match feed.snapshot(ContractSymbol::BtcUsd) {
+            Message::OrderbookSnapshot { orders, .. } => assert!(orders.is_empty()),
+            message => panic!("Unexpected message {message}"),
+        }
+    }
+
+    #[test]
+    fn unknown_order_deletion_is_ignored() {
+        let feed = OrderbookFeed::new(vec![]);
+        let mut receiver = feed.subscribe();
+
+        feed.apply(&Message::DeleteOrder(Uuid::new_v4()));
+
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn reload_skips_a_sequence_number() {
+        let order = dummy_order(Direction::Long, dec!(40_000));
+        let feed = OrderbookFeed::new(vec![]);
+        feed.apply(&Message::NewOrder(order.clone()));
+
+        feed.reload(vec![order]);
+
+        match feed.snapshot(ContractSymbol::BtcUsd) {
+            Message::OrderbookSnapshot { seq, orders, .. } => {
+                assert_eq!(seq, 2);
+                assert_eq!(orders.len(), 1);
+            }
+            message => panic!("Unexpected message {message}"),
+        }
+    }
+
+    #[test]
+    fn depth_follows_deltas() {
+        let order = dummy_order(Direction::Short, dec!(41_000));
+        let feed = OrderbookFeed::new(vec![order.clone()]);
+
+        feed.apply(&Message::NewOrder(dummy_order(
+            Direction::Short,
+            dec!(41_000),
+        )));
+        feed.apply(&Message::DeleteOrder(order.id));
+
+        let depth = feed.depth(ContractSymbol::BtcUsd, &DepthFilter::default());
+        assert!(depth.bids.is_empty());
+        assert_eq!(depth.asks.len(), 1);
+        assert_eq!(depth.asks[0].quantity, dec!(100));
+        assert_eq!(depth.asks[0].orders, 1);
+    }
+
+    #[test]
+    fn top_of_book_only_published_on_change() {
+        let feed = OrderbookFeed::new(vec![dummy_order(Direction::Long, dec!(40_000))]);
+        let mut receiver = feed.subscribe();
+
+        feed.apply(&Message::NewOrder(dummy_order(
+            Direction::Long,
+            dec!(39_000),
+        )));
+
+        assert!(matches!(
+            receiver.try_recv().unwrap(),
+            Message::OrderbookDelta { .. }
+        ));
+        assert!(receiver.try_recv().is_err());
+        match feed.top_of_book(ContractSymbol::BtcUsd) {
+            Message::TopOfBook { price, .. } => assert_eq!(price.bid, Some(dec!(40_000))),
+            message => panic!("Unexpected message {message}"),
+        }
+    }
+
+    fn dummy_order(direction: Direction, price: Decimal) -> Order {
+        Order {
+            id: Uuid::new_v4(),
+            price,
+            trader_id: PublicKey::from_str(
+                "027f31ebc5462c1fdce1b737ecff52d37d75dea43ce11c74d25aa297165faa2007",
+            )
+            .unwrap(),
+            direction,
+            leverage: 1.0,
+            contract_symbol: ContractSymbol::BtcUsd,
+            quantity: dec!(100),
+            order_type: OrderType::Limit,
+            timestamp: OffsetDateTime::now_utc(),
+            expiry: OffsetDateTime::now_utc() + Duration::minutes(1),
+            order_state: OrderState::Open,
+            order_reason: OrderReason::Manual,
+            stable: false,
+            time_in_force: TimeInForce::GoodTillDate,
+        }
+    }
+}
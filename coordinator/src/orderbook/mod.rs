@@ -1,9 +1,12 @@
 pub mod async_match;
+pub mod candles;
 pub mod collaborative_revert;
 pub mod db;
+pub mod depth;
+pub mod feed;
 pub mod routes;
 pub mod trading;
 pub mod websocket;
 
 #[cfg(test)]
-mod tests;
+pub(crate) mod tests;
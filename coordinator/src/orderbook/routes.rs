@@ -1,31 +1,47 @@
+use crate::db;
 use crate::orderbook;
+use crate::orderbook::trading::AmendOrderMessage;
 use crate::orderbook::trading::NewOrderMessage;
 use crate::orderbook::trading::TradingError;
+use crate::orderbook::trading::TradingMessage;
+use crate::orderbook::websocket::hide_maker_identity;
 use crate::orderbook::websocket::websocket_connection;
 use crate::routes::AppState;
+use crate::trader_auth::signed_by_node_id;
+use crate::trader_auth::SignedRequest;
 use crate::AppError;
 use anyhow::Context;
 use anyhow::Result;
 use axum::extract::ws::WebSocketUpgrade;
 use axum::extract::Path;
+use axum::extract::Query;
 use axum::extract::State;
 use axum::response::IntoResponse;
 use axum::Json;
+use commons::Candle;
+use commons::CandleFilter;
+use commons::Depth;
+use commons::DepthFilter;
+use commons::Fill;
+use commons::HistoryFilter;
 use commons::Message;
 use commons::NewOrder;
 use commons::Order;
 use commons::OrderReason;
 use commons::OrderState;
 use commons::OrderType;
+use commons::PublicTrade;
+use commons::UpdateOrder;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::PooledConnection;
 use diesel::PgConnection;
-use serde::Deserialize;
-use serde::Serialize;
+use rust_decimal::Decimal;
 use std::sync::Arc;
+use time::OffsetDateTime;
 use tokio::sync::broadcast::Sender;
 use tokio::sync::mpsc;
 use tracing::instrument;
+use trade::ContractSymbol;
 use uuid::Uuid;
 
 #[instrument(skip_all, err(Debug))]
@@ -39,6 +55,7 @@ fn get_db_connection(
         .map_err(|e| AppError::InternalServerError(format!("Failed to get db access: {e:#}")))
 }
 
+/// The order with the given id, without revealing the maker.
 #[instrument(skip_all, err(Debug))]
 pub async fn get_order(
     Path(order_id): Path<Uuid>,
@@ -50,9 +67,11 @@ pub async fn get_order(
         .context(format!("Order not found {order_id}"))
         .map_err(|e| AppError::BadRequest(format!("{e:#}")))?;
 
-    Ok(Json(order))
+    let coordinator_id = state.node.inner.info.pubkey;
+    Ok(Json(hide_maker_identity(order, None, coordinator_id)))
 }
 
+/// All open limit orders, without revealing the makers.
 #[instrument(skip_all, err(Debug))]
 pub async fn get_orders(State(state): State<Arc<AppState>>) -> Result<Json<Vec<Order>>, AppError> {
     let mut conn = get_db_connection(&state)?;
@@ -60,14 +79,99 @@ pub async fn get_orders(State(state): State<Arc<AppState>>) -> Result<Json<Vec<O
         orderbook::db::orders::get_all_orders(&mut conn, OrderType::Limit, OrderState::Open, true)
             .map_err(|e| AppError::InternalServerError(format!("Failed to load order: {e:#}")))?;
 
+    let coordinator_id = state.node.inner.info.pubkey;
+    let orders = orders
+        .into_iter()
+        .map(|order| hide_maker_identity(order, None, coordinator_id))
+        .collect();
+
+    Ok(Json(orders))
+}
+
+#[instrument(skip_all, err(Debug))]
+pub async fn get_trades(
+    Query(filter): Query<HistoryFilter>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<PublicTrade>>, AppError> {
+    let mut conn = get_db_connection(&state)?;
+    let trades = db::trades::get_all(&mut conn, &filter)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to load trades: {e:#}")))?;
+
+    Ok(Json(trades.iter().map(PublicTrade::from).collect()))
+}
+
+#[instrument(skip_all, err(Debug))]
+pub async fn get_candles(
+    Query(filter): Query<CandleFilter>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<Candle>>, AppError> {
+    let mut conn = get_db_connection(&state)?;
+    let candles = db::candles::get(&mut conn, &filter)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to load candles: {e:#}")))?;
+
+    Ok(Json(candles))
+}
+
+/// The orderbook of the contract symbol aggregated by price, without revealing the makers.
+#[instrument(skip_all, err(Debug))]
+pub async fn get_depth(
+    Path(contract_symbol): Path<ContractSymbol>,
+    Query(filter): Query<DepthFilter>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Depth>, AppError> {
+    Ok(Json(state.orderbook_feed.depth(contract_symbol, &filter)))
+}
+
+#[instrument(skip_all, err(Debug))]
+pub async fn get_order_history(
+    Path(trader_id): Path<String>,
+    Query(filter): Query<HistoryFilter>,
+    State(state): State<Arc<AppState>>,
+    signed_request: SignedRequest<()>,
+) -> Result<Json<Vec<Order>>, AppError> {
+    let trader_id = signed_by_node_id(&trader_id, signed_request.trader_id)?;
+
+    let mut conn = get_db_connection(&state)?;
+    let orders = orderbook::db::orders::get_by_trader_id(&mut conn, trader_id, &filter)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to load orders: {e:#}")))?;
+
     Ok(Json(orders))
 }
 
+#[instrument(skip_all, err(Debug))]
+pub async fn get_fill_history(
+    Path(trader_id): Path<String>,
+    Query(filter): Query<HistoryFilter>,
+    State(state): State<Arc<AppState>>,
+    signed_request: SignedRequest<()>,
+) -> Result<Json<Vec<Fill>>, AppError> {
+    let trader_id = signed_by_node_id(&trader_id, signed_request.trader_id)?;
+
+    let mut conn = get_db_connection(&state)?;
+    let fills = orderbook::db::matches::get_fills_by_trader_id(&mut conn, trader_id, &filter)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to load fills: {e:#}")))?;
+
+    Ok(Json(fills))
+}
+
 #[instrument(skip_all, err(Debug))]
 pub async fn post_order(
     State(state): State<Arc<AppState>>,
-    Json(new_order): Json<NewOrder>,
+    SignedRequest {
+        trader_id,
+        body: new_order,
+    }: SignedRequest<NewOrder>,
 ) -> Result<Json<Order>, AppError> {
+    if new_order.trader_id != trader_id {
+        return Err(AppError::Forbidden(
+            "Order has to be signed by the trader".to_string(),
+        ));
+    }
+
+    state
+        .rate_limiter
+        .check_node_id(trader_id, "/api/orderbook/orders")?;
+
     let (sender, mut receiver) = mpsc::channel::<Result<Order>>(1);
 
     let message = NewOrderMessage {
@@ -75,9 +179,13 @@ pub async fn post_order(
         order_reason: OrderReason::Manual,
         sender,
     };
-    state.trading_sender.send(message).await.map_err(|e| {
-        AppError::InternalServerError(format!("Failed to send new order message: {e:#}"))
-    })?;
+    state
+        .trading_sender
+        .send(TradingMessage::NewOrder(message))
+        .await
+        .map_err(|e| {
+            AppError::InternalServerError(format!("Failed to send new order message: {e:#}"))
+        })?;
 
     let result = receiver
         .recv()
@@ -85,15 +193,24 @@ pub async fn post_order(
         .context("Failed to receive response from trading sender")
         .map_err(|e| AppError::InternalServerError(format!("{e:#}")))?;
 
-    let order = result.map_err(|e| match e.downcast_ref() {
-        Some(TradingError::InvalidOrder(reason)) => AppError::InvalidOrder(reason.to_string()),
-        Some(TradingError::NoMatchFound(message)) => AppError::NoMatchFound(message.to_string()),
-        _ => AppError::InternalServerError(format!("Failed to post order. Error: {e:#}")),
-    })?;
+    let order = result.map_err(|e| trading_error(e, "Failed to post order"))?;
 
     Ok(Json(order))
 }
 
+/// Maps an error of processing an order to the response for the trader.
+fn trading_error(e: anyhow::Error, context: &str) -> AppError {
+    match e.downcast_ref() {
+        Some(TradingError::InvalidOrder(reason)) => AppError::InvalidOrder(reason.to_string()),
+        Some(TradingError::NoMatchFound(message)) => AppError::NoMatchFound(message.to_string()),
+        Some(TradingError::TooManyOrders(message)) => {
+            AppError::TooManyRequests(message.to_string())
+        }
+        Some(TradingError::OrderNotOpen(message)) => AppError::Conflict(message.to_string()),
+        None => AppError::InternalServerError(format!("{context}. Error: {e:#}")),
+    }
+}
+
 fn update_pricefeed(pricefeed_msg: Message, sender: Sender<Message>) {
     match sender.send(pricefeed_msg) {
         Ok(_) => {
@@ -105,22 +222,105 @@ fn update_pricefeed(pricefeed_msg: Message, sender: Sender<Message>) {
     }
 }
 
-#[derive(Deserialize, Serialize)]
-pub struct UpdateOrder {
-    pub taken: bool,
-}
-
+/// Amends or cancels an open order of the trader.
+///
+/// Amendments are processed like new orders, i.e. they are validated, count against the order rate
+/// limit and are matched at their new price. Orders which are not open anymore are never changed,
+/// which is reported with a `409 Conflict`.
 #[instrument(skip_all, err(Debug))]
 pub async fn put_order(
     Path(order_id): Path<Uuid>,
     State(state): State<Arc<AppState>>,
-    Json(updated_order): Json<UpdateOrder>,
+    SignedRequest {
+        trader_id,
+        body: updated_order,
+    }: SignedRequest<UpdateOrder>,
 ) -> Result<Json<Order>, AppError> {
     let mut conn = get_db_connection(&state)?;
-    let order = orderbook::db::orders::set_is_taken(&mut conn, order_id, updated_order.taken)
-        .map_err(|e| AppError::InternalServerError(format!("Failed to update order: {e:#}")))?;
-    let sender = state.tx_price_feed.clone();
-    update_pricefeed(Message::Update(order.clone()), sender);
+    let order = orderbook::db::orders::get_with_id(&mut conn, order_id)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to load order: {e:#}")))?
+        .context(format!("Order not found {order_id}"))
+        .map_err(|e| AppError::BadRequest(format!("{e:#}")))?;
+    if order.trader_id != trader_id {
+        return Err(AppError::Forbidden(
+            "Order can only be updated by its trader".to_string(),
+        ));
+    }
+
+    if updated_order.is_amendment() {
+        if updated_order
+            .price
+            .map_or(false, |price| price <= Decimal::ZERO)
+            || updated_order
+                .quantity
+                .map_or(false, |quantity| quantity <= Decimal::ZERO)
+        {
+            return Err(AppError::InvalidOrder(
+                "Price and quantity have to be positive".to_string(),
+            ));
+        }
+        if updated_order
+            .expiry
+            .map_or(false, |expiry| expiry <= OffsetDateTime::now_utc())
+        {
+            return Err(AppError::InvalidOrder(
+                "Expiry has to be in the future".to_string(),
+            ));
+        }
+    }
+
+    let order = if updated_order.taken {
+        // Cancelling does not count as an order, so that traders can always withdraw their orders.
+        let order = orderbook::db::orders::update_open_order(
+            &mut conn,
+            order_id,
+            updated_order.price,
+            updated_order.quantity,
+            updated_order.expiry,
+            true,
+        )
+        .map_err(|e| AppError::InternalServerError(format!("Failed to update order: {e:#}")))?
+        .ok_or_else(|| AppError::Conflict(format!("Order {order_id} is not open")))?;
+
+        let sender = state.tx_price_feed.clone();
+        update_pricefeed(Message::Update(order.clone()), sender);
+
+        order
+    } else if updated_order.is_amendment() {
+        drop(conn);
+
+        state
+            .rate_limiter
+            .check_node_id(trader_id, "/api/orderbook/orders/:order_id")?;
+
+        let (sender, mut receiver) = mpsc::channel::<Result<Order>>(1);
+
+        let message = AmendOrderMessage {
+            order_id,
+            price: updated_order.price,
+            quantity: updated_order.quantity,
+            expiry: updated_order.expiry,
+            sender,
+        };
+        state
+            .trading_sender
+            .send(TradingMessage::AmendOrder(message))
+            .await
+            .map_err(|e| {
+                AppError::InternalServerError(format!("Failed to send amend order message: {e:#}"))
+            })?;
+
+        let result = receiver
+            .recv()
+            .await
+            .context("Failed to receive response from trading sender")
+            .map_err(|e| AppError::InternalServerError(format!("{e:#}")))?;
+
+        result.map_err(|e| trading_error(e, "Failed to amend order"))?
+    } else {
+        // There is nothing to change, in particular an order is never reopened.
+        order
+    };
 
     Ok(Json(order))
 }
@@ -129,5 +329,7 @@ pub async fn websocket_handler(
     ws: WebSocketUpgrade,
     State(state): State<Arc<AppState>>,
 ) -> impl IntoResponse {
-    ws.on_upgrade(|socket| websocket_connection(socket, state))
+    let max_message_size = state.rate_limiter.settings().max_request_size_bytes;
+    ws.max_message_size(max_message_size)
+        .on_upgrade(|socket| websocket_connection(socket, state))
 }
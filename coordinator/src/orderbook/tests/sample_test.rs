@@ -3,12 +3,19 @@ use crate::orderbook::db::orders;
 use crate::orderbook::tests::setup_db;
 use crate::orderbook::tests::start_postgres;
 use bitcoin::secp256k1::PublicKey;
+use commons::HistoryFilter;
 use commons::NewOrder;
 use commons::OrderReason;
 use commons::OrderState;
 use commons::OrderType;
+use commons::TimeInForce;
+use diesel::Connection;
+use diesel::PgConnection;
 use rust_decimal_macros::dec;
 use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::Barrier;
+use std::thread;
 use testcontainers::clients::Cli;
 use time::Duration;
 use time::OffsetDateTime;
@@ -34,10 +41,54 @@ async fn crud_test() {
     )
     .unwrap();
 
-    let order = orders::set_is_taken(&mut conn, order.id, true).unwrap();
+    let order = orders::update_open_order(&mut conn, order.id, None, None, None, true)
+        .unwrap()
+        .unwrap();
     assert_eq!(order.order_state, OrderState::Taken);
 }
 
+#[tokio::test]
+async fn only_open_orders_are_updated() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec);
+
+    let order = orders::insert(
+        &mut conn,
+        dummy_order(
+            OffsetDateTime::now_utc() + Duration::minutes(1),
+            OrderType::Limit,
+        ),
+        OrderReason::Manual,
+    )
+    .unwrap();
+
+    let amended =
+        orders::update_open_order(&mut conn, order.id, Some(dec!(21_000)), None, None, false)
+            .unwrap()
+            .unwrap();
+    assert_eq!(amended.price, dec!(21_000));
+    assert_eq!(amended.quantity, order.quantity);
+    assert_eq!(amended.order_state, OrderState::Open);
+
+    orders::set_order_state(&mut conn, order.id, OrderState::Matched).unwrap();
+
+    let cancelled = orders::update_open_order(&mut conn, order.id, None, None, None, true).unwrap();
+    assert!(cancelled.is_none());
+
+    let amended =
+        orders::update_open_order(&mut conn, order.id, Some(dec!(22_000)), None, None, false)
+            .unwrap();
+    assert!(amended.is_none());
+
+    let order = orders::get_with_id(&mut conn, order.id).unwrap().unwrap();
+    assert_eq!(order.order_state, OrderState::Matched);
+    assert_eq!(order.price, dec!(21_000));
+}
+
 #[tokio::test]
 async fn test_all_limit_orders() {
     init_tracing_for_test();
@@ -85,6 +136,140 @@ async fn test_all_limit_orders() {
     assert_eq!(orders.len(), 1);
 }
 
+#[tokio::test]
+async fn test_split_order() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec);
+
+    let order = orders::insert(
+        &mut conn,
+        dummy_order(
+            OffsetDateTime::now_utc() + Duration::minutes(1),
+            OrderType::Limit,
+        ),
+        OrderReason::Manual,
+    )
+    .unwrap();
+
+    let filled_order_id = Uuid::new_v4();
+    let filled_order = orders::split_order(&mut conn, order.id, filled_order_id, dec!(40)).unwrap();
+    assert_eq!(filled_order.id, filled_order_id);
+    assert_eq!(filled_order.quantity, dec!(40));
+    assert_eq!(filled_order.price, order.price);
+    assert_eq!(filled_order.order_state, OrderState::Open);
+
+    let order = orders::get_with_id(&mut conn, order.id).unwrap().unwrap();
+    assert_eq!(order.order_state, OrderState::Open);
+    assert_eq!(order.quantity, dec!(60));
+}
+
+#[tokio::test]
+async fn concurrent_splits_do_not_overfill_order() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec.clone());
+
+    let order = orders::insert(
+        &mut conn,
+        dummy_order(
+            OffsetDateTime::now_utc() + Duration::minutes(1),
+            OrderType::Limit,
+        ),
+        OrderReason::Manual,
+    )
+    .unwrap();
+
+    // Two takers filling 60 of the 100 contracts of the same order at the same time.
+    let order_id = order.id;
+    let barrier = Arc::new(Barrier::new(2));
+    let splits = (0..2)
+        .map(|_| {
+            let conn_spec = conn_spec.clone();
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                let mut conn = PgConnection::establish(&conn_spec).unwrap();
+                barrier.wait();
+                orders::split_order(&mut conn, order_id, Uuid::new_v4(), dec!(60))
+            })
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|split| split.join().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(splits.iter().filter(|split| split.is_ok()).count(), 1);
+
+    let order = orders::get_with_id(&mut conn, order.id).unwrap().unwrap();
+    assert_eq!(order.order_state, OrderState::Open);
+    assert_eq!(order.quantity, dec!(40));
+}
+
+#[tokio::test]
+async fn test_order_history_of_trader() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec);
+
+    let expiry = OffsetDateTime::now_utc() + Duration::minutes(1);
+    for direction in [Direction::Long, Direction::Long, Direction::Short] {
+        orders::insert(
+            &mut conn,
+            NewOrder {
+                direction,
+                ..dummy_order(expiry, OrderType::Market)
+            },
+            OrderReason::Manual,
+        )
+        .unwrap();
+    }
+
+    let trader_id = dummy_order(expiry, OrderType::Market).trader_id;
+
+    let history =
+        orders::get_by_trader_id(&mut conn, trader_id, &HistoryFilter::default()).unwrap();
+    assert_eq!(history.len(), 3);
+
+    let long_orders = orders::get_by_trader_id(
+        &mut conn,
+        trader_id,
+        &HistoryFilter {
+            direction: Some(Direction::Long),
+            ..HistoryFilter::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(long_orders.len(), 2);
+
+    let second_page = orders::get_by_trader_id(
+        &mut conn,
+        trader_id,
+        &HistoryFilter {
+            limit: Some(1),
+            offset: Some(1),
+            ..HistoryFilter::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(second_page, vec![history[1].clone()]);
+
+    let other_trader_id =
+        PublicKey::from_str("02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655")
+            .unwrap();
+    let history =
+        orders::get_by_trader_id(&mut conn, other_trader_id, &HistoryFilter::default()).unwrap();
+    assert!(history.is_empty());
+}
+
 fn dummy_order(expiry: OffsetDateTime, order_type: OrderType) -> NewOrder {
     NewOrder {
         id: Uuid::new_v4(),
@@ -100,5 +285,6 @@ fn dummy_order(expiry: OffsetDateTime, order_type: OrderType) -> NewOrder {
         contract_symbol: trade::ContractSymbol::BtcUsd,
         leverage: 1.0,
         stable: false,
+        time_in_force: TimeInForce::GoodTillDate,
     }
 }
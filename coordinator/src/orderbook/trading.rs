@@ -2,6 +2,9 @@ use crate::message::OrderbookMessage;
 use crate::notifications::NotificationKind;
 use crate::orderbook::db::matches;
 use crate::orderbook::db::orders;
+use crate::rate_limit::record_rejection;
+use crate::rate_limit::RateLimiter;
+use crate::rate_limit::Rejection;
 use anyhow::anyhow;
 use anyhow::bail;
 use anyhow::Context;
@@ -17,14 +20,17 @@ use commons::Order;
 use commons::OrderReason;
 use commons::OrderState;
 use commons::OrderType;
+use commons::TimeInForce;
 use commons::TradeParams;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::Pool;
+use diesel::Connection;
 use diesel::PgConnection;
 use futures::future::RemoteHandle;
 use futures::FutureExt;
 use rust_decimal::Decimal;
 use std::cmp::Ordering;
+use std::sync::Arc;
 use thiserror::Error;
 use time::OffsetDateTime;
 use tokio::sync::broadcast;
@@ -33,28 +39,48 @@ use tokio::task::spawn_blocking;
 use trade::Direction;
 use uuid::Uuid;
 
-/// This value is arbitrarily set to 100 and defines the number of new order messages buffered in
+/// This value is arbitrarily set to 100 and defines the number of trading messages buffered in
 /// the channel.
 const NEW_ORDERS_BUFFER_SIZE: usize = 100;
 
+pub enum TradingMessage {
+    NewOrder(NewOrderMessage),
+    AmendOrder(AmendOrderMessage),
+}
+
 pub struct NewOrderMessage {
     pub new_order: NewOrder,
     pub order_reason: OrderReason,
     pub sender: mpsc::Sender<Result<Order>>,
 }
 
+/// Changes the price, quantity and expiry of an open limit order, leaving those which are `None`
+/// as they are.
+pub struct AmendOrderMessage {
+    pub order_id: Uuid,
+    pub price: Option<Decimal>,
+    pub quantity: Option<Decimal>,
+    pub expiry: Option<OffsetDateTime>,
+    pub sender: mpsc::Sender<Result<Order>>,
+}
+
 #[derive(Error, Debug, PartialEq)]
 pub enum TradingError {
     #[error("Invalid order: {0}")]
     InvalidOrder(String),
     #[error("{0}")]
     NoMatchFound(String),
+    #[error("Too many orders: {0}")]
+    TooManyOrders(String),
+    #[error("{0}")]
+    OrderNotOpen(String),
 }
 
 #[derive(Clone)]
 pub struct MatchParams {
     pub taker_match: TraderMatchParams,
     pub makers_matches: Vec<TraderMatchParams>,
+    pub partial_fills: Vec<PartialFill>,
 }
 
 #[derive(Clone)]
@@ -63,39 +89,73 @@ pub struct TraderMatchParams {
     pub filled_with: FilledWith,
 }
 
-/// Spawn a task that processes [`NewOrderMessage`]s.
+/// An order which has only been partially filled by a match.
+///
+/// The filled part is split off into a new order with the `filled_order_id`, while the remainder
+/// stays in the orderbook under the original `order_id`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PartialFill {
+    pub order_id: Uuid,
+    pub filled_order_id: Uuid,
+    pub quantity: Decimal,
+}
+
+/// Spawn a task that processes [`TradingMessage`]s.
 ///
 /// To feed messages to this task, the caller can use the corresponding
-/// [`mpsc::Sender<NewOrderMessage>`] returned.
+/// [`mpsc::Sender<TradingMessage>`] returned.
 pub fn start(
     pool: Pool<ConnectionManager<PgConnection>>,
     tx_price_feed: broadcast::Sender<Message>,
     notifier: mpsc::Sender<OrderbookMessage>,
     network: Network,
     oracle_pk: XOnlyPublicKey,
-) -> (RemoteHandle<()>, mpsc::Sender<NewOrderMessage>) {
-    let (sender, mut receiver) = mpsc::channel::<NewOrderMessage>(NEW_ORDERS_BUFFER_SIZE);
+    rate_limiter: Arc<RateLimiter>,
+) -> (RemoteHandle<()>, mpsc::Sender<TradingMessage>) {
+    let (sender, mut receiver) = mpsc::channel::<TradingMessage>(NEW_ORDERS_BUFFER_SIZE);
 
     let (fut, remote_handle) = async move {
-        while let Some(new_order_msg) = receiver.recv().await {
+        while let Some(trading_msg) = receiver.recv().await {
             tokio::spawn({
                 let tx_price_feed = tx_price_feed.clone();
                 let notifier = notifier.clone();
                 let pool = pool.clone();
+                let rate_limiter = rate_limiter.clone();
                 async move {
-                    let result = process_new_order(
-                        pool,
-                        notifier,
-                        tx_price_feed,
-                        new_order_msg.new_order,
-                        new_order_msg.order_reason,
-                        network,
-                        oracle_pk,
-                    )
-                    .await;
-
-                    if let Err(e) = new_order_msg.sender.send(result).await {
-                        tracing::error!("Failed to respond to NewOrderMessage: {e:#}");
+                    match trading_msg {
+                        TradingMessage::NewOrder(new_order_msg) => {
+                            let result = process_new_order(
+                                pool,
+                                notifier,
+                                tx_price_feed,
+                                new_order_msg.new_order,
+                                new_order_msg.order_reason,
+                                network,
+                                oracle_pk,
+                                &rate_limiter,
+                            )
+                            .await;
+
+                            if let Err(e) = new_order_msg.sender.send(result).await {
+                                tracing::error!("Failed to respond to NewOrderMessage: {e:#}");
+                            }
+                        }
+                        TradingMessage::AmendOrder(amend_order_msg) => {
+                            let result = process_amended_order(
+                                pool,
+                                notifier,
+                                tx_price_feed,
+                                &amend_order_msg,
+                                network,
+                                oracle_pk,
+                                &rate_limiter,
+                            )
+                            .await;
+
+                            if let Err(e) = amend_order_msg.sender.send(result).await {
+                                tracing::error!("Failed to respond to AmendOrderMessage: {e:#}");
+                            }
+                        }
                     }
                 }
             });
@@ -112,12 +172,21 @@ pub fn start(
 
 /// Process a [`NewOrder`].
 ///
-/// If the [`NewOrder`] is of [`OrderType::Limit`]: update the price feed.
+/// The [`NewOrder`] is matched with the opposite limit orders in the orderbook.
+///
+/// If the [`NewOrder`] is of [`OrderType::Market`]: the order has to be filled completely,
+/// otherwise it fails.
 ///
-/// If the [`NewOrder`] is of [`OrderType::Market`]: find match and notify traders.
+/// If the [`NewOrder`] is of [`OrderType::Limit`]: the order is filled with all limit orders
+/// crossing its price. The remainder rests in the orderbook and gets matched once an opposite
+/// order crosses it.
+///
+/// Orders placed by the trader, as opposed to the ones placed by the coordinator on their behalf,
+/// are rejected if the trader exceeds the order rate or the maximum number of open orders.
 ///
 /// TODO(holzeis): The limit and market order models should be separated so we can process the
 /// models independently.
+#[allow(clippy::too_many_arguments)]
 pub async fn process_new_order(
     pool: Pool<ConnectionManager<PgConnection>>,
     notifier: mpsc::Sender<OrderbookMessage>,
@@ -126,6 +195,7 @@ pub async fn process_new_order(
     order_reason: OrderReason,
     network: Network,
     oracle_pk: XOnlyPublicKey,
+    rate_limiter: &RateLimiter,
 ) -> Result<Order> {
     tracing::info!(
         trader_id = %new_order.trader_id,
@@ -137,35 +207,19 @@ pub async fn process_new_order(
         .await
         .expect("task to complete")?;
 
-    if new_order.order_type == OrderType::Limit && new_order.price == Decimal::ZERO {
-        return Err(TradingError::InvalidOrder(
-            "Limit orders with zero price are not allowed".to_string(),
-        ))?;
+    if order_reason == OrderReason::Manual {
+        check_order_limits(&mut conn, &new_order, rate_limiter)?;
     }
 
-    // Before processing any match we set all expired limit orders to failed, to ensure they do not
-    // get matched.
-    //
-    // TODO(holzeis): Orders should probably not have an expiry, but should either be replaced or
-    // deleted if not wanted anymore.
-    let expired_limit_orders = orders::set_expired_limit_orders_to_failed(&mut conn)?;
-    for expired_limit_order in expired_limit_orders {
-        tx_price_feed
-            .send(Message::DeleteOrder(expired_limit_order.id))
-            .map_err(|e| anyhow!(e))
-            .context("Could not update price feed")?;
-    }
+    validate_order(&new_order)?;
+
+    fail_expired_limit_orders(&mut conn, &tx_price_feed)?;
 
     let order = orders::insert(&mut conn, new_order.clone(), order_reason)
         .map_err(|e| anyhow!(e))
         .context("Failed to insert new order into DB")?;
 
-    if new_order.order_type == OrderType::Limit {
-        tx_price_feed
-            .send(Message::NewOrder(order.clone()))
-            .map_err(|e| anyhow!(e))
-            .context("Could not update price feed")?;
-    } else {
+    if new_order.order_type == OrderType::Market {
         // Reject new order if there is already a matched order waiting for execution.
         if let Some(order) =
             orders::get_by_trader_id_and_state(&mut conn, new_order.trader_id, OrderState::Matched)?
@@ -176,199 +230,567 @@ pub async fn process_new_order(
                 new_order.trader_id, order.id
             )));
         }
+    }
 
-        let opposite_direction_limit_orders = orders::all_by_direction_and_type(
-            &mut conn,
-            order.direction.opposite(),
-            OrderType::Limit,
-            true,
-        )?;
-
-        let matched_orders =
-            match match_order(&order, opposite_direction_limit_orders, network, oracle_pk) {
-                Ok(Some(matched_orders)) => matched_orders,
-                Ok(None) => {
-                    // TODO(holzeis): Currently we still respond to the user immediately if there
-                    // has been a match or not, that's the reason why we also have to set the order
-                    // to failed here. But actually we could keep the order until either expired or
-                    // a match has been found and then update the state accordingly.
-
-                    orders::set_order_state(&mut conn, order.id, OrderState::Failed)?;
-                    bail!(TradingError::NoMatchFound(format!(
-                        "Could not match order {}",
-                        order.id
-                    )));
-                }
-                Err(e) => {
-                    orders::set_order_state(&mut conn, order.id, OrderState::Failed)?;
-                    bail!("Failed to match order: {e:#}")
-                }
-            };
+    match_and_execute(
+        &mut conn,
+        &notifier,
+        &tx_price_feed,
+        order,
+        network,
+        oracle_pk,
+        Message::NewOrder,
+    )
+    .await
+}
 
-        tracing::info!(
-            trader_id=%order.trader_id,
-            order_id=%order.id,
-            "Found a match with {} makers for new order",
-            matched_orders.taker_match.filled_with.matches.len()
-        );
+/// Process an [`AmendOrderMessage`].
+///
+/// The amended order is rate limited and validated like a new order, and matched with the opposite
+/// limit orders crossing its new price. Its remainder stays in the orderbook under the same id.
+///
+/// Fails with [`TradingError::OrderNotOpen`] if the order has been filled or cancelled in the
+/// meantime, in which case it is left unchanged.
+pub async fn process_amended_order(
+    pool: Pool<ConnectionManager<PgConnection>>,
+    notifier: mpsc::Sender<OrderbookMessage>,
+    tx_price_feed: broadcast::Sender<Message>,
+    amendment: &AmendOrderMessage,
+    network: Network,
+    oracle_pk: XOnlyPublicKey,
+    rate_limiter: &RateLimiter,
+) -> Result<Order> {
+    let order_id = amendment.order_id;
+
+    let mut conn = spawn_blocking(move || pool.get())
+        .await
+        .expect("task to complete")?;
+
+    let order = orders::get_with_id(&mut conn, order_id)?
+        .with_context(|| format!("Could not find order {order_id}"))?;
+
+    tracing::info!(trader_id = %order.trader_id, %order_id, "Processing amended order");
+
+    check_order_rate(order.trader_id, rate_limiter)?;
+
+    if order.order_type != OrderType::Limit {
+        bail!(TradingError::InvalidOrder(
+            "Only limit orders can be amended".to_string()
+        ));
+    }
+
+    validate_order(&NewOrder {
+        id: order.id,
+        contract_symbol: order.contract_symbol,
+        price: amendment.price.unwrap_or(order.price),
+        quantity: amendment.quantity.unwrap_or(order.quantity),
+        trader_id: order.trader_id,
+        direction: order.direction,
+        leverage: order.leverage,
+        order_type: order.order_type,
+        expiry: amendment.expiry.unwrap_or(order.expiry),
+        stable: order.stable,
+        time_in_force: order.time_in_force,
+    })?;
+
+    fail_expired_limit_orders(&mut conn, &tx_price_feed)?;
+
+    let order = orders::update_open_order(
+        &mut conn,
+        order_id,
+        amendment.price,
+        amendment.quantity,
+        amendment.expiry,
+        false,
+    )?
+    .ok_or_else(|| TradingError::OrderNotOpen(format!("Order {order_id} is not open")))?;
+
+    let result = match_and_execute(
+        &mut conn,
+        &notifier,
+        &tx_price_feed,
+        order,
+        network,
+        oracle_pk,
+        Message::Update,
+    )
+    .await;
+
+    // Unlike a new order, the amended order is already in the orderbook of the clients, hence it
+    // has to be updated there once it has been filled or failed.
+    if let Some(order) = orders::get_with_id(&mut conn, order_id)? {
+        if order.order_state != OrderState::Open {
+            tx_price_feed
+                .send(Message::Update(order))
+                .map_err(|e| anyhow!(e))
+                .context("Could not update price feed")?;
+        }
+    }
+
+    result
+}
+
+/// Matches the order with the opposite limit orders crossing its price and notifies the matched
+/// traders.
+///
+/// A limit order which is not filled completely rests in the orderbook and is announced to the
+/// clients with the `announce` message.
+async fn match_and_execute(
+    conn: &mut PgConnection,
+    notifier: &mpsc::Sender<OrderbookMessage>,
+    tx_price_feed: &broadcast::Sender<Message>,
+    order: Order,
+    network: Network,
+    oracle_pk: XOnlyPublicKey,
+    announce: fn(Order) -> Message,
+) -> Result<Order> {
+    let opposite_direction_limit_orders = orders::all_by_direction_and_type(
+        conn,
+        order.direction.opposite(),
+        OrderType::Limit,
+        true,
+    )?
+    .into_iter()
+    // The orders of a trader must never be matched with each other.
+    .filter(|o| o.trader_id != order.trader_id)
+    .collect();
+
+    let matched_orders =
+        match match_order(&order, opposite_direction_limit_orders, network, oracle_pk) {
+            Ok(Some(matched_orders)) => matched_orders,
+            Ok(None) if rests_in_orderbook(&order) => {
+                tracing::debug!(
+                    trader_id=%order.trader_id,
+                    order_id=%order.id,
+                    "Limit order does not cross the orderbook, adding it to the orderbook"
+                );
+
+                tx_price_feed
+                    .send(announce(order.clone()))
+                    .map_err(|e| anyhow!(e))
+                    .context("Could not update price feed")?;
+
+                return Ok(order);
+            }
+            Ok(None) => {
+                // TODO(holzeis): Currently we still respond to the user immediately if there
+                // has been a match or not, that's the reason why we also have to set the order
+                // to failed here. But actually we could keep the order until either expired or
+                // a match has been found and then update the state accordingly.
+
+                orders::set_order_state(conn, order.id, OrderState::Failed)?;
+                bail!(TradingError::NoMatchFound(format!(
+                    "Could not match order {}",
+                    order.id
+                )));
+            }
+            Err(e) => {
+                orders::set_order_state(conn, order.id, OrderState::Failed)?;
+                bail!("Failed to match order: {e:#}")
+            }
+        };
 
-        for match_param in matched_orders.matches() {
-            matches::insert(&mut conn, match_param)?;
+    tracing::info!(
+        trader_id=%order.trader_id,
+        order_id=%order.id,
+        "Found a match with {} makers for new order",
+        matched_orders.taker_match.filled_with.matches.len()
+    );
 
-            let trader_id = match_param.trader_id;
-            let order_id = match_param.filled_with.order_id.to_string();
+    // The matched orders may have been filled by another order in the meantime, as orders are
+    // processed concurrently. In that case none of them is split.
+    let split = conn.transaction(|conn| {
+        for partial_fill in matched_orders.partial_fills.iter() {
+            orders::split_order(
+                conn,
+                partial_fill.order_id,
+                partial_fill.filled_order_id,
+                partial_fill.quantity,
+            )?;
+        }
 
-            tracing::info!(%trader_id, order_id, "Notifying trader about match");
+        anyhow::Ok(())
+    });
+    if let Err(e) = split {
+        orders::set_order_state(conn, order.id, OrderState::Failed)?;
+        bail!("Failed to fill the matched orders: {e:#}")
+    }
 
-            let message = match &order.order_reason {
-                OrderReason::Manual => Message::Match(match_param.filled_with.clone()),
-                OrderReason::Expired => Message::AsyncMatch {
-                    order: order.clone(),
-                    filled_with: match_param.filled_with.clone(),
-                },
-            };
+    for match_param in matched_orders.matches() {
+        matches::insert(conn, match_param)?;
+
+        let trader_id = match_param.trader_id;
+        let filled_order_id = match_param.filled_with.order_id;
+        let order_id = filled_order_id.to_string();
+
+        // If only a part of an order got filled, the filled part has been split off into its own
+        // order. The trader still needs to know which of their orders got filled.
+        let original_order_id = matched_orders
+            .partial_fills
+            .iter()
+            .find(|partial_fill| partial_fill.filled_order_id == filled_order_id)
+            .map(|partial_fill| partial_fill.order_id)
+            .unwrap_or(filled_order_id);
+        let original_order = orders::get_with_id(conn, original_order_id)?
+            .with_context(|| format!("Could not find order {original_order_id}"))?;
+
+        let is_new_order = original_order.id == order.id;
+
+        tracing::info!(%trader_id, order_id, "Notifying trader about match");
+
+        let message = if filled_order_id == order.id && order.order_reason == OrderReason::Manual {
+            // The trader is waiting for the order they have just submitted to get matched.
+            Message::Match(match_param.filled_with.clone())
+        } else {
+            Message::AsyncMatch {
+                order: original_order,
+                filled_with: match_param.filled_with.clone(),
+            }
+        };
 
-            let notification = match &order.order_reason {
-                OrderReason::Expired => Some(NotificationKind::PositionExpired),
-                OrderReason::Manual => None,
-            };
+        // Every fill is announced once: the push notification is only sent if the message cannot
+        // be delivered to the trader via the websocket, see `process_orderbook_message`.
+        let notification = match (&order.order_reason, is_new_order) {
+            (_, false) => Some(NotificationKind::LimitOrderFilled),
+            (OrderReason::Expired, true) => Some(NotificationKind::PositionExpired),
+            (OrderReason::StopLoss, true) => Some(NotificationKind::StopLossTriggered),
+            (OrderReason::TakeProfit, true) => Some(NotificationKind::TakeProfitTriggered),
+            (OrderReason::Liquidated, true) => Some(NotificationKind::PositionLiquidated),
+            (OrderReason::Manual, true) => None,
+        };
 
-            let msg = OrderbookMessage::TraderMessage {
-                trader_id,
-                message,
-                notification,
-            };
+        let msg = OrderbookMessage::TraderMessage {
+            trader_id,
+            message,
+            notification,
+        };
 
-            let order_state = match notifier.send(msg).await {
-                Ok(()) => {
-                    tracing::debug!(%trader_id, order_id, "Successfully notified trader");
+        let order_state = match notifier.send(msg).await {
+            Ok(()) => {
+                tracing::debug!(%trader_id, order_id, "Successfully notified trader");
+                OrderState::Matched
+            }
+            Err(e) => {
+                tracing::warn!(%trader_id, order_id, "Failed to send trader message: {e:#}");
+
+                if !is_new_order {
+                    // FIXME: The maker is currently not connected to the WebSocket so we can't
+                    // notify him about a trade. However, trades are always accepted by the
+                    // maker at the moment so in order to not have all limit orders in order
+                    // state `Match` we are setting the order to `Taken` even if we couldn't
+                    // notify the maker.
+
+                    OrderState::Taken
+                } else {
                     OrderState::Matched
                 }
-                Err(e) => {
-                    tracing::warn!(%trader_id, order_id, "Failed to send trader message: {e:#}");
-
-                    if order.order_type == OrderType::Limit {
-                        // FIXME: The maker is currently not connected to the WebSocket so we can't
-                        // notify him about a trade. However, trades are always accepted by the
-                        // maker at the moment so in order to not have all limit orders in order
-                        // state `Match` we are setting the order to `Taken` even if we couldn't
-                        // notify the maker.
-
-                        OrderState::Taken
-                    } else {
-                        OrderState::Matched
-                    }
-                }
-            };
+            }
+        };
+
+        tracing::debug!(%trader_id, order_id, "Updating the order state to {order_state:?}");
+
+        orders::set_order_state(conn, filled_order_id, order_state)?;
 
-            tracing::debug!(%trader_id, order_id, "Updating the order state to {order_state:?}");
+        if !is_new_order {
+            // The resting limit order has either been filled completely or it remains in the
+            // orderbook with a reduced quantity.
+            let resting_order = orders::get_with_id(conn, original_order_id)?
+                .with_context(|| format!("Could not find order {original_order_id}"))?;
 
-            orders::set_order_state(&mut conn, match_param.filled_with.order_id, order_state)?;
+            tx_price_feed
+                .send(Message::Update(resting_order))
+                .map_err(|e| anyhow!(e))
+                .context("Could not update price feed")?;
         }
     }
 
+    let order = orders::get_with_id(conn, order.id)?
+        .with_context(|| format!("Could not find order {}", order.id))?;
+
+    let order = if order.order_state == OrderState::Open {
+        if rests_in_orderbook(&order) {
+            // The remainder of a partially filled limit order rests in the orderbook.
+            tx_price_feed
+                .send(announce(order.clone()))
+                .map_err(|e| anyhow!(e))
+                .context("Could not update price feed")?;
+
+            order
+        } else {
+            tracing::debug!(
+                order_id = %order.id,
+                remaining_quantity = %order.quantity,
+                "Cancelling the unfilled remainder of the order"
+            );
+
+            orders::set_order_state(conn, order.id, OrderState::Failed)?
+        }
+    } else {
+        order
+    };
+
     Ok(order)
 }
 
-/// Matches an [`Order`] of [`OrderType::Market`] with a list of [`Order`]s of [`OrderType::Limit`].
+/// Rejects orders which must never enter the orderbook.
+fn validate_order(new_order: &NewOrder) -> Result<()> {
+    if new_order.order_type == OrderType::Limit && new_order.price == Decimal::ZERO {
+        return Err(TradingError::InvalidOrder(
+            "Limit orders with zero price are not allowed".to_string(),
+        ))?;
+    }
+
+    let tick_size = new_order.contract_symbol.metadata().tick_size;
+    if new_order.order_type == OrderType::Limit && !(new_order.price % tick_size).is_zero() {
+        return Err(TradingError::InvalidOrder(format!(
+            "Limit order price {} is not a multiple of the {} tick size {tick_size}",
+            new_order.price, new_order.contract_symbol
+        )))?;
+    }
+
+    if new_order.order_type == OrderType::Limit
+        && new_order.time_in_force == TimeInForce::GoodTillDate
+        && new_order.expiry <= OffsetDateTime::now_utc()
+    {
+        return Err(TradingError::InvalidOrder(
+            "Good-till-date orders must expire in the future".to_string(),
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// Sets all expired limit orders to failed, to ensure they do not get matched.
 ///
-/// The caller is expected to provide a list of `opposite_direction_orders` of [`OrderType::Limit`]
-/// and opposite [`Direction`] to the `market_order`. We nevertheless ensure that this is the case
-/// to be on the safe side.
+/// TODO(holzeis): Orders should probably not have an expiry, but should either be replaced or
+/// deleted if not wanted anymore.
+fn fail_expired_limit_orders(
+    conn: &mut PgConnection,
+    tx_price_feed: &broadcast::Sender<Message>,
+) -> Result<()> {
+    let expired_limit_orders = orders::set_expired_limit_orders_to_failed(conn)?;
+    for expired_limit_order in expired_limit_orders {
+        tx_price_feed
+            .send(Message::DeleteOrder(expired_limit_order.id))
+            .map_err(|e| anyhow!(e))
+            .context("Could not update price feed")?;
+    }
+
+    Ok(())
+}
+
+/// Protects the orderbook from traders spamming it with orders.
+fn check_order_limits(
+    conn: &mut PgConnection,
+    new_order: &NewOrder,
+    rate_limiter: &RateLimiter,
+) -> Result<()> {
+    let trader_id = new_order.trader_id;
+    let settings = rate_limiter.settings();
 
+    check_order_rate(trader_id, rate_limiter)?;
+
+    if new_order.order_type == OrderType::Limit {
+        let open_orders = orders::count_open_limit_orders(conn, trader_id)?;
+        if open_orders as usize >= settings.max_open_orders_per_trader {
+            tracing::warn!(
+                %trader_id,
+                open_orders,
+                "Rejecting order exceeding the maximum number of open orders"
+            );
+            record_rejection(Rejection::MaxOpenOrders, "process_new_order");
+
+            bail!(TradingError::TooManyOrders(format!(
+                "Exceeded the limit of {} open orders",
+                settings.max_open_orders_per_trader
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Counts an order of the trader, including amendments, against the order rate limit.
+fn check_order_rate(trader_id: PublicKey, rate_limiter: &RateLimiter) -> Result<()> {
+    if !rate_limiter.allow_order(trader_id) {
+        tracing::warn!(%trader_id, "Rejecting order exceeding the order rate limit");
+        record_rejection(Rejection::OrderRateLimit, "process_new_order");
+
+        bail!(TradingError::TooManyOrders(format!(
+            "Exceeded the limit of {} orders per second",
+            rate_limiter.settings().max_orders_per_second_per_trader
+        )));
+    }
+
+    Ok(())
+}
+
+/// Matches an [`Order`] with a list of [`Order`]s of [`OrderType::Limit`].
+///
+/// The caller is expected to provide a list of `opposite_direction_orders` of [`OrderType::Limit`]
+/// and opposite [`Direction`] to the `order`. We nevertheless ensure that this is the case to be on
+/// the safe side.
+///
+/// The order is filled across as many limit orders as needed, following price-time priority (see
+/// [`sort_orders`]). Every maker gets a [`FilledWith`] for the quantity taken from their order,
+/// while the taker gets a single [`FilledWith`] aggregating all fills.
+///
+/// An order of [`OrderType::Market`] has to be filled completely, unless it is
+/// [`TimeInForce::ImmediateOrCancel`]. If the orderbook does not provide enough liquidity, no match
+/// is returned.
+///
+/// An order of [`OrderType::Limit`] is only matched with limit orders crossing its price, and it
+/// might only be partially filled, unless it is [`TimeInForce::FillOrKill`].
+///
+/// If only a part of an order is filled, the filled part is split off into a new order which is
+/// referenced by the [`FilledWith`] and recorded in [`MatchParams::partial_fills`].
 fn match_order(
-    market_order: &Order,
+    order: &Order,
     opposite_direction_orders: Vec<Order>,
     network: Network,
     oracle_pk: XOnlyPublicKey,
 ) -> Result<Option<MatchParams>> {
-    if market_order.order_type == OrderType::Limit {
-        // We don't match limit orders with other limit orders at the moment.
-        return Ok(None);
-    }
-
     let opposite_direction_orders = opposite_direction_orders
         .into_iter()
-        .filter(|o| !o.direction.eq(&market_order.direction))
+        .filter(|o| !o.direction.eq(&order.direction))
+        .filter(|o| o.contract_symbol == order.contract_symbol)
+        .filter(|o| o.order_type == OrderType::Limit)
+        .filter(|o| order.order_type == OrderType::Market || is_crossing(order, o))
         .collect();
 
-    let mut orders = sort_orders(opposite_direction_orders, market_order.direction);
+    let orders = sort_orders(opposite_direction_orders, order.direction);
 
-    let mut remaining_quantity = market_order.quantity;
+    // Walk the limit orders by price-time priority and fill as much of the order as possible with
+    // each of them. The last maker order might only be partially consumed.
+    let mut remaining_quantity = order.quantity;
     let mut matched_orders = vec![];
-    while !orders.is_empty() {
-        let matched_order = orders.remove(0);
-        remaining_quantity -= matched_order.quantity;
-        matched_orders.push(matched_order);
-
+    for maker_order in orders {
         if remaining_quantity <= Decimal::ZERO {
             break;
         }
-    }
 
-    // For the time being we do not want to support multi-matches.
-    if matched_orders.len() > 1 {
-        bail!("More than one matched order, please reduce order quantity");
+        let quantity = maker_order.quantity.min(remaining_quantity);
+        remaining_quantity -= quantity;
+        matched_orders.push((maker_order, quantity));
     }
 
     if matched_orders.is_empty() {
         return Ok(None);
     }
 
-    let expiry_timestamp = commons::calculate_next_expiry(OffsetDateTime::now_utc(), network);
+    let mut partial_fills = vec![];
 
-    let matches = matched_orders
-        .iter()
-        .map(|maker_order| {
-            (
-                TraderMatchParams {
-                    trader_id: maker_order.trader_id,
-                    filled_with: FilledWith {
-                        order_id: maker_order.id,
-                        expiry_timestamp,
-                        oracle_pk,
-                        matches: vec![Match {
-                            id: Uuid::new_v4(),
-                            order_id: market_order.id,
-                            quantity: market_order.quantity,
-                            pubkey: market_order.trader_id,
-                            execution_price: maker_order.price,
-                        }],
-                    },
-                },
-                Match {
-                    id: Uuid::new_v4(),
-                    order_id: maker_order.id,
-                    quantity: market_order.quantity,
-                    pubkey: maker_order.trader_id,
-                    execution_price: maker_order.price,
-                },
-            )
-        })
-        .collect::<Vec<(TraderMatchParams, Match)>>();
+    let taker_order_id = if remaining_quantity > Decimal::ZERO {
+        if !allows_partial_fill(order) {
+            tracing::debug!(
+                order_id = %order.id,
+                %remaining_quantity,
+                "Not enough liquidity in the orderbook to fill order completely"
+            );
+            return Ok(None);
+        }
+
+        let filled_order_id = Uuid::new_v4();
+        partial_fills.push(PartialFill {
+            order_id: order.id,
+            filled_order_id,
+            quantity: order.quantity - remaining_quantity,
+        });
+
+        filled_order_id
+    } else {
+        order.id
+    };
+
+    let expiry_timestamp = commons::calculate_next_expiry(OffsetDateTime::now_utc(), network);
 
     let mut maker_matches = vec![];
     let mut taker_matches = vec![];
 
-    for (mm, taker_match) in matches {
-        maker_matches.push(mm);
-        taker_matches.push(taker_match);
+    for (maker_order, quantity) in matched_orders {
+        let maker_order_id = if quantity < maker_order.quantity {
+            let filled_order_id = Uuid::new_v4();
+            partial_fills.push(PartialFill {
+                order_id: maker_order.id,
+                filled_order_id,
+                quantity,
+            });
+
+            filled_order_id
+        } else {
+            maker_order.id
+        };
+
+        maker_matches.push(TraderMatchParams {
+            trader_id: maker_order.trader_id,
+            filled_with: FilledWith {
+                order_id: maker_order_id,
+                expiry_timestamp,
+                oracle_pk,
+                matches: vec![Match {
+                    id: Uuid::new_v4(),
+                    order_id: taker_order_id,
+                    quantity,
+                    pubkey: order.trader_id,
+                    execution_price: maker_order.price,
+                }],
+            },
+        });
+
+        taker_matches.push(Match {
+            id: Uuid::new_v4(),
+            order_id: maker_order_id,
+            quantity,
+            pubkey: maker_order.trader_id,
+            execution_price: maker_order.price,
+        });
     }
 
     Ok(Some(MatchParams {
         taker_match: TraderMatchParams {
-            trader_id: market_order.trader_id,
+            trader_id: order.trader_id,
             filled_with: FilledWith {
-                order_id: market_order.id,
+                order_id: taker_order_id,
                 expiry_timestamp,
                 oracle_pk,
                 matches: taker_matches,
             },
         },
         makers_matches: maker_matches,
+        partial_fills,
     }))
 }
 
+/// Whether the given `order` may be filled only partially.
+///
+/// Market orders are fill-or-kill by default.
+fn allows_partial_fill(order: &Order) -> bool {
+    match (order.order_type, order.time_in_force) {
+        (_, TimeInForce::ImmediateOrCancel) => true,
+        (_, TimeInForce::FillOrKill) => false,
+        (OrderType::Market, _) => false,
+        (OrderType::Limit, _) => true,
+    }
+}
+
+/// Whether the unfilled part of the given `order` rests in the orderbook.
+fn rests_in_orderbook(order: &Order) -> bool {
+    order.order_type == OrderType::Limit
+        && matches!(
+            order.time_in_force,
+            TimeInForce::GoodTillCancelled | TimeInForce::GoodTillDate
+        )
+}
+
+/// Whether the opposite `limit_order` offers a price which is at least as good as the price of
+/// the limit `order`.
+fn is_crossing(order: &Order, limit_order: &Order) -> bool {
+    match order.direction {
+        Direction::Long => limit_order.price <= order.price,
+        Direction::Short => limit_order.price >= order.price,
+    }
+}
+
 /// Sort the provided list of limit [`Order`]s based on the [`Direction`] of the market order to be
 /// matched.
 ///
@@ -424,8 +846,12 @@ impl From<&TradeParams> for TraderMatchParams {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::orderbook::tests::setup_db;
+    use crate::orderbook::tests::start_postgres;
+    use crate::rate_limit::RateLimitSettings;
     use rust_decimal_macros::dec;
     use std::str::FromStr;
+    use testcontainers::clients::Cli;
     use time::Duration;
     use trade::ContractSymbol;
 
@@ -567,6 +993,7 @@ mod tests {
             order_state: OrderState::Open,
             order_reason: OrderReason::Manual,
             stable: false,
+            time_in_force: TimeInForce::FillOrKill,
         };
 
         let matched_orders = match_order(
@@ -603,9 +1030,8 @@ mod tests {
         );
     }
 
-    /// This test is for safety reasons only. Once we want multiple matches we should update it
     #[test]
-    fn given_limit_and_market_with_smaller_amount_then_error() {
+    fn given_limit_and_market_with_bigger_amount_then_match_multiple_makers() {
         let order1 = dummy_long_order(
             dec!(20_000),
             Uuid::new_v4(),
@@ -628,36 +1054,147 @@ mod tests {
             dec!(20_000),
             Uuid::new_v4(),
             dec!(300),
+            Duration::seconds(1),
+        );
+        let all_orders = vec![order1.clone(), order2.clone(), order3.clone(), order4];
+
+        let order = dummy_market_order(Direction::Short, dec!(350));
+
+        let matched_orders = match_order(
+            &order,
+            all_orders,
+            Network::Bitcoin,
+            get_oracle_public_key(),
+        )
+        .unwrap()
+        .unwrap();
+
+        // The best bid is taken first, followed by the second best bid.
+        assert_eq!(matched_orders.makers_matches.len(), 3);
+
+        let expected_fills = [
+            (order3.id, dec!(100), dec!(22_000)),
+            (order2.id, dec!(200), dec!(21_000)),
+            (order1.id, dec!(50), dec!(20_000)),
+        ];
+
+        for (maker_match, (order_id, quantity, execution_price)) in
+            matched_orders.makers_matches.iter().zip(expected_fills)
+        {
+            if order_id == order1.id {
+                // Only a part of the order got filled, hence it has been split off.
+                assert_ne!(maker_match.filled_with.order_id, order_id);
+            } else {
+                assert_eq!(maker_match.filled_with.order_id, order_id);
+            }
+            assert_eq!(maker_match.filled_with.matches.len(), 1);
+
+            let maker_fill = maker_match.filled_with.matches.get(0).unwrap();
+            assert_eq!(maker_fill.order_id, order.id);
+            assert_eq!(maker_fill.quantity, quantity);
+            assert_eq!(maker_fill.execution_price, execution_price);
+        }
+
+        assert_eq!(
+            matched_orders.partial_fills,
+            vec![PartialFill {
+                order_id: order1.id,
+                filled_order_id: matched_orders.makers_matches[2].filled_with.order_id,
+                quantity: dec!(50),
+            }]
+        );
+
+        let taker_filled_with = matched_orders.taker_match.filled_with;
+        assert_eq!(taker_filled_with.order_id, order.id);
+        assert_eq!(taker_filled_with.matches.len(), 3);
+        assert_eq!(
+            taker_filled_with
+                .matches
+                .iter()
+                .map(|m| m.quantity)
+                .sum::<Decimal>(),
+            order.quantity
+        );
+        assert_eq!(
+            taker_filled_with.average_execution_price().round_dp(2),
+            dec!(21_123.45)
+        );
+    }
+
+    #[test]
+    fn given_limit_and_market_with_smaller_amount_then_partially_fill_limit_order() {
+        let maker_order = dummy_long_order(
+            dec!(20_000),
+            Uuid::new_v4(),
+            dec!(500),
             Duration::seconds(0),
         );
-        let all_orders = vec![order1, order2, order3, order4];
 
-        let order = Order {
-            id: Uuid::new_v4(),
-            price: Default::default(),
-            trader_id: PublicKey::from_str(
-                "027f31ebc5462c1fdce1b737ecff52d37d75dea43ce11c74d25aa297165faa2007",
-            )
-            .unwrap(),
-            direction: Direction::Short,
-            leverage: 1.0,
-            contract_symbol: ContractSymbol::BtcUsd,
-            quantity: dec!(200),
-            order_type: OrderType::Market,
-            timestamp: OffsetDateTime::now_utc(),
-            expiry: OffsetDateTime::now_utc() + Duration::minutes(1),
-            order_state: OrderState::Open,
-            order_reason: OrderReason::Manual,
-            stable: false,
-        };
+        let order = dummy_market_order(Direction::Short, dec!(200));
 
-        assert!(match_order(
+        let matched_orders = match_order(
+            &order,
+            vec![maker_order.clone()],
+            Network::Bitcoin,
+            get_oracle_public_key(),
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(matched_orders.makers_matches.len(), 1);
+        let maker_match = matched_orders.makers_matches.get(0).unwrap();
+        assert_eq!(
+            maker_match.filled_with.matches.get(0).unwrap().quantity,
+            dec!(200)
+        );
+
+        assert_eq!(
+            matched_orders.partial_fills,
+            vec![PartialFill {
+                order_id: maker_order.id,
+                filled_order_id: maker_match.filled_with.order_id,
+                quantity: dec!(200),
+            }]
+        );
+
+        assert_eq!(matched_orders.taker_match.filled_with.matches.len(), 1);
+        assert_eq!(
+            matched_orders
+                .taker_match
+                .filled_with
+                .average_execution_price(),
+            dec!(20_000)
+        );
+    }
+
+    #[test]
+    fn given_not_enough_liquidity_then_no_match() {
+        let all_orders = vec![
+            dummy_long_order(
+                dec!(20_000),
+                Uuid::new_v4(),
+                dec!(100),
+                Duration::seconds(0),
+            ),
+            dummy_long_order(
+                dec!(21_000),
+                Uuid::new_v4(),
+                dec!(200),
+                Duration::seconds(0),
+            ),
+        ];
+
+        let order = dummy_market_order(Direction::Short, dec!(500));
+
+        let matched_orders = match_order(
             &order,
             all_orders,
             Network::Bitcoin,
-            get_oracle_public_key()
+            get_oracle_public_key(),
         )
-        .is_err());
+        .unwrap();
+
+        assert!(matched_orders.is_none());
     }
 
     #[test]
@@ -706,6 +1243,209 @@ mod tests {
             order_state: OrderState::Open,
             order_reason: OrderReason::Manual,
             stable: false,
+            time_in_force: TimeInForce::FillOrKill,
+        };
+
+        let matched_orders = match_order(
+            &order,
+            all_orders,
+            Network::Bitcoin,
+            get_oracle_public_key(),
+        )
+        .unwrap();
+
+        assert!(matched_orders.is_none());
+    }
+
+    #[test]
+    fn given_limit_order_of_other_contract_symbol_then_no_match() {
+        let all_orders = vec![Order {
+            contract_symbol: ContractSymbol::EthUsd,
+            ..dummy_limit_order(Direction::Short, dec!(2_000), dec!(100))
+        }];
+
+        let order = dummy_market_order(Direction::Long, dec!(100));
+
+        let matched_orders = match_order(
+            &order,
+            all_orders,
+            Network::Bitcoin,
+            get_oracle_public_key(),
+        )
+        .unwrap();
+
+        assert!(matched_orders.is_none());
+    }
+
+    #[test]
+    fn given_limit_order_not_crossing_then_no_match() {
+        let all_orders = vec![
+            dummy_long_order(
+                dec!(20_000),
+                Uuid::new_v4(),
+                dec!(100),
+                Duration::seconds(0),
+            ),
+            dummy_long_order(
+                dec!(21_000),
+                Uuid::new_v4(),
+                dec!(200),
+                Duration::seconds(0),
+            ),
+        ];
+
+        let order = dummy_limit_order(Direction::Short, dec!(21_500), dec!(100));
+
+        let matched_orders = match_order(
+            &order,
+            all_orders,
+            Network::Bitcoin,
+            get_oracle_public_key(),
+        )
+        .unwrap();
+
+        assert!(matched_orders.is_none());
+    }
+
+    #[test]
+    fn given_limit_order_crossing_then_match_at_resting_price() {
+        let order1 = dummy_long_order(
+            dec!(20_000),
+            Uuid::new_v4(),
+            dec!(100),
+            Duration::seconds(0),
+        );
+        let order2 = dummy_long_order(
+            dec!(21_000),
+            Uuid::new_v4(),
+            dec!(200),
+            Duration::seconds(0),
+        );
+        let all_orders = vec![order1, order2.clone()];
+
+        let order = dummy_limit_order(Direction::Short, dec!(20_500), dec!(200));
+
+        let matched_orders = match_order(
+            &order,
+            all_orders,
+            Network::Bitcoin,
+            get_oracle_public_key(),
+        )
+        .unwrap()
+        .unwrap();
+
+        assert!(matched_orders.partial_fills.is_empty());
+        assert_eq!(matched_orders.makers_matches.len(), 1);
+        assert_eq!(
+            matched_orders.makers_matches[0].filled_with.order_id,
+            order2.id
+        );
+
+        let taker_filled_with = matched_orders.taker_match.filled_with;
+        assert_eq!(taker_filled_with.order_id, order.id);
+        assert_eq!(taker_filled_with.average_execution_price(), dec!(21_000));
+    }
+
+    #[test]
+    fn given_limit_order_partially_crossing_then_split_filled_part() {
+        let order1 = dummy_long_order(
+            dec!(20_000),
+            Uuid::new_v4(),
+            dec!(100),
+            Duration::seconds(0),
+        );
+        let order2 = dummy_long_order(
+            dec!(21_000),
+            Uuid::new_v4(),
+            dec!(200),
+            Duration::seconds(0),
+        );
+        let all_orders = vec![order1, order2.clone()];
+
+        let order = dummy_limit_order(Direction::Short, dec!(20_500), dec!(500));
+
+        let matched_orders = match_order(
+            &order,
+            all_orders,
+            Network::Bitcoin,
+            get_oracle_public_key(),
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(matched_orders.makers_matches.len(), 1);
+        assert_eq!(
+            matched_orders.makers_matches[0].filled_with.order_id,
+            order2.id
+        );
+
+        let taker_filled_with = matched_orders.taker_match.filled_with;
+        assert_ne!(taker_filled_with.order_id, order.id);
+        assert_eq!(
+            matched_orders.partial_fills,
+            vec![PartialFill {
+                order_id: order.id,
+                filled_order_id: taker_filled_with.order_id,
+                quantity: dec!(200),
+            }]
+        );
+    }
+
+    #[test]
+    fn given_not_enough_liquidity_and_immediate_or_cancel_then_partially_fill_market_order() {
+        let order1 = dummy_long_order(
+            dec!(20_000),
+            Uuid::new_v4(),
+            dec!(100),
+            Duration::seconds(0),
+        );
+        let order2 = dummy_long_order(
+            dec!(21_000),
+            Uuid::new_v4(),
+            dec!(200),
+            Duration::seconds(0),
+        );
+        let all_orders = vec![order1, order2];
+
+        let order = Order {
+            time_in_force: TimeInForce::ImmediateOrCancel,
+            ..dummy_market_order(Direction::Short, dec!(500))
+        };
+
+        let matched_orders = match_order(
+            &order,
+            all_orders,
+            Network::Bitcoin,
+            get_oracle_public_key(),
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(matched_orders.makers_matches.len(), 2);
+
+        let taker_filled_with = matched_orders.taker_match.filled_with;
+        assert_eq!(
+            matched_orders.partial_fills,
+            vec![PartialFill {
+                order_id: order.id,
+                filled_order_id: taker_filled_with.order_id,
+                quantity: dec!(300),
+            }]
+        );
+    }
+
+    #[test]
+    fn given_limit_order_partially_crossing_and_fill_or_kill_then_no_match() {
+        let all_orders = vec![dummy_long_order(
+            dec!(21_000),
+            Uuid::new_v4(),
+            dec!(200),
+            Duration::seconds(0),
+        )];
+
+        let order = Order {
+            time_in_force: TimeInForce::FillOrKill,
+            ..dummy_limit_order(Direction::Short, dec!(20_500), dec!(500))
         };
 
         let matched_orders = match_order(
@@ -719,6 +1459,113 @@ mod tests {
         assert!(matched_orders.is_none());
     }
 
+    #[test]
+    fn unfilled_remainder_rests_in_orderbook_only_for_limit_orders_with_duration() {
+        let limit_order = dummy_limit_order(Direction::Short, dec!(20_500), dec!(500));
+
+        assert!(rests_in_orderbook(&limit_order));
+        assert!(rests_in_orderbook(&Order {
+            time_in_force: TimeInForce::GoodTillCancelled,
+            ..limit_order.clone()
+        }));
+        assert!(!rests_in_orderbook(&Order {
+            time_in_force: TimeInForce::ImmediateOrCancel,
+            ..limit_order.clone()
+        }));
+        assert!(!rests_in_orderbook(&Order {
+            time_in_force: TimeInForce::FillOrKill,
+            ..limit_order
+        }));
+        assert!(!rests_in_orderbook(&dummy_market_order(
+            Direction::Short,
+            dec!(500)
+        )));
+    }
+
+    #[tokio::test]
+    async fn reject_limit_orders_exceeding_open_orders_limit() {
+        let docker = Cli::default();
+        let (_container, conn_spec) = start_postgres(&docker).unwrap();
+        let mut conn = setup_db(conn_spec);
+
+        let rate_limiter = RateLimiter::new(RateLimitSettings {
+            max_open_orders_per_trader: 2,
+            ..RateLimitSettings::default()
+        });
+
+        for _ in 0..2 {
+            let order = dummy_new_order(OrderType::Limit);
+            check_order_limits(&mut conn, &order, &rate_limiter).unwrap();
+            orders::insert(&mut conn, order, OrderReason::Manual).unwrap();
+        }
+
+        let error =
+            check_order_limits(&mut conn, &dummy_new_order(OrderType::Limit), &rate_limiter)
+                .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<TradingError>(),
+            Some(TradingError::TooManyOrders(_))
+        ));
+
+        // Market orders do not rest in the orderbook.
+        check_order_limits(
+            &mut conn,
+            &dummy_new_order(OrderType::Market),
+            &rate_limiter,
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn reject_orders_exceeding_order_rate_limit() {
+        let docker = Cli::default();
+        let (_container, conn_spec) = start_postgres(&docker).unwrap();
+        let mut conn = setup_db(conn_spec);
+
+        let rate_limiter = RateLimiter::new(RateLimitSettings {
+            max_orders_per_second_per_trader: 2,
+            ..RateLimitSettings::default()
+        });
+
+        let order = dummy_new_order(OrderType::Market);
+        check_order_limits(&mut conn, &order, &rate_limiter).unwrap();
+        check_order_limits(&mut conn, &order, &rate_limiter).unwrap();
+
+        let error = check_order_limits(&mut conn, &order, &rate_limiter).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<TradingError>(),
+            Some(TradingError::TooManyOrders(_))
+        ));
+
+        let other_trader = NewOrder {
+            trader_id: PublicKey::from_str(
+                "02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655",
+            )
+            .unwrap(),
+            ..order
+        };
+        check_order_limits(&mut conn, &other_trader, &rate_limiter).unwrap();
+    }
+
+    fn dummy_new_order(order_type: OrderType) -> NewOrder {
+        NewOrder {
+            id: Uuid::new_v4(),
+            contract_symbol: ContractSymbol::BtcUsd,
+            price: dec!(20_000),
+            quantity: dec!(100),
+            trader_id: PublicKey::from_str(
+                "027f31ebc5462c1fdce1b737ecff52d37d75dea43ce11c74d25aa297165faa2007",
+            )
+            .unwrap(),
+            direction: Direction::Long,
+            leverage: 1.0,
+            order_type,
+            expiry: OffsetDateTime::now_utc() + Duration::minutes(1),
+            stable: false,
+            time_in_force: TimeInForce::GoodTillDate,
+        }
+    }
+
     fn dummy_long_order(
         price: Decimal,
         id: Uuid,
@@ -742,6 +1589,38 @@ mod tests {
             order_state: OrderState::Open,
             order_reason: OrderReason::Manual,
             stable: false,
+            time_in_force: TimeInForce::GoodTillDate,
+        }
+    }
+
+    fn dummy_market_order(direction: Direction, quantity: Decimal) -> Order {
+        Order {
+            id: Uuid::new_v4(),
+            price: Default::default(),
+            trader_id: PublicKey::from_str(
+                "027f31ebc5462c1fdce1b737ecff52d37d75dea43ce11c74d25aa297165faa2007",
+            )
+            .unwrap(),
+            direction,
+            leverage: 1.0,
+            contract_symbol: ContractSymbol::BtcUsd,
+            quantity,
+            order_type: OrderType::Market,
+            timestamp: OffsetDateTime::now_utc(),
+            expiry: OffsetDateTime::now_utc() + Duration::minutes(1),
+            order_state: OrderState::Open,
+            order_reason: OrderReason::Manual,
+            stable: false,
+            time_in_force: TimeInForce::FillOrKill,
+        }
+    }
+
+    fn dummy_limit_order(direction: Direction, price: Decimal, quantity: Decimal) -> Order {
+        Order {
+            price,
+            order_type: OrderType::Limit,
+            time_in_force: TimeInForce::GoodTillDate,
+            ..dummy_market_order(direction, quantity)
         }
     }
 
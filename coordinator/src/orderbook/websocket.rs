@@ -5,20 +5,197 @@ use crate::orderbook::db::orders;
 use crate::routes::AppState;
 use axum::extract::ws::Message as WebsocketMessage;
 use axum::extract::ws::WebSocket;
+use bitcoin::secp256k1::PublicKey;
 use commons::create_sign_message;
+use commons::DepthFilter;
 use commons::LspConfig;
 use commons::Message;
+use commons::Order;
+use commons::OrderbookDelta;
 use commons::OrderbookRequest;
+use commons::Topic;
 use commons::AUTH_SIGN_MESSAGE;
 use futures::SinkExt;
 use futures::StreamExt;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::mpsc;
+use trade::ContractSymbol;
 
 const WEBSOCKET_SEND_TIMEOUT: Duration = Duration::from_secs(5);
 
+/// The messages a client is subscribed to.
+#[derive(Default)]
+struct Subscriptions {
+    /// `None` if the client never subscribed to a topic, in which case it receives all messages
+    /// which have been sent before topics had been introduced.
+    topics: Option<HashSet<Topic>>,
+    /// The contract symbols the client is interested in. Empty for all contract symbols.
+    symbols: HashSet<ContractSymbol>,
+    depth: DepthFilter,
+}
+
+impl Subscriptions {
+    fn subscribe(&mut self, topics: Vec<Topic>, symbols: Vec<ContractSymbol>, depth: DepthFilter) {
+        if topics.contains(&Topic::Depth) {
+            self.depth = depth;
+        }
+        self.topics.get_or_insert_with(HashSet::new).extend(topics);
+        self.symbols.extend(symbols);
+    }
+
+    fn unsubscribe(&mut self, topics: Vec<Topic>) {
+        let subscribed = self.topics.get_or_insert_with(HashSet::new);
+        for topic in topics.iter() {
+            subscribed.remove(topic);
+        }
+    }
+
+    fn subscribed(&self) -> Message {
+        Message::Subscribed {
+            topics: self.topics.iter().flatten().copied().collect::<Vec<_>>(),
+            symbols: self.symbols.iter().copied().collect(),
+        }
+    }
+
+    fn symbols(&self) -> Vec<ContractSymbol> {
+        if self.symbols.is_empty() {
+            ContractSymbol::ALL.to_vec()
+        } else {
+            self.symbols.iter().copied().collect()
+        }
+    }
+
+    /// The filter of the depth of the contract symbol, or `None` if the client is not subscribed
+    /// to it.
+    fn depth(&self, contract_symbol: ContractSymbol) -> Option<DepthFilter> {
+        let subscribed = self
+            .topics
+            .as_ref()
+            .map(|topics| topics.contains(&Topic::Depth))
+            .unwrap_or(false);
+
+        if subscribed && (self.symbols.is_empty() || self.symbols.contains(&contract_symbol)) {
+            Some(self.depth)
+        } else {
+            None
+        }
+    }
+
+    fn wants(&self, message: &Message) -> bool {
+        let topics = match &self.topics {
+            Some(topics) => topics,
+            None => {
+                // Clients which do not know about topics do not know about the messages which
+                // have been introduced with them either.
+                return !matches!(
+                    message,
+                    Message::OrderbookSnapshot { .. }
+                        | Message::OrderbookDelta { .. }
+                        | Message::TopOfBook { .. }
+                        | Message::Depth(_)
+                        | Message::Subscribed { .. }
+                );
+            }
+        };
+
+        let topic = match message.topic() {
+            Some(topic) => topic,
+            None => return true,
+        };
+
+        if !topics.contains(&topic) {
+            return false;
+        }
+
+        // Subscribers of the orderbook receive sequenced deltas instead of the individual order
+        // messages.
+        if topic == Topic::Orderbook
+            && !matches!(
+                message,
+                Message::OrderbookSnapshot { .. } | Message::OrderbookDelta { .. }
+            )
+        {
+            return false;
+        }
+
+        match message.contract_symbol() {
+            Some(contract_symbol) => {
+                self.symbols.is_empty() || self.symbols.contains(&contract_symbol)
+            }
+            None => true,
+        }
+    }
+}
+
+/// A request for the current state of the orderbook feed of a contract symbol.
+enum SnapshotRequest {
+    Orderbook(ContractSymbol),
+    TopOfBook(ContractSymbol),
+    Depth(ContractSymbol),
+}
+
+/// Replaces the trader id of the orders of other traders with the coordinator's node id, so that
+/// traders do not learn which maker offers a price. Traders always trade with the coordinator
+/// anyways.
+fn hide_maker_identities(
+    message: Message,
+    trader_id: Option<PublicKey>,
+    coordinator_id: PublicKey,
+) -> Message {
+    let hide = |order: Order| hide_maker_identity(order, trader_id, coordinator_id);
+
+    match message {
+        Message::AllOrders(orders) => Message::AllOrders(orders.into_iter().map(hide).collect()),
+        Message::NewOrder(order) => Message::NewOrder(hide(order)),
+        Message::Update(order) => Message::Update(hide(order)),
+        Message::OrderbookSnapshot {
+            contract_symbol,
+            seq,
+            orders,
+        } => Message::OrderbookSnapshot {
+            contract_symbol,
+            seq,
+            orders: orders.into_iter().map(hide).collect(),
+        },
+        Message::OrderbookDelta {
+            contract_symbol,
+            seq,
+            delta,
+        } => Message::OrderbookDelta {
+            contract_symbol,
+            seq,
+            delta: match delta {
+                OrderbookDelta::New(order) => OrderbookDelta::New(hide(order)),
+                OrderbookDelta::Update(order) => OrderbookDelta::Update(hide(order)),
+                delta @ OrderbookDelta::Delete(_) => delta,
+            },
+        },
+        message => message,
+    }
+}
+
+/// Replaces the trader id of the order with the coordinator's node id, unless the order belongs
+/// to the given trader.
+pub(crate) fn hide_maker_identity(
+    order: Order,
+    trader_id: Option<PublicKey>,
+    coordinator_id: PublicKey,
+) -> Order {
+    if Some(order.trader_id) == trader_id {
+        order
+    } else {
+        Order {
+            trader_id: coordinator_id,
+            ..order
+        }
+    }
+}
+
 // This function deals with a single websocket connection, i.e., a single
 // connected client / user, for which we will spawn two independent tasks (for
 // receiving / sending messages).
@@ -29,25 +206,43 @@ pub async fn websocket_connection(stream: WebSocket, state: Arc<AppState>) {
     // We subscribe *before* sending the "joined" message, so that we will also
     // display it to our client.
     let mut price_feed = state.tx_price_feed.subscribe();
+    let mut orderbook_feed = state.orderbook_feed.subscribe();
+
+    let subscriptions = Arc::new(RwLock::new(Subscriptions::default()));
+    // The trader id of the client once it has authenticated.
+    let authenticated = Arc::new(RwLock::new(None::<PublicKey>));
+    let coordinator_id = state.node.inner.info.pubkey;
 
     let (local_sender, mut local_receiver) = mpsc::channel::<Message>(100);
+    let (snapshot_sender, mut snapshot_receiver) = mpsc::channel::<SnapshotRequest>(10);
 
-    let mut local_recv_task = tokio::spawn(async move {
-        while let Some(local_msg) = local_receiver.recv().await {
-            match serde_json::to_string(&local_msg) {
-                Ok(msg) => {
-                    if let Err(err) = tokio::time::timeout(
-                        WEBSOCKET_SEND_TIMEOUT,
-                        sender.send(WebsocketMessage::Text(msg.clone())),
-                    )
-                    .await
-                    {
-                        tracing::error!("Could not forward message {msg} : {err:#}");
-                        return;
-                    }
+    let mut local_recv_task = tokio::spawn({
+        let subscriptions = subscriptions.clone();
+        let authenticated = authenticated.clone();
+        async move {
+            while let Some(local_msg) = local_receiver.recv().await {
+                if !subscriptions.read().wants(&local_msg) {
+                    continue;
                 }
-                Err(error) => {
-                    tracing::warn!("Could not deserialize message {error:#}");
+
+                let trader_id = *authenticated.read();
+                let local_msg = hide_maker_identities(local_msg, trader_id, coordinator_id);
+
+                match serde_json::to_string(&local_msg) {
+                    Ok(msg) => {
+                        if let Err(err) = tokio::time::timeout(
+                            WEBSOCKET_SEND_TIMEOUT,
+                            sender.send(WebsocketMessage::Text(msg.clone())),
+                        )
+                        .await
+                        {
+                            tracing::error!("Could not forward message {msg} : {err:#}");
+                            return;
+                        }
+                    }
+                    Err(error) => {
+                        tracing::warn!("Could not deserialize message {error:#}");
+                    }
                 }
             }
         }
@@ -78,6 +273,91 @@ pub async fn websocket_connection(stream: WebSocket, state: Arc<AppState>) {
         })
     };
 
+    // Spawn a task that sends the sequenced orderbook deltas following the snapshots requested by
+    // the client, and the depth whenever it changes.
+    let mut feed_task = {
+        let local_sender = local_sender.clone();
+        let subscriptions = subscriptions.clone();
+        let feed = state.orderbook_feed.clone();
+        tokio::spawn(async move {
+            // The sequence numbers of the latest snapshots sent to the client. Deltas of
+            // contract symbols without a snapshot or preceding it are not sent.
+            let mut snapshot_seqs = HashMap::new();
+            // The latest depth sent to the client per contract symbol.
+            let mut depths = HashMap::new();
+            loop {
+                let mut messages = vec![];
+                let mut changed_symbols = vec![];
+                tokio::select! {
+                    message = orderbook_feed.recv() => match message {
+                        Ok(message @ Message::OrderbookDelta { contract_symbol, seq, .. }) => {
+                            if matches!(
+                                snapshot_seqs.get(&contract_symbol),
+                                Some(snapshot_seq) if seq > *snapshot_seq
+                            ) {
+                                messages.push(message);
+                            }
+                            changed_symbols.push(contract_symbol);
+                        }
+                        Ok(message) => messages.push(message),
+                        Err(RecvError::Closed) => {
+                            tracing::error!("Orderbook feed sender died! Channel closed.");
+                            break;
+                        }
+                        Err(RecvError::Lagged(skip)) => {
+                            // The client will notice the gap in the sequence numbers and resync,
+                            // but we have to catch up on the depth ourselves.
+                            tracing::warn!(%skip, "Lagging behind on orderbook feed.");
+                            changed_symbols.extend(ContractSymbol::ALL);
+                        }
+                    },
+                    request = snapshot_receiver.recv() => match request {
+                        Some(SnapshotRequest::Orderbook(contract_symbol)) => {
+                            let snapshot = feed.snapshot(contract_symbol);
+                            if let Message::OrderbookSnapshot { seq, .. } = snapshot {
+                                snapshot_seqs.insert(contract_symbol, seq);
+                            }
+                            messages.push(snapshot);
+                        }
+                        Some(SnapshotRequest::TopOfBook(contract_symbol)) => {
+                            messages.push(feed.top_of_book(contract_symbol));
+                        }
+                        Some(SnapshotRequest::Depth(contract_symbol)) => {
+                            // Always send the depth, even if it did not change.
+                            depths.remove(&contract_symbol);
+                            changed_symbols.push(contract_symbol);
+                        }
+                        None => break,
+                    },
+                };
+
+                for contract_symbol in changed_symbols {
+                    let filter = subscriptions.read().depth(contract_symbol);
+                    let filter = match filter {
+                        Some(filter) => filter,
+                        None => {
+                            depths.remove(&contract_symbol);
+                            continue;
+                        }
+                    };
+
+                    let depth = feed.depth(contract_symbol, &filter);
+                    if depths.get(&contract_symbol) != Some(&depth) {
+                        depths.insert(contract_symbol, depth.clone());
+                        messages.push(Message::Depth(depth));
+                    }
+                }
+
+                for message in messages {
+                    if let Err(error) = local_sender.send(message).await {
+                        tracing::error!("Could not send message {error:#}");
+                        return;
+                    }
+                }
+            }
+        })
+    };
+
     // Spawn a task that takes messages from the websocket
     let local_sender = local_sender.clone();
     let mut recv_task = tokio::spawn(async move {
@@ -154,6 +434,8 @@ pub async fn websocket_connection(stream: WebSocket, state: Arc<AppState>) {
                                 return;
                             }
 
+                            *authenticated.write() = Some(trader_id);
+
                             let orders = orders::all_limit_orders(&mut conn).unwrap_or_default();
                             if let Err(e) = local_sender.send(Message::AllOrders(orders)).await {
                                 tracing::error!(%trader_id, "Failed to send all orders to user {e:#}");
@@ -188,6 +470,64 @@ pub async fn websocket_connection(stream: WebSocket, state: Arc<AppState>) {
                         }
                     }
                 }
+                Ok(OrderbookRequest::Subscribe {
+                    topics,
+                    symbols,
+                    depth,
+                }) => {
+                    let (subscribed, requests) = {
+                        let mut subscriptions = subscriptions.write();
+                        subscriptions.subscribe(topics.clone(), symbols, depth);
+
+                        let mut requests = vec![];
+                        for contract_symbol in subscriptions.symbols() {
+                            if topics.contains(&Topic::Orderbook) {
+                                requests.push(SnapshotRequest::Orderbook(contract_symbol));
+                            }
+                            if topics.contains(&Topic::TopOfBook) {
+                                requests.push(SnapshotRequest::TopOfBook(contract_symbol));
+                            }
+                            if topics.contains(&Topic::Depth) {
+                                requests.push(SnapshotRequest::Depth(contract_symbol));
+                            }
+                        }
+
+                        (subscriptions.subscribed(), requests)
+                    };
+
+                    if let Err(e) = local_sender.send(subscribed).await {
+                        tracing::error!("Failed to confirm subscription: {e:#}");
+                        return;
+                    }
+
+                    for request in requests {
+                        if let Err(e) = snapshot_sender.send(request).await {
+                            tracing::error!("Failed to request orderbook snapshot: {e:#}");
+                            return;
+                        }
+                    }
+                }
+                Ok(OrderbookRequest::Unsubscribe { topics }) => {
+                    let subscribed = {
+                        let mut subscriptions = subscriptions.write();
+                        subscriptions.unsubscribe(topics);
+                        subscriptions.subscribed()
+                    };
+
+                    if let Err(e) = local_sender.send(subscribed).await {
+                        tracing::error!("Failed to confirm unsubscription: {e:#}");
+                        return;
+                    }
+                }
+                Ok(OrderbookRequest::ResyncOrderbook { contract_symbol }) => {
+                    if let Err(e) = snapshot_sender
+                        .send(SnapshotRequest::Orderbook(contract_symbol))
+                        .await
+                    {
+                        tracing::error!("Failed to request orderbook snapshot: {e:#}");
+                        return;
+                    }
+                }
                 Err(err) => {
                     tracing::trace!("Could not deserialize msg: {text} {err:#}");
                 }
@@ -199,15 +539,23 @@ pub async fn websocket_connection(stream: WebSocket, state: Arc<AppState>) {
     tokio::select! {
         _ = (&mut send_task) => {
             recv_task.abort();
+            feed_task.abort();
             local_recv_task.abort()
         },
         _ = (&mut recv_task) => {
+            send_task.abort();
+            feed_task.abort();
+            local_recv_task.abort()
+        },
+        _ = (&mut feed_task) => {
+            recv_task.abort();
             send_task.abort();
             local_recv_task.abort()
         },
         _ = (&mut local_recv_task) => {
             recv_task.abort();
             send_task.abort();
+            feed_task.abort();
         },
     };
 }
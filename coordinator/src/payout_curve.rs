@@ -1,4 +1,3 @@
-use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
 use dlc_manager::contract::numerical_descriptor::NumericalDescriptor;
@@ -15,6 +14,7 @@ use rust_decimal::Decimal;
 use tracing::instrument;
 use trade::cfd::calculate_long_liquidation_price;
 use trade::cfd::calculate_short_liquidation_price;
+use trade::ContractKind;
 use trade::ContractSymbol;
 use trade::Direction;
 
@@ -35,14 +35,11 @@ pub fn build_contract_descriptor(
     quantity: f32,
     symbol: ContractSymbol,
 ) -> Result<ContractDescriptor> {
-    if symbol != ContractSymbol::BtcUsd {
-        bail!("We only support BTCUSD at the moment. For other symbols we will need a different payout curve");
-    }
-
     tracing::info!("Building contract descriptor");
 
     Ok(ContractDescriptor::Numerical(NumericalDescriptor {
-        payout_function: build_inverse_payout_function(
+        payout_function: build_payout_function(
+            symbol,
             coordinator_margin,
             trader_margin,
             initial_price,
@@ -56,15 +53,16 @@ pub fn build_contract_descriptor(
         difference_params: None,
         oracle_numeric_infos: dlc_trie::OracleNumericInfo {
             base: 2,
-            nb_digits: vec![20],
+            nb_digits: vec![symbol.metadata().oracle_nb_digits()],
         },
     }))
 }
 
-/// Build a [`PayoutFunction`] for an inverse perpetual future e.g. BTCUSD. Perspective is always
-/// from the person who offers, i.e. in our case from the coordinator.
+/// Build a [`PayoutFunction`] for a perpetual future of the given `contract_symbol`. Perspective is
+/// always from the person who offers, i.e. in our case from the coordinator.
 #[allow(clippy::too_many_arguments)]
-fn build_inverse_payout_function(
+fn build_payout_function(
+    contract_symbol: ContractSymbol,
     coordinator_collateral: u64,
     trader_collateral: u64,
     initial_price: Decimal,
@@ -79,13 +77,20 @@ fn build_inverse_payout_function(
     let leverage_trader = Decimal::from_f32(leverage_trader).expect("to fit into decimal");
 
     let (coordinator_liquidation_price, trader_liquidation_price) = get_liquidation_prices(
+        contract_symbol,
         initial_price,
         coordinator_direction,
         leverage_coordinator,
         leverage_trader,
     );
 
-    let payout_points = payout_curve::build_inverse_payout_function(
+    let build_payout_points = match contract_symbol.metadata().kind {
+        ContractKind::Inverse => payout_curve::build_inverse_payout_function,
+        ContractKind::Quanto { .. } => payout_curve::build_quanto_payout_function,
+    };
+
+    let payout_points = build_payout_points(
+        contract_symbol,
         quantity,
         coordinator_collateral,
         trader_collateral,
@@ -121,6 +126,7 @@ fn build_inverse_payout_function(
 
 /// Returns the liquidation price for `(coordinator, maker)`
 fn get_liquidation_prices(
+    contract_symbol: ContractSymbol,
     initial_price: Decimal,
     coordinator_direction: Direction,
     leverage_coordinator: Decimal,
@@ -128,12 +134,12 @@ fn get_liquidation_prices(
 ) -> (Decimal, Decimal) {
     let (coordinator_liquidation_price, trader_liquidation_price) = match coordinator_direction {
         Direction::Long => (
-            calculate_long_liquidation_price(leverage_coordinator, initial_price),
-            calculate_short_liquidation_price(leverage_trader, initial_price),
+            calculate_long_liquidation_price(contract_symbol, leverage_coordinator, initial_price),
+            calculate_short_liquidation_price(contract_symbol, leverage_trader, initial_price),
         ),
         Direction::Short => (
-            calculate_short_liquidation_price(leverage_coordinator, initial_price),
-            calculate_long_liquidation_price(leverage_trader, initial_price),
+            calculate_short_liquidation_price(contract_symbol, leverage_coordinator, initial_price),
+            calculate_long_liquidation_price(contract_symbol, leverage_trader, initial_price),
         ),
     };
     (coordinator_liquidation_price, trader_liquidation_price)
@@ -160,10 +166,20 @@ mod tests {
         let initial_price = dec!(36780);
         let quantity = 19.0;
         let leverage_coordinator = 2.0;
-        let coordinator_margin = calculate_margin(initial_price, quantity, leverage_coordinator);
+        let coordinator_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            initial_price,
+            quantity,
+            leverage_coordinator,
+        );
 
         let leverage_trader = 1.0;
-        let trader_margin = calculate_margin(initial_price, quantity, leverage_trader);
+        let trader_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            initial_price,
+            quantity,
+            leverage_trader,
+        );
 
         let coordinator_direction = Direction::Long;
 
@@ -209,6 +225,58 @@ mod tests {
         }
     }
 
+    #[test]
+    fn ethusd_payout_price_range_is_below_max_price() {
+        let initial_price = dec!(2250.5);
+        let quantity = 1000.0;
+        let leverage_coordinator = 2.0;
+        let coordinator_margin = calculate_margin(
+            ContractSymbol::EthUsd,
+            initial_price,
+            quantity,
+            leverage_coordinator,
+        );
+
+        let leverage_trader = 1.0;
+        let trader_margin = calculate_margin(
+            ContractSymbol::EthUsd,
+            initial_price,
+            quantity,
+            leverage_trader,
+        );
+
+        let total_collateral = coordinator_margin + trader_margin;
+        let rounding_intervals = create_rounding_interval(total_collateral);
+
+        let descriptor = build_contract_descriptor(
+            initial_price,
+            coordinator_margin,
+            trader_margin,
+            leverage_coordinator,
+            leverage_trader,
+            Direction::Short,
+            0,
+            rounding_intervals,
+            quantity,
+            ContractSymbol::EthUsd,
+        )
+        .unwrap();
+
+        let range_payouts = match descriptor {
+            ContractDescriptor::Enum(_) => unreachable!(),
+            ContractDescriptor::Numerical(numerical) => {
+                assert_eq!(numerical.oracle_numeric_infos.nb_digits, vec![17]);
+                numerical.get_range_payouts(total_collateral).unwrap()
+            }
+        };
+
+        let max_price = 2usize.pow(17);
+
+        for range_payout in &range_payouts {
+            assert!(range_payout.start + range_payout.count <= max_price);
+        }
+    }
+
     #[test]
     fn calculate_liquidation_price_coordinator_long() {
         let initial_price = dec!(30_000);
@@ -217,6 +285,7 @@ mod tests {
         let leverage_trader = dec!(3.0);
 
         let (coordinator, maker) = get_liquidation_prices(
+            ContractSymbol::BtcUsd,
             initial_price,
             coordinator_direction,
             leverage_coordinator,
@@ -235,6 +304,7 @@ mod tests {
         let leverage_trader = dec!(3.0);
 
         let (coordinator, maker) = get_liquidation_prices(
+            ContractSymbol::BtcUsd,
             initial_price,
             coordinator_direction,
             leverage_coordinator,
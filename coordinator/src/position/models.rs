@@ -8,6 +8,7 @@ use bitcoin::Address;
 use bitcoin::Amount;
 use bitcoin::Txid;
 use commons::order_matching_fee_taker;
+use commons::OrderReason;
 use commons::TradeParams;
 use dlc_manager::ContractId;
 use lightning::ln::ChannelId;
@@ -15,11 +16,12 @@ use rust_decimal::prelude::Signed;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use time::OffsetDateTime;
-use trade::bitmex_client::Quote;
 use trade::cfd::calculate_margin;
 use trade::cfd::calculate_pnl;
 use trade::ContractSymbol;
 use trade::Direction;
+use trade::Price;
+use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct NewPosition {
@@ -61,6 +63,11 @@ pub enum PositionState {
     /// We proposed a new protocol round for resizing, at the moment, we close the existing channel
     /// and open a new one, in the future this will be the resize protocol by rust-dlc
     ResizeOpeningSubchannelProposed,
+    /// The position was closed by the coordinator because the index price crossed its
+    /// liquidation price.
+    Liquidated {
+        pnl: i64,
+    },
 }
 
 /// The position acts as an aggregate of one contract of one user.
@@ -98,6 +105,10 @@ pub struct Position {
     pub closing_price: Option<f32>,
     pub trader_margin: i64,
     pub stable: bool,
+    /// The price at which the position gets closed to limit the trader's loss.
+    pub stop_loss: Option<f32>,
+    /// The price at which the position gets closed to realise the trader's profit.
+    pub take_profit: Option<f32>,
 }
 
 impl Position {
@@ -107,9 +118,9 @@ impl Position {
     }
 
     /// Calculates the profit and loss for the coordinator in satoshis
-    pub fn calculate_coordinator_pnl(&self, quote: Quote) -> Result<i64> {
+    pub fn calculate_coordinator_pnl(&self, price: Price) -> Result<i64> {
         let closing_price = match self.closing_price {
-            None => quote.get_price_for_direction(self.direction.opposite()),
+            None => price.get_price_for_direction(self.direction.opposite()),
             Some(closing_price) => {
                 Decimal::try_from(closing_price).expect("f32 closing price to fit into decimal")
             }
@@ -126,10 +137,21 @@ impl Position {
         // the position in the database is the trader's position, our direction is opposite
         let direction = self.direction.opposite();
 
-        let long_margin = calculate_margin(average_entry_price, self.quantity, long_leverage);
-        let short_margin = calculate_margin(average_entry_price, self.quantity, short_leverage);
+        let long_margin = calculate_margin(
+            self.contract_symbol,
+            average_entry_price,
+            self.quantity,
+            long_leverage,
+        );
+        let short_margin = calculate_margin(
+            self.contract_symbol,
+            average_entry_price,
+            self.quantity,
+            short_leverage,
+        );
 
         let pnl = calculate_pnl(
+            self.contract_symbol,
             average_entry_price,
             closing_price,
             self.quantity,
@@ -142,6 +164,53 @@ impl Position {
         Ok(pnl)
     }
 
+    /// Returns the [`OrderReason`] for closing the position if the given `closing_price` hits its
+    /// stop loss or take profit.
+    ///
+    /// The `closing_price` is the price at which the position would be closed, i.e. the best bid
+    /// for a long position and the best ask for a short position.
+    pub fn triggered_order_reason(&self, closing_price: Decimal) -> Option<OrderReason> {
+        let closing_price = closing_price.to_f32().expect("to fit into f32");
+
+        let is_stop_loss_hit = self
+            .stop_loss
+            .map_or(false, |stop_loss| match self.direction {
+                Direction::Long => closing_price <= stop_loss,
+                Direction::Short => closing_price >= stop_loss,
+            });
+        if is_stop_loss_hit {
+            return Some(OrderReason::StopLoss);
+        }
+
+        let is_take_profit_hit =
+            self.take_profit
+                .map_or(false, |take_profit| match self.direction {
+                    Direction::Long => closing_price >= take_profit,
+                    Direction::Short => closing_price <= take_profit,
+                });
+        if is_take_profit_hit {
+            return Some(OrderReason::TakeProfit);
+        }
+
+        None
+    }
+
+    /// Returns true if the given index `price` crossed the liquidation price of the position.
+    ///
+    /// A long position is liquidated at the best bid and a short position at the best ask, i.e. at
+    /// the price at which the position would be closed.
+    pub fn is_liquidated(&self, price: Price) -> bool {
+        let closing_price = price
+            .get_price_for_direction(self.direction.opposite())
+            .to_f32()
+            .expect("to fit into f32");
+
+        match self.direction {
+            Direction::Long => closing_price <= self.liquidation_price,
+            Direction::Short => closing_price >= self.liquidation_price,
+        }
+    }
+
     /// Calculate the settlement amount for the accept party (i.e. the trader) when closing the
     /// _entire_ position.
     pub fn calculate_accept_settlement_amount(&self, closing_price: Decimal) -> Result<u64> {
@@ -159,6 +228,7 @@ impl Position {
         );
 
         calculate_accept_settlement_amount(
+            self.contract_symbol,
             opening_price,
             closing_price,
             self.quantity,
@@ -175,6 +245,7 @@ impl Position {
         trade_params: &TradeParams,
     ) -> Result<Amount> {
         calculate_accept_settlement_amount_partial_close(
+            self.contract_symbol,
             self.quantity,
             self.direction,
             self.average_entry_price,
@@ -189,6 +260,7 @@ impl Position {
 
 /// Calculate the accept settlement amount based on the PNL.
 fn calculate_accept_settlement_amount(
+    contract_symbol: ContractSymbol,
     opening_price: Decimal,
     closing_price: Decimal,
     quantity: f32,
@@ -198,10 +270,11 @@ fn calculate_accept_settlement_amount(
 ) -> Result<u64> {
     let close_position_fee = order_matching_fee_taker(quantity, closing_price).to_sat() as i64;
 
-    let long_margin = calculate_margin(opening_price, quantity, long_leverage);
-    let short_margin = calculate_margin(opening_price, quantity, short_leverage);
+    let long_margin = calculate_margin(contract_symbol, opening_price, quantity, long_leverage);
+    let short_margin = calculate_margin(contract_symbol, opening_price, quantity, short_leverage);
 
     let pnl = calculate_pnl(
+        contract_symbol,
         opening_price,
         closing_price,
         quantity,
@@ -215,7 +288,8 @@ fn calculate_accept_settlement_amount(
         Direction::Short => short_leverage,
     };
 
-    let margin_trader_without_opening_fees = calculate_margin(opening_price, quantity, leverage);
+    let margin_trader_without_opening_fees =
+        calculate_margin(contract_symbol, opening_price, quantity, leverage);
 
     let accept_settlement_amount = Decimal::from(margin_trader_without_opening_fees)
         + Decimal::from(pnl)
@@ -249,6 +323,7 @@ fn calculate_accept_settlement_amount(
 /// fee, so we don't have to do anything about that.
 #[allow(clippy::too_many_arguments)]
 fn calculate_accept_settlement_amount_partial_close(
+    contract_symbol: ContractSymbol,
     position_quantity: f32,
     position_direction: Direction,
     position_average_execution_price: f32,
@@ -277,6 +352,7 @@ fn calculate_accept_settlement_amount_partial_close(
     );
 
     let position_trader_margin = calculate_margin(
+        contract_symbol,
         decimal_from_f32(position_average_execution_price),
         position_quantity,
         position_trader_leverage,
@@ -293,10 +369,21 @@ fn calculate_accept_settlement_amount_partial_close(
 
         let opening_price = decimal_from_f32(position_average_execution_price);
 
-        let long_margin = calculate_margin(opening_price, settled_contracts, leverage_long);
-        let short_margin = calculate_margin(opening_price, settled_contracts, leverage_short);
+        let long_margin = calculate_margin(
+            contract_symbol,
+            opening_price,
+            settled_contracts,
+            leverage_long,
+        );
+        let short_margin = calculate_margin(
+            contract_symbol,
+            opening_price,
+            settled_contracts,
+            leverage_short,
+        );
 
         let pnl = calculate_pnl(
+            contract_symbol,
             opening_price,
             trade_average_execution_price,
             settled_contracts,
@@ -316,10 +403,21 @@ fn calculate_accept_settlement_amount_partial_close(
 
         let opening_price = decimal_from_f32(position_average_execution_price);
 
-        let long_margin = calculate_margin(opening_price, settled_contracts, leverage_long);
-        let short_margin = calculate_margin(opening_price, settled_contracts, leverage_short);
+        let long_margin = calculate_margin(
+            contract_symbol,
+            opening_price,
+            settled_contracts,
+            leverage_long,
+        );
+        let short_margin = calculate_margin(
+            contract_symbol,
+            opening_price,
+            settled_contracts,
+            leverage_short,
+        );
 
         let pnl = calculate_pnl(
+            contract_symbol,
             opening_price,
             trade_average_execution_price,
             settled_contracts,
@@ -366,6 +464,32 @@ pub fn leverage_short(
     }
 }
 
+/// Audit record of a position which got liquidated by the coordinator.
+#[derive(Clone, Debug)]
+pub struct Liquidation {
+    pub id: i32,
+    pub position_id: i32,
+    pub trader_pubkey: PublicKey,
+    /// The order which has been created to close the position.
+    pub order_id: Uuid,
+    pub liquidation_price: f32,
+    /// The index price which crossed the liquidation price.
+    pub index_price: f32,
+    /// The name of the price source the index price was taken from.
+    pub price_source: String,
+    pub timestamp: OffsetDateTime,
+}
+
+#[derive(Clone, Debug)]
+pub struct NewLiquidation {
+    pub position_id: i32,
+    pub trader_pubkey: PublicKey,
+    pub order_id: Uuid,
+    pub liquidation_price: f32,
+    pub index_price: f32,
+    pub price_source: String,
+}
+
 #[derive(Clone, Debug)]
 pub struct CollaborativeRevert {
     pub channel_id: ChannelId,
@@ -422,6 +546,8 @@ impl std::fmt::Debug for Position {
             .field("closing_price", &self.closing_price)
             .field("trader_margin", &self.trader_margin)
             .field("stable", &self.stable)
+            .field("stop_loss", &self.stop_loss)
+            .field("take_profit", &self.take_profit)
             .finish()
     }
 }
@@ -441,6 +567,7 @@ mod tests {
         let closing_price = Decimal::from(23000);
         let quantity: f32 = 1.0;
         let accept_settlement_amount = calculate_accept_settlement_amount(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -450,7 +577,7 @@ mod tests {
         )
         .unwrap();
 
-        let margin_trader = calculate_margin(opening_price, quantity, 1.0);
+        let margin_trader = calculate_margin(ContractSymbol::BtcUsd, opening_price, quantity, 1.0);
         assert!(accept_settlement_amount > margin_trader);
     }
 
@@ -460,6 +587,7 @@ mod tests {
         let closing_price = Decimal::from(23000);
         let quantity: f32 = 1.0;
         let accept_settlement_amount = calculate_accept_settlement_amount(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -469,7 +597,7 @@ mod tests {
         )
         .unwrap();
 
-        let margin_trader = calculate_margin(opening_price, quantity, 1.0);
+        let margin_trader = calculate_margin(ContractSymbol::BtcUsd, opening_price, quantity, 1.0);
         assert!(accept_settlement_amount < margin_trader);
     }
 
@@ -479,6 +607,7 @@ mod tests {
         let closing_price = Decimal::from(22000);
         let quantity: f32 = 1.0;
         let accept_settlement_amount = calculate_accept_settlement_amount(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -488,7 +617,7 @@ mod tests {
         )
         .unwrap();
 
-        let margin_trader = calculate_margin(opening_price, quantity, 1.0);
+        let margin_trader = calculate_margin(ContractSymbol::BtcUsd, opening_price, quantity, 1.0);
         assert!(accept_settlement_amount < margin_trader);
     }
 
@@ -498,6 +627,7 @@ mod tests {
         let closing_price = Decimal::from(22000);
         let quantity: f32 = 1.0;
         let accept_settlement_amount = calculate_accept_settlement_amount(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -507,7 +637,7 @@ mod tests {
         )
         .unwrap();
 
-        let margin_trader = calculate_margin(opening_price, quantity, 1.0);
+        let margin_trader = calculate_margin(ContractSymbol::BtcUsd, opening_price, quantity, 1.0);
         assert!(accept_settlement_amount > margin_trader);
     }
 
@@ -517,6 +647,7 @@ mod tests {
         let closing_price = Decimal::from(23000);
         let quantity: f32 = 1.0;
         let accept_settlement_amount = calculate_accept_settlement_amount(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -526,7 +657,7 @@ mod tests {
         )
         .unwrap();
 
-        let margin_trader = calculate_margin(opening_price, quantity, 2.0);
+        let margin_trader = calculate_margin(ContractSymbol::BtcUsd, opening_price, quantity, 2.0);
         assert!(accept_settlement_amount > margin_trader);
     }
 
@@ -536,6 +667,7 @@ mod tests {
         let closing_price = Decimal::from(23000);
         let quantity: f32 = 1.0;
         let accept_settlement_amount = calculate_accept_settlement_amount(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -545,7 +677,7 @@ mod tests {
         )
         .unwrap();
 
-        let margin_trader = calculate_margin(opening_price, quantity, 1.0);
+        let margin_trader = calculate_margin(ContractSymbol::BtcUsd, opening_price, quantity, 1.0);
         assert!(accept_settlement_amount < margin_trader);
     }
 
@@ -555,6 +687,7 @@ mod tests {
         let closing_price = Decimal::from(22000);
         let quantity: f32 = 1.0;
         let accept_settlement_amount = calculate_accept_settlement_amount(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -564,7 +697,7 @@ mod tests {
         )
         .unwrap();
 
-        let margin_trader = calculate_margin(opening_price, quantity, 2.0);
+        let margin_trader = calculate_margin(ContractSymbol::BtcUsd, opening_price, quantity, 2.0);
         assert!(accept_settlement_amount < margin_trader);
     }
 
@@ -574,6 +707,7 @@ mod tests {
         let closing_price = Decimal::from(22000);
         let quantity: f32 = 1.0;
         let accept_settlement_amount = calculate_accept_settlement_amount(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -583,7 +717,7 @@ mod tests {
         )
         .unwrap();
 
-        let margin_trader = calculate_margin(opening_price, quantity, 2.0);
+        let margin_trader = calculate_margin(ContractSymbol::BtcUsd, opening_price, quantity, 2.0);
         assert!(accept_settlement_amount > margin_trader);
     }
 
@@ -595,9 +729,9 @@ mod tests {
             .with_average_entry_price(1000.0)
             .with_direction(Direction::Long);
 
-        let quote = dummy_quote(1000, 0);
+        let price = dummy_price(1000, 0);
 
-        let coordinator_pnl = position.calculate_coordinator_pnl(quote).unwrap();
+        let coordinator_pnl = position.calculate_coordinator_pnl(price).unwrap();
 
         assert_eq!(coordinator_pnl, 0);
     }
@@ -610,9 +744,9 @@ mod tests {
             .with_average_entry_price(1000.0)
             .with_direction(Direction::Short);
 
-        let quote = dummy_quote(0, 1000);
+        let price = dummy_price(0, 1000);
 
-        let coordinator_pnl = position.calculate_coordinator_pnl(quote).unwrap();
+        let coordinator_pnl = position.calculate_coordinator_pnl(price).unwrap();
 
         assert_eq!(coordinator_pnl, 0);
     }
@@ -639,9 +773,9 @@ mod tests {
             .with_average_entry_price(20000.0)
             .with_direction(Direction::Long);
 
-        let quote = dummy_quote(22000, 0);
+        let price = dummy_price(22000, 0);
 
-        let coordinator_pnl = position.calculate_coordinator_pnl(quote).unwrap();
+        let coordinator_pnl = position.calculate_coordinator_pnl(price).unwrap();
 
         assert_eq!(coordinator_pnl, -9_090_909);
     }
@@ -655,9 +789,9 @@ mod tests {
             .with_average_entry_price(20000.0)
             .with_direction(Direction::Short);
 
-        let quote = dummy_quote(0, 22000);
+        let price = dummy_price(0, 22000);
 
-        let coordinator_pnl = position.calculate_coordinator_pnl(quote).unwrap();
+        let coordinator_pnl = position.calculate_coordinator_pnl(price).unwrap();
 
         assert_eq!(coordinator_pnl, 9_090_909);
     }
@@ -671,9 +805,9 @@ mod tests {
             .with_average_entry_price(20000.0)
             .with_direction(Direction::Long);
 
-        let quote = dummy_quote(18000, 0);
+        let price = dummy_price(18000, 0);
 
-        let coordinator_pnl = position.calculate_coordinator_pnl(quote).unwrap();
+        let coordinator_pnl = position.calculate_coordinator_pnl(price).unwrap();
 
         assert_eq!(coordinator_pnl, 11_111_111);
     }
@@ -687,9 +821,9 @@ mod tests {
             .with_average_entry_price(20000.0)
             .with_direction(Direction::Short);
 
-        let quote = dummy_quote(0, 18000);
+        let price = dummy_price(0, 18000);
 
-        let coordinator_pnl = position.calculate_coordinator_pnl(quote).unwrap();
+        let coordinator_pnl = position.calculate_coordinator_pnl(price).unwrap();
 
         assert_eq!(coordinator_pnl, -11_111_111);
     }
@@ -697,6 +831,7 @@ mod tests {
     #[test]
     fn accept_settlement_amount_partial_close_position_reduced() {
         let amount = calculate_accept_settlement_amount_partial_close(
+            ContractSymbol::BtcUsd,
             10_000.0,
             Direction::Long,
             30_000.0,
@@ -714,6 +849,7 @@ mod tests {
     #[test]
     fn accept_settlement_amount_partial_close_position_direction_changed() {
         let amount = calculate_accept_settlement_amount_partial_close(
+            ContractSymbol::BtcUsd,
             10_000.0,
             Direction::Long,
             5_000.0,
@@ -728,6 +864,7 @@ mod tests {
         assert_eq!(amount.to_sat(), 133_333_333);
 
         let amount = calculate_accept_settlement_amount_partial_close(
+            ContractSymbol::BtcUsd,
             10_000.0,
             Direction::Long,
             5_000.0,
@@ -745,6 +882,7 @@ mod tests {
     #[test]
     fn accept_settlement_amount_partial_close_position_increased() {
         let amount = calculate_accept_settlement_amount_partial_close(
+            ContractSymbol::BtcUsd,
             10_000.0,
             Direction::Long,
             5_000.0,
@@ -763,6 +901,7 @@ mod tests {
     #[should_panic]
     fn accept_settlement_amount_partial_close_position_goes_to_zero_panics() {
         let _ = calculate_accept_settlement_amount_partial_close(
+            ContractSymbol::BtcUsd,
             10_000.0,
             Direction::Long,
             5_000.0,
@@ -778,6 +917,7 @@ mod tests {
     #[should_panic]
     fn accept_settlement_amount_partial_close_position_unchanged_panics() {
         let _ = calculate_accept_settlement_amount_partial_close(
+            ContractSymbol::BtcUsd,
             10_000.0,
             Direction::Long,
             5_000.0,
@@ -789,19 +929,79 @@ mod tests {
         );
     }
 
-    fn dummy_quote(bid: u64, ask: u64) -> Quote {
-        Quote {
-            bid_size: 0,
-            ask_size: 0,
-            bid_price: Decimal::from(bid),
-            ask_price: Decimal::from(ask),
-            symbol: "".to_string(),
-            timestamp: OffsetDateTime::now_utc(),
+    #[test]
+    fn given_long_position_when_price_drops_to_stop_loss_then_stop_loss_triggered() {
+        let position = Position::dummy()
+            .with_direction(Direction::Long)
+            .with_triggers(Some(9_000.0), Some(11_000.0));
+
+        assert_eq!(position.triggered_order_reason(dec!(9_500)), None);
+        assert_eq!(
+            position.triggered_order_reason(dec!(9_000)),
+            Some(OrderReason::StopLoss)
+        );
+        assert_eq!(
+            position.triggered_order_reason(dec!(11_500)),
+            Some(OrderReason::TakeProfit)
+        );
+    }
+
+    #[test]
+    fn given_short_position_when_price_rises_to_stop_loss_then_stop_loss_triggered() {
+        let position = Position::dummy()
+            .with_direction(Direction::Short)
+            .with_triggers(Some(11_000.0), Some(9_000.0));
+
+        assert_eq!(position.triggered_order_reason(dec!(10_500)), None);
+        assert_eq!(
+            position.triggered_order_reason(dec!(11_000)),
+            Some(OrderReason::StopLoss)
+        );
+        assert_eq!(
+            position.triggered_order_reason(dec!(8_500)),
+            Some(OrderReason::TakeProfit)
+        );
+    }
+
+    #[test]
+    fn given_position_without_triggers_then_nothing_triggered() {
+        let position = Position::dummy();
+
+        assert_eq!(position.triggered_order_reason(dec!(0)), None);
+        assert_eq!(position.triggered_order_reason(dec!(1_000_000)), None);
+    }
+
+    #[test]
+    fn given_long_position_when_bid_crosses_liquidation_price_then_liquidated() {
+        let position = Position::dummy()
+            .with_direction(Direction::Long)
+            .with_liquidation_price(6_667.0);
+
+        assert!(!position.is_liquidated(dummy_price(7_000, 6_000)));
+        assert!(position.is_liquidated(dummy_price(6_667, 7_000)));
+        assert!(position.is_liquidated(dummy_price(6_000, 7_000)));
+    }
+
+    #[test]
+    fn given_short_position_when_ask_crosses_liquidation_price_then_liquidated() {
+        let position = Position::dummy()
+            .with_direction(Direction::Short)
+            .with_liquidation_price(20_000.0);
+
+        assert!(!position.is_liquidated(dummy_price(21_000, 19_000)));
+        assert!(position.is_liquidated(dummy_price(19_000, 20_000)));
+        assert!(position.is_liquidated(dummy_price(19_000, 21_000)));
+    }
+
+    fn dummy_price(bid: u64, ask: u64) -> Price {
+        Price {
+            bid: Decimal::from(bid),
+            ask: Decimal::from(ask),
         }
     }
 
     impl Position {
-        fn dummy() -> Self {
+        pub(crate) fn dummy() -> Self {
             Position {
                 id: 0,
                 contract_symbol: ContractSymbol::BtcUsd,
@@ -824,6 +1024,8 @@ mod tests {
                 coordinator_leverage: 2.0,
                 trader_margin: 1000,
                 stable: false,
+                stop_loss: None,
+                take_profit: None,
             }
         }
 
@@ -846,5 +1048,16 @@ mod tests {
             self.direction = direction;
             self
         }
+
+        fn with_liquidation_price(mut self, liquidation_price: f32) -> Self {
+            self.liquidation_price = liquidation_price;
+            self
+        }
+
+        fn with_triggers(mut self, stop_loss: Option<f32>, take_profit: Option<f32>) -> Self {
+            self.stop_loss = stop_loss;
+            self.take_profit = take_profit;
+            self
+        }
     }
 }
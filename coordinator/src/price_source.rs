@@ -0,0 +1,87 @@
+use crate::orderbook::db::orders;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use async_trait::async_trait;
+use bitcoin::Network;
+use commons::best_current_price;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::PgConnection;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::task::spawn_blocking;
+use trade::price_source::BitmexPriceSource;
+use trade::price_source::MedianPriceSource;
+use trade::price_source::PriceSource;
+use trade::ContractSymbol;
+use trade::Price;
+
+/// The source of the index price used by the coordinator, e.g. to calculate the unrealized PnL of
+/// open positions.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceSourceKind {
+    /// The best bid and ask of our own orderbook.
+    Orderbook,
+    /// The latest BitMEX quote.
+    #[default]
+    Bitmex,
+    /// The median of our own orderbook and BitMEX.
+    Median,
+}
+
+/// Price source using the best bid and ask of the coordinator's own orderbook.
+pub struct OrderbookPriceSource {
+    pool: Pool<ConnectionManager<PgConnection>>,
+}
+
+impl OrderbookPriceSource {
+    pub fn new(pool: Pool<ConnectionManager<PgConnection>>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PriceSource for OrderbookPriceSource {
+    fn name(&self) -> &'static str {
+        "orderbook"
+    }
+
+    async fn get_price(&self, contract_symbol: ContractSymbol) -> Result<Price> {
+        let pool = self.pool.clone();
+        let orders = spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            let orders = orders::all_limit_orders(&mut conn)?;
+            anyhow::Ok(orders)
+        })
+        .await
+        .expect("task to complete")?;
+
+        let prices = best_current_price(&orders);
+        let price = prices
+            .get(&contract_symbol)
+            .with_context(|| format!("No price for {contract_symbol} in orderbook"))?;
+
+        match (price.bid, price.ask) {
+            (Some(bid), Some(ask)) => Ok(Price { bid, ask }),
+            _ => bail!("Orderbook does not provide both a bid and an ask for {contract_symbol}"),
+        }
+    }
+}
+
+/// Creates the [`PriceSource`] for the given [`PriceSourceKind`].
+pub fn new_price_source(
+    kind: PriceSourceKind,
+    pool: Pool<ConnectionManager<PgConnection>>,
+    network: Network,
+) -> Box<dyn PriceSource> {
+    match kind {
+        PriceSourceKind::Orderbook => Box::new(OrderbookPriceSource::new(pool)),
+        PriceSourceKind::Bitmex => Box::new(BitmexPriceSource::new(network)),
+        PriceSourceKind::Median => Box::new(MedianPriceSource::new(vec![
+            Box::new(OrderbookPriceSource::new(pool)),
+            Box::new(BitmexPriceSource::new(network)),
+        ])),
+    }
+}
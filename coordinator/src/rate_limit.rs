@@ -0,0 +1,446 @@
+use crate::metrics::REJECTED_REQUESTS;
+use crate::AppError;
+use axum::extract::ConnectInfo;
+use axum::extract::MatchedPath;
+use axum::extract::State;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::IntoResponse;
+use axum::response::Response;
+use bitcoin::secp256k1::PublicKey;
+use opentelemetry::KeyValue;
+use parking_lot::Mutex;
+use parking_lot::RwLock;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::net::IpAddr;
+use std::net::Ipv6Addr;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+/// The maximum number of keys tracked per limit. Events of new keys are allowed without being
+/// tracked while the limit is reached, until the expired windows have been pruned with
+/// [`RateLimiter::prune`].
+const MAX_TRACKED_KEYS: usize = 100_000;
+
+/// Limits protecting the public endpoints of the coordinator from abusive clients.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub struct RateLimitSettings {
+    /// The maximum number of requests per minute from a single IP address.
+    pub max_requests_per_minute_per_ip: u32,
+    /// The maximum number of requests per minute on behalf of a single node id.
+    ///
+    /// Has to allow the maker to requote all its price levels several times per minute.
+    pub max_requests_per_minute_per_node_id: u32,
+    /// Whether to take the client IP address from the `X-Forwarded-For` header, e.g. when
+    /// running behind a reverse proxy. The right-most address of the header is used, which has
+    /// to be appended by the reverse proxy.
+    pub use_forwarded_for_header: bool,
+    /// The maximum size of a request body. Changes only take effect after a restart.
+    pub max_request_size_bytes: usize,
+    /// The maximum size of a backup. Changes only take effect after a restart.
+    pub max_backup_size_bytes: usize,
+    /// The maximum number of limit orders a trader can have in the orderbook.
+    pub max_open_orders_per_trader: usize,
+    /// The maximum number of orders a trader can submit per second.
+    ///
+    /// Has to allow the maker to place or amend the orders of all its price levels on both sides
+    /// at once.
+    pub max_orders_per_second_per_trader: u32,
+}
+
+impl Default for RateLimitSettings {
+    fn default() -> Self {
+        Self {
+            max_requests_per_minute_per_ip: 600,
+            max_requests_per_minute_per_node_id: 600,
+            use_forwarded_for_header: false,
+            max_request_size_bytes: 64 * 1024,
+            max_backup_size_bytes: 10 * 1024 * 1024,
+            max_open_orders_per_trader: 100,
+            max_orders_per_second_per_trader: 50,
+        }
+    }
+}
+
+/// Why a request has been rejected, reported as label of the [`REJECTED_REQUESTS`] metric.
+#[derive(Debug, Clone, Copy)]
+pub enum Rejection {
+    IpRateLimit,
+    NodeIdRateLimit,
+    OrderRateLimit,
+    MaxOpenOrders,
+    RequestTooLarge,
+    InvalidSignature,
+    ReplayedRequest,
+}
+
+impl Rejection {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Rejection::IpRateLimit => "ip_rate_limit",
+            Rejection::NodeIdRateLimit => "node_id_rate_limit",
+            Rejection::OrderRateLimit => "order_rate_limit",
+            Rejection::MaxOpenOrders => "max_open_orders",
+            Rejection::RequestTooLarge => "request_too_large",
+            Rejection::InvalidSignature => "invalid_signature",
+            Rejection::ReplayedRequest => "replayed_request",
+        }
+    }
+}
+
+pub fn record_rejection(rejection: Rejection, endpoint: &str) {
+    let cx = opentelemetry::Context::current();
+    REJECTED_REQUESTS.add(
+        &cx,
+        1,
+        &[
+            KeyValue::new("reason", rejection.as_str()),
+            KeyValue::new("endpoint", endpoint.to_string()),
+        ],
+    );
+}
+
+pub struct RateLimiter {
+    settings: RwLock<RateLimitSettings>,
+    per_ip: FixedWindow<IpAddr>,
+    per_node_id: FixedWindow<PublicKey>,
+    orders: FixedWindow<PublicKey>,
+}
+
+impl RateLimiter {
+    pub fn new(settings: RateLimitSettings) -> Self {
+        Self {
+            settings: RwLock::new(settings),
+            per_ip: FixedWindow::new(Duration::from_secs(60)),
+            per_node_id: FixedWindow::new(Duration::from_secs(60)),
+            orders: FixedWindow::new(Duration::from_secs(1)),
+        }
+    }
+
+    pub fn settings(&self) -> RateLimitSettings {
+        *self.settings.read()
+    }
+
+    pub fn update_settings(&self, settings: RateLimitSettings) {
+        tracing::info!(?settings, "Updating rate limits");
+        *self.settings.write() = settings;
+    }
+
+    /// Counts a request on behalf of the node id, failing if it exceeds the limit.
+    pub fn check_node_id(&self, node_id: PublicKey, endpoint: &str) -> Result<(), AppError> {
+        let limit = self.settings.read().max_requests_per_minute_per_node_id;
+        if self.per_node_id.allow(node_id, limit, Instant::now()) {
+            return Ok(());
+        }
+
+        tracing::warn!(%node_id, endpoint, "Rejecting request exceeding the node id rate limit");
+        record_rejection(Rejection::NodeIdRateLimit, endpoint);
+
+        Err(AppError::TooManyRequests(format!(
+            "Exceeded the limit of {limit} requests per minute"
+        )))
+    }
+
+    /// Counts an order of the trader, returning `false` if it exceeds the limit.
+    pub fn allow_order(&self, trader_id: PublicKey) -> bool {
+        let limit = self.settings.read().max_orders_per_second_per_trader;
+        self.orders.allow(trader_id, limit, Instant::now())
+    }
+
+    /// Forgets the keys whose windows have expired.
+    ///
+    /// Should be called periodically to bound the memory used by the limits.
+    pub fn prune(&self) {
+        let now = Instant::now();
+        self.per_ip.prune(now);
+        self.per_node_id.prune(now);
+        self.orders.prune(now);
+    }
+
+    fn allow_ip(&self, ip: IpAddr) -> bool {
+        let limit = self.settings.read().max_requests_per_minute_per_ip;
+        self.per_ip.allow(ip_key(ip), limit, Instant::now())
+    }
+}
+
+/// The key under which the requests of the IP address are counted.
+///
+/// A single client usually gets assigned a whole /64 IPv6 subnet, hence all addresses of a /64
+/// subnet share their limit.
+fn ip_key(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(_) => ip,
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => IpAddr::V4(ip),
+            None => IpAddr::V6(Ipv6Addr::from(u128::from(ip) & !u128::from(u64::MAX))),
+        },
+    }
+}
+
+/// Middleware rejecting the requests of IP addresses exceeding their rate limit.
+///
+/// Also records the requests which have been rejected for being too large.
+pub async fn limit_by_ip<B>(
+    State(rate_limiter): State<Arc<RateLimiter>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let endpoint = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_default();
+
+    let use_forwarded_for_header = rate_limiter.settings().use_forwarded_for_header;
+    if let Some(ip) = client_ip(&request, use_forwarded_for_header) {
+        if !rate_limiter.allow_ip(ip) {
+            tracing::warn!(%ip, endpoint, "Rejecting request exceeding the IP rate limit");
+            record_rejection(Rejection::IpRateLimit, &endpoint);
+
+            return AppError::TooManyRequests("Too many requests".to_string()).into_response();
+        }
+    }
+
+    let response = next.run(request).await;
+
+    if response.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        record_rejection(Rejection::RequestTooLarge, &endpoint);
+    }
+
+    response
+}
+
+fn client_ip<B>(request: &Request<B>, use_forwarded_for_header: bool) -> Option<IpAddr> {
+    if use_forwarded_for_header {
+        // The right-most address has been added by our reverse proxy, all other addresses are
+        // provided by the client and cannot be trusted.
+        let forwarded_for = request
+            .headers()
+            .get_all("X-Forwarded-For")
+            .iter()
+            .last()
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit(',').next())
+            .and_then(|ip| ip.trim().parse().ok());
+
+        if forwarded_for.is_some() {
+            return forwarded_for;
+        }
+    }
+
+    request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+}
+
+/// Counts the events per key in fixed windows of `period`.
+struct FixedWindow<K> {
+    period: Duration,
+    windows: Mutex<HashMap<K, (Instant, u32)>>,
+}
+
+impl<K: Eq + Hash> FixedWindow<K> {
+    fn new(period: Duration) -> Self {
+        Self {
+            period,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Counts an event of the key, returning `false` if the key has exceeded the limit within the
+    /// current window.
+    ///
+    /// Events of new keys are allowed without counting them while [`MAX_TRACKED_KEYS`] are
+    /// tracked, so that filling up the windows does not lock out every other client.
+    fn allow(&self, key: K, limit: u32, now: Instant) -> bool {
+        let mut windows = self.windows.lock();
+
+        if windows.len() >= MAX_TRACKED_KEYS && !windows.contains_key(&key) {
+            tracing::debug!("Allowing event of untracked key, too many keys are tracked");
+            return true;
+        }
+
+        let (start, count) = windows.entry(key).or_insert((now, 0));
+        if now.duration_since(*start) >= self.period {
+            *start = now;
+            *count = 0;
+        }
+
+        if *count >= limit {
+            return false;
+        }
+
+        *count += 1;
+        true
+    }
+
+    fn prune(&self, now: Instant) {
+        self.windows
+            .lock()
+            .retain(|_, (start, _)| now.duration_since(*start) < self.period);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rate_limit::ip_key;
+    use crate::rate_limit::limit_by_ip;
+    use crate::rate_limit::FixedWindow;
+    use crate::rate_limit::RateLimitSettings;
+    use crate::rate_limit::RateLimiter;
+    use crate::rate_limit::MAX_TRACKED_KEYS;
+    use axum::body::Body;
+    use axum::extract::ConnectInfo;
+    use axum::http::Request;
+    use axum::http::StatusCode;
+    use axum::middleware;
+    use axum::routing::get;
+    use axum::Router;
+    use bitcoin::secp256k1::SecretKey;
+    use bitcoin::secp256k1::SECP256K1;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use std::time::Instant;
+    use tower::ServiceExt;
+
+    #[test]
+    fn rejects_events_exceeding_the_limit() {
+        let window = FixedWindow::new(Duration::from_secs(60));
+        let now = Instant::now();
+
+        assert!(window.allow("a", 2, now));
+        assert!(window.allow("a", 2, now));
+        assert!(!window.allow("a", 2, now));
+        assert!(window.allow("b", 2, now));
+    }
+
+    #[test]
+    fn limit_is_reset_with_the_next_window() {
+        let window = FixedWindow::new(Duration::from_secs(60));
+        let now = Instant::now();
+
+        assert!(window.allow("a", 1, now));
+        assert!(!window.allow("a", 1, now + Duration::from_secs(59)));
+        assert!(window.allow("a", 1, now + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn new_keys_are_not_limited_until_expired_windows_are_pruned() {
+        let window = FixedWindow::new(Duration::from_secs(60));
+        let now = Instant::now();
+
+        for key in 0..MAX_TRACKED_KEYS {
+            assert!(window.allow(key, 1, now));
+        }
+
+        // Tracked keys are still limited, while new keys are let through untracked.
+        assert!(!window.allow(0, 1, now));
+        assert!(window.allow(MAX_TRACKED_KEYS, 1, now));
+        assert!(window.allow(MAX_TRACKED_KEYS, 1, now));
+
+        window.prune(now + Duration::from_secs(60));
+        assert!(window.allow(MAX_TRACKED_KEYS, 1, now + Duration::from_secs(60)));
+        assert!(!window.allow(MAX_TRACKED_KEYS, 1, now + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn ipv6_addresses_are_limited_per_64_subnet() {
+        let ip = |ip: &str| ip_key(ip.parse().unwrap());
+
+        assert_eq!(ip("2001:db8:1:2::1"), ip("2001:db8:1:2:ffff::1"));
+        assert_ne!(ip("2001:db8:1:2::1"), ip("2001:db8:1:3::1"));
+        assert_eq!(ip("::ffff:1.1.1.1"), ip("1.1.1.1"));
+        assert_ne!(ip("1.1.1.1"), ip("1.1.1.2"));
+    }
+
+    #[test]
+    fn default_limits_allow_the_maker_to_requote_all_levels() {
+        let rate_limiter = RateLimiter::new(RateLimitSettings::default());
+        let maker_id = SecretKey::from_slice(&[1; 32])
+            .unwrap()
+            .public_key(SECP256K1);
+
+        // The maker quotes five levels per side by default.
+        for _ in 0..2 * 5 {
+            assert!(rate_limiter.allow_order(maker_id));
+            rate_limiter
+                .check_node_id(maker_id, "/api/orderbook/orders")
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn requests_exceeding_the_ip_limit_are_rejected() {
+        let app = app(RateLimitSettings {
+            max_requests_per_minute_per_ip: 2,
+            ..RateLimitSettings::default()
+        });
+
+        assert_eq!(send(&app, from_ip("1.1.1.1")).await, StatusCode::OK);
+        assert_eq!(send(&app, from_ip("1.1.1.1")).await, StatusCode::OK);
+        assert_eq!(
+            send(&app, from_ip("1.1.1.1")).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(send(&app, from_ip("2.2.2.2")).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn right_most_forwarded_for_address_is_rate_limited() {
+        let app = app(RateLimitSettings {
+            max_requests_per_minute_per_ip: 1,
+            use_forwarded_for_header: true,
+            ..RateLimitSettings::default()
+        });
+
+        let forwarded_for = |addresses: &str| {
+            let mut request = from_ip("10.0.0.1");
+            request
+                .headers_mut()
+                .insert("X-Forwarded-For", addresses.parse().unwrap());
+            request
+        };
+
+        assert_eq!(
+            send(&app, forwarded_for("1.1.1.1, 2.2.2.2")).await,
+            StatusCode::OK
+        );
+        // A client cannot evade the limit by prepending addresses.
+        assert_eq!(
+            send(&app, forwarded_for("3.3.3.3, 2.2.2.2")).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(
+            send(&app, forwarded_for("1.1.1.1, 4.4.4.4")).await,
+            StatusCode::OK
+        );
+    }
+
+    fn app(settings: RateLimitSettings) -> Router {
+        let rate_limiter = Arc::new(RateLimiter::new(settings));
+
+        Router::new()
+            .route("/", get(|| async {}))
+            .route_layer(middleware::from_fn_with_state(rate_limiter, limit_by_ip))
+    }
+
+    fn from_ip(ip: &str) -> Request<Body> {
+        let addr = SocketAddr::new(ip.parse().unwrap(), 1234);
+
+        let mut request = Request::builder().uri("/").body(Body::empty()).unwrap();
+        request.extensions_mut().insert(ConnectInfo(addr));
+        request
+    }
+
+    async fn send(app: &Router, request: Request<Body>) -> StatusCode {
+        app.clone().oneshot(request).await.unwrap().status()
+    }
+}
@@ -4,14 +4,18 @@ use crate::admin::connect_to_peer;
 use crate::admin::expert_collaborative_revert;
 use crate::admin::get_balance;
 use crate::admin::is_connected;
+use crate::admin::list_audit_log;
 use crate::admin::list_channels;
 use crate::admin::list_dlc_channels;
+use crate::admin::list_liquidations;
 use crate::admin::list_on_chain_transactions;
 use crate::admin::list_peers;
 use crate::admin::open_channel;
 use crate::admin::send_payment;
 use crate::admin::sign_message;
-use crate::backup::SledBackup;
+use crate::admin_auth::require_scope;
+use crate::admin_auth::AdminScope;
+use crate::backup::UserBackup;
 use crate::collaborative_revert::confirm_collaborative_revert;
 use crate::db;
 use crate::db::liquidity::LiquidityRequestLog;
@@ -20,37 +24,53 @@ use crate::is_liquidity_sufficient;
 use crate::message::NewUserMessage;
 use crate::message::OrderbookMessage;
 use crate::node::Node;
+use crate::orderbook::feed::OrderbookFeed;
+use crate::orderbook::routes::get_candles;
+use crate::orderbook::routes::get_depth;
+use crate::orderbook::routes::get_fill_history;
 use crate::orderbook::routes::get_order;
+use crate::orderbook::routes::get_order_history;
 use crate::orderbook::routes::get_orders;
+use crate::orderbook::routes::get_trades;
 use crate::orderbook::routes::post_order;
 use crate::orderbook::routes::put_order;
 use crate::orderbook::routes::websocket_handler;
-use crate::orderbook::trading::NewOrderMessage;
+use crate::orderbook::trading::TradingMessage;
 use crate::parse_channel_id;
+use crate::rate_limit::limit_by_ip;
+use crate::rate_limit::RateLimiter;
 use crate::settings::Settings;
 use crate::settings::SettingsFile;
+use crate::trader_auth::signed_by_node_id;
+use crate::trader_auth::NonceCache;
+use crate::trader_auth::SignedRequest;
 use crate::AppError;
+use axum::extract::DefaultBodyLimit;
 use axum::extract::Path;
 use axum::extract::Query;
 use axum::extract::State;
 use axum::http::StatusCode;
+use axum::middleware;
 use axum::response::IntoResponse;
 use axum::routing::delete;
 use axum::routing::get;
 use axum::routing::post;
+use axum::routing::put;
 use axum::Json;
 use axum::Router;
 use bitcoin::consensus::encode::serialize_hex;
 use bitcoin::hashes::hex::ToHex;
-use bitcoin::secp256k1::ecdsa::Signature;
 use bitcoin::secp256k1::PublicKey;
 use commons::Backup;
+use commons::BackupVersion;
 use commons::CollaborativeRevertTraderResponse;
 use commons::DeleteBackup;
 use commons::Message;
 use commons::OnboardingParam;
+use commons::PositionTriggers;
 use commons::RegisterParams;
 use commons::Restore;
+use commons::RestoreParams;
 use commons::RouteHintHop;
 use commons::TradeParams;
 use diesel::r2d2::ConnectionManager;
@@ -71,7 +91,6 @@ use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use serde::Deserialize;
 use serde::Serialize;
-use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::broadcast;
 use tokio::sync::mpsc;
@@ -84,16 +103,26 @@ pub struct AppState {
     // Channel used to send messages to all connected clients.
     pub tx_price_feed: broadcast::Sender<Message>,
     pub tx_user_feed: broadcast::Sender<NewUserMessage>,
-    pub trading_sender: mpsc::Sender<NewOrderMessage>,
+    pub trading_sender: mpsc::Sender<TradingMessage>,
     pub pool: Pool<ConnectionManager<PgConnection>>,
     pub settings: RwLock<Settings>,
     pub exporter: PrometheusExporter,
     pub announcement_addresses: Vec<SocketAddress>,
     pub node_alias: String,
     pub auth_users_notifier: mpsc::Sender<OrderbookMessage>,
-    pub user_backup: SledBackup,
+    pub user_backup: UserBackup,
+    pub orderbook_feed: Arc<OrderbookFeed>,
+    pub rate_limiter: Arc<RateLimiter>,
+    /// The nonces of the recently accepted requests signed by traders, to reject replays.
+    pub nonces: Arc<NonceCache>,
+    /// Whether the admin API is served without requiring an admin token.
+    pub admin_auth_disabled: bool,
 }
 
+/// Returns the routers of the public API and of the admin API.
+///
+/// Every admin route requires a token granting the [`AdminScope`] of the route, unless
+/// `admin_auth_disabled` is set.
 #[allow(clippy::too_many_arguments)]
 pub fn router(
     node: Node,
@@ -102,12 +131,17 @@ pub fn router(
     exporter: PrometheusExporter,
     announcement_addresses: Vec<SocketAddress>,
     node_alias: &str,
-    trading_sender: mpsc::Sender<NewOrderMessage>,
+    trading_sender: mpsc::Sender<TradingMessage>,
     tx_price_feed: broadcast::Sender<Message>,
     tx_user_feed: broadcast::Sender<NewUserMessage>,
     auth_users_notifier: mpsc::Sender<OrderbookMessage>,
-    user_backup: SledBackup,
-) -> Router {
+    user_backup: UserBackup,
+    orderbook_feed: Arc<OrderbookFeed>,
+    rate_limiter: Arc<RateLimiter>,
+    nonces: Arc<NonceCache>,
+    admin_auth_disabled: bool,
+) -> (Router, Router) {
+    let rate_limits = rate_limiter.settings();
     let app_state = Arc::new(AppState {
         node,
         pool,
@@ -120,12 +154,22 @@ pub fn router(
         node_alias: node_alias.to_string(),
         auth_users_notifier,
         user_backup,
+        orderbook_feed,
+        rate_limiter,
+        nonces,
+        admin_auth_disabled,
     });
 
-    Router::new()
+    let public = Router::new()
         .route("/", get(index))
         .route("/api/version", get(version))
-        .route("/api/backup/:node_id", post(back_up).delete(delete_backup))
+        .route(
+            "/api/backup/:node_id",
+            post(back_up)
+                .delete(delete_backup)
+                .layer(DefaultBodyLimit::max(rate_limits.max_backup_size_bytes)),
+        )
+        .route("/api/backup/:node_id/versions", get(list_backup_versions))
         .route("/api/restore/:node_id", get(restore))
         .route(
             "/api/prepare_onboarding_payment",
@@ -139,41 +183,122 @@ pub fn router(
             "/api/orderbook/orders/:order_id",
             get(get_order).put(put_order),
         )
+        .route("/api/orderbook/trades", get(get_trades))
+        .route("/api/orderbook/candles", get(get_candles))
+        .route("/api/orderbook/depth/:contract_symbol", get(get_depth))
+        .route(
+            "/api/orderbook/history/:trader_id/orders",
+            get(get_order_history),
+        )
+        .route(
+            "/api/orderbook/history/:trader_id/fills",
+            get(get_fill_history),
+        )
         .route("/api/orderbook/websocket", get(websocket_handler))
         .route("/api/trade", post(post_trade))
         .route("/api/rollover/:dlc_channel_id", post(rollover))
         .route("/api/register", post(post_register))
-        .route("/api/admin/balance", get(get_balance))
-        .route("/api/admin/channels", get(list_channels).post(open_channel))
-        .route("/api/admin/channels/:channel_id", delete(close_channel))
-        .route("/api/admin/peers", get(list_peers))
-        .route("/api/admin/send_payment/:invoice", post(send_payment))
-        .route("/api/admin/dlc_channels", get(list_dlc_channels))
-        .route("/api/admin/transactions", get(list_on_chain_transactions))
-        .route("/api/admin/sign/:msg", get(sign_message))
-        .route("/api/admin/connect", post(connect_to_peer))
-        .route("/api/admin/channels/revert", post(collaborative_revert))
+        .route("/api/position/triggers", post(post_position_triggers))
+        .route(
+            "/api/channels/revertconfirm",
+            post(collaborative_revert_confirm),
+        )
+        // The limit only applies to the public routes above.
+        .route_layer(middleware::from_fn_with_state(
+            app_state.rate_limiter.clone(),
+            limit_by_ip,
+        ))
+        .route("/metrics", get(get_metrics))
+        .route("/health", get(get_health))
+        .layer(DefaultBodyLimit::max(rate_limits.max_request_size_bytes))
+        .with_state(app_state.clone());
+
+    let read_only =
+        middleware::from_fn_with_state((app_state.clone(), AdminScope::ReadOnly), require_scope);
+    let channel_ops =
+        middleware::from_fn_with_state((app_state.clone(), AdminScope::ChannelOps), require_scope);
+    let funds_movement = middleware::from_fn_with_state(
+        (app_state.clone(), AdminScope::FundsMovement),
+        require_scope,
+    );
+
+    let admin = Router::new()
+        .route(
+            "/api/admin/balance",
+            get(get_balance).route_layer(read_only.clone()),
+        )
+        .route(
+            "/api/admin/channels",
+            get(list_channels)
+                .route_layer(read_only.clone())
+                .merge(post(open_channel).route_layer(channel_ops.clone())),
+        )
+        .route(
+            "/api/admin/channels/:channel_id",
+            delete(close_channel).route_layer(channel_ops.clone()),
+        )
+        .route(
+            "/api/admin/peers",
+            get(list_peers).route_layer(read_only.clone()),
+        )
+        .route(
+            "/api/admin/send_payment/:invoice",
+            post(send_payment).route_layer(funds_movement.clone()),
+        )
+        .route(
+            "/api/admin/dlc_channels",
+            get(list_dlc_channels).route_layer(read_only.clone()),
+        )
+        .route(
+            "/api/admin/liquidations",
+            get(list_liquidations).route_layer(read_only.clone()),
+        )
+        .route(
+            "/api/admin/transactions",
+            get(list_on_chain_transactions).route_layer(read_only.clone()),
+        )
+        .route(
+            "/api/admin/sign/:msg",
+            get(sign_message).route_layer(funds_movement.clone()),
+        )
+        .route(
+            "/api/admin/connect",
+            post(connect_to_peer).route_layer(channel_ops.clone()),
+        )
+        .route(
+            "/api/admin/channels/revert",
+            post(collaborative_revert).route_layer(channel_ops.clone()),
+        )
         .route(
             "/api/admin/channels/revert-expert",
-            post(expert_collaborative_revert),
+            post(expert_collaborative_revert).route_layer(channel_ops.clone()),
         )
         .route(
-            "/api/channels/revertconfirm",
-            post(collaborative_revert_confirm),
+            "/api/admin/is_connected/:target_pubkey",
+            get(is_connected).route_layer(read_only.clone()),
         )
-        .route("/api/admin/is_connected/:target_pubkey", get(is_connected))
         .route(
             "/api/admin/settings",
-            get(get_settings).put(update_settings),
+            get(get_settings)
+                .route_layer(read_only.clone())
+                .merge(put(update_settings).route_layer(channel_ops.clone())),
+        )
+        .route(
+            "/api/admin/sync",
+            post(post_sync).route_layer(channel_ops.clone()),
         )
-        .route("/api/admin/sync", post(post_sync))
         .route(
             "/api/admin/broadcast_announcement",
-            post(post_broadcast_announcement),
+            post(post_broadcast_announcement).route_layer(channel_ops),
         )
-        .route("/metrics", get(get_metrics))
-        .route("/health", get(get_health))
-        .with_state(app_state)
+        .route(
+            "/api/admin/audit_log",
+            get(list_audit_log).route_layer(read_only),
+        )
+        .layer(DefaultBodyLimit::max(rate_limits.max_request_size_bytes))
+        .with_state(app_state);
+
+    (public, admin)
 }
 
 #[derive(serde::Serialize)]
@@ -321,9 +446,20 @@ pub async fn get_invoice(
 
 pub async fn post_trade(
     State(state): State<Arc<AppState>>,
-    trade_params: Json<TradeParams>,
+    SignedRequest {
+        trader_id,
+        body: trade_params,
+    }: SignedRequest<TradeParams>,
 ) -> Result<(), AppError> {
-    state.node.trade(&trade_params.0).await.map_err(|e| {
+    if trade_params.pubkey != trader_id {
+        return Err(AppError::Forbidden(
+            "Trade has to be signed by the trader".to_string(),
+        ));
+    }
+
+    state.rate_limiter.check_node_id(trader_id, "/api/trade")?;
+
+    state.node.trade(&trade_params).await.map_err(|e| {
         AppError::InternalServerError(format!("Could not handle trade request: {e:#}"))
     })
 }
@@ -333,6 +469,7 @@ pub async fn post_trade(
 pub async fn rollover(
     State(state): State<Arc<AppState>>,
     Path(dlc_channel_id): Path<String>,
+    SignedRequest { trader_id, .. }: SignedRequest<()>,
 ) -> Result<(), AppError> {
     let dlc_channel_id = DlcChannelId::from_hex(dlc_channel_id.clone()).map_err(|e| {
         AppError::InternalServerError(format!(
@@ -340,6 +477,22 @@ pub async fn rollover(
         ))
     })?;
 
+    let contract = state
+        .node
+        .inner
+        .get_contract_by_dlc_channel_id(&dlc_channel_id)
+        .map_err(|e| {
+            AppError::BadRequest(format!(
+                "Could not find contract of dlc channel with id {}: {e:#}",
+                dlc_channel_id.to_hex()
+            ))
+        })?;
+    if contract.get_counter_party_id() != trader_id {
+        return Err(AppError::Forbidden(
+            "Rollover has to be signed by the counterparty of the dlc channel".to_string(),
+        ));
+    }
+
     state
         .node
         .propose_rollover(&dlc_channel_id, state.node.inner.network)
@@ -393,9 +546,21 @@ pub async fn post_sync(State(state): State<Arc<AppState>>) -> Result<(), AppErro
 #[instrument(skip_all, err(Debug))]
 pub async fn post_register(
     State(state): State<Arc<AppState>>,
-    params: Json<RegisterParams>,
+    SignedRequest {
+        trader_id,
+        body: register_params,
+    }: SignedRequest<RegisterParams>,
 ) -> Result<(), AppError> {
-    let register_params = params.0;
+    if register_params.pubkey != trader_id {
+        return Err(AppError::Forbidden(
+            "Registration has to be signed by the trader".to_string(),
+        ));
+    }
+
+    state
+        .rate_limiter
+        .check_node_id(trader_id, "/api/register")?;
+
     tracing::info!(?register_params, "Registered new user");
 
     let mut conn = state
@@ -413,6 +578,45 @@ pub async fn post_register(
     Ok(())
 }
 
+#[instrument(skip_all, err(Debug))]
+pub async fn post_position_triggers(
+    State(state): State<Arc<AppState>>,
+    SignedRequest {
+        trader_id,
+        body: triggers,
+    }: SignedRequest<PositionTriggers>,
+) -> Result<(), AppError> {
+    tracing::info!(?triggers, "Setting position triggers");
+
+    if triggers.pubkey != trader_id {
+        return Err(AppError::Forbidden(
+            "Position triggers have to be signed by the trader".to_string(),
+        ));
+    }
+
+    let is_invalid_price = |price: Option<f32>| price.map_or(false, |price| price <= 0.0);
+    if is_invalid_price(triggers.stop_loss) || is_invalid_price(triggers.take_profit) {
+        return Err(AppError::BadRequest(
+            "Stop loss and take profit have to be positive".to_string(),
+        ));
+    }
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|e| AppError::InternalServerError(format!("Could not get connection: {e:#}")))?;
+
+    db::positions::Position::set_position_triggers(
+        &mut conn,
+        triggers.pubkey.to_string(),
+        triggers.stop_loss,
+        triggers.take_profit,
+    )
+    .map_err(|e| AppError::BadRequest(format!("Could not set position triggers: {e:#}")))?;
+
+    Ok(())
+}
+
 async fn get_settings(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     let settings = state.settings.read().await;
     serde_json::to_string(&*settings).expect("to be able to serialise settings")
@@ -448,6 +652,12 @@ async fn update_settings(
     // Forward relevant settings down to the LDK node.
     state.node.update_ldk_settings(settings.to_ldk_settings());
 
+    state
+        .user_backup
+        .update_retention(settings.backup_retention);
+
+    state.rate_limiter.update_settings(settings.rate_limits);
+
     Ok(())
 }
 
@@ -499,7 +709,10 @@ pub async fn version() -> Result<Json<Version>, AppError> {
 #[instrument(skip_all, err(Debug))]
 pub async fn collaborative_revert_confirm(
     State(state): State<Arc<AppState>>,
-    revert_params: Json<CollaborativeRevertTraderResponse>,
+    SignedRequest {
+        trader_id,
+        body: revert_params,
+    }: SignedRequest<CollaborativeRevertTraderResponse>,
 ) -> Result<Json<String>, AppError> {
     let mut conn = state.pool.clone().get().map_err(|error| {
         AppError::InternalServerError(format!("Could not acquire db lock {error:#}"))
@@ -514,6 +727,17 @@ pub async fn collaborative_revert_confirm(
         AppError::BadRequest("Invalid channel id provided".to_string())
     })?;
 
+    let revert = db::collaborative_reverts::get_by_channel_id(&mut conn, &channel_id)
+        .map_err(|e| {
+            AppError::InternalServerError(format!("Could not load collaborative revert: {e:#}"))
+        })?
+        .ok_or_else(|| AppError::BadRequest("Unknown collaborative revert".to_string()))?;
+    if revert.trader_pubkey != trader_id {
+        return Err(AppError::Forbidden(
+            "Collaborative revert has to be confirmed by the trader".to_string(),
+        ));
+    }
+
     tracing::info!(
         channel_id = channel_id_string,
         "Confirming collaborative channel revert"
@@ -543,18 +767,24 @@ pub async fn collaborative_revert_confirm(
 pub async fn back_up(
     Path(node_id): Path<String>,
     State(state): State<Arc<AppState>>,
-    backup: Json<Backup>,
+    SignedRequest {
+        trader_id,
+        body: backup,
+    }: SignedRequest<Backup>,
 ) -> Result<(), AppError> {
-    let node_id = PublicKey::from_str(&node_id)
-        .map_err(|e| AppError::BadRequest(format!("Invalid node id provided. {e:#}")))?;
+    let node_id = signed_by_node_id(&node_id, trader_id)?;
 
     backup
         .verify(&node_id)
         .map_err(|_| AppError::Unauthorized)?;
 
+    state
+        .rate_limiter
+        .check_node_id(node_id, "/api/backup/:node_id")?;
+
     state
         .user_backup
-        .back_up(node_id, backup.0)
+        .back_up(node_id, backup)
         .await
         .map_err(|e| AppError::InternalServerError(e.to_string()))
 }
@@ -563,10 +793,12 @@ pub async fn back_up(
 pub async fn delete_backup(
     Path(node_id): Path<String>,
     State(state): State<Arc<AppState>>,
-    backup: Json<DeleteBackup>,
+    SignedRequest {
+        trader_id,
+        body: backup,
+    }: SignedRequest<DeleteBackup>,
 ) -> Result<(), AppError> {
-    let node_id = PublicKey::from_str(&node_id)
-        .map_err(|e| AppError::BadRequest(format!("Invalid node id provided. {e:#}")))?;
+    let node_id = signed_by_node_id(&node_id, trader_id)?;
 
     backup
         .verify(&node_id)
@@ -574,29 +806,44 @@ pub async fn delete_backup(
 
     state
         .user_backup
-        .delete(node_id, backup.0)
+        .delete(node_id, backup)
+        .await
         .map_err(|e| AppError::InternalServerError(e.to_string()))
 }
 
 #[instrument(skip_all, err(Debug))]
 async fn restore(
     Path(node_id): Path<String>,
+    Query(params): Query<RestoreParams>,
     State(state): State<Arc<AppState>>,
-    signature: Json<Signature>,
+    SignedRequest { trader_id, .. }: SignedRequest<()>,
 ) -> Result<Json<Vec<Restore>>, AppError> {
-    let node_id = PublicKey::from_str(&node_id)
-        .map_err(|e| AppError::BadRequest(format!("Invalid node id provided. {e:#}")))?;
-
-    let message = node_id.to_string().as_bytes().to_vec();
-    let message = commons::create_sign_message(message);
-    signature
-        .verify(&message, &node_id)
-        .map_err(|_| AppError::Unauthorized)?;
+    let node_id = signed_by_node_id(&node_id, trader_id)?;
 
     let backup = state
         .user_backup
-        .restore(node_id)
+        .restore(node_id, params)
+        .await
         .map_err(|e| AppError::InternalServerError(format!("Failed to restore backup. {e:#}")))?;
 
     Ok(Json(backup))
 }
+
+#[instrument(skip_all, err(Debug))]
+async fn list_backup_versions(
+    Path(node_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    SignedRequest { trader_id, .. }: SignedRequest<()>,
+) -> Result<Json<Vec<BackupVersion>>, AppError> {
+    let node_id = signed_by_node_id(&node_id, trader_id)?;
+
+    let versions = state
+        .user_backup
+        .list_versions(node_id)
+        .await
+        .map_err(|e| {
+            AppError::InternalServerError(format!("Failed to list backup versions. {e:#}"))
+        })?;
+
+    Ok(Json(versions))
+}
@@ -1,6 +1,10 @@
 // @generated automatically by Diesel CLI.
 
 pub mod sql_types {
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "CandleResolution_Type"))]
+    pub struct CandleResolutionType;
+
     #[derive(diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "ChannelState_Type"))]
     pub struct ChannelStateType;
@@ -40,6 +44,62 @@ pub mod sql_types {
     #[derive(diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "PositionState_Type"))]
     pub struct PositionStateType;
+
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "TimeInForce_Type"))]
+    pub struct TimeInForceType;
+}
+
+diesel::table! {
+    admin_audit_log (id) {
+        id -> Int4,
+        token_name -> Text,
+        scope -> Text,
+        method -> Text,
+        path -> Text,
+        status_code -> Int4,
+        timestamp -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    admin_tokens (id) {
+        id -> Int4,
+        name -> Text,
+        token_hash -> Text,
+        scopes -> Array<Text>,
+        created_at -> Timestamptz,
+        revoked_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    backup_versions (node_id, version) {
+        node_id -> Text,
+        version -> Int8,
+        key -> Text,
+        timestamp -> Timestamptz,
+        deleted -> Bool,
+        pruned -> Bool,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ContractSymbolType;
+    use super::sql_types::CandleResolutionType;
+
+    candles (contract_symbol, resolution, start_time) {
+        contract_symbol -> ContractSymbolType,
+        resolution -> CandleResolutionType,
+        start_time -> Timestamptz,
+        open -> Float4,
+        high -> Float4,
+        low -> Float4,
+        close -> Float4,
+        volume -> Float4,
+        updated_at -> Timestamptz,
+    }
 }
 
 diesel::table! {
@@ -76,6 +136,19 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    liquidations (id) {
+        id -> Int4,
+        position_id -> Int4,
+        trader_pubkey -> Text,
+        order_id -> Uuid,
+        liquidation_price -> Float4,
+        index_price -> Float4,
+        price_source -> Text,
+        timestamp -> Timestamptz,
+    }
+}
+
 diesel::table! {
     liquidity_options (id) {
         id -> Int4,
@@ -129,6 +202,7 @@ diesel::table! {
     use super::sql_types::OrderStateType;
     use super::sql_types::ContractSymbolType;
     use super::sql_types::OrderReasonType;
+    use super::sql_types::TimeInForceType;
 
     orders (id) {
         id -> Int4,
@@ -145,6 +219,7 @@ diesel::table! {
         leverage -> Float4,
         order_reason -> OrderReasonType,
         stable -> Bool,
+        time_in_force -> TimeInForceType,
     }
 }
 
@@ -197,6 +272,8 @@ diesel::table! {
         coordinator_leverage -> Float4,
         trader_margin -> Int8,
         stable -> Bool,
+        stop_loss -> Nullable<Float4>,
+        take_profit -> Nullable<Float4>,
     }
 }
 
@@ -250,6 +327,14 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    user_backups (tree, key) {
+        tree -> Text,
+        key -> Bytea,
+        value -> Bytea,
+    }
+}
+
 diesel::table! {
     users (id) {
         id -> Int4,
@@ -262,12 +347,18 @@ diesel::table! {
     }
 }
 
+diesel::joinable!(liquidations -> positions (position_id));
 diesel::joinable!(liquidity_request_logs -> liquidity_options (liquidity_option));
 diesel::joinable!(trades -> positions (position_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    admin_audit_log,
+    admin_tokens,
+    backup_versions,
+    candles,
     channels,
     collaborative_reverts,
+    liquidations,
     liquidity_options,
     liquidity_request_logs,
     matches,
@@ -278,5 +369,6 @@ diesel::allow_tables_to_appear_in_same_query!(
     spendable_outputs,
     trades,
     transactions,
+    user_backups,
     users,
 );
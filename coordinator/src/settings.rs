@@ -1,4 +1,8 @@
+use crate::backup::BackupRetention;
+use crate::backup::BackupStoreKind;
 use crate::node::NodeSettings;
+use crate::price_source::PriceSourceKind;
+use crate::rate_limit::RateLimitSettings;
 use anyhow::Context;
 use anyhow::Result;
 use lightning::util::config::UserConfig;
@@ -59,6 +63,18 @@ pub struct Settings {
     /// Min balance to keep in on-chain wallet at all times
     pub min_liquidity_threshold_sats: u64,
 
+    /// The source of the index price, e.g. used to calculate the unrealized PnL of positions
+    pub price_source: PriceSourceKind,
+
+    /// Defines which versions of the user backups are kept
+    pub backup_retention: BackupRetention,
+
+    /// Where the user backups are stored. Changes only take effect after a restart.
+    pub backup_store: BackupStoreKind,
+
+    /// Rate and size limits of the public endpoints
+    pub rate_limits: RateLimitSettings,
+
     // Location of the settings file in the file system.
     path: PathBuf,
 }
@@ -97,6 +113,7 @@ impl Settings {
             max_allowed_tx_fee_rate_when_opening_channel: self
                 .max_allowed_tx_fee_rate_when_opening_channel,
             contract_tx_fee_rate: self.contract_tx_fee_rate,
+            price_source: self.price_source,
         }
     }
 
@@ -132,6 +149,10 @@ impl Settings {
             rollover_window_close_scheduler: file.rollover_window_close_scheduler,
             close_expired_position_scheduler: file.close_expired_position_scheduler,
             min_liquidity_threshold_sats: file.min_liquidity_threshold_sats,
+            price_source: file.price_source,
+            backup_retention: file.backup_retention,
+            backup_store: file.backup_store,
+            rate_limits: file.rate_limits,
             path,
         }
     }
@@ -156,6 +177,18 @@ pub struct SettingsFile {
     close_expired_position_scheduler: String,
 
     min_liquidity_threshold_sats: u64,
+
+    #[serde(default)]
+    price_source: PriceSourceKind,
+
+    #[serde(default)]
+    backup_retention: BackupRetention,
+
+    #[serde(default)]
+    backup_store: BackupStoreKind,
+
+    #[serde(default)]
+    rate_limits: RateLimitSettings,
 }
 
 impl From<Settings> for SettingsFile {
@@ -173,6 +206,10 @@ impl From<Settings> for SettingsFile {
             rollover_window_close_scheduler: value.rollover_window_close_scheduler,
             close_expired_position_scheduler: value.close_expired_position_scheduler,
             min_liquidity_threshold_sats: value.min_liquidity_threshold_sats,
+            price_source: value.price_source,
+            backup_retention: value.backup_retention,
+            backup_store: value.backup_store,
+            rate_limits: value.rate_limits,
         }
     }
 }
@@ -209,6 +246,21 @@ mod tests {
             rollover_window_close_scheduler: "bar".to_string(),
             close_expired_position_scheduler: "baz".to_string(),
             min_liquidity_threshold_sats: 2,
+            price_source: PriceSourceKind::Median,
+            backup_retention: BackupRetention {
+                max_versions: 5,
+                max_age_days: Some(30),
+            },
+            backup_store: BackupStoreKind::Postgres,
+            rate_limits: RateLimitSettings {
+                max_requests_per_minute_per_ip: 1,
+                max_requests_per_minute_per_node_id: 2,
+                use_forwarded_for_header: true,
+                max_request_size_bytes: 3,
+                max_backup_size_bytes: 4,
+                max_open_orders_per_trader: 5,
+                max_orders_per_second_per_trader: 6,
+            },
         };
 
         let serialized = toml::to_string_pretty(&original).unwrap();
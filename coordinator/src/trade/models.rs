@@ -1,4 +1,6 @@
+use crate::decimal_from_f32;
 use bitcoin::secp256k1::PublicKey;
+use commons::PublicTrade;
 use lightning::ln::PaymentHash;
 use time::OffsetDateTime;
 use trade::ContractSymbol;
@@ -37,3 +39,15 @@ pub struct Trade {
     pub timestamp: OffsetDateTime,
     pub fee_payment_hash: PaymentHash,
 }
+
+impl From<&Trade> for PublicTrade {
+    fn from(value: &Trade) -> Self {
+        PublicTrade {
+            contract_symbol: value.contract_symbol,
+            price: decimal_from_f32(value.average_price),
+            quantity: decimal_from_f32(value.quantity),
+            direction: value.direction,
+            timestamp: value.timestamp,
+        }
+    }
+}
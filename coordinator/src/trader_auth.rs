@@ -0,0 +1,187 @@
+use crate::rate_limit::record_rejection;
+use crate::rate_limit::Rejection;
+use crate::routes::AppState;
+use crate::AppError;
+use axum::async_trait;
+use axum::body::Body;
+use axum::body::Bytes;
+use axum::extract::FromRequest;
+use axum::extract::MatchedPath;
+use axum::http::Request;
+use axum::response::IntoResponse;
+use axum::response::Response;
+use bitcoin::secp256k1::PublicKey;
+use commons::RequestSignature;
+use commons::MAX_REQUEST_AGE;
+use parking_lot::Mutex;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use time::OffsetDateTime;
+
+/// A JSON request body signed by a trader.
+///
+/// Extracting it verifies the [`RequestSignature`] in the request headers and rejects requests
+/// which have already been accepted before. It is up to the handler to check that the trader is
+/// allowed to act on whatever the request refers to.
+pub struct SignedRequest<T> {
+    pub trader_id: PublicKey,
+    pub body: T,
+}
+
+#[async_trait]
+impl<T> FromRequest<Arc<AppState>, Body> for SignedRequest<T>
+where
+    T: DeserializeOwned,
+{
+    type Rejection = Response;
+
+    async fn from_request(request: Request<Body>, state: &Arc<AppState>) -> Result<Self, Response> {
+        let method = request.method().to_string();
+        let path = request
+            .uri()
+            .path_and_query()
+            .map(|path| path.as_str().to_string())
+            .unwrap_or_default();
+        let endpoint = request
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str().to_string())
+            .unwrap_or_default();
+
+        let headers = request.headers();
+        let signature = RequestSignature::from_headers(|name| {
+            headers.get(name).and_then(|value| value.to_str().ok())
+        });
+
+        let body = Bytes::from_request(request, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        let now = OffsetDateTime::now_utc();
+        let signature = signature
+            .and_then(|signature| {
+                signature.verify(&method, &path, &body, now)?;
+                Ok(signature)
+            })
+            .map_err(|e| {
+                tracing::warn!(endpoint, "Rejecting request with invalid signature: {e:#}");
+                record_rejection(Rejection::InvalidSignature, &endpoint);
+                AppError::Unauthorized.into_response()
+            })?;
+
+        let trader_id = signature.node_id;
+        if !state.nonces.insert(trader_id, signature.nonce, now) {
+            tracing::warn!(%trader_id, endpoint, "Rejecting replayed request");
+            record_rejection(Rejection::ReplayedRequest, &endpoint);
+            return Err(AppError::Unauthorized.into_response());
+        }
+
+        // Requests without a body, e.g. to rollover, are signed over the empty body.
+        let body = if body.is_empty() {
+            &b"null"[..]
+        } else {
+            &body[..]
+        };
+        let body = serde_json::from_slice(body).map_err(|e| {
+            AppError::BadRequest(format!("Invalid request body: {e:#}")).into_response()
+        })?;
+
+        Ok(Self { trader_id, body })
+    }
+}
+
+/// Parses the node id a request refers to, e.g. in its path, failing unless the request has been
+/// signed by that node.
+pub fn signed_by_node_id(node_id: &str, trader_id: PublicKey) -> Result<PublicKey, AppError> {
+    let node_id = PublicKey::from_str(node_id)
+        .map_err(|e| AppError::BadRequest(format!("Invalid node id provided. {e:#}")))?;
+
+    if node_id != trader_id {
+        return Err(AppError::Forbidden(
+            "Request has to be signed by the node".to_string(),
+        ));
+    }
+
+    Ok(node_id)
+}
+
+/// The nonces of the recently accepted signed requests.
+#[derive(Default)]
+pub struct NonceCache {
+    accepted: Mutex<HashMap<(PublicKey, String), OffsetDateTime>>,
+}
+
+impl NonceCache {
+    /// Remembers the nonce used by the trader, returning `false` if it has already been used.
+    fn insert(&self, trader_id: PublicKey, nonce: String, now: OffsetDateTime) -> bool {
+        self.accepted
+            .lock()
+            .insert((trader_id, nonce), now)
+            .is_none()
+    }
+
+    /// Forgets the nonces of requests which would be rejected as expired anyway.
+    ///
+    /// Should be called periodically to bound the memory used by the cache.
+    pub fn prune(&self, now: OffsetDateTime) {
+        // A request may be signed up to `MAX_REQUEST_AGE` in the future, hence its nonce has to be
+        // remembered for twice as long.
+        self.accepted
+            .lock()
+            .retain(|_, accepted_at| now - *accepted_at <= MAX_REQUEST_AGE * 2);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::trader_auth::NonceCache;
+    use bitcoin::secp256k1::PublicKey;
+    use commons::MAX_REQUEST_AGE;
+    use std::str::FromStr;
+    use time::Duration;
+    use time::OffsetDateTime;
+
+    #[test]
+    fn reused_nonce_is_rejected() {
+        let cache = NonceCache::default();
+        let now = OffsetDateTime::now_utc();
+
+        assert!(cache.insert(dummy_trader_id(), "nonce".to_string(), now));
+        assert!(!cache.insert(dummy_trader_id(), "nonce".to_string(), now));
+        assert!(cache.insert(dummy_trader_id(), "other nonce".to_string(), now));
+    }
+
+    #[test]
+    fn nonces_are_tracked_per_trader() {
+        let cache = NonceCache::default();
+        let now = OffsetDateTime::now_utc();
+        let other_trader_id = PublicKey::from_str(
+            "02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655",
+        )
+        .unwrap();
+
+        assert!(cache.insert(dummy_trader_id(), "nonce".to_string(), now));
+        assert!(cache.insert(other_trader_id, "nonce".to_string(), now));
+    }
+
+    #[test]
+    fn nonces_are_remembered_until_requests_expire() {
+        let cache = NonceCache::default();
+        let now = OffsetDateTime::now_utc();
+
+        assert!(cache.insert(dummy_trader_id(), "nonce".to_string(), now));
+
+        cache.prune(now + MAX_REQUEST_AGE * 2);
+        assert!(!cache.insert(dummy_trader_id(), "nonce".to_string(), now));
+
+        cache.prune(now + MAX_REQUEST_AGE * 2 + Duration::seconds(1));
+        assert!(cache.insert(dummy_trader_id(), "nonce".to_string(), now));
+    }
+
+    fn dummy_trader_id() -> PublicKey {
+        PublicKey::from_str("027f31ebc5462c1fdce1b737ecff52d37d75dea43ce11c74d25aa297165faa2007")
+            .unwrap()
+    }
+}
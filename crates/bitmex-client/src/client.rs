@@ -1,5 +1,8 @@
+use crate::models::CancelOrderRequest;
 use crate::models::ContractSymbol;
+use crate::models::Execution;
 use crate::models::GetPositionRequest;
+use crate::models::GetTradeHistoryRequest;
 use crate::models::Network;
 use crate::models::OrdType;
 use crate::models::Order;
@@ -7,6 +10,7 @@ use crate::models::Position;
 use crate::models::PostOrderRequest;
 use crate::models::Request;
 use crate::models::Side;
+use crate::models::TimeInForce;
 use anyhow::bail;
 use anyhow::Result;
 use hex::encode as hexify;
@@ -25,6 +29,8 @@ use std::ops::Add;
 use std::time::Duration;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
+use time::OffsetDateTime;
+use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct Client {
@@ -66,18 +72,81 @@ impl Client {
                 side: Some(side),
                 order_qty: Some(quantity),
                 ord_type: Some(OrdType::Market),
+                price: None,
+                time_in_force: None,
                 text,
             })
             .await?;
         Ok(order)
     }
 
+    pub async fn create_limit_order(
+        &self,
+        symbol: ContractSymbol,
+        quantity: i32,
+        side: Side,
+        price: f64,
+        time_in_force: TimeInForce,
+        text: Option<String>,
+    ) -> Result<Order> {
+        let order = self
+            .send_request(PostOrderRequest {
+                symbol,
+                side: Some(side),
+                order_qty: Some(quantity),
+                ord_type: Some(OrdType::Limit),
+                price: Some(price),
+                time_in_force: Some(time_in_force),
+                text,
+            })
+            .await?;
+        Ok(order)
+    }
+
+    pub async fn cancel_order(&self, order_id: Uuid) -> Result<Order> {
+        let orders: Vec<Order> = self.send_request(CancelOrderRequest { order_id }).await?;
+
+        match orders.into_iter().next() {
+            Some(order) => Ok(order),
+            None => bail!("Order {order_id} was not cancelled"),
+        }
+    }
+
     /// Retrieve the position information for all contract symbols.
     pub async fn positions(&self) -> Result<Vec<Position>> {
         let positions = self.send_request(GetPositionRequest).await?;
         Ok(positions)
     }
 
+    /// Retrieve the trade and funding executions of the contract symbol since `start_time`, oldest
+    /// first.
+    pub async fn trade_history(
+        &self,
+        symbol: ContractSymbol,
+        start_time: OffsetDateTime,
+    ) -> Result<Vec<Execution>> {
+        const PAGE_SIZE: u32 = 500;
+
+        let mut executions = vec![];
+        loop {
+            let page = self
+                .send_request(GetTradeHistoryRequest {
+                    symbol,
+                    start_time,
+                    count: PAGE_SIZE,
+                    start: executions.len() as u32,
+                })
+                .await?;
+
+            let is_last_page = page.len() < PAGE_SIZE as usize;
+            executions.extend(page);
+
+            if is_last_page {
+                return Ok(executions);
+            }
+        }
+    }
+
     async fn send_request<R>(&self, req: R) -> Result<R::Response>
     where
         R: Request,
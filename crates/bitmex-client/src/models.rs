@@ -165,6 +165,13 @@ pub struct PostOrderRequest {
     /// specified. Defaults to 'StopLimit' when `price` and `stopPx` are specified.
     #[serde(rename = "ordType", skip_serializing_if = "Option::is_none")]
     pub ord_type: Option<OrdType>,
+    /// Optional limit price for 'Limit', 'StopLimit', and 'LimitIfTouched' orders.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<f64>,
+    /// Time in force. Valid options: Day, GoodTillCancel, ImmediateOrCancel, FillOrKill. Defaults
+    /// to 'GoodTillCancel' for 'Limit', 'StopLimit', and 'LimitIfTouched' orders.
+    #[serde(rename = "timeInForce", skip_serializing_if = "Option::is_none")]
+    pub time_in_force: Option<TimeInForce>,
     /// Optional order annotation. e.g. 'Take profit'.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub text: Option<String>,
@@ -178,10 +185,28 @@ impl Request for PostOrderRequest {
     type Response = Order;
 }
 
+/// Cancel an order.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CancelOrderRequest {
+    /// The id of the order to cancel.
+    #[serde(rename = "orderID")]
+    pub order_id: Uuid,
+}
+
+impl Request for CancelOrderRequest {
+    const METHOD: Method = Method::DELETE;
+    const SIGNED: bool = true;
+    const ENDPOINT: &'static str = "/order";
+    const HAS_PAYLOAD: bool = true;
+    type Response = Vec<Order>;
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Hash, Eq)]
 pub enum ContractSymbol {
     #[serde(rename = "XBTUSD")]
     XbtUsd,
+    #[serde(rename = "ETHUSD")]
+    EthUsd,
 }
 
 /// Get your positions.
@@ -196,6 +221,50 @@ impl Request for GetPositionRequest {
     type Response = Vec<Position>;
 }
 
+/// Get the executions which affected your balance, i.e. trades and funding.
+#[derive(Clone, Debug, Serialize)]
+pub struct GetTradeHistoryRequest {
+    /// Instrument symbol. e.g. 'XBTUSD'.
+    pub symbol: ContractSymbol,
+    /// Starting date filter for results.
+    #[serde(rename = "startTime", with = "time::serde::rfc3339")]
+    pub start_time: OffsetDateTime,
+    /// Number of results to fetch, at most 1000.
+    pub count: u32,
+    /// Starting point for results.
+    pub start: u32,
+}
+
+impl Request for GetTradeHistoryRequest {
+    const METHOD: Method = Method::GET;
+    const SIGNED: bool = true;
+    const ENDPOINT: &'static str = "/execution/tradeHistory";
+    const HAS_PAYLOAD: bool = true;
+    type Response = Vec<Execution>;
+}
+
+/// A trade or funding which affected your balance.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Execution {
+    #[serde(rename = "execID")]
+    pub exec_id: Uuid,
+    pub symbol: ContractSymbol,
+    /// Empty for funding.
+    pub side: Option<Side>,
+    #[serde(rename = "lastQty")]
+    pub last_qty: Option<i64>,
+    #[serde(rename = "lastPx")]
+    pub last_px: Option<f64>,
+    #[serde(rename = "execType")]
+    pub exec_type: ExecType,
+    /// The commission paid in satoshi, negative for rebates. For funding executions it is the
+    /// funding paid.
+    #[serde(rename = "execComm")]
+    pub exec_comm: Option<i64>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+}
+
 /// Summary of Open and Closed Positions
 #[derive(Clone, Debug, Deserialize)]
 pub struct Position {
@@ -3,14 +3,40 @@ use secp256k1::ecdsa::Signature;
 use secp256k1::PublicKey;
 use serde::Deserialize;
 use serde::Serialize;
+use time::OffsetDateTime;
 
 /// A message to restore a key with its value.
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Restore {
     pub key: String,
     pub value: Vec<u8>,
 }
 
+/// Selects the state of the backup to restore.
+///
+/// If both a `version` and a point in time (`at`) are given, the backup is restored as of
+/// whichever is earlier. If neither is given, the latest backup is restored.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RestoreParams {
+    /// Restore every key as of this backup version.
+    pub version: Option<u64>,
+    /// Restore every key as it was at this point in time.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub at: Option<OffsetDateTime>,
+}
+
+/// A stored version of a backed up key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupVersion {
+    pub key: String,
+    /// The sequence number of the version, increasing with every backup of the user.
+    pub version: u64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+    /// Whether the key has been deleted with this version.
+    pub deleted: bool,
+}
+
 /// A message to backup a key with its value.
 #[derive(Serialize, Deserialize)]
 pub struct Backup {
@@ -0,0 +1,217 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt;
+use time::Duration;
+use time::OffsetDateTime;
+use trade::ContractSymbol;
+
+/// The time span covered by a single [`Candle`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Resolution {
+    #[serde(rename = "1m")]
+    OneMinute,
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "1h")]
+    OneHour,
+    #[serde(rename = "1d")]
+    OneDay,
+}
+
+impl Resolution {
+    pub const ALL: [Resolution; 4] = [
+        Resolution::OneMinute,
+        Resolution::FiveMinutes,
+        Resolution::OneHour,
+        Resolution::OneDay,
+    ];
+
+    pub fn duration(&self) -> Duration {
+        match self {
+            Resolution::OneMinute => Duration::minutes(1),
+            Resolution::FiveMinutes => Duration::minutes(5),
+            Resolution::OneHour => Duration::hours(1),
+            Resolution::OneDay => Duration::days(1),
+        }
+    }
+
+    /// Returns the start of the candle containing the given timestamp.
+    pub fn candle_start(&self, timestamp: OffsetDateTime) -> OffsetDateTime {
+        let seconds = self.duration().whole_seconds();
+        let timestamp = timestamp.unix_timestamp();
+
+        OffsetDateTime::from_unix_timestamp(timestamp - timestamp.rem_euclid(seconds))
+            .expect("to be a valid timestamp")
+    }
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let resolution = match self {
+            Resolution::OneMinute => "1m",
+            Resolution::FiveMinutes => "5m",
+            Resolution::OneHour => "1h",
+            Resolution::OneDay => "1d",
+        };
+
+        resolution.fmt(f)
+    }
+}
+
+/// The open, high, low and close price and the traded volume of a contract over the time span of
+/// the [`Resolution`] starting at `start`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Candle {
+    pub contract_symbol: ContractSymbol,
+    pub resolution: Resolution,
+    #[serde(with = "time::serde::rfc3339")]
+    pub start: OffsetDateTime,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub open: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub high: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub low: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub close: Decimal,
+    /// The traded quantity in contracts.
+    #[serde(with = "rust_decimal::serde::float")]
+    pub volume: Decimal,
+}
+
+impl Candle {
+    /// Opens a new candle with the price at the given timestamp.
+    pub fn new(
+        contract_symbol: ContractSymbol,
+        resolution: Resolution,
+        timestamp: OffsetDateTime,
+        price: Decimal,
+        volume: Decimal,
+    ) -> Self {
+        Self {
+            contract_symbol,
+            resolution,
+            start: resolution.candle_start(timestamp),
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume,
+        }
+    }
+
+    /// Whether the given timestamp falls into the time span of this candle.
+    pub fn contains(&self, timestamp: OffsetDateTime) -> bool {
+        self.start <= timestamp && timestamp < self.start + self.resolution.duration()
+    }
+
+    /// Adds a price update to the candle.
+    ///
+    /// Returns `true` if the candle changed.
+    pub fn update(&mut self, price: Decimal, volume: Decimal) -> bool {
+        let before = self.clone();
+
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.volume += volume;
+
+        *self != before
+    }
+}
+
+/// Selects the candles returned by the candles endpoint of the coordinator.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CandleFilter {
+    pub contract_symbol: ContractSymbol,
+    pub resolution: Resolution,
+    /// Only include candles starting at or after this time.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub from: Option<OffsetDateTime>,
+    /// Only include candles starting before this time.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub to: Option<OffsetDateTime>,
+    /// The maximum number of candles to return, capped at [`CandleFilter::MAX_LIMIT`].
+    pub limit: Option<u32>,
+}
+
+impl CandleFilter {
+    pub const DEFAULT_LIMIT: u32 = 500;
+    pub const MAX_LIMIT: u32 = 5_000;
+
+    pub fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(Self::DEFAULT_LIMIT)
+            .min(Self::MAX_LIMIT) as i64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::candle::Candle;
+    use crate::candle::Resolution;
+    use rust_decimal_macros::dec;
+    use time::format_description::well_known::Rfc3339;
+    use time::OffsetDateTime;
+    use trade::ContractSymbol;
+
+    fn timestamp(timestamp: &str) -> OffsetDateTime {
+        OffsetDateTime::parse(timestamp, &Rfc3339).unwrap()
+    }
+
+    #[test]
+    fn candle_start_is_aligned_to_resolution() {
+        let now = timestamp("2023-12-19T10:17:42Z");
+
+        assert_eq!(
+            Resolution::OneMinute.candle_start(now),
+            timestamp("2023-12-19T10:17:00Z")
+        );
+        assert_eq!(
+            Resolution::FiveMinutes.candle_start(now),
+            timestamp("2023-12-19T10:15:00Z")
+        );
+        assert_eq!(
+            Resolution::OneHour.candle_start(now),
+            timestamp("2023-12-19T10:00:00Z")
+        );
+        assert_eq!(
+            Resolution::OneDay.candle_start(now),
+            timestamp("2023-12-19T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn update_candle() {
+        let mut candle = Candle::new(
+            ContractSymbol::BtcUsd,
+            Resolution::OneMinute,
+            timestamp("2023-12-19T10:17:42Z"),
+            dec!(40_000),
+            dec!(100),
+        );
+
+        assert!(candle.update(dec!(41_000), dec!(50)));
+        assert!(candle.update(dec!(39_000), dec!(0)));
+        assert!(candle.update(dec!(40_500), dec!(0)));
+        assert!(!candle.update(dec!(40_500), dec!(0)));
+
+        assert_eq!(candle.start, timestamp("2023-12-19T10:17:00Z"));
+        assert_eq!(candle.open, dec!(40_000));
+        assert_eq!(candle.high, dec!(41_000));
+        assert_eq!(candle.low, dec!(39_000));
+        assert_eq!(candle.close, dec!(40_500));
+        assert_eq!(candle.volume, dec!(150));
+        assert!(candle.contains(timestamp("2023-12-19T10:17:59Z")));
+        assert!(!candle.contains(timestamp("2023-12-19T10:18:00Z")));
+    }
+
+    #[test]
+    fn deserialize_resolution() {
+        let resolution: Resolution = serde_json::from_str(r#""5m""#).unwrap();
+
+        assert_eq!(resolution, Resolution::FiveMinutes);
+        assert_eq!(resolution.to_string(), "5m");
+    }
+}
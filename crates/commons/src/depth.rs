@@ -0,0 +1,52 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde::Serialize;
+use trade::ContractSymbol;
+
+/// The orderbook of a contract symbol aggregated by price.
+///
+/// Unlike the individual orders, the depth does not reveal which maker offers a price.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Depth {
+    pub contract_symbol: ContractSymbol,
+    /// The price levels of the long orders, starting with the best (highest) bid.
+    pub bids: Vec<PriceLevel>,
+    /// The price levels of the short orders, starting with the best (lowest) ask.
+    pub asks: Vec<PriceLevel>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PriceLevel {
+    #[serde(with = "rust_decimal::serde::float")]
+    pub price: Decimal,
+    /// The total quantity of all orders at this price level.
+    #[serde(with = "rust_decimal::serde::float")]
+    pub quantity: Decimal,
+    /// The number of orders at this price level.
+    pub orders: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct DepthFilter {
+    /// The maximum number of price levels per side, capped at [`DepthFilter::MAX_LEVELS`].
+    pub levels: Option<usize>,
+    /// Groups the prices into multiples of the tick, rounding bids down and asks up.
+    pub tick: Option<Decimal>,
+}
+
+impl DepthFilter {
+    pub const DEFAULT_LEVELS: usize = 20;
+    pub const MAX_LEVELS: usize = 100;
+
+    pub fn levels(&self) -> usize {
+        self.levels
+            .unwrap_or(Self::DEFAULT_LEVELS)
+            .min(Self::MAX_LEVELS)
+    }
+
+    /// The tick to group the prices by, or `None` if the prices should not be grouped.
+    pub fn tick(&self) -> Option<Decimal> {
+        self.tick
+            .filter(|tick| tick.is_sign_positive() && !tick.is_zero())
+    }
+}
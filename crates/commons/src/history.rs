@@ -0,0 +1,113 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+use trade::ContractSymbol;
+use trade::Direction;
+use uuid::Uuid;
+
+/// Filters and paginates the history endpoints of the coordinator.
+///
+/// Entries are always returned most recent first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HistoryFilter {
+    pub contract_symbol: Option<ContractSymbol>,
+    pub direction: Option<Direction>,
+    /// Only include entries created at or after this time.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub from: Option<OffsetDateTime>,
+    /// Only include entries created before this time.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub to: Option<OffsetDateTime>,
+    /// The maximum number of entries to return, capped at [`HistoryFilter::MAX_LIMIT`].
+    pub limit: Option<u32>,
+    /// The number of entries to skip.
+    pub offset: Option<u32>,
+}
+
+impl HistoryFilter {
+    pub const DEFAULT_LIMIT: u32 = 100;
+    pub const MAX_LIMIT: u32 = 1_000;
+
+    pub fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(Self::DEFAULT_LIMIT)
+            .min(Self::MAX_LIMIT) as i64
+    }
+
+    pub fn offset(&self) -> i64 {
+        self.offset.unwrap_or_default() as i64
+    }
+}
+
+/// An executed trade as shown on the public trade tape.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PublicTrade {
+    pub contract_symbol: ContractSymbol,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub price: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub quantity: Decimal,
+    /// The direction of the trader who took liquidity from the orderbook.
+    pub direction: Direction,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+}
+
+/// A (partial) execution of one of the orders of a trader.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Fill {
+    pub match_id: Uuid,
+    /// The order of the trader which got filled.
+    pub order_id: Uuid,
+    pub contract_symbol: ContractSymbol,
+    /// The direction of the trader.
+    pub direction: Direction,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub price: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub quantity: Decimal,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::history::HistoryFilter;
+    use time::format_description::well_known::Rfc3339;
+    use time::OffsetDateTime;
+    use trade::ContractSymbol;
+    use trade::Direction;
+
+    #[test]
+    fn history_filter_limit_is_capped() {
+        let filter = HistoryFilter {
+            limit: Some(HistoryFilter::MAX_LIMIT + 1),
+            ..HistoryFilter::default()
+        };
+
+        assert_eq!(filter.limit(), HistoryFilter::MAX_LIMIT as i64);
+        assert_eq!(
+            HistoryFilter::default().limit(),
+            HistoryFilter::DEFAULT_LIMIT as i64
+        );
+    }
+
+    #[test]
+    fn deserialize_history_filter_with_missing_fields() {
+        let filter: HistoryFilter = serde_json::from_str(
+            r#"{"contract_symbol":"BtcUsd","direction":"Long","from":"2023-12-19T10:00:00Z","limit":10}"#,
+        )
+        .unwrap();
+
+        assert_eq!(filter.contract_symbol, Some(ContractSymbol::BtcUsd));
+        assert_eq!(filter.direction, Some(Direction::Long));
+        assert_eq!(
+            filter.from,
+            Some(OffsetDateTime::parse("2023-12-19T10:00:00Z", &Rfc3339).unwrap())
+        );
+        assert_eq!(filter.to, None);
+        assert_eq!(filter.limit(), 10);
+        assert_eq!(filter.offset(), 0);
+    }
+}
@@ -4,7 +4,10 @@ use serde::Deserialize;
 use serde::Serialize;
 
 mod backup;
+mod candle;
 mod collab_revert;
+mod depth;
+mod history;
 mod liquidity_option;
 mod message;
 mod order;
@@ -13,10 +16,14 @@ mod price;
 mod rollover;
 mod route;
 mod signature;
+mod signed_request;
 mod trade;
 
 pub use crate::backup::*;
+pub use crate::candle::*;
 pub use crate::collab_revert::*;
+pub use crate::depth::*;
+pub use crate::history::*;
 pub use crate::liquidity_option::*;
 pub use crate::message::*;
 pub use crate::order::*;
@@ -27,10 +34,23 @@ pub use crate::price::Prices;
 pub use crate::rollover::*;
 pub use crate::route::*;
 pub use crate::signature::*;
+pub use crate::signed_request::*;
 pub use crate::trade::*;
 
 pub const AUTH_SIGN_MESSAGE: &[u8; 19] = b"Hello it's me Mario";
 
+/// Prices at which the coordinator closes the open position of a trader on their behalf.
+///
+/// Setting a trigger to `None` removes it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionTriggers {
+    pub pubkey: PublicKey,
+    /// Close the position once the price moves against the trader to this price.
+    pub stop_loss: Option<f32>,
+    /// Close the position once the price moves in favour of the trader to this price.
+    pub take_profit: Option<f32>,
+}
+
 /// Registration details for enrolling into the beta program
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegisterParams {
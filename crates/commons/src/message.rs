@@ -1,4 +1,9 @@
+use crate::candle::Candle;
+use crate::depth::Depth;
+use crate::depth::DepthFilter;
+use crate::history::PublicTrade;
 use crate::order::Order;
+use crate::price::Price;
 use crate::signature::Signature;
 use crate::trade::FilledWith;
 use crate::LiquidityOption;
@@ -12,6 +17,7 @@ use serde::Deserialize;
 use serde::Serialize;
 use std::fmt::Display;
 use tokio_tungstenite::tungstenite;
+use trade::ContractSymbol;
 use uuid::Uuid;
 
 pub type ChannelId = [u8; 32];
@@ -45,6 +51,67 @@ pub enum Message {
         execution_price: Decimal,
         funding_txo: OutPoint,
     },
+    /// A trade which has just been executed, streamed to everyone for the public trade tape.
+    Trade(PublicTrade),
+    /// A candle which has just been updated.
+    Candle(Candle),
+    /// All open orders of the orderbook of a contract symbol, sent when subscribing to
+    /// [`Topic::Orderbook`] and on [`OrderbookRequest::ResyncOrderbook`].
+    ///
+    /// The following [`Message::OrderbookDelta`]s continue with `seq + 1`.
+    OrderbookSnapshot {
+        contract_symbol: ContractSymbol,
+        seq: u64,
+        orders: Vec<Order>,
+    },
+    /// An incremental update of the orderbook of a contract symbol.
+    ///
+    /// The sequence number increases by one with every delta of the contract symbol. If a
+    /// client observes a gap, it has missed a delta and should resync.
+    OrderbookDelta {
+        contract_symbol: ContractSymbol,
+        seq: u64,
+        delta: OrderbookDelta,
+    },
+    /// The best bid and ask of a contract symbol, sent whenever either of them changes.
+    TopOfBook {
+        contract_symbol: ContractSymbol,
+        price: Price,
+    },
+    /// The aggregated orderbook of a contract symbol, sent whenever it changes.
+    Depth(Depth),
+    /// The topics the client is subscribed to.
+    Subscribed {
+        topics: Vec<Topic>,
+        symbols: Vec<ContractSymbol>,
+    },
+}
+
+#[derive(Serialize, Clone, Deserialize, Debug, PartialEq)]
+pub enum OrderbookDelta {
+    New(Order),
+    Update(Order),
+    Delete(Uuid),
+}
+
+/// The topics of the orderbook websocket.
+///
+/// Clients which never subscribe to a topic receive all messages.
+#[derive(Serialize, Clone, Copy, Deserialize, Debug, PartialEq, Eq, Hash)]
+pub enum Topic {
+    /// The full orderbook as a snapshot followed by sequenced deltas.
+    Orderbook,
+    /// Only the best bid and ask of the orderbook.
+    TopOfBook,
+    /// The orderbook aggregated by price, see [`OrderbookRequest::Subscribe`].
+    Depth,
+    /// The public trade tape.
+    Trades,
+    Candles,
+    /// Matches and fills of the client's own orders.
+    OwnOrders,
+    /// Updates of the client's position, e.g. rollovers.
+    Positions,
 }
 
 #[derive(Serialize, Clone, Deserialize, Debug)]
@@ -64,6 +131,25 @@ pub enum OrderbookRequest {
     LimitOrderFilledMatches {
         trader_id: PublicKey,
     },
+    /// Subscribes to the given topics in addition to the ones already subscribed to.
+    ///
+    /// If `symbols` is not empty, only messages of the given contract symbols are sent. The
+    /// `depth` filter configures the [`Message::Depth`]s of [`Topic::Depth`].
+    Subscribe {
+        topics: Vec<Topic>,
+        #[serde(default)]
+        symbols: Vec<ContractSymbol>,
+        #[serde(default)]
+        depth: DepthFilter,
+    },
+    Unsubscribe {
+        topics: Vec<Topic>,
+    },
+    /// Requests a new [`Message::OrderbookSnapshot`], e.g. after a gap in the sequence numbers of
+    /// the [`Message::OrderbookDelta`]s.
+    ResyncOrderbook {
+        contract_symbol: ContractSymbol,
+    },
 }
 
 impl TryFrom<OrderbookRequest> for tungstenite::Message {
@@ -111,6 +197,73 @@ impl Display for Message {
             Message::CollaborativeRevert { .. } => {
                 write!(f, "CollaborativeRevert")
             }
+            Message::Trade(_) => {
+                write!(f, "Trade")
+            }
+            Message::Candle(_) => {
+                write!(f, "Candle")
+            }
+            Message::OrderbookSnapshot { .. } => {
+                write!(f, "OrderbookSnapshot")
+            }
+            Message::OrderbookDelta { .. } => {
+                write!(f, "OrderbookDelta")
+            }
+            Message::TopOfBook { .. } => {
+                write!(f, "TopOfBook")
+            }
+            Message::Depth(_) => {
+                write!(f, "Depth")
+            }
+            Message::Subscribed { .. } => {
+                write!(f, "Subscribed")
+            }
+        }
+    }
+}
+
+impl Message {
+    /// The topic of the message, or `None` if the message is always sent.
+    pub fn topic(&self) -> Option<Topic> {
+        match self {
+            Message::AllOrders(_)
+            | Message::NewOrder(_)
+            | Message::DeleteOrder(_)
+            | Message::Update(_)
+            | Message::OrderbookSnapshot { .. }
+            | Message::OrderbookDelta { .. } => Some(Topic::Orderbook),
+            Message::TopOfBook { .. } => Some(Topic::TopOfBook),
+            Message::Depth(_) => Some(Topic::Depth),
+            Message::Trade(_) => Some(Topic::Trades),
+            Message::Candle(_) => Some(Topic::Candles),
+            Message::Match(_) | Message::AsyncMatch { .. } => Some(Topic::OwnOrders),
+            Message::Rollover(_) | Message::CollaborativeRevert { .. } => Some(Topic::Positions),
+            // Responses to requests of the client are always sent.
+            Message::LimitOrderFilledMatches { .. }
+            | Message::InvalidAuthentication(_)
+            | Message::Authenticated(_)
+            | Message::Subscribed { .. } => None,
+        }
+    }
+
+    /// The contract symbol the message is about, or `None` if it is not specific to one.
+    pub fn contract_symbol(&self) -> Option<ContractSymbol> {
+        match self {
+            Message::NewOrder(order) | Message::Update(order) => Some(order.contract_symbol),
+            Message::AsyncMatch { order, .. } => Some(order.contract_symbol),
+            Message::Trade(trade) => Some(trade.contract_symbol),
+            Message::Candle(candle) => Some(candle.contract_symbol),
+            Message::Depth(depth) => Some(depth.contract_symbol),
+            Message::OrderbookSnapshot {
+                contract_symbol, ..
+            }
+            | Message::OrderbookDelta {
+                contract_symbol, ..
+            }
+            | Message::TopOfBook {
+                contract_symbol, ..
+            } => Some(*contract_symbol),
+            _ => None,
         }
     }
 }
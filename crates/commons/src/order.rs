@@ -21,6 +21,42 @@ pub struct NewOrder {
     pub order_type: OrderType,
     pub expiry: OffsetDateTime,
     pub stable: bool,
+    #[serde(default)]
+    pub time_in_force: TimeInForce,
+}
+
+/// Changes to an order by its trader.
+///
+/// An open order can be amended and cancelled at once, in which case the amendments are applied
+/// before the order is taken out of the orderbook.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct UpdateOrder {
+    /// Whether the order is taken, i.e. cancelled. Taken orders are never reopened.
+    pub taken: bool,
+    /// The new price of the order.
+    #[serde(default, with = "rust_decimal::serde::float_option")]
+    pub price: Option<Decimal>,
+    /// The new quantity of the order.
+    #[serde(default, with = "rust_decimal::serde::float_option")]
+    pub quantity: Option<Decimal>,
+    /// The new expiry of the order.
+    #[serde(default)]
+    pub expiry: Option<OffsetDateTime>,
+}
+
+impl UpdateOrder {
+    /// Cancels the order.
+    pub fn cancel() -> Self {
+        Self {
+            taken: true,
+            ..Self::default()
+        }
+    }
+
+    /// Whether the price, quantity or expiry of the order is changed.
+    pub fn is_amendment(&self) -> bool {
+        self.price.is_some() || self.quantity.is_some() || self.expiry.is_some()
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -30,6 +66,23 @@ pub enum OrderType {
     Limit,
 }
 
+/// Defines how long an order remains active in the orderbook.
+///
+/// Market orders never rest in the orderbook: they are filled completely or not at all, unless
+/// [`TimeInForce::ImmediateOrCancel`] is used.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum TimeInForce {
+    /// Fill as much of the order as possible immediately and cancel the remainder.
+    ImmediateOrCancel,
+    /// Fill the complete order immediately or cancel it.
+    FillOrKill,
+    /// Keep the order in the orderbook until it is filled or cancelled.
+    GoodTillCancelled,
+    /// Keep the order in the orderbook until it is filled or its `expiry` is reached.
+    #[default]
+    GoodTillDate,
+}
+
 #[derive(Deserialize)]
 pub struct OrderResponse {
     pub id: Uuid,
@@ -54,6 +107,9 @@ pub enum OrderState {
 pub enum OrderReason {
     Manual,
     Expired,
+    StopLoss,
+    TakeProfit,
+    Liquidated,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -75,4 +131,6 @@ pub struct Order {
     pub order_state: OrderState,
     pub order_reason: OrderReason,
     pub stable: bool,
+    #[serde(default)]
+    pub time_in_force: TimeInForce,
 }
@@ -29,7 +29,9 @@ pub fn best_current_price(current_orders: &[Order]) -> Prices {
             },
         );
     };
-    add_price_for_symbol(ContractSymbol::BtcUsd);
+    for symbol in ContractSymbol::ALL {
+        add_price_for_symbol(symbol);
+    }
     prices
 }
 
@@ -48,15 +50,14 @@ fn best_price_for(
     direction: Direction,
     symbol: ContractSymbol,
 ) -> Option<Decimal> {
-    assert_eq!(
-        symbol,
-        ContractSymbol::BtcUsd,
-        "only btcusd supported for now"
-    );
     let use_max = direction == Direction::Long;
     current_orders
         .iter()
-        .filter(|order| order.order_state == OrderState::Open && order.direction == direction)
+        .filter(|order| {
+            order.order_state == OrderState::Open
+                && order.direction == direction
+                && order.contract_symbol == symbol
+        })
         .map(|order| order.price.to_f64().expect("to represent decimal as f64"))
         // get the best price
         .fold(None, |acc, x| match acc {
@@ -73,6 +74,7 @@ mod test {
     use crate::order::OrderReason;
     use crate::order::OrderState;
     use crate::order::OrderType;
+    use crate::order::TimeInForce;
     use crate::price::best_ask_price;
     use crate::price::best_bid_price;
     use rust_decimal::Decimal;
@@ -105,6 +107,7 @@ mod test {
             order_state,
             order_reason: OrderReason::Manual,
             stable: false,
+            time_in_force: TimeInForce::GoodTillDate,
         }
     }
 
@@ -132,6 +135,23 @@ mod test {
         assert_eq!(best_ask_price(&current_orders, BtcUsd), Some(dec!(10_000)));
     }
 
+    #[test]
+    fn test_best_price_ignores_other_symbols() {
+        let current_orders = vec![
+            dummy_order(dec!(30_000), Direction::Short, OrderState::Open),
+            Order {
+                contract_symbol: ContractSymbol::EthUsd,
+                ..dummy_order(dec!(2_000), Direction::Short, OrderState::Open)
+            },
+        ];
+
+        assert_eq!(best_ask_price(&current_orders, BtcUsd), Some(dec!(30_000)));
+        assert_eq!(
+            best_ask_price(&current_orders, ContractSymbol::EthUsd),
+            Some(dec!(2_000))
+        );
+    }
+
     #[test]
     fn test_no_price() {
         let all_orders_taken = vec![
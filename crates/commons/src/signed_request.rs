@@ -0,0 +1,183 @@
+use crate::signature::create_sign_message;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use secp256k1::ecdsa::Signature;
+use secp256k1::Message as SecpMessage;
+use secp256k1::PublicKey;
+use secp256k1::Secp256k1;
+use secp256k1::SecretKey;
+use std::str::FromStr;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// The header carrying the node id of the trader signing the request.
+pub const NODE_ID_HEADER: &str = "x-10101-node-id";
+/// The header carrying the hex encoded DER signature of the request.
+pub const SIGNATURE_HEADER: &str = "x-10101-signature";
+/// The header carrying the unix timestamp in seconds at which the request has been signed.
+pub const TIMESTAMP_HEADER: &str = "x-10101-timestamp";
+/// The header carrying the random value making every signed request unique.
+pub const NONCE_HEADER: &str = "x-10101-nonce";
+
+/// Requests signed longer ago, or further in the future, are rejected.
+///
+/// This bounds the time for which the nonces of accepted requests have to be remembered to detect
+/// replayed requests.
+pub const MAX_REQUEST_AGE: time::Duration = time::Duration::seconds(60);
+
+/// The signature of a trader over a REST request, sent along in the request headers.
+///
+/// The signature covers the method, the path (including the query), the timestamp, the nonce and
+/// the body of the request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestSignature {
+    pub node_id: PublicKey,
+    pub signature: Signature,
+    pub timestamp: i64,
+    pub nonce: String,
+}
+
+impl RequestSignature {
+    /// Signs a request to be sent now.
+    pub fn new(secret_key: &SecretKey, method: &str, path: &str, body: &[u8]) -> Self {
+        let timestamp = OffsetDateTime::now_utc().unix_timestamp();
+        let nonce = Uuid::new_v4().to_string();
+
+        let message = request_message(method, path, timestamp, &nonce, body);
+        let secp = Secp256k1::signing_only();
+
+        Self {
+            node_id: secret_key.public_key(&secp),
+            signature: secp.sign_ecdsa(&message, secret_key),
+            timestamp,
+            nonce,
+        }
+    }
+
+    /// The headers to add to the signed request.
+    pub fn headers(&self) -> [(&'static str, String); 4] {
+        [
+            (NODE_ID_HEADER, self.node_id.to_string()),
+            (SIGNATURE_HEADER, self.signature.to_string()),
+            (TIMESTAMP_HEADER, self.timestamp.to_string()),
+            (NONCE_HEADER, self.nonce.clone()),
+        ]
+    }
+
+    /// Reads the signature from the headers of a request, looked up by name with `header`.
+    pub fn from_headers<'a>(header: impl Fn(&str) -> Option<&'a str>) -> Result<Self> {
+        let header = |name: &str| header(name).with_context(|| format!("Missing header {name}"));
+
+        let node_id = PublicKey::from_str(header(NODE_ID_HEADER)?).context("Invalid node id")?;
+        let signature =
+            Signature::from_str(header(SIGNATURE_HEADER)?).context("Invalid signature")?;
+        let timestamp = header(TIMESTAMP_HEADER)?
+            .parse()
+            .context("Invalid timestamp")?;
+        let nonce = header(NONCE_HEADER)?.to_string();
+
+        Ok(Self {
+            node_id,
+            signature,
+            timestamp,
+            nonce,
+        })
+    }
+
+    /// Verifies that the request has been signed by the node id within [`MAX_REQUEST_AGE`] of
+    /// `now`.
+    ///
+    /// It is up to the caller to reject requests reusing a nonce.
+    pub fn verify(&self, method: &str, path: &str, body: &[u8], now: OffsetDateTime) -> Result<()> {
+        let signed_at = OffsetDateTime::from_unix_timestamp(self.timestamp)?;
+        if (now - signed_at).abs() > MAX_REQUEST_AGE {
+            bail!("Request has been signed at {signed_at}, too far from {now}");
+        }
+
+        let message = request_message(method, path, self.timestamp, &self.nonce, body);
+        Secp256k1::verification_only().verify_ecdsa(&message, &self.signature, &self.node_id)?;
+
+        Ok(())
+    }
+}
+
+fn request_message(
+    method: &str,
+    path: &str,
+    timestamp: i64,
+    nonce: &str,
+    body: &[u8],
+) -> SecpMessage {
+    let mut message = format!("{method}\n{path}\n{timestamp}\n{nonce}\n").into_bytes();
+    message.extend_from_slice(body);
+
+    create_sign_message(message)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::signed_request::RequestSignature;
+    use crate::signed_request::NONCE_HEADER;
+    use secp256k1::SecretKey;
+    use std::collections::HashMap;
+    use time::Duration;
+    use time::OffsetDateTime;
+
+    fn dummy_secret_key() -> SecretKey {
+        SecretKey::from_slice(&[1; 32]).unwrap()
+    }
+
+    #[test]
+    fn signed_request_can_be_verified() {
+        let signature = RequestSignature::new(&dummy_secret_key(), "POST", "/api/trade", b"{}");
+
+        signature
+            .verify("POST", "/api/trade", b"{}", OffsetDateTime::now_utc())
+            .unwrap();
+    }
+
+    #[test]
+    fn tampered_request_is_rejected() {
+        let signature = RequestSignature::new(&dummy_secret_key(), "POST", "/api/trade", b"{}");
+        let now = OffsetDateTime::now_utc();
+
+        assert!(signature.verify("PUT", "/api/trade", b"{}", now).is_err());
+        assert!(signature.verify("POST", "/api/other", b"{}", now).is_err());
+        assert!(signature.verify("POST", "/api/trade", b"[]", now).is_err());
+
+        let mut other_nonce = signature.clone();
+        other_nonce.nonce = "other".to_string();
+        assert!(other_nonce
+            .verify("POST", "/api/trade", b"{}", now)
+            .is_err());
+    }
+
+    #[test]
+    fn stale_request_is_rejected() {
+        let signature = RequestSignature::new(&dummy_secret_key(), "POST", "/api/trade", b"{}");
+        let later = OffsetDateTime::now_utc() + Duration::minutes(2);
+
+        assert!(signature
+            .verify("POST", "/api/trade", b"{}", later)
+            .is_err());
+    }
+
+    #[test]
+    fn signature_roundtrips_through_headers() {
+        let signature = RequestSignature::new(&dummy_secret_key(), "POST", "/api/trade", b"{}");
+        let headers: HashMap<_, _> = signature.headers().into_iter().collect();
+
+        let parsed =
+            RequestSignature::from_headers(|name| headers.get(name).map(String::as_str)).unwrap();
+        assert_eq!(parsed, signature);
+
+        let parsed = RequestSignature::from_headers(|name| {
+            headers
+                .get(name)
+                .map(String::as_str)
+                .filter(|_| name != NONCE_HEADER)
+        });
+        assert!(parsed.is_err());
+    }
+}
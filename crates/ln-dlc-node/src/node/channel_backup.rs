@@ -0,0 +1,644 @@
+use crate::node::Node;
+use crate::node::NodeInfo;
+use crate::node::Storage;
+use crate::storage::TenTenOneStorage;
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::hashes::hex::ToHex;
+use bitcoin::hashes::Hash;
+use bitcoin::secp256k1::PublicKey;
+use bitcoin::Script;
+use bitcoin::Transaction;
+use bitcoin::TxOut;
+use bitcoin::Txid;
+use bitcoin::WPubkeyHash;
+use dlc_manager::DlcChannelId;
+use lightning::chain::transaction::OutPoint;
+use lightning::io::Cursor;
+use lightning::ln::msgs::DecodeError;
+use lightning::ln::ChannelId;
+use lightning::sign::ChannelSigner;
+use lightning::sign::SignerProvider;
+use lightning::sign::SpendableOutputDescriptor;
+use lightning::sign::StaticPaymentOutputDescriptor;
+use lightning::util::ser::Readable;
+use lightning::util::ser::Writeable;
+use lightning::util::ser::Writer;
+use std::time::SystemTime;
+
+/// The version of the serialized [`StaticChannelBackup`].
+const STATIC_CHANNEL_BACKUP_VERSION: u8 = 1;
+
+/// The number of transactions between the funding transaction and the commitment transaction.
+///
+/// LN-DLC channels spend the funding output with a split transaction first.
+const MAX_COMMITMENT_DEPTH: usize = 2;
+
+/// The static data needed to recover the funds of our channels, even if all channel state has
+/// been lost.
+///
+/// It does not change with channel updates and only needs to be exported whenever a channel is
+/// opened.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaticChannelBackup {
+    pub node_id: PublicKey,
+    /// The unix timestamp in seconds at which the backup has been created.
+    pub created_at: u64,
+    pub channels: Vec<ChannelRecoveryData>,
+}
+
+/// The state of a channel recovered from a [`StaticChannelBackup`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecoveredChannel {
+    /// The channel has not been closed yet.
+    Open,
+    /// Our output of the commitment transaction has been handed over to be swept into the on-chain
+    /// wallet.
+    Swept,
+    /// The channel has been closed without an output for us to sweep, e.g. because it has been
+    /// closed cooperatively, paying our balance to our shutdown script, or because our balance
+    /// was dust.
+    ClosedWithoutOutput { closing_txid: Txid },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelRecoveryData {
+    pub channel_id: ChannelId,
+    pub counterparty: PublicKey,
+    pub funding_txo: OutPoint,
+    pub channel_value_satoshis: u64,
+    /// Allows to derive the keys of the channel, e.g. to spend our output of the counterparty's
+    /// commitment transaction.
+    pub channel_keys_id: [u8; 32],
+    /// The DLC channel of the sub-channel, if the LN channel has a DLC channel on top.
+    pub dlc_channel_id: Option<DlcChannelId>,
+}
+
+impl StaticChannelBackup {
+    pub fn serialize(&self) -> Vec<u8> {
+        self.encode()
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Self> {
+        Self::read(&mut Cursor::new(bytes))
+            .map_err(|e| anyhow!("Invalid static channel backup: {e:?}"))
+    }
+}
+
+impl Writeable for StaticChannelBackup {
+    fn write<W: Writer>(&self, writer: &mut W) -> Result<(), lightning::io::Error> {
+        STATIC_CHANNEL_BACKUP_VERSION.write(writer)?;
+        self.node_id.write(writer)?;
+        self.created_at.write(writer)?;
+        (self.channels.len() as u16).write(writer)?;
+        for channel in self.channels.iter() {
+            channel.write(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Readable for StaticChannelBackup {
+    fn read<R: lightning::io::Read>(reader: &mut R) -> Result<Self, DecodeError> {
+        let version: u8 = Readable::read(reader)?;
+        if version != STATIC_CHANNEL_BACKUP_VERSION {
+            return Err(DecodeError::UnknownVersion);
+        }
+
+        let node_id = Readable::read(reader)?;
+        let created_at = Readable::read(reader)?;
+        let len: u16 = Readable::read(reader)?;
+        let mut channels = Vec::with_capacity(len as usize);
+        for _ in 0..len {
+            channels.push(Readable::read(reader)?);
+        }
+
+        Ok(Self {
+            node_id,
+            created_at,
+            channels,
+        })
+    }
+}
+
+impl Writeable for ChannelRecoveryData {
+    fn write<W: Writer>(&self, writer: &mut W) -> Result<(), lightning::io::Error> {
+        self.channel_id.0.write(writer)?;
+        self.counterparty.write(writer)?;
+        self.funding_txo.txid.write(writer)?;
+        self.funding_txo.index.write(writer)?;
+        self.channel_value_satoshis.write(writer)?;
+        self.channel_keys_id.write(writer)?;
+        self.dlc_channel_id.write(writer)?;
+
+        Ok(())
+    }
+}
+
+impl Readable for ChannelRecoveryData {
+    fn read<R: lightning::io::Read>(reader: &mut R) -> Result<Self, DecodeError> {
+        let channel_id = ChannelId(Readable::read(reader)?);
+        let counterparty = Readable::read(reader)?;
+        let txid = Readable::read(reader)?;
+        let index = Readable::read(reader)?;
+        let channel_value_satoshis = Readable::read(reader)?;
+        let channel_keys_id = Readable::read(reader)?;
+        let dlc_channel_id = Readable::read(reader)?;
+
+        Ok(Self {
+            channel_id,
+            counterparty,
+            funding_txo: OutPoint { txid, index },
+            channel_value_satoshis,
+            channel_keys_id,
+            dlc_channel_id,
+        })
+    }
+}
+
+impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S, N> {
+    /// Exports the data needed to recover the funds of all funded channels.
+    pub fn static_channel_backup(&self) -> Result<StaticChannelBackup> {
+        let dlc_channels = self.list_dlc_channels()?;
+
+        let mut channels = vec![];
+        for channel in self.channel_manager.list_channels() {
+            let channel_id = channel.channel_id;
+            let funding_txo = match channel.funding_txo {
+                Some(funding_txo) => funding_txo,
+                None => {
+                    tracing::debug!(
+                        channel_id = %channel_id.0.to_hex(),
+                        "Skipping unfunded channel in static channel backup"
+                    );
+                    continue;
+                }
+            };
+
+            let dlc_channel = dlc_channels
+                .iter()
+                .find(|dlc_channel| dlc_channel.channel_id == channel_id);
+
+            let channel_keys_id = dlc_channel
+                .and_then(|dlc_channel| dlc_channel.channel_keys_id)
+                .or(self
+                    .channel_manager
+                    .get_channel_details(&channel_id)
+                    .map(|details| details.channel_keys_id))
+                .with_context(|| {
+                    format!(
+                        "Could not get channel keys ID for channel {}",
+                        channel_id.0.to_hex()
+                    )
+                })?;
+
+            channels.push(ChannelRecoveryData {
+                channel_id,
+                counterparty: channel.counterparty.node_id,
+                funding_txo,
+                channel_value_satoshis: channel.channel_value_satoshis,
+                channel_keys_id,
+                dlc_channel_id: dlc_channel
+                    .and_then(|dlc_channel| dlc_channel.get_dlc_channel_id(0)),
+            });
+        }
+
+        let created_at = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("current time to be after the unix epoch")
+            .as_secs();
+
+        Ok(StaticChannelBackup {
+            node_id: self.info.pubkey,
+            created_at,
+            channels,
+        })
+    }
+
+    /// Connects to the counterparties of the backed up channels, so that they close them.
+    ///
+    /// We never close a channel ourselves during the recovery, as our channel state might be
+    /// outdated and publishing it would allow the counterparty to claim all funds of the channel.
+    /// Neither do we request the counterparty to close the channel. Instead, we connect to the
+    /// counterparty, which will then try to re-establish the channel. As we cannot do so, the
+    /// recovery relies on the counterparty force-closing the channel with its latest commitment
+    /// transaction on its own. Our output of that transaction can be claimed with
+    /// [`Node::sweep_recovered_channel`].
+    pub async fn recover_channels(
+        &self,
+        backup: &StaticChannelBackup,
+        counterparties: &[NodeInfo],
+    ) -> Result<()> {
+        if backup.node_id != self.info.pubkey {
+            bail!(
+                "Static channel backup belongs to node {}, not to {}",
+                backup.node_id,
+                self.info.pubkey
+            );
+        }
+
+        for recovery in backup.channels.iter() {
+            let channel_id = recovery.channel_id;
+            let counterparty = recovery.counterparty;
+
+            if self.is_connected(counterparty) {
+                tracing::info!(
+                    channel_id = %channel_id.0.to_hex(),
+                    %counterparty,
+                    "Already connected to counterparty of recovered channel"
+                );
+                continue;
+            }
+
+            let peer = counterparties
+                .iter()
+                .find(|peer| peer.pubkey == counterparty)
+                .with_context(|| format!("Unknown address of counterparty {counterparty}"))?;
+
+            tracing::info!(
+                channel_id = %channel_id.0.to_hex(),
+                %counterparty,
+                "Connecting to counterparty of recovered channel, expecting it to force-close the channel"
+            );
+
+            // We only need the connection for the channel re-establishment.
+            let _connection_closed_future = self.connect(*peer).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Looks for our output of the commitment transaction closing the recovered channel.
+    ///
+    /// If found, the output is handed over to the spendable outputs management, which will sweep
+    /// it into the on-chain wallet.
+    pub fn sweep_recovered_channel(
+        &self,
+        recovery: &ChannelRecoveryData,
+    ) -> Result<RecoveredChannel> {
+        let signer = self
+            .keys_manager
+            .derive_channel_signer(recovery.channel_value_satoshis, recovery.channel_keys_id);
+        let payment_point = signer.pubkeys().payment_point;
+        let script_pubkey = Script::new_v0_p2wpkh(&WPubkeyHash::hash(&payment_point.serialize()));
+
+        let client = self.esplora_client.client();
+        let closing_output =
+            find_recovered_output(recovery.funding_txo, &script_pubkey, |txid, index| {
+                spending_transaction(client, txid, index)
+            })
+            .with_context(|| {
+                format!(
+                    "Could not recover channel {}",
+                    recovery.channel_id.0.to_hex()
+                )
+            })?;
+
+        let (outpoint, output) = match closing_output {
+            ClosingOutput::Pending => return Ok(RecoveredChannel::Open),
+            ClosingOutput::Ours(outpoint, output) => (outpoint, output),
+            ClosingOutput::Missing(closing_txid) => {
+                return Ok(RecoveredChannel::ClosedWithoutOutput { closing_txid })
+            }
+        };
+
+        tracing::info!(
+            channel_id = %recovery.channel_id.0.to_hex(),
+            txid = %outpoint.txid,
+            amount_sats = output.value,
+            "Found our output of the recovered channel"
+        );
+
+        let descriptor =
+            SpendableOutputDescriptor::StaticPaymentOutput(StaticPaymentOutputDescriptor {
+                outpoint,
+                output,
+                channel_keys_id: recovery.channel_keys_id,
+                channel_value_satoshis: recovery.channel_value_satoshis,
+                channel_transaction_parameters: None,
+            });
+
+        self.node_storage.insert_spendable_output(descriptor)?;
+
+        Ok(RecoveredChannel::Swept)
+    }
+}
+
+/// Our output of the transaction closing a channel.
+#[derive(Debug, PartialEq)]
+enum ClosingOutput {
+    /// The channel has not been closed yet.
+    Pending,
+    Ours(OutPoint, TxOut),
+    /// The channel has been closed by the given transaction without an output for us.
+    Missing(Txid),
+}
+
+/// Follows the transactions spending the funding output of a channel until finding our output
+/// of the transaction closing the channel.
+///
+/// The funding outputs, i.e. the 2-of-2 multisig outputs, are followed until reaching either a
+/// commitment transaction or a transaction without funding outputs, i.e. a cooperative close
+/// paying to the shutdown scripts of both parties.
+fn find_recovered_output(
+    funding_txo: OutPoint,
+    script_pubkey: &Script,
+    spending_transaction: impl Fn(&Txid, u64) -> Result<Option<Transaction>>,
+) -> Result<ClosingOutput> {
+    let mut outpoints = vec![(funding_txo.txid, funding_txo.index as u64)];
+    let mut is_pending = false;
+    let mut closing_txid = None;
+    for _ in 0..MAX_COMMITMENT_DEPTH {
+        let mut next_outpoints = vec![];
+        for (txid, index) in outpoints {
+            let spending_tx = match spending_transaction(&txid, index)? {
+                Some(spending_tx) => spending_tx,
+                None => {
+                    is_pending = true;
+                    continue;
+                }
+            };
+            let spending_txid = spending_tx.txid();
+
+            if let Some((vout, output)) = spending_tx
+                .output
+                .iter()
+                .enumerate()
+                .find(|(_, output)| output.script_pubkey == *script_pubkey)
+            {
+                let outpoint = OutPoint {
+                    txid: spending_txid,
+                    index: vout as u16,
+                };
+                return Ok(ClosingOutput::Ours(outpoint, output.clone()));
+            }
+
+            // The funding output of an LN-DLC channel is spent by the split transaction, whose
+            // outputs fund the LN channel and the DLC channel.
+            let funding_outputs = spending_tx
+                .output
+                .iter()
+                .enumerate()
+                .filter(|(_, output)| output.script_pubkey.is_v0_p2wsh())
+                .map(|(vout, _)| (spending_txid, vout as u64))
+                .collect::<Vec<_>>();
+
+            if is_commitment_transaction(&spending_tx) {
+                return Ok(ClosingOutput::Missing(spending_txid));
+            }
+
+            if funding_outputs.is_empty() {
+                closing_txid = Some(spending_txid);
+                continue;
+            }
+
+            next_outpoints.extend(funding_outputs);
+        }
+
+        outpoints = next_outpoints;
+    }
+
+    match (is_pending, closing_txid) {
+        (true, _) => Ok(ClosingOutput::Pending),
+        (false, Some(closing_txid)) => Ok(ClosingOutput::Missing(closing_txid)),
+        (false, None) => bail!("Could not find the transaction closing the channel"),
+    }
+}
+
+/// Whether the transaction is an LN commitment transaction, which encodes the obscured commitment
+/// number in its lock time and the sequence of its input, see BOLT 3.
+fn is_commitment_transaction(tx: &Transaction) -> bool {
+    match tx.input.as_slice() {
+        [input] => tx.lock_time.0 >> 24 == 0x20 && input.sequence.0 >> 24 == 0x80,
+        _ => false,
+    }
+}
+
+fn spending_transaction(
+    client: &esplora_client::BlockingClient,
+    txid: &Txid,
+    index: u64,
+) -> Result<Option<Transaction>> {
+    let status = client
+        .get_output_status(txid, index)
+        .context("Could not get output status")?;
+
+    let spending_txid = match status.and_then(|status| status.txid) {
+        Some(spending_txid) => spending_txid,
+        None => return Ok(None),
+    };
+
+    let spending_tx = client
+        .get_tx(&spending_txid)
+        .context("Could not get spending transaction")?
+        .with_context(|| format!("Unknown spending transaction {spending_txid}"))?;
+
+    Ok(Some(spending_tx))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::node::channel_backup::find_recovered_output;
+    use crate::node::channel_backup::ChannelRecoveryData;
+    use crate::node::channel_backup::ClosingOutput;
+    use crate::node::channel_backup::StaticChannelBackup;
+    use bitcoin::hashes::Hash;
+    use bitcoin::secp256k1::PublicKey;
+    use bitcoin::secp256k1::Secp256k1;
+    use bitcoin::secp256k1::SecretKey;
+    use bitcoin::PackedLockTime;
+    use bitcoin::Script;
+    use bitcoin::Sequence;
+    use bitcoin::Transaction;
+    use bitcoin::TxIn;
+    use bitcoin::TxOut;
+    use bitcoin::Txid;
+    use bitcoin::WPubkeyHash;
+    use bitcoin::WScriptHash;
+    use bitcoin::Witness;
+    use lightning::chain::transaction::OutPoint;
+    use lightning::ln::ChannelId;
+    use std::collections::HashMap;
+
+    #[test]
+    fn static_channel_backup_roundtrip() {
+        let backup = StaticChannelBackup {
+            node_id: public_key(1),
+            created_at: 1_703_160_000,
+            channels: vec![
+                ChannelRecoveryData {
+                    channel_id: ChannelId([1; 32]),
+                    counterparty: public_key(2),
+                    funding_txo: OutPoint {
+                        txid: Txid::from_inner([3; 32]),
+                        index: 1,
+                    },
+                    channel_value_satoshis: 200_000,
+                    channel_keys_id: [4; 32],
+                    dlc_channel_id: Some([5; 32]),
+                },
+                ChannelRecoveryData {
+                    channel_id: ChannelId([6; 32]),
+                    counterparty: public_key(2),
+                    funding_txo: OutPoint {
+                        txid: Txid::from_inner([7; 32]),
+                        index: 0,
+                    },
+                    channel_value_satoshis: 50_000,
+                    channel_keys_id: [8; 32],
+                    dlc_channel_id: None,
+                },
+            ],
+        };
+
+        let serialized = backup.serialize();
+
+        assert_eq!(
+            StaticChannelBackup::deserialize(&serialized).unwrap(),
+            backup
+        );
+    }
+
+    #[test]
+    fn reject_unknown_version() {
+        let mut serialized = StaticChannelBackup {
+            node_id: public_key(1),
+            created_at: 0,
+            channels: vec![],
+        }
+        .serialize();
+        serialized[0] = 2;
+
+        assert!(StaticChannelBackup::deserialize(&serialized).is_err());
+    }
+
+    #[test]
+    fn find_our_output_of_commitment_spending_split_transaction() {
+        let funding_txo = OutPoint {
+            txid: Txid::from_inner([1; 32]),
+            index: 1,
+        };
+        let our_script = script(1);
+
+        // The LN channel is funded by the second output of the split transaction.
+        let split_tx = transaction(funding_txo, false, vec![multisig(2), multisig(3)]);
+        let split_txid = split_tx.txid();
+        let commitment_tx = transaction(
+            OutPoint {
+                txid: split_txid,
+                index: 1,
+            },
+            true,
+            vec![script(4), our_script.clone()],
+        );
+        let spending_txs = HashMap::from([
+            ((funding_txo.txid, 1), split_tx),
+            ((split_txid, 1), commitment_tx.clone()),
+        ]);
+
+        let recovered = find_recovered_output(funding_txo, &our_script, |txid, index| {
+            Ok(spending_txs.get(&(*txid, index)).cloned())
+        })
+        .unwrap();
+
+        assert_eq!(
+            recovered,
+            ClosingOutput::Ours(
+                OutPoint {
+                    txid: commitment_tx.txid(),
+                    index: 1
+                },
+                commitment_tx.output[1].clone()
+            )
+        );
+    }
+
+    #[test]
+    fn open_channel_has_no_recovered_output() {
+        let funding_txo = OutPoint {
+            txid: Txid::from_inner([1; 32]),
+            index: 0,
+        };
+
+        let recovered = find_recovered_output(funding_txo, &script(1), |_, _| Ok(None)).unwrap();
+
+        assert_eq!(recovered, ClosingOutput::Pending);
+    }
+
+    #[test]
+    fn commitment_without_output_for_us_closes_channel() {
+        let funding_txo = OutPoint {
+            txid: Txid::from_inner([1; 32]),
+            index: 0,
+        };
+        let commitment_tx = transaction(funding_txo, true, vec![script(2)]);
+
+        let recovered = find_recovered_output(funding_txo, &script(1), |_, _| {
+            Ok(Some(commitment_tx.clone()))
+        })
+        .unwrap();
+
+        assert_eq!(recovered, ClosingOutput::Missing(commitment_tx.txid()));
+    }
+
+    #[test]
+    fn cooperative_close_has_no_output_to_sweep() {
+        let funding_txo = OutPoint {
+            txid: Txid::from_inner([1; 32]),
+            index: 0,
+        };
+        // A cooperative close pays to the shutdown scripts instead of the payment point.
+        let closing_tx = transaction(funding_txo, false, vec![script(2), script(3)]);
+
+        let recovered = find_recovered_output(funding_txo, &script(1), |txid, index| {
+            Ok(((*txid, index) == (funding_txo.txid, 0)).then(|| closing_tx.clone()))
+        })
+        .unwrap();
+
+        assert_eq!(recovered, ClosingOutput::Missing(closing_tx.txid()));
+    }
+
+    fn transaction(spent: OutPoint, is_commitment: bool, outputs: Vec<Script>) -> Transaction {
+        // Commitment transactions encode the obscured commitment number in the lock time and
+        // sequence.
+        let (lock_time, sequence) = match is_commitment {
+            true => (0x2000_0001, 0x8000_0002),
+            false => (0, 0xFFFF_FFFE),
+        };
+
+        Transaction {
+            version: 2,
+            lock_time: PackedLockTime(lock_time),
+            input: vec![TxIn {
+                previous_output: spent.into_bitcoin_outpoint(),
+                script_sig: Script::new(),
+                sequence: Sequence(sequence),
+                witness: Witness::new(),
+            }],
+            output: outputs
+                .into_iter()
+                .map(|script_pubkey| TxOut {
+                    value: 10_000,
+                    script_pubkey,
+                })
+                .collect(),
+        }
+    }
+
+    fn script(byte: u8) -> Script {
+        Script::new_v0_p2wpkh(&WPubkeyHash::from_inner([byte; 20]))
+    }
+
+    fn multisig(byte: u8) -> Script {
+        Script::new_v0_p2wsh(&WScriptHash::from_inner([byte; 32]))
+    }
+
+    fn public_key(byte: u8) -> PublicKey {
+        SecretKey::from_slice(&[byte; 32])
+            .unwrap()
+            .public_key(&Secp256k1::new())
+    }
+}
@@ -65,6 +65,7 @@ use std::time::SystemTime;
 use tokio::sync::RwLock;
 use tokio::task::spawn_blocking;
 
+mod channel_backup;
 mod channel_manager;
 mod connection;
 mod dlc_manager;
@@ -83,6 +84,9 @@ pub use crate::node::dlc_manager::signed_channel_state_name;
 pub use crate::node::dlc_manager::DlcManager;
 pub use crate::node::oracle::OracleInfo;
 pub use ::dlc_manager as rust_dlc_manager;
+pub use channel_backup::ChannelRecoveryData;
+pub use channel_backup::RecoveredChannel;
+pub use channel_backup::StaticChannelBackup;
 pub use channel_manager::ChannelManager;
 pub use dlc_channel::dlc_message_name;
 pub use dlc_channel::send_dlc_message;
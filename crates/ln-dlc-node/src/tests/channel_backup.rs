@@ -0,0 +1,117 @@
+use crate::node::Node;
+use crate::node::RecoveredChannel;
+use crate::node::StaticChannelBackup;
+use crate::node::Storage;
+use crate::tests::bitcoind;
+use crate::tests::init_tracing;
+use crate::tests::wait_for_n_usable_channels;
+use bitcoin::Amount;
+use lightning::sign::SpendableOutputDescriptor;
+use std::time::Duration;
+use tokio::task::block_in_place;
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore]
+async fn recovering_channels_does_not_close_them() {
+    init_tracing();
+
+    // Arrange
+
+    let (app, _running_app) = Node::start_test_app("app").unwrap();
+    let (coordinator, _running_coord) = Node::start_test_coordinator("coordinator").unwrap();
+
+    app.connect(coordinator.info).await.unwrap();
+
+    coordinator.fund(Amount::from_sat(200_000)).await.unwrap();
+
+    coordinator
+        .open_private_channel(&app, 50_000, 50_000)
+        .await
+        .unwrap();
+
+    let backup = app.static_channel_backup().unwrap();
+    assert_eq!(backup.channels.len(), 1);
+
+    // Act
+
+    app.disconnect(coordinator.info);
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    app.recover_channels(&backup, &[coordinator.info])
+        .await
+        .unwrap();
+
+    // Assert
+
+    assert!(app.is_connected(coordinator.info.pubkey));
+    wait_for_n_usable_channels(1, &app).await.unwrap();
+
+    let foreign_backup = StaticChannelBackup {
+        node_id: coordinator.info.pubkey,
+        ..backup
+    };
+    assert!(app
+        .recover_channels(&foreign_backup, &[coordinator.info])
+        .await
+        .is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore]
+async fn sweep_recovered_channel_after_counterparty_force_close() {
+    init_tracing();
+
+    // Arrange
+
+    let (app, _running_app) = Node::start_test_app("app").unwrap();
+    let (coordinator, _running_coord) = Node::start_test_coordinator("coordinator").unwrap();
+
+    app.connect(coordinator.info).await.unwrap();
+
+    coordinator.fund(Amount::from_sat(200_000)).await.unwrap();
+
+    let channel_details = coordinator
+        .open_private_channel(&app, 50_000, 50_000)
+        .await
+        .unwrap();
+
+    let backup = app.static_channel_backup().unwrap();
+    let recovery = backup.channels.first().unwrap();
+
+    assert_eq!(
+        block_in_place(|| app.sweep_recovered_channel(recovery)).unwrap(),
+        RecoveredChannel::Open
+    );
+
+    // Act
+
+    coordinator.force_close_channel(&channel_details).unwrap();
+
+    // Give some time for the commitment transaction to be broadcast before trying to include it
+    // in a block
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    bitcoind::mine(1).await.unwrap();
+
+    // Assert
+
+    let swept = tokio::time::timeout(Duration::from_secs(30), async {
+        loop {
+            if block_in_place(|| app.sweep_recovered_channel(recovery)).unwrap()
+                == RecoveredChannel::Swept
+            {
+                break;
+            }
+
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    })
+    .await;
+    assert!(swept.is_ok());
+
+    let spendable_outputs = app.node_storage.all_spendable_outputs().unwrap();
+    assert!(spendable_outputs.iter().any(|descriptor| matches!(
+        descriptor,
+        SpendableOutputDescriptor::StaticPaymentOutput(output)
+            if output.channel_keys_id == recovery.channel_keys_id
+    )));
+}
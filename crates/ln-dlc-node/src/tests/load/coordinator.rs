@@ -6,6 +6,9 @@ use anyhow::bail;
 use anyhow::Result;
 use bitcoin::secp256k1::PublicKey;
 use bitcoin::XOnlyPublicKey;
+use commons::RequestSignature;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::RequestBuilder;
 use reqwest::Response;
 use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::Decimal;
@@ -175,7 +178,18 @@ impl Coordinator {
             },
         };
 
-        self.post("api/trade", &trade_params).await?;
+        let body = serde_json::to_vec(&trade_params)?;
+        let signature = RequestSignature::new(&app.node_key(), "POST", "/api/trade", &body);
+
+        let mut request = reqwest::Client::new()
+            .post(format!("http://{}/api/trade", self.http_endpoint))
+            .header(CONTENT_TYPE, "application/json")
+            .body(body);
+        for (name, value) in signature.headers() {
+            request = request.header(name, value);
+        }
+
+        Self::send(request).await?;
 
         tracing::info!("Sent trade request to coordinator successfully");
 
@@ -186,12 +200,15 @@ impl Coordinator {
     where
         B: Serialize,
     {
-        let client = reqwest::Client::new();
-        let response = client
+        let request = reqwest::Client::new()
             .post(format!("http://{}/{path}", self.http_endpoint))
-            .json(json)
-            .send()
-            .await?;
+            .json(json);
+
+        Self::send(request).await
+    }
+
+    async fn send(request: RequestBuilder) -> Result<Response> {
+        let response = request.send().await?;
 
         if !response.status().is_success() {
             let response_text = match response.text().await {
@@ -60,6 +60,7 @@ use tokio::sync::watch;
 use tokio::task::block_in_place;
 
 mod bitcoind;
+mod channel_backup;
 mod dlc;
 mod just_in_time_channel;
 mod multi_hop_payment;
@@ -821,6 +821,27 @@ fn deserialize_sub_channel(buff: &Vec<u8>) -> Result<SubChannel, Error> {
     SubChannel::deserialize(&mut cursor).map_err(to_storage_error)
 }
 
+/// Describes the state of a serialized DLC channel or sub-channel without deserializing it, e.g.
+/// `Signed(Settled)`.
+///
+/// Returns `None` if the value of the given kind is not a channel or sub-channel.
+pub fn channel_state(kind: u8, value: &[u8]) -> Option<String> {
+    match kind {
+        CHANNEL => match ChannelPrefix::try_from(*value.first()?).ok()? {
+            ChannelPrefix::Signed => {
+                let state = SignedChannelPrefix::try_from(*value.get(1)?).ok()?;
+                Some(format!("Signed({state:?})"))
+            }
+            prefix => Some(format!("{prefix:?}")),
+        },
+        SUB_CHANNEL => {
+            let prefix = SubChannelPrefix::try_from(*value.first()?).ok()?;
+            Some(format!("{prefix:?}"))
+        }
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -828,6 +849,20 @@ mod tests {
     use dlc_manager::channel::signed_channel::SignedChannelState;
     use dlc_manager::Storage;
 
+    #[test]
+    fn describe_channel_state() {
+        assert_eq!(
+            channel_state(CHANNEL, &[ChannelPrefix::Signed.into(), 6, 0xff]),
+            Some("Signed(Settled)".to_string())
+        );
+        assert_eq!(
+            channel_state(SUB_CHANNEL, &[SubChannelPrefix::Signed.into(), 0xff]),
+            Some("Signed".to_string())
+        );
+        assert_eq!(channel_state(CONTRACT, &[1]), None);
+        assert_eq!(channel_state(SUB_CHANNEL, &[]), None);
+    }
+
     fn deserialize_object<T>(serialized: &[u8]) -> T
     where
         T: Serializable,
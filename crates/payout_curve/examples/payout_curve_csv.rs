@@ -17,6 +17,7 @@ use trade::cfd::calculate_long_liquidation_price;
 use trade::cfd::calculate_margin;
 use trade::cfd::calculate_pnl;
 use trade::cfd::calculate_short_liquidation_price;
+use trade::ContractSymbol;
 use trade::Direction;
 
 /// The example below will export the computed payout curve and how it should look like as CSV.
@@ -37,8 +38,18 @@ fn main() -> Result<()> {
         .to_u64()
         .expect("to fit into u64");
 
-    let margin_short = calculate_margin(initial_price, quantity, leverage_short);
-    let margin_long = calculate_margin(initial_price, quantity, leverage_long);
+    let margin_short = calculate_margin(
+        ContractSymbol::BtcUsd,
+        initial_price,
+        quantity,
+        leverage_short,
+    );
+    let margin_long = calculate_margin(
+        ContractSymbol::BtcUsd,
+        initial_price,
+        quantity,
+        leverage_long,
+    );
 
     // offerer is long
     discretized_payouts_as_csv(
@@ -50,10 +61,12 @@ fn main() -> Result<()> {
         Direction::Long,
         "./crates/payout_curve/examples/offerer_long.csv",
         calculate_long_liquidation_price(
+            ContractSymbol::BtcUsd,
             Decimal::from_f32(leverage_long).expect("to be able to parse f32"),
             initial_price,
         ),
         calculate_short_liquidation_price(
+            ContractSymbol::BtcUsd,
             Decimal::from_f32(leverage_short).expect("to be able to parse f32"),
             initial_price,
         ),
@@ -69,10 +82,12 @@ fn main() -> Result<()> {
         Direction::Short,
         "./crates/payout_curve/examples/offerer_short.csv",
         calculate_short_liquidation_price(
+            ContractSymbol::BtcUsd,
             Decimal::from_f32(leverage_short).expect("to be able to parse f32"),
             initial_price,
         ),
         calculate_long_liquidation_price(
+            ContractSymbol::BtcUsd,
             Decimal::from_f32(leverage_long).expect("to be able to parse f32"),
             initial_price,
         ),
@@ -87,10 +102,12 @@ fn main() -> Result<()> {
         Direction::Long,
         "./crates/payout_curve/examples/computed_payout.csv",
         calculate_long_liquidation_price(
+            ContractSymbol::BtcUsd,
             Decimal::from_f32(leverage_long).expect("to be able to parse f32"),
             initial_price,
         ),
         calculate_short_liquidation_price(
+            ContractSymbol::BtcUsd,
             Decimal::from_f32(leverage_short).expect("to be able to parse f32"),
             initial_price,
         ),
@@ -125,6 +142,7 @@ fn computed_payout_curve(
     short_liquidation_price: Decimal,
 ) -> Result<()> {
     let payout_points = build_inverse_payout_function(
+        ContractSymbol::BtcUsd,
         quantity,
         coordinator_collateral,
         trader_collateral,
@@ -194,6 +212,7 @@ fn discretized_payouts_as_csv(
     accept_liquidation_price: Decimal,
 ) -> Result<()> {
     let payout_points = build_inverse_payout_function(
+        ContractSymbol::BtcUsd,
         quantity,
         offer_collateral,
         accept_collateral,
@@ -240,8 +259,10 @@ pub fn should_payouts_as_csv(
     let file = File::create(csv_path)?;
     let mut wtr = csv::WriterBuilder::new().delimiter(b';').from_writer(file);
 
-    let long_liquidation_price = calculate_long_liquidation_price(leverage_long, initial_price);
-    let short_liquidation_price = calculate_short_liquidation_price(leverage_short, initial_price);
+    let long_liquidation_price =
+        calculate_long_liquidation_price(ContractSymbol::BtcUsd, leverage_long, initial_price);
+    let short_liquidation_price =
+        calculate_short_liquidation_price(ContractSymbol::BtcUsd, leverage_short, initial_price);
 
     wtr.write_record(["start", "coordinator", "trader"])?;
     wtr.write_record(&[0.to_string(), total_collateral.to_string(), 0.to_string()])?;
@@ -260,14 +281,25 @@ pub fn should_payouts_as_csv(
     let leverage_long = leverage_long.to_f32().expect("to be able to convert");
     let leverage_short = leverage_short.to_f32().expect("to be able to convert");
 
-    let long_margin = calculate_margin(initial_price, quantity, leverage_long);
-    let short_margin = calculate_margin(initial_price, quantity, leverage_short);
+    let long_margin = calculate_margin(
+        ContractSymbol::BtcUsd,
+        initial_price,
+        quantity,
+        leverage_long,
+    );
+    let short_margin = calculate_margin(
+        ContractSymbol::BtcUsd,
+        initial_price,
+        quantity,
+        leverage_short,
+    );
 
     for price in long_liquidation_price_i32..short_liquidation_price_i32 {
         wtr.write_record(&[
             price.to_string(),
             ((coordinator_collateral as i64)
                 + calculate_pnl(
+                    ContractSymbol::BtcUsd,
                     initial_price,
                     Decimal::from(price),
                     quantity,
@@ -278,6 +310,7 @@ pub fn should_payouts_as_csv(
             .to_string(),
             ((trader_collateral as i64)
                 + calculate_pnl(
+                    ContractSymbol::BtcUsd,
                     initial_price,
                     Decimal::from(price),
                     quantity,
@@ -292,6 +325,7 @@ pub fn should_payouts_as_csv(
         short_liquidation_price.to_string(),
         ((coordinator_collateral as i64)
             + calculate_pnl(
+                ContractSymbol::BtcUsd,
                 initial_price,
                 short_liquidation_price,
                 quantity,
@@ -302,6 +336,7 @@ pub fn should_payouts_as_csv(
         .to_string(),
         ((trader_collateral as i64)
             + calculate_pnl(
+                ContractSymbol::BtcUsd,
                 initial_price,
                 short_liquidation_price,
                 quantity,
@@ -315,6 +350,7 @@ pub fn should_payouts_as_csv(
         100_000.to_string(),
         ((coordinator_collateral as i64)
             + calculate_pnl(
+                ContractSymbol::BtcUsd,
                 initial_price,
                 Decimal::from(100_000),
                 quantity,
@@ -325,6 +361,7 @@ pub fn should_payouts_as_csv(
         .to_string(),
         ((trader_collateral as i64)
             + calculate_pnl(
+                ContractSymbol::BtcUsd,
                 initial_price,
                 Decimal::from(100_000),
                 quantity,
@@ -1,11 +1,15 @@
+use anyhow::bail;
+use anyhow::ensure;
 use anyhow::Context;
 use anyhow::Result;
+use bitcoin::Amount;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use serde::Deserialize;
 use serde::Serialize;
 use trade::cfd::calculate_pnl;
-use trade::cfd::BTCUSD_MAX_PRICE;
+use trade::ContractKind;
+use trade::ContractSymbol;
 use trade::Direction;
 
 /// We use this variable to indicate the step interval for our payout function. It should be
@@ -38,6 +42,7 @@ pub struct PayoutPoint {
 /// Note: `fee` is always paid towards the offerer
 #[allow(clippy::too_many_arguments)]
 pub fn build_inverse_payout_function(
+    contract_symbol: ContractSymbol,
     quantity: f32,
     offer_collateral: u64,
     accept_collateral: u64,
@@ -47,6 +52,12 @@ pub fn build_inverse_payout_function(
     fee: u64,
     offer_direction: Direction,
 ) -> Result<Vec<(PayoutPoint, PayoutPoint)>> {
+    ensure!(
+        contract_symbol.metadata().kind == ContractKind::Inverse,
+        "{contract_symbol} is not an inverse contract"
+    );
+
+    let max_price = contract_symbol.max_price();
     let mut pieces = vec![];
     let total_collateral = offer_collateral + accept_collateral;
 
@@ -54,7 +65,7 @@ pub fn build_inverse_payout_function(
         Direction::Long => (offer_liquidation_price, accept_liquidation_price),
         Direction::Short => (accept_liquidation_price, offer_liquidation_price),
     };
-    let short_liquidation_price = short_liquidation_price.min(Decimal::from(BTCUSD_MAX_PRICE));
+    let short_liquidation_price = short_liquidation_price.min(Decimal::from(max_price));
 
     let (long_liquidation_range_lower, long_liquidation_range_upper) =
         calculate_short_liquidation_interval_payouts(
@@ -69,6 +80,7 @@ pub fn build_inverse_payout_function(
     pieces.push((long_liquidation_range_lower, long_liquidation_range_upper));
 
     let mid_range = calculate_mid_range_payouts(
+        contract_symbol,
         accept_collateral,
         offer_collateral,
         initial_price,
@@ -92,11 +104,157 @@ pub fn build_inverse_payout_function(
     for (lower, upper) in mid_range {
         pieces.push((lower, upper));
     }
-    // if the upper bound is already [`BTCUSD_MAX_PRICE`] we don't have to add the upper bound
-    // anymore
-    if last_mid_range.event_outcome < BTCUSD_MAX_PRICE {
-        let upper_range_payout_points =
-            calculate_upper_range_payouts(offer_direction, total_collateral, last_mid_range, fee)?;
+    // if the upper bound is already the max price we don't have to add the upper bound anymore
+    if last_mid_range.event_outcome < max_price {
+        let upper_range_payout_points = calculate_upper_range_payouts(
+            offer_direction,
+            total_collateral,
+            last_mid_range,
+            fee,
+            max_price,
+        )?;
+        pieces.push(upper_range_payout_points);
+    }
+
+    Ok(pieces)
+}
+
+/// Build a [`PayoutFunction`] for a quanto perpetual future e.g. ETHUSD. Perspective is always
+/// from the person who offers, i.e. in our case from the offerer.
+///
+/// The PnL of a quanto contract is linear in the price, hence the payout function is built with
+/// [`build_linear_payout_function`].
+///
+/// Returns a Vec<(PayoutPoint, PayoutPoint)>>. Each tuple is meant to be put into one
+/// [`dlc_manager::payout_curve::PolynomialPayoutCurvePiece`]
+/// Note: `fee` is always paid towards the offerer
+#[allow(clippy::too_many_arguments)]
+pub fn build_quanto_payout_function(
+    contract_symbol: ContractSymbol,
+    quantity: f32,
+    offer_collateral: u64,
+    accept_collateral: u64,
+    initial_price: Decimal,
+    offer_liquidation_price: Decimal,
+    accept_liquidation_price: Decimal,
+    fee: u64,
+    offer_direction: Direction,
+) -> Result<Vec<(PayoutPoint, PayoutPoint)>> {
+    let multiplier = match contract_symbol.metadata().kind {
+        ContractKind::Quanto { multiplier } => multiplier,
+        ContractKind::Inverse => bail!("{contract_symbol} is not a quanto contract"),
+    };
+
+    let quantity = Decimal::try_from(quantity).context("quantity to fit into decimal")?;
+    // the PnL of the position in sats for a price move of 1
+    let quantity_sats = quantity * multiplier * Decimal::from(Amount::ONE_BTC.to_sat());
+
+    build_linear_payout_function(
+        quantity_sats,
+        offer_collateral,
+        accept_collateral,
+        initial_price,
+        offer_liquidation_price,
+        accept_liquidation_price,
+        fee,
+        offer_direction,
+        contract_symbol.max_price(),
+    )
+}
+
+/// Build a [`PayoutFunction`] for a linear perpetual future, i.e. a contract whose PnL is
+/// `quantity * (closing_price - opening_price)` denominated in the collateral unit. Perspective is
+/// always from the person who offers, i.e. in our case from the offerer.
+///
+/// As the PnL is linear in the price, the range between the two liquidation prices is covered by
+/// a single piece. Below the long liquidation price and above the short liquidation price the
+/// payout is flat.
+///
+/// Returns a Vec<(PayoutPoint, PayoutPoint)>>. Each tuple is meant to be put into one
+/// [`dlc_manager::payout_curve::PolynomialPayoutCurvePiece`]
+/// Note: `fee` is always paid towards the offerer
+#[allow(clippy::too_many_arguments)]
+pub fn build_linear_payout_function(
+    quantity: Decimal,
+    offer_collateral: u64,
+    accept_collateral: u64,
+    initial_price: Decimal,
+    offer_liquidation_price: Decimal,
+    accept_liquidation_price: Decimal,
+    fee: u64,
+    offer_direction: Direction,
+    max_price: u64,
+) -> Result<Vec<(PayoutPoint, PayoutPoint)>> {
+    ensure!(quantity > Decimal::ZERO, "Quantity must be positive");
+
+    let mut pieces = vec![];
+    let total_collateral = offer_collateral + accept_collateral;
+
+    let (long_liquidation_price, short_liquidation_price) = match offer_direction {
+        Direction::Long => (offer_liquidation_price, accept_liquidation_price),
+        Direction::Short => (accept_liquidation_price, offer_liquidation_price),
+    };
+    let long_liquidation_price = long_liquidation_price.max(Decimal::ZERO);
+    let short_liquidation_price = short_liquidation_price.min(Decimal::from(max_price));
+
+    let (long_liquidation_range_lower, long_liquidation_range_upper) =
+        calculate_short_liquidation_interval_payouts(
+            offer_direction,
+            total_collateral,
+            long_liquidation_price,
+            fee,
+        )?;
+
+    let mid_range_lower = long_liquidation_range_upper.clone();
+
+    // with a leverage of 1 the long party is only liquidated at a price of 0, hence there is no
+    // range to cover below the liquidation price
+    if long_liquidation_range_upper.event_outcome > 0 {
+        pieces.push((long_liquidation_range_lower, long_liquidation_range_upper));
+    }
+
+    let (long_margin, short_margin) = match offer_direction {
+        Direction::Long => (offer_collateral, accept_collateral),
+        Direction::Short => (accept_collateral, offer_collateral),
+    };
+    let long_pnl = (quantity * (short_liquidation_price - initial_price))
+        .max(-Decimal::from(long_margin))
+        .min(Decimal::from(short_margin));
+    let offer_pnl = match offer_direction {
+        Direction::Long => long_pnl,
+        Direction::Short => -long_pnl,
+    };
+    let offer_payout = (Decimal::from(offer_collateral) + offer_pnl)
+        .round()
+        .max(Decimal::ZERO)
+        .to_u64()
+        .expect("to fit into u64");
+
+    let mid_range_upper = PayoutPoint {
+        event_outcome: short_liquidation_price
+            .to_u64()
+            .expect("to fit dec into u64"),
+        outcome_payout: (offer_payout + fee).min(total_collateral),
+        extra_precision: 0,
+    };
+
+    ensure!(
+        mid_range_lower.event_outcome < mid_range_upper.event_outcome,
+        "Long liquidation price {long_liquidation_price} must be below short liquidation price \
+         {short_liquidation_price}"
+    );
+
+    pieces.push((mid_range_lower, mid_range_upper.clone()));
+
+    // if the upper bound is already the max price we don't have to add the upper bound anymore
+    if mid_range_upper.event_outcome < max_price {
+        let upper_range_payout_points = calculate_upper_range_payouts(
+            offer_direction,
+            total_collateral,
+            mid_range_upper,
+            fee,
+            max_price,
+        )?;
         pieces.push(upper_range_payout_points);
     }
 
@@ -109,6 +267,7 @@ pub fn build_inverse_payout_function(
 /// points on the payout curve
 #[allow(clippy::too_many_arguments)]
 fn calculate_mid_range_payouts(
+    contract_symbol: ContractSymbol,
     accept_collateral: u64,
     offer_collateral: u64,
     initial_price: Decimal,
@@ -137,6 +296,7 @@ fn calculate_mid_range_payouts(
             } else {
                 offer_collateral as i64
                     + calculate_pnl(
+                        contract_symbol,
                         initial_price,
                         Decimal::from(current_price),
                         quantity,
@@ -156,9 +316,10 @@ fn calculate_mid_range_payouts(
                 lower_event_outcome_payout as u64
             };
 
-            let upper_event_outcome =
-                (current_price + PAYOUT_CURVE_DISCRETIZATION_STEPS).min(BTCUSD_MAX_PRICE);
+            let upper_event_outcome = (current_price + PAYOUT_CURVE_DISCRETIZATION_STEPS)
+                .min(contract_symbol.max_price());
             let pnl = calculate_pnl(
+                contract_symbol,
                 initial_price,
                 Decimal::from(upper_event_outcome),
                 quantity,
@@ -233,12 +394,14 @@ fn calculate_short_liquidation_interval_payouts(
     Ok((lower, upper))
 }
 
-/// Calculates the upper range payout points between upper liquidation point and BTCUSD_MAX_PRICE
+/// Calculates the upper range payout points between upper liquidation point and the max price of
+/// the contract
 fn calculate_upper_range_payouts(
     offer_direction: Direction,
     total_collateral: u64,
     last_payout_point: PayoutPoint,
     fee: u64,
+    max_price: u64,
 ) -> Result<(PayoutPoint, PayoutPoint)> {
     let (lower_range_lower, lower_range_upper) = match offer_direction {
         // if offerer is long, he gets everything from the acceptor's liquidation point to
@@ -250,9 +413,7 @@ fn calculate_upper_range_payouts(
                 extra_precision: 0,
             },
             PayoutPoint {
-                event_outcome: BTCUSD_MAX_PRICE
-                    .to_u64()
-                    .expect("to be able to fit decimal into u64"),
+                event_outcome: max_price,
                 outcome_payout: total_collateral,
                 extra_precision: 0,
             },
@@ -265,7 +426,7 @@ fn calculate_upper_range_payouts(
                 extra_precision: 0,
             },
             PayoutPoint {
-                event_outcome: BTCUSD_MAX_PRICE,
+                event_outcome: max_price,
                 outcome_payout: 0,
                 extra_precision: 0,
             },
@@ -277,6 +438,8 @@ fn calculate_upper_range_payouts(
 
 #[cfg(test)]
 mod tests {
+    use crate::build_inverse_payout_function;
+    use crate::build_quanto_payout_function;
     use crate::calculate_mid_range_payouts;
     use crate::calculate_short_liquidation_interval_payouts;
     use crate::calculate_upper_range_payouts;
@@ -295,7 +458,7 @@ mod tests {
     use trade::cfd::calculate_long_liquidation_price;
     use trade::cfd::calculate_margin;
     use trade::cfd::calculate_short_liquidation_price;
-    use trade::cfd::BTCUSD_MAX_PRICE;
+    use trade::ContractSymbol;
     use trade::Direction;
 
     /// set this to true to export test data to csv files
@@ -417,14 +580,26 @@ mod tests {
         let initial_price = dec!(30_000);
         let quantity = 60_000.0;
         let fee = 300_000;
-        let accept_collateral = calculate_margin(initial_price, quantity, short_leverage);
-        let offer_collateral = calculate_margin(initial_price, quantity, long_leverage);
+        let accept_collateral = calculate_margin(
+            ContractSymbol::BtcUsd,
+            initial_price,
+            quantity,
+            short_leverage,
+        );
+        let offer_collateral = calculate_margin(
+            ContractSymbol::BtcUsd,
+            initial_price,
+            quantity,
+            long_leverage,
+        );
 
         let short_liquidation_price = calculate_short_liquidation_price(
+            ContractSymbol::BtcUsd,
             Decimal::from_f32(short_leverage).expect("to fit into f32"),
             initial_price,
         );
         let long_liquidation_price = calculate_long_liquidation_price(
+            ContractSymbol::BtcUsd,
             Decimal::from_f32(long_leverage).expect("to fit into f32"),
             initial_price,
         );
@@ -437,6 +612,7 @@ mod tests {
 
         // act: offer long
         let mid_range_payouts_offer_long = calculate_mid_range_payouts(
+            ContractSymbol::BtcUsd,
             accept_collateral,
             offer_collateral,
             initial_price,
@@ -455,6 +631,7 @@ mod tests {
 
         // act: offer short
         let mid_range_payouts_offer_short = calculate_mid_range_payouts(
+            ContractSymbol::BtcUsd,
             accept_collateral,
             offer_collateral,
             initial_price,
@@ -543,14 +720,26 @@ mod tests {
         let initial_price = dec!(36780);
         let quantity = 19.0;
         let fee = 155;
-        let accept_collateral = calculate_margin(initial_price, quantity, short_leverage);
-        let offer_collateral = calculate_margin(initial_price, quantity, long_leverage);
+        let accept_collateral = calculate_margin(
+            ContractSymbol::BtcUsd,
+            initial_price,
+            quantity,
+            short_leverage,
+        );
+        let offer_collateral = calculate_margin(
+            ContractSymbol::BtcUsd,
+            initial_price,
+            quantity,
+            long_leverage,
+        );
 
         let short_liquidation_price = calculate_short_liquidation_price(
+            ContractSymbol::BtcUsd,
             Decimal::from_f32(short_leverage).expect("to fit into f32"),
             initial_price,
         );
         let long_liquidation_price = calculate_long_liquidation_price(
+            ContractSymbol::BtcUsd,
             Decimal::from_f32(long_leverage).expect("to fit into f32"),
             initial_price,
         );
@@ -560,6 +749,7 @@ mod tests {
 
         // act: offer long
         let mid_range_payouts_offer_long = calculate_mid_range_payouts(
+            ContractSymbol::BtcUsd,
             accept_collateral,
             offer_collateral,
             initial_price,
@@ -578,16 +768,16 @@ mod tests {
 
         for (lower, upper) in &mid_range_payouts_offer_long {
             assert!(
-                lower.event_outcome <= BTCUSD_MAX_PRICE,
+                lower.event_outcome <= ContractSymbol::BtcUsd.max_price(),
                 "{} > {}",
                 lower.event_outcome,
-                BTCUSD_MAX_PRICE
+                ContractSymbol::BtcUsd.max_price()
             );
             assert!(
-                upper.event_outcome <= BTCUSD_MAX_PRICE,
+                upper.event_outcome <= ContractSymbol::BtcUsd.max_price(),
                 "{} > {}",
                 upper.event_outcome,
-                BTCUSD_MAX_PRICE
+                ContractSymbol::BtcUsd.max_price()
             );
         }
     }
@@ -609,13 +799,14 @@ mod tests {
             total_collateral,
             last_payout.clone(),
             fee,
+            ContractSymbol::BtcUsd.max_price(),
         )
         .unwrap();
 
         // assert
         assert_eq!(lower.event_outcome, last_payout.event_outcome);
         assert_eq!(lower.outcome_payout, 0);
-        assert_eq!(upper.event_outcome, BTCUSD_MAX_PRICE);
+        assert_eq!(upper.event_outcome, ContractSymbol::BtcUsd.max_price());
         assert_eq!(upper.outcome_payout, 0);
 
         if PRINT_CSV {
@@ -645,13 +836,14 @@ mod tests {
             total_collateral,
             last_payout.clone(),
             fee,
+            ContractSymbol::BtcUsd.max_price(),
         )
         .unwrap();
 
         // assert
         assert_eq!(lower.event_outcome, last_payout.event_outcome);
         assert_eq!(lower.outcome_payout, total_collateral);
-        assert_eq!(upper.event_outcome, BTCUSD_MAX_PRICE);
+        assert_eq!(upper.event_outcome, ContractSymbol::BtcUsd.max_price());
         assert_eq!(upper.outcome_payout, total_collateral);
 
         if PRINT_CSV {
@@ -668,7 +860,7 @@ mod tests {
         // setup
         let total_collateral = Amount::ONE_BTC.to_sat() * 2;
         let last_payout = PayoutPoint {
-            event_outcome: BTCUSD_MAX_PRICE,
+            event_outcome: ContractSymbol::BtcUsd.max_price(),
             outcome_payout: total_collateral,
             extra_precision: 0,
         };
@@ -680,12 +872,98 @@ mod tests {
             total_collateral,
             last_payout.clone(),
             fee,
+            ContractSymbol::BtcUsd.max_price(),
         )
         .unwrap();
 
         // assert
         assert_eq!(lower.event_outcome, last_payout.event_outcome);
-        assert_eq!(upper.event_outcome, BTCUSD_MAX_PRICE);
+        assert_eq!(upper.event_outcome, ContractSymbol::BtcUsd.max_price());
+    }
+
+    #[test]
+    fn quanto_payout_function_is_linear_between_liquidation_prices() {
+        // setup
+        let initial_price = dec!(2_000);
+        let quantity = 100.0;
+        let leverage = 2.0;
+        let collateral =
+            calculate_margin(ContractSymbol::EthUsd, initial_price, quantity, leverage);
+        let long_liquidation_price = calculate_long_liquidation_price(
+            ContractSymbol::EthUsd,
+            Decimal::from_f32(leverage).unwrap(),
+            initial_price,
+        );
+        let short_liquidation_price = calculate_short_liquidation_price(
+            ContractSymbol::EthUsd,
+            Decimal::from_f32(leverage).unwrap(),
+            initial_price,
+        );
+
+        // act
+        let pieces = build_quanto_payout_function(
+            ContractSymbol::EthUsd,
+            quantity,
+            collateral,
+            collateral,
+            initial_price,
+            long_liquidation_price,
+            short_liquidation_price,
+            0,
+            Direction::Long,
+        )
+        .unwrap();
+
+        // assert
+        let outcomes = pieces
+            .iter()
+            .map(|(lower, upper)| {
+                (
+                    (lower.event_outcome, lower.outcome_payout),
+                    (upper.event_outcome, upper.outcome_payout),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outcomes,
+            vec![
+                ((0, 0), (1_000, 0)),
+                ((1_000, 0), (3_000, 20_000_000)),
+                (
+                    (3_000, 20_000_000),
+                    (ContractSymbol::EthUsd.max_price(), 20_000_000)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn payout_function_builders_reject_other_contract_kinds() {
+        let inverse = build_inverse_payout_function(
+            ContractSymbol::EthUsd,
+            100.0,
+            10_000_000,
+            10_000_000,
+            dec!(2_000),
+            dec!(1_000),
+            dec!(3_000),
+            0,
+            Direction::Long,
+        );
+        let quanto = build_quanto_payout_function(
+            ContractSymbol::BtcUsd,
+            100.0,
+            10_000_000,
+            10_000_000,
+            dec!(30_000),
+            dec!(20_000),
+            dec!(60_000),
+            0,
+            Direction::Long,
+        );
+
+        assert!(inverse.is_err());
+        assert!(quanto.is_err());
     }
 
     /// Loads the sample data from a csv file
@@ -773,12 +1051,12 @@ mod tests {
 
             // act
             let (lower, upper) =
-                calculate_upper_range_payouts(Direction::Short, total_collateral, last_payout.clone(), fee).unwrap();
+                calculate_upper_range_payouts(Direction::Short, total_collateral, last_payout.clone(), fee, ContractSymbol::BtcUsd.max_price()).unwrap();
 
             // assert
             prop_assert_eq!(lower.event_outcome, last_payout.event_outcome);
             prop_assert_eq!(lower.outcome_payout, last_payout.outcome_payout);
-            prop_assert_eq!(upper.event_outcome, BTCUSD_MAX_PRICE);
+            prop_assert_eq!(upper.event_outcome, ContractSymbol::BtcUsd.max_price());
             prop_assert_eq!(upper.outcome_payout, 0);
         }
 
@@ -796,12 +1074,12 @@ mod tests {
             let fee = 300_000;
             // act
             let (lower, upper) =
-                calculate_upper_range_payouts(Direction::Long, total_collateral, last_payout.clone(), fee).unwrap();
+                calculate_upper_range_payouts(Direction::Long, total_collateral, last_payout.clone(), fee, ContractSymbol::BtcUsd.max_price()).unwrap();
 
             // assert
             assert_eq!(lower.event_outcome, last_payout.event_outcome);
             assert_eq!(lower.outcome_payout, total_collateral);
-            assert_eq!(upper.event_outcome, BTCUSD_MAX_PRICE);
+            assert_eq!(upper.event_outcome, ContractSymbol::BtcUsd.max_price());
             assert_eq!(upper.outcome_payout, total_collateral);
         }
     }
@@ -822,14 +1100,14 @@ mod tests {
                 .to_u64()
                 .expect("to fit into u64");
 
-            let accept_collateral = calculate_margin(initial_price, quantity, short_leverage);
-            let offer_collateral = calculate_margin(initial_price, quantity, long_leverage);
+            let accept_collateral = calculate_margin(ContractSymbol::BtcUsd, initial_price, quantity, short_leverage);
+            let offer_collateral = calculate_margin(ContractSymbol::BtcUsd, initial_price, quantity, long_leverage);
 
-            let short_liquidation_price = calculate_short_liquidation_price(
+            let short_liquidation_price = calculate_short_liquidation_price(ContractSymbol::BtcUsd,
                 Decimal::from_f32(short_leverage).expect("to fit into f32"),
                 initial_price,
             );
-            let long_liquidation_price = calculate_long_liquidation_price(
+            let long_liquidation_price = calculate_long_liquidation_price(ContractSymbol::BtcUsd,
                 Decimal::from_f32(long_leverage).expect("to fit into f32"),
                 initial_price,
             );
@@ -838,7 +1116,7 @@ mod tests {
             let upper_limit = short_liquidation_price.to_u64().expect("to fit into u64");
 
             // act: offer long
-            let mid_range_payouts_offer_long = calculate_mid_range_payouts(
+            let mid_range_payouts_offer_long = calculate_mid_range_payouts(ContractSymbol::BtcUsd,
                 accept_collateral,
                 offer_collateral,
                 initial_price,
@@ -6,9 +6,13 @@ use dlc_manager::payout_curve::PolynomialPayoutCurvePiece;
 use dlc_manager::payout_curve::RoundingInterval;
 use dlc_manager::payout_curve::RoundingIntervals;
 use payout_curve::build_inverse_payout_function;
+use payout_curve::build_linear_payout_function;
+use payout_curve::build_quanto_payout_function;
+use payout_curve::PayoutPoint;
 use payout_curve::ROUNDING_PERCENT;
 use proptest::prelude::*;
 use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use std::fs::File;
@@ -17,11 +21,15 @@ use std::time::UNIX_EPOCH;
 use trade::cfd::calculate_long_liquidation_price;
 use trade::cfd::calculate_margin;
 use trade::cfd::calculate_short_liquidation_price;
+use trade::ContractSymbol;
 use trade::Direction;
 
 /// set this to true to export test data to csv files
 const PRINT_CSV: bool = false;
 
+/// The max price used for linear contracts, i.e. a 20 digit oracle.
+const LINEAR_MAX_PRICE: u64 = 1_048_575;
+
 /// taken from a past crash
 #[test]
 fn calculating_payout_curve_doesnt_crash_1() {
@@ -32,14 +40,26 @@ fn calculating_payout_curve_doesnt_crash_1() {
     let coordinator_direction = Direction::Short;
     let quantity = 1.0;
 
-    let coordinator_collateral = calculate_margin(initial_price, quantity, leverage_coordinator);
-    let trader_collateral = calculate_margin(initial_price, quantity, leverage_trader);
+    let coordinator_collateral = calculate_margin(
+        ContractSymbol::BtcUsd,
+        initial_price,
+        quantity,
+        leverage_coordinator,
+    );
+    let trader_collateral = calculate_margin(
+        ContractSymbol::BtcUsd,
+        initial_price,
+        quantity,
+        leverage_trader,
+    );
 
     let offer_liquidation_price = calculate_short_liquidation_price(
+        ContractSymbol::BtcUsd,
         Decimal::from_f32(leverage_coordinator).expect("to be able to parse f32"),
         initial_price,
     );
     let accept_liquidation_price = calculate_long_liquidation_price(
+        ContractSymbol::BtcUsd,
         Decimal::from_f32(leverage_trader).expect("to be able to parse f32"),
         initial_price,
     );
@@ -68,14 +88,26 @@ fn calculating_payout_curve_doesnt_crash_2() {
     let coordinator_direction = Direction::Short;
     let quantity = 10.0;
 
-    let coordinator_collateral = calculate_margin(initial_price, quantity, leverage_coordinator);
-    let trader_collateral = calculate_margin(initial_price, quantity, leverage_trader);
+    let coordinator_collateral = calculate_margin(
+        ContractSymbol::BtcUsd,
+        initial_price,
+        quantity,
+        leverage_coordinator,
+    );
+    let trader_collateral = calculate_margin(
+        ContractSymbol::BtcUsd,
+        initial_price,
+        quantity,
+        leverage_trader,
+    );
 
     let offer_liquidation_price = calculate_short_liquidation_price(
+        ContractSymbol::BtcUsd,
         Decimal::from_f32(leverage_coordinator).expect("to be able to parse f32"),
         initial_price,
     );
     let accept_liquidation_price = calculate_long_liquidation_price(
+        ContractSymbol::BtcUsd,
         Decimal::from_f32(leverage_trader).expect("to be able to parse f32"),
         initial_price,
     );
@@ -103,14 +135,26 @@ fn calculating_payout_curve_doesnt_crash_3() {
     let coordinator_direction = Direction::Short;
     let quantity = 1.0;
 
-    let coordinator_collateral = calculate_margin(initial_price, quantity, leverage_coordinator);
-    let trader_collateral = calculate_margin(initial_price, quantity, leverage_trader);
+    let coordinator_collateral = calculate_margin(
+        ContractSymbol::BtcUsd,
+        initial_price,
+        quantity,
+        leverage_coordinator,
+    );
+    let trader_collateral = calculate_margin(
+        ContractSymbol::BtcUsd,
+        initial_price,
+        quantity,
+        leverage_trader,
+    );
 
     let offer_liquidation_price = calculate_short_liquidation_price(
+        ContractSymbol::BtcUsd,
         Decimal::from_f32(leverage_coordinator).expect("to be able to parse f32"),
         initial_price,
     );
     let accept_liquidation_price = calculate_long_liquidation_price(
+        ContractSymbol::BtcUsd,
         Decimal::from_f32(leverage_trader).expect("to be able to parse f32"),
         initial_price,
     );
@@ -149,17 +193,17 @@ proptest! {
         let quantity = 10.0;
         let fee= 0;
 
-        let coordinator_collateral= calculate_margin(initial_price, quantity, leverage_coordinator);
-        let trader_collateral= calculate_margin(initial_price, quantity, leverage_trader);
+        let coordinator_collateral= calculate_margin(ContractSymbol::BtcUsd, initial_price, quantity, leverage_coordinator);
+        let trader_collateral= calculate_margin(ContractSymbol::BtcUsd, initial_price, quantity, leverage_trader);
 
         let (offer_liquidation_price, accept_liquidation_price) = match coordinator_direction {
             Direction::Long => {
                 (
-                    calculate_long_liquidation_price(
+                    calculate_long_liquidation_price(ContractSymbol::BtcUsd,
                         Decimal::from_f32(leverage_coordinator).expect("to be able to parse f32"),
                         initial_price,
                     ),
-                    calculate_short_liquidation_price(
+                    calculate_short_liquidation_price(ContractSymbol::BtcUsd,
                         Decimal::from_f32(leverage_trader).expect("to be able to parse f32"),
                         initial_price,
                     )
@@ -167,11 +211,11 @@ proptest! {
             }
             Direction::Short => {
                 (
-                    calculate_short_liquidation_price(
+                    calculate_short_liquidation_price(ContractSymbol::BtcUsd,
                         Decimal::from_f32(leverage_coordinator).expect("to be able to parse f32"),
                         initial_price,
                     ),
-                    calculate_long_liquidation_price(
+                    calculate_long_liquidation_price(ContractSymbol::BtcUsd,
                         Decimal::from_f32(leverage_trader).expect("to be able to parse f32"),
                         initial_price,
                     )
@@ -213,6 +257,121 @@ proptest! {
     }
 }
 
+proptest! {
+    #[test]
+    fn linear_payout_curve_is_continuous_and_bounded(
+        quantity in 1u64..10_000,
+        initial_price in 1_000u64..100_000,
+        leverage_long in 1u8..5,
+        leverage_short in 1u8..5,
+        fee in 0u64..10_000,
+        direction in 0..2,
+    ) {
+        let offer_direction = if direction == 0 {
+            Direction::Short
+        }
+        else {
+            Direction::Long
+        };
+
+        let quantity = Decimal::from(quantity);
+        let initial_price = Decimal::from(initial_price);
+
+        let long_margin = (quantity * initial_price / Decimal::from(leverage_long)).to_u64().unwrap();
+        let short_margin = (quantity * initial_price / Decimal::from(leverage_short)).to_u64().unwrap();
+        let total_collateral = long_margin + short_margin;
+
+        let long_liquidation_price = initial_price - Decimal::from(long_margin) / quantity;
+        let short_liquidation_price = initial_price + Decimal::from(short_margin) / quantity;
+
+        let (offer_collateral, accept_collateral, offer_liquidation_price, accept_liquidation_price) =
+            match offer_direction {
+                Direction::Long => (long_margin, short_margin, long_liquidation_price, short_liquidation_price),
+                Direction::Short => (short_margin, long_margin, short_liquidation_price, long_liquidation_price),
+            };
+
+        let payout_points = build_linear_payout_function(
+            quantity,
+            offer_collateral,
+            accept_collateral,
+            initial_price,
+            offer_liquidation_price,
+            accept_liquidation_price,
+            fee,
+            offer_direction,
+            LINEAR_MAX_PRICE,
+        ).unwrap();
+
+        // the payout function covers all outcomes from 0 to the max price without any gaps
+        prop_assert_eq!(payout_points.first().unwrap().0.event_outcome, 0);
+        prop_assert_eq!(payout_points.last().unwrap().1.event_outcome, LINEAR_MAX_PRICE);
+        for pieces in payout_points.windows(2) {
+            prop_assert_eq!(&pieces[0].1, &pieces[1].0);
+        }
+
+        for (lower, upper) in &payout_points {
+            prop_assert!(lower.event_outcome < upper.event_outcome);
+            prop_assert!(lower.outcome_payout <= total_collateral);
+            prop_assert!(upper.outcome_payout <= total_collateral);
+        }
+
+        to_range_payouts(payout_points, total_collateral).unwrap();
+    }
+}
+
+proptest! {
+    #[test]
+    fn calculating_quanto_payout_curve_doesnt_crash(
+         quantity in 1u32..10_000,
+         initial_price in 1_000u32..5_000,
+         trader_leverage in 1u8..5,
+         direction in 0..2,
+    ) {
+        let contract_symbol = ContractSymbol::EthUsd;
+        let coordinator_direction = if direction == 0 {
+            Direction::Short
+        }
+        else {
+            Direction::Long
+        };
+
+        let quantity = quantity as f32;
+        let initial_price = Decimal::from(initial_price);
+        let leverage_trader = Decimal::from(trader_leverage);
+        let leverage_coordinator = Decimal::TWO;
+
+        let coordinator_collateral = calculate_margin(contract_symbol, initial_price, quantity, 2.0);
+        let trader_collateral = calculate_margin(contract_symbol, initial_price, quantity, trader_leverage as f32);
+
+        let (offer_liquidation_price, accept_liquidation_price) = match coordinator_direction {
+            Direction::Long => (
+                calculate_long_liquidation_price(contract_symbol, leverage_coordinator, initial_price),
+                calculate_short_liquidation_price(contract_symbol, leverage_trader, initial_price),
+            ),
+            Direction::Short => (
+                calculate_short_liquidation_price(contract_symbol, leverage_coordinator, initial_price),
+                calculate_long_liquidation_price(contract_symbol, leverage_trader, initial_price),
+            ),
+        };
+
+        let payout_points = build_quanto_payout_function(
+            contract_symbol,
+            quantity,
+            coordinator_collateral,
+            trader_collateral,
+            initial_price,
+            offer_liquidation_price,
+            accept_liquidation_price,
+            0,
+            coordinator_direction,
+        ).unwrap();
+
+        prop_assert_eq!(payout_points.last().unwrap().1.event_outcome, contract_symbol.max_price());
+
+        to_range_payouts(payout_points, coordinator_collateral + trader_collateral).unwrap();
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn computed_payout_curve(
     quantity: f32,
@@ -225,6 +384,7 @@ fn computed_payout_curve(
     accept_liquidation_price: Decimal,
 ) -> Result<()> {
     let payout_points = build_inverse_payout_function(
+        ContractSymbol::BtcUsd,
         quantity,
         coordinator_collateral,
         trader_collateral,
@@ -235,6 +395,15 @@ fn computed_payout_curve(
         coordinator_direction,
     )?;
 
+    to_range_payouts(payout_points, coordinator_collateral + trader_collateral)
+}
+
+/// Converts the payout points into a [`PayoutFunction`] and computes its range payouts, which
+/// fails if the payout points don't describe a valid payout function.
+fn to_range_payouts(
+    payout_points: Vec<(PayoutPoint, PayoutPoint)>,
+    total_collateral: u64,
+) -> Result<()> {
     let start = SystemTime::now();
     let now = start.duration_since(UNIX_EPOCH)?;
 
@@ -276,7 +445,6 @@ fn computed_payout_curve(
     let payout_function =
         PayoutFunction::new(pieces).context("could not create payout function")?;
 
-    let total_collateral = coordinator_collateral + trader_collateral;
     let _ = payout_function.to_range_payouts(
         total_collateral,
         &RoundingIntervals {
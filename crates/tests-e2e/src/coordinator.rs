@@ -1,9 +1,11 @@
 use anyhow::Context;
 use anyhow::Result;
+use bitcoin::secp256k1::SecretKey;
 use bitcoin::Address;
 use bitcoin::Txid;
 use commons::CollaborativeRevertCoordinatorExpertRequest;
 use commons::CollaborativeRevertCoordinatorRequest;
+use commons::RequestSignature;
 use coordinator::admin::Balance;
 use coordinator::routes::InvoiceParams;
 use ln_dlc_node::lightning_invoice;
@@ -193,9 +195,26 @@ impl Coordinator {
         .await
     }
 
-    pub async fn rollover(&self, dlc_channel_id: &str) -> Result<reqwest::Response> {
-        self.post(format!("/api/rollover/{dlc_channel_id}").as_str())
+    /// Triggers the rollover on behalf of the trader, signing the request with their key.
+    pub async fn rollover(
+        &self,
+        dlc_channel_id: &str,
+        trader_key: &SecretKey,
+    ) -> Result<reqwest::Response> {
+        let path = format!("/api/rollover/{dlc_channel_id}");
+        let signature = RequestSignature::new(trader_key, "POST", &path, &[]);
+
+        let mut request = self.client.post(format!("{0}{path}", self.host));
+        for (name, value) in signature.headers() {
+            request = request.header(name, value);
+        }
+
+        request
+            .send()
             .await
+            .context("Could not send POST request to coordinator")?
+            .error_for_status()
+            .context("Coordinator did not return 200 OK")
     }
 
     async fn get(&self, path: &str) -> Result<reqwest::Response> {
@@ -4,6 +4,7 @@ use native::health::Service;
 use native::health::ServiceStatus;
 use native::trade::order::api::NewOrder;
 use native::trade::order::api::OrderType;
+use native::trade::order::api::TimeInForce;
 use native::trade::position::PositionState;
 use tests_e2e::setup::TestSetup;
 use tests_e2e::wait_until;
@@ -17,6 +18,7 @@ fn dummy_order() -> NewOrder {
         quantity: 1.0,
         order_type: Box::new(OrderType::Market),
         stable: false,
+        time_in_force: Box::new(TimeInForce::FillOrKill),
     }
 }
 
@@ -3,6 +3,7 @@ use native::api::ContractSymbol;
 use native::api::Direction;
 use native::trade::order::api::NewOrder;
 use native::trade::order::api::OrderType;
+use native::trade::order::api::TimeInForce;
 use native::trade::position::PositionState;
 use tests_e2e::setup::TestSetup;
 use tests_e2e::wait_until;
@@ -23,6 +24,7 @@ async fn can_resize_position() {
         quantity: 10.0,
         order_type: Box::new(OrderType::Market),
         stable: false,
+        time_in_force: Box::new(TimeInForce::FillOrKill),
     };
 
     spawn_blocking({
@@ -48,6 +50,7 @@ async fn can_resize_position() {
         quantity: 5.0,
         order_type: Box::new(OrderType::Market),
         stable: false,
+        time_in_force: Box::new(TimeInForce::FillOrKill),
     };
 
     spawn_blocking({
@@ -84,6 +87,7 @@ async fn can_resize_position() {
         quantity: 10.0,
         order_type: Box::new(OrderType::Market),
         stable: false,
+        time_in_force: Box::new(TimeInForce::FillOrKill),
     };
 
     spawn_blocking({
@@ -121,6 +125,7 @@ async fn can_resize_position() {
         quantity: 5.0,
         order_type: Box::new(OrderType::Market),
         stable: false,
+        time_in_force: Box::new(TimeInForce::FillOrKill),
     };
 
     spawn_blocking({
@@ -158,6 +163,7 @@ async fn can_resize_position() {
         quantity: 10.0,
         order_type: Box::new(OrderType::Market),
         stable: false,
+        time_in_force: Box::new(TimeInForce::FillOrKill),
     };
 
     spawn_blocking({
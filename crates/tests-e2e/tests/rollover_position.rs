@@ -2,6 +2,7 @@
 
 use bitcoin::Network;
 use native::api;
+use native::ln_dlc;
 use native::trade::position;
 use position::PositionState;
 use tests_e2e::app::AppHandle;
@@ -27,7 +28,10 @@ async fn can_rollover_position() {
     let new_expiry = commons::calculate_next_expiry(OffsetDateTime::now_utc(), Network::Regtest);
 
     coordinator
-        .rollover(&dlc_channel.dlc_channel_id.unwrap())
+        .rollover(
+            &dlc_channel.dlc_channel_id.unwrap(),
+            &ln_dlc::get_node_key(),
+        )
         .await
         .unwrap();
 
@@ -1,3 +1,5 @@
+use crate::ContractKind;
+use crate::ContractSymbol;
 use crate::Direction;
 use anyhow::Context;
 use anyhow::Result;
@@ -8,10 +10,13 @@ use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use std::ops::Neg;
 
-pub const BTCUSD_MAX_PRICE: u64 = 1_048_575;
-
 /// Calculate the collateral in BTC.
-pub fn calculate_margin(open_price: Decimal, quantity: f32, leverage: f32) -> u64 {
+pub fn calculate_margin(
+    contract_symbol: ContractSymbol,
+    open_price: Decimal,
+    quantity: f32,
+    leverage: f32,
+) -> u64 {
     let quantity = Decimal::try_from(quantity).expect("quantity to fit into decimal");
     let leverage = Decimal::try_from(leverage).expect("leverage to fix into decimal");
 
@@ -20,7 +25,10 @@ pub fn calculate_margin(open_price: Decimal, quantity: f32, leverage: f32) -> u6
         return 0;
     }
 
-    let margin = quantity / (open_price * leverage);
+    let margin = match contract_symbol.metadata().kind {
+        ContractKind::Inverse => quantity / (open_price * leverage),
+        ContractKind::Quanto { multiplier } => quantity * open_price * multiplier / leverage,
+    };
 
     // TODO: Shift the decimal without going into float
     let margin =
@@ -34,7 +42,12 @@ pub fn calculate_margin(open_price: Decimal, quantity: f32, leverage: f32) -> u6
 
 /// Calculate the quantity from price, collateral and leverage
 /// Margin in sats, calculation in BTC
-pub fn calculate_quantity(opening_price: f32, margin: u64, leverage: f32) -> f32 {
+pub fn calculate_quantity(
+    contract_symbol: ContractSymbol,
+    opening_price: f32,
+    margin: u64,
+    leverage: f32,
+) -> f32 {
     let margin_amount = bitcoin::Amount::from_sat(margin);
 
     let margin = Decimal::try_from(margin_amount.to_float_in(Denomination::Bitcoin))
@@ -42,22 +55,45 @@ pub fn calculate_quantity(opening_price: f32, margin: u64, leverage: f32) -> f32
     let open_price = Decimal::try_from(opening_price).expect("price to fit into decimal");
     let leverage = Decimal::try_from(leverage).expect("leverage to fit into decimal");
 
-    let quantity = margin * open_price * leverage;
+    let quantity = match contract_symbol.metadata().kind {
+        ContractKind::Inverse => margin * open_price * leverage,
+        ContractKind::Quanto { .. } if open_price == Decimal::ZERO => Decimal::ZERO,
+        ContractKind::Quanto { multiplier } => margin * leverage / (open_price * multiplier),
+    };
     quantity.to_f32().expect("quantity to fit into f32")
 }
 
-pub fn calculate_long_liquidation_price(leverage: Decimal, price: Decimal) -> Decimal {
-    price * leverage / (leverage + Decimal::ONE)
+/// Calculate liquidation price for the party going long.
+pub fn calculate_long_liquidation_price(
+    contract_symbol: ContractSymbol,
+    leverage: Decimal,
+    price: Decimal,
+) -> Decimal {
+    match contract_symbol.metadata().kind {
+        ContractKind::Inverse => price * leverage / (leverage + Decimal::ONE),
+        ContractKind::Quanto { .. } => price * (leverage - Decimal::ONE) / leverage,
+    }
 }
 
 /// Calculate liquidation price for the party going short.
-pub fn calculate_short_liquidation_price(leverage: Decimal, price: Decimal) -> Decimal {
-    // If the leverage is equal to 1, the liquidation price will go towards infinity
-    if leverage == Decimal::ONE {
-        return Decimal::from(BTCUSD_MAX_PRICE);
-    }
+///
+/// The liquidation price is capped at the max price of the contract, because the oracle can't
+/// attest to a higher price.
+pub fn calculate_short_liquidation_price(
+    contract_symbol: ContractSymbol,
+    leverage: Decimal,
+    price: Decimal,
+) -> Decimal {
+    let max_price = Decimal::from(contract_symbol.max_price());
+
+    let liquidation_price = match contract_symbol.metadata().kind {
+        // If the leverage is equal to 1, the liquidation price will go towards infinity
+        ContractKind::Inverse if leverage == Decimal::ONE => return max_price,
+        ContractKind::Inverse => price * leverage / (leverage - Decimal::ONE),
+        ContractKind::Quanto { .. } => price * (leverage + Decimal::ONE) / leverage,
+    };
 
-    price * leverage / (leverage - Decimal::ONE)
+    liquidation_price.min(max_price)
 }
 
 /// Compute the payout for the given CFD parameters at a particular `closing_price`.
@@ -70,6 +106,7 @@ pub fn calculate_short_liquidation_price(leverage: Decimal, price: Decimal) -> D
 /// Both leverages are supplied so that the total margin can be calculated and the PnL is capped by
 /// the total margin available.
 pub fn calculate_pnl(
+    contract_symbol: ContractSymbol,
     opening_price: Decimal,
     closing_price: Decimal,
     quantity: f32,
@@ -80,9 +117,16 @@ pub fn calculate_pnl(
     let uncapped_pnl_long = {
         let quantity = Decimal::try_from(quantity).expect("quantity to fit into decimal");
 
-        let uncapped_pnl = match opening_price != Decimal::ZERO && closing_price != Decimal::ZERO {
-            true => (quantity / opening_price) - (quantity / closing_price),
-            false => dec!(0.0),
+        let uncapped_pnl = match contract_symbol.metadata().kind {
+            ContractKind::Inverse
+                if opening_price != Decimal::ZERO && closing_price != Decimal::ZERO =>
+            {
+                (quantity / opening_price) - (quantity / closing_price)
+            }
+            ContractKind::Inverse => dec!(0.0),
+            ContractKind::Quanto { multiplier } => {
+                quantity * (closing_price - opening_price) * multiplier
+            }
         };
 
         let uncapped_pnl = uncapped_pnl * dec!(100_000_000);
@@ -127,10 +171,21 @@ mod tests {
         let quantity = 1.0;
         let long_leverage = 2.0;
         let short_leverage = 1.0;
-        let long_margin = calculate_margin(opening_price, quantity, long_leverage);
-        let short_margin = calculate_margin(opening_price, quantity, short_leverage);
+        let long_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            long_leverage,
+        );
+        let short_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            short_leverage,
+        );
 
         let pnl_long = calculate_pnl(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -140,6 +195,7 @@ mod tests {
         )
         .unwrap();
         let pnl_short = calculate_pnl(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -160,10 +216,21 @@ mod tests {
         let quantity = 100.0;
         let long_leverage = 2.0;
         let short_leverage = 1.0;
-        let long_margin = calculate_margin(opening_price, quantity, long_leverage);
-        let short_margin = calculate_margin(opening_price, quantity, short_leverage);
+        let long_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            long_leverage,
+        );
+        let short_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            short_leverage,
+        );
 
         let pnl_long = calculate_pnl(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -183,10 +250,21 @@ mod tests {
         let quantity = 100.0;
         let long_leverage = 2.0;
         let short_leverage = 1.0;
-        let long_margin = calculate_margin(opening_price, quantity, long_leverage);
-        let short_margin = calculate_margin(opening_price, quantity, short_leverage);
+        let long_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            long_leverage,
+        );
+        let short_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            short_leverage,
+        );
 
         let pnl_long = calculate_pnl(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -207,10 +285,21 @@ mod tests {
         let quantity = 100.0;
         let long_leverage = 1.0;
         let short_leverage = 2.0;
-        let long_margin = calculate_margin(opening_price, quantity, long_leverage);
-        let short_margin = calculate_margin(opening_price, quantity, short_leverage);
+        let long_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            long_leverage,
+        );
+        let short_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            short_leverage,
+        );
 
         let pnl_long = calculate_pnl(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -230,10 +319,21 @@ mod tests {
         let quantity = 100.0;
         let long_leverage = 1.0;
         let short_leverage = 2.0;
-        let long_margin = calculate_margin(opening_price, quantity, long_leverage);
-        let short_margin = calculate_margin(opening_price, quantity, short_leverage);
+        let long_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            long_leverage,
+        );
+        let short_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            short_leverage,
+        );
 
         let pnl_long = calculate_pnl(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -254,10 +354,21 @@ mod tests {
         let quantity = 20000.0;
         let long_leverage = 2.0;
         let short_leverage = 1.0;
-        let long_margin = calculate_margin(opening_price, quantity, long_leverage);
-        let short_margin = calculate_margin(opening_price, quantity, short_leverage);
+        let long_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            long_leverage,
+        );
+        let short_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            short_leverage,
+        );
 
         let pnl_long = calculate_pnl(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -278,10 +389,21 @@ mod tests {
         let quantity = 20000.0;
         let long_leverage = 2.0;
         let short_leverage = 1.0;
-        let long_margin = calculate_margin(opening_price, quantity, long_leverage);
-        let short_margin = calculate_margin(opening_price, quantity, short_leverage);
+        let long_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            long_leverage,
+        );
+        let short_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            short_leverage,
+        );
 
         let pnl_long = calculate_pnl(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -302,10 +424,21 @@ mod tests {
         let quantity = 20000.0;
         let long_leverage = 2.0;
         let short_leverage = 1.0;
-        let long_margin = calculate_margin(opening_price, quantity, long_leverage);
-        let short_margin = calculate_margin(opening_price, quantity, short_leverage);
+        let long_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            long_leverage,
+        );
+        let short_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            short_leverage,
+        );
 
         let pnl_long = calculate_pnl(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -326,10 +459,21 @@ mod tests {
         let quantity = 20000.0;
         let long_leverage = 2.0;
         let short_leverage = 1.0;
-        let long_margin = calculate_margin(opening_price, quantity, long_leverage);
-        let short_margin = calculate_margin(opening_price, quantity, short_leverage);
+        let long_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            long_leverage,
+        );
+        let short_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            short_leverage,
+        );
 
         let pnl_long = calculate_pnl(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -350,10 +494,21 @@ mod tests {
         let quantity = 20000.0;
         let long_leverage = 2.0;
         let short_leverage = 1.0;
-        let long_margin = calculate_margin(opening_price, quantity, long_leverage);
-        let short_margin = calculate_margin(opening_price, quantity, short_leverage);
+        let long_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            long_leverage,
+        );
+        let short_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            short_leverage,
+        );
 
         let pnl_long = calculate_pnl(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -374,10 +529,21 @@ mod tests {
         let quantity = 10.0;
         let long_leverage = 2.0;
         let short_leverage = 1.0;
-        let long_margin = calculate_margin(opening_price, quantity, long_leverage);
-        let short_margin = calculate_margin(opening_price, quantity, short_leverage);
+        let long_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            long_leverage,
+        );
+        let short_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            short_leverage,
+        );
 
         let pnl_long = calculate_pnl(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -399,10 +565,21 @@ mod tests {
         let quantity = 60_000.0;
         let long_leverage = 2.0;
         let short_leverage = 2.0;
-        let long_margin = calculate_margin(opening_price, quantity, long_leverage);
-        let short_margin = calculate_margin(opening_price, quantity, short_leverage);
+        let long_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            long_leverage,
+        );
+        let short_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            short_leverage,
+        );
 
         let pnl_long = calculate_pnl(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -424,12 +601,28 @@ mod tests {
         let quantity = 60_000.0;
         let long_leverage = 2.0;
         let short_leverage = 3.0;
-        let long_margin = calculate_margin(opening_price, quantity, long_leverage);
-        let short_margin = calculate_margin(opening_price, quantity, short_leverage);
+        let long_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            long_leverage,
+        );
+        let short_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            short_leverage,
+        );
 
-        let margin = calculate_margin(opening_price, quantity, short_leverage);
+        let margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            short_leverage,
+        );
 
         let pnl_short = calculate_pnl(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -449,11 +642,27 @@ mod tests {
         let quantity = 60_000.0;
         let long_leverage = 5.0;
         let short_leverage = 1.0;
-        let long_margin = calculate_margin(opening_price, quantity, long_leverage);
-        let short_margin = calculate_margin(opening_price, quantity, short_leverage);
+        let long_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            long_leverage,
+        );
+        let short_margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            short_leverage,
+        );
 
-        let margin = calculate_margin(opening_price, quantity, long_leverage);
+        let margin = calculate_margin(
+            ContractSymbol::BtcUsd,
+            opening_price,
+            quantity,
+            long_leverage,
+        );
         let pnl_short = calculate_pnl(
+            ContractSymbol::BtcUsd,
             opening_price,
             closing_price,
             quantity,
@@ -465,4 +674,73 @@ mod tests {
 
         assert_eq!(pnl_short, (margin as i64).neg());
     }
+
+    #[test]
+    fn quanto_margin_is_proportional_to_price() {
+        let margin = calculate_margin(ContractSymbol::EthUsd, dec!(2000), 100.0, 2.0);
+
+        assert_eq!(margin, 10_000_000);
+    }
+
+    #[test]
+    fn quanto_quantity_from_margin() {
+        let quantity = calculate_quantity(ContractSymbol::EthUsd, 2000.0, 10_000_000, 2.0);
+
+        assert_eq!(quantity, 100.0);
+    }
+
+    #[test]
+    fn given_long_quanto_position_when_price_10_pc_up_then_profit_is_linear() {
+        let opening_price = dec!(2000);
+        let closing_price = dec!(2200);
+        let quantity = 100.0;
+        let long_margin = calculate_margin(ContractSymbol::EthUsd, opening_price, quantity, 2.0);
+        let short_margin = calculate_margin(ContractSymbol::EthUsd, opening_price, quantity, 1.0);
+
+        let pnl_long = calculate_pnl(
+            ContractSymbol::EthUsd,
+            opening_price,
+            closing_price,
+            quantity,
+            Direction::Long,
+            long_margin,
+            short_margin,
+        )
+        .unwrap();
+        let pnl_short = calculate_pnl(
+            ContractSymbol::EthUsd,
+            opening_price,
+            closing_price,
+            quantity,
+            Direction::Short,
+            long_margin,
+            short_margin,
+        )
+        .unwrap();
+
+        assert_eq!(pnl_long, 2_000_000);
+        assert_eq!(pnl_short, -2_000_000);
+    }
+
+    #[test]
+    fn quanto_liquidation_prices() {
+        let long_liquidation_price =
+            calculate_long_liquidation_price(ContractSymbol::EthUsd, dec!(2), dec!(2000));
+        let short_liquidation_price =
+            calculate_short_liquidation_price(ContractSymbol::EthUsd, dec!(2), dec!(2000));
+
+        assert_eq!(long_liquidation_price, dec!(1000));
+        assert_eq!(short_liquidation_price, dec!(3000));
+    }
+
+    #[test]
+    fn short_liquidation_price_is_capped_at_max_price() {
+        let liquidation_price =
+            calculate_short_liquidation_price(ContractSymbol::BtcUsd, dec!(1), dec!(30_000));
+
+        assert_eq!(
+            liquidation_price,
+            Decimal::from(ContractSymbol::BtcUsd.max_price())
+        );
+    }
 }
@@ -1,5 +1,6 @@
 use anyhow::bail;
 use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
 use serde::Deserialize;
 use serde::Serialize;
 use std::fmt;
@@ -7,21 +8,86 @@ use std::str::FromStr;
 
 pub mod bitmex_client;
 pub mod cfd;
+pub mod price_source;
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum ContractSymbol {
+    /// Inverse contract, i.e. the quantity is denominated in USD and the margin in BTC.
     BtcUsd,
+    /// Quanto contract, i.e. the price is denominated in USD, but the margin and the PnL are
+    /// denominated in BTC at a fixed rate.
+    EthUsd,
+}
+
+/// How the PnL of a contract is calculated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractKind {
+    /// The PnL is `quantity / opening_price - quantity / closing_price` BTC.
+    Inverse,
+    /// The PnL is `quantity * (closing_price - opening_price) * multiplier` BTC.
+    Quanto {
+        /// The BTC value of a 1 USD price move of one contract.
+        multiplier: Decimal,
+    },
+}
+
+/// Static information about a [`ContractSymbol`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContractMetadata {
+    pub kind: ContractKind,
+    /// The prefix of the oracle event id, which is completed with the maturity of the contract.
+    pub oracle_event_id_prefix: &'static str,
+    /// The minimum price increment of an order.
+    pub tick_size: Decimal,
+    /// The highest price the oracle can attest to. Prices above are attested as the max price.
+    ///
+    /// Has to be of the form `2^n - 1`, where `n` is the number of digits the oracle attests to.
+    pub max_price: u64,
+}
+
+impl ContractMetadata {
+    /// The number of binary digits the oracle attests to.
+    pub fn oracle_nb_digits(&self) -> usize {
+        (u64::BITS - self.max_price.leading_zeros()) as usize
+    }
 }
 
 impl ContractSymbol {
+    /// All the contract symbols which can be traded.
+    pub const ALL: [ContractSymbol; 2] = [ContractSymbol::BtcUsd, ContractSymbol::EthUsd];
+
     pub fn label(self) -> String {
         match self {
             ContractSymbol::BtcUsd => "btcusd".to_string(),
+            ContractSymbol::EthUsd => "ethusd".to_string(),
+        }
+    }
+
+    pub fn metadata(self) -> ContractMetadata {
+        match self {
+            ContractSymbol::BtcUsd => ContractMetadata {
+                kind: ContractKind::Inverse,
+                oracle_event_id_prefix: "btcusd",
+                tick_size: dec!(0.5),
+                max_price: 1_048_575,
+            },
+            ContractSymbol::EthUsd => ContractMetadata {
+                kind: ContractKind::Quanto {
+                    multiplier: dec!(0.000001),
+                },
+                oracle_event_id_prefix: "ethusd",
+                tick_size: dec!(0.05),
+                max_price: 131_071,
+            },
         }
     }
+
+    pub fn max_price(self) -> u64 {
+        self.metadata().max_price
+    }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum Direction {
     Long,
     Short,
@@ -73,6 +139,7 @@ impl FromStr for ContractSymbol {
             "btcusd" => Ok(ContractSymbol::BtcUsd),
             // BitMEX representation
             "xbtusd" => Ok(ContractSymbol::BtcUsd),
+            "ethusd" => Ok(ContractSymbol::EthUsd),
             unknown => bail!("Unknown contract symbol {unknown}"),
         }
     }
@@ -82,6 +149,7 @@ impl fmt::Display for ContractSymbol {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let symbol = match self {
             ContractSymbol::BtcUsd => "btcusd",
+            ContractSymbol::EthUsd => "ethusd",
         };
         symbol.to_string().fmt(f)
     }
@@ -106,6 +174,16 @@ mod tests {
             ContractSymbol::from_str("xbtusd").unwrap(),
             ContractSymbol::BtcUsd
         );
+        assert_eq!(
+            ContractSymbol::from_str("ETHUSD").unwrap(),
+            ContractSymbol::EthUsd
+        );
         assert!(ContractSymbol::from_str("dogeusd").is_err());
     }
+
+    #[test]
+    fn oracle_nb_digits_cover_max_price() {
+        assert_eq!(ContractSymbol::BtcUsd.metadata().oracle_nb_digits(), 20);
+        assert_eq!(ContractSymbol::EthUsd.metadata().oracle_nb_digits(), 17);
+    }
 }
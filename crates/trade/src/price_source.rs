@@ -0,0 +1,177 @@
+use crate::bitmex_client::BitmexClient;
+use crate::ContractSymbol;
+use crate::Price;
+use anyhow::bail;
+use anyhow::ensure;
+use anyhow::Result;
+use async_trait::async_trait;
+use bitcoin::Network;
+use rust_decimal::Decimal;
+use time::OffsetDateTime;
+
+/// A source for the current [`Price`] of a [`ContractSymbol`].
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    /// The name of the price source, e.g. to be used in logs and metrics.
+    fn name(&self) -> &'static str;
+
+    /// Gets the current best bid and ask for the given `contract_symbol`.
+    async fn get_price(&self, contract_symbol: ContractSymbol) -> Result<Price>;
+}
+
+/// Price source using the latest BitMEX quote.
+pub struct BitmexPriceSource {
+    network: Network,
+}
+
+impl BitmexPriceSource {
+    pub fn new(network: Network) -> Self {
+        Self { network }
+    }
+}
+
+#[async_trait]
+impl PriceSource for BitmexPriceSource {
+    fn name(&self) -> &'static str {
+        "bitmex"
+    }
+
+    async fn get_price(&self, contract_symbol: ContractSymbol) -> Result<Price> {
+        ensure!(
+            contract_symbol == ContractSymbol::BtcUsd,
+            "BitMEX price source only supports {}",
+            ContractSymbol::BtcUsd
+        );
+
+        let quote = BitmexClient::get_quote(&self.network, &OffsetDateTime::now_utc()).await?;
+
+        Ok(Price::from(quote))
+    }
+}
+
+/// Price index using the median bid and ask across multiple price sources.
+///
+/// Price sources which fail to provide a price are ignored, as long as at least one of them
+/// succeeds.
+pub struct MedianPriceSource {
+    sources: Vec<Box<dyn PriceSource>>,
+}
+
+impl MedianPriceSource {
+    pub fn new(sources: Vec<Box<dyn PriceSource>>) -> Self {
+        Self { sources }
+    }
+}
+
+#[async_trait]
+impl PriceSource for MedianPriceSource {
+    fn name(&self) -> &'static str {
+        "median"
+    }
+
+    async fn get_price(&self, contract_symbol: ContractSymbol) -> Result<Price> {
+        let mut prices = vec![];
+        let mut errors = vec![];
+        for source in self.sources.iter() {
+            match source.get_price(contract_symbol).await {
+                Ok(price) => prices.push(price),
+                Err(e) => errors.push(format!("{}: {e:#}", source.name())),
+            }
+        }
+
+        if prices.is_empty() {
+            bail!(
+                "None of the price sources provided a price: [{}]",
+                errors.join(", ")
+            );
+        }
+
+        median_price(&prices)
+    }
+}
+
+/// Calculates the median bid and the median ask of the given prices.
+fn median_price(prices: &[Price]) -> Result<Price> {
+    let bid = median(prices.iter().map(|price| price.bid).collect())?;
+    let ask = median(prices.iter().map(|price| price.ask).collect())?;
+
+    Ok(Price { bid, ask })
+}
+
+fn median(mut values: Vec<Decimal>) -> Result<Decimal> {
+    ensure!(!values.is_empty(), "Can't calculate median of no values");
+
+    values.sort();
+
+    let middle = values.len() / 2;
+    let median = if values.len() % 2 == 0 {
+        (values[middle - 1] + values[middle]) / Decimal::TWO
+    } else {
+        values[middle]
+    };
+
+    Ok(median)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn median_of_odd_number_of_prices() {
+        let prices = [
+            Price {
+                bid: dec!(30_000),
+                ask: dec!(30_010),
+            },
+            Price {
+                bid: dec!(29_000),
+                ask: dec!(29_020),
+            },
+            Price {
+                bid: dec!(31_000),
+                ask: dec!(31_005),
+            },
+        ];
+
+        let price = median_price(&prices).unwrap();
+
+        assert_eq!(
+            price,
+            Price {
+                bid: dec!(30_000),
+                ask: dec!(30_010),
+            }
+        );
+    }
+
+    #[test]
+    fn median_of_even_number_of_prices() {
+        let prices = [
+            Price {
+                bid: dec!(30_000),
+                ask: dec!(30_010),
+            },
+            Price {
+                bid: dec!(29_000),
+                ask: dec!(29_020),
+            },
+        ];
+
+        let price = median_price(&prices).unwrap();
+
+        assert_eq!(
+            price,
+            Price {
+                bid: dec!(29_500),
+                ask: dec!(29_515),
+            }
+        );
+    }
+
+    #[test]
+    fn median_of_no_prices_fails() {
+        assert!(median_price(&[]).is_err());
+    }
+}
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE "fills";
+
+DROP TYPE "ContractSymbol_Type";
+DROP TYPE "FillKind_Type";
+DROP TYPE "Book_Type";
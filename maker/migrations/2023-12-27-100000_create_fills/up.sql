@@ -0,0 +1,21 @@
+-- Your SQL goes here
+CREATE TYPE "Book_Type" AS ENUM ('TenTenOne', 'Hedge');
+CREATE TYPE "FillKind_Type" AS ENUM ('Trade', 'Funding');
+CREATE TYPE "ContractSymbol_Type" AS ENUM ('BtcUsd', 'EthUsd');
+
+CREATE TABLE "fills"
+(
+    id              UUID PRIMARY KEY         NOT NULL,
+    book            "Book_Type"              NOT NULL,
+    contract_symbol "ContractSymbol_Type"    NOT NULL,
+    kind            "FillKind_Type"          NOT NULL,
+    -- Positive if the maker bought, negative if it sold.
+    contracts       NUMERIC                  NOT NULL,
+    price           NUMERIC                  NOT NULL,
+    -- Paid by the maker, negative if the maker received it.
+    fee_sats        BIGINT                   NOT NULL,
+    timestamp       timestamp WITH TIME ZONE NOT NULL,
+    created_at      timestamp WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS fills_timestamp ON fills(timestamp);
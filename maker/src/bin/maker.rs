@@ -1,11 +1,11 @@
 use anyhow::Context;
 use anyhow::Result;
-use bitcoin::Network;
 use diesel::r2d2;
 use diesel::r2d2::ConnectionManager;
 use diesel::PgConnection;
 use ln_dlc_node::node::InMemoryStore;
 use ln_dlc_node::seed::Bip39Seed;
+use maker::cli;
 use maker::cli::Opts;
 use maker::health;
 use maker::ln::ldk_config;
@@ -15,12 +15,17 @@ use maker::logger;
 use maker::metrics;
 use maker::metrics::init_meter;
 use maker::orderbook_ws;
+use maker::pnl;
 use maker::position;
+use maker::position::hedging::Hedger;
 use maker::probing::send_payment_probes_regularly;
 use maker::routes::router;
 use maker::run_migration;
 use maker::storage::MakerTenTenOneStorage;
 use maker::trading;
+use maker::venue::Bitmex;
+use maker::venue::HedgingVenue;
+use maker::venue::Paper;
 use rand::thread_rng;
 use rand::RngCore;
 use std::backtrace::Backtrace;
@@ -29,6 +34,7 @@ use std::net::Ipv4Addr;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::mpsc;
 use tokio::task::spawn_blocking;
 use tracing::metadata::LevelFilter;
 
@@ -37,6 +43,8 @@ const PROCESS_PROMETHEUS_METRICS: Duration = Duration::from_secs(10);
 /// Interval after which we'll try to reconnect to the pricefeed again
 const PRICEFEED_RECONNECT_INTERVAL: Duration = Duration::from_secs(10);
 
+const SYNC_TENTENONE_FILLS_INTERVAL: Duration = Duration::from_secs(30);
+
 #[tokio::main]
 async fn main() -> Result<()> {
     std::panic::set_hook(
@@ -60,6 +68,7 @@ async fn main() -> Result<()> {
     let network = opts.network();
     let bitmex_api_key = opts.bitmex_api_key.clone();
     let bitmex_api_secret = opts.bitmex_api_secret.clone();
+    let hedging_config = opts.hedging_config()?;
 
     logger::init_tracing(LevelFilter::DEBUG, opts.json)?;
 
@@ -110,43 +119,68 @@ async fn main() -> Result<()> {
 
     let (health, health_tx) = health::Health::new();
 
-    let bitmex_http_client = bitmex_client::client::Client::new(match network {
-        Network::Bitcoin => bitmex_client::models::Network::Mainnet,
-        _ => bitmex_client::models::Network::Testnet,
-    });
+    let manager = ConnectionManager::<PgConnection>::new(opts.database.clone());
+    let pool = r2d2::Pool::builder()
+        .build(manager)
+        .expect("Failed to create pool.");
+
+    let mut conn = pool.get().expect("to get connection from pool");
+    run_migration(&mut conn);
+
+    let pnl_tracker = pnl::Tracker::new(pool);
 
-    let bitmex_http_client = match (bitmex_api_key.clone(), bitmex_api_secret.clone()) {
-        (Some(bitmex_api_key), Some(bitmex_secret)) => {
-            tracing::info!("BitMEX credentials provided");
-            bitmex_http_client.with_credentials(bitmex_api_key, bitmex_secret)
+    let venue: Arc<dyn HedgingVenue> = match opts.hedging_venue {
+        cli::HedgingVenue::Bitmex => {
+            Arc::new(Bitmex::new(network, bitmex_api_key, bitmex_api_secret))
         }
-        _ => {
-            tracing::info!("BitMEX credentials not provided");
-            bitmex_http_client
+        cli::HedgingVenue::Paper => {
+            tracing::info!("Paper trading against BitMEX quotes");
+
+            let paper = Arc::new(Paper::new());
+            tokio::spawn(paper.clone().follow_quotes(
+                Arc::new(Bitmex::new(network, None, None)),
+                PRICEFEED_RECONNECT_INTERVAL,
+            ));
+
+            paper
         }
     };
 
     let (position_manager, mailbox) = xtra::Mailbox::unbounded();
     tokio::spawn(xtra::run(
         mailbox,
-        position::Manager::new(bitmex_http_client),
+        position::Manager::new(Hedger::new(hedging_config, venue.clone())),
     ));
 
+    tokio::spawn(
+        pnl_tracker
+            .clone()
+            .follow_venue(venue.clone(), PRICEFEED_RECONNECT_INTERVAL),
+    );
+    tokio::spawn(pnl_tracker.clone().sync_tentenone_fills(
+        opts.orderbook.clone(),
+        node.node_key(),
+        SYNC_TENTENONE_FILLS_INTERVAL,
+    ));
+
+    let (own_orders_tx, own_orders_rx) = mpsc::unbounded_channel();
+
     let node_pubkey = node.info.pubkey;
     tokio::spawn({
         let orderbook_url = opts.orderbook.clone();
         let position_manager = position_manager.clone();
+        let auth_sk = node.node_key();
+        let quoting = opts.quoting_config();
         async move {
             trading::run(
                 &orderbook_url,
-                node_pubkey,
-                network,
-                opts.concurrent_orders,
+                auth_sk,
+                venue,
+                quoting,
                 time::Duration::seconds(opts.order_expiry_after_seconds as i64),
                 health_tx.bitmex_pricefeed,
                 position_manager,
-                bitmex_api_key,
-                bitmex_api_secret,
+                own_orders_rx,
                 PRICEFEED_RECONNECT_INTERVAL,
             )
             .await;
@@ -165,11 +199,13 @@ async fn main() -> Result<()> {
     let _collect_prometheus_metrics = tokio::spawn({
         let node = node.clone();
         let health = health.clone();
+        let pnl_tracker = pnl_tracker.clone();
         async move {
             loop {
                 let node = node.clone();
                 let health = health.clone();
-                spawn_blocking(move || metrics::collect(node, health))
+                let pnl_tracker = pnl_tracker.clone();
+                spawn_blocking(move || metrics::collect(node, health, pnl_tracker))
                     .await
                     .expect("To spawn blocking thread");
                 tokio::time::sleep(PROCESS_PROMETHEUS_METRICS).await;
@@ -177,20 +213,13 @@ async fn main() -> Result<()> {
         }
     });
 
-    let manager = ConnectionManager::<PgConnection>::new(opts.database);
-    let pool = r2d2::Pool::builder()
-        .build(manager)
-        .expect("Failed to create pool.");
-
-    let mut conn = pool.get().expect("to get connection from pool");
-    run_migration(&mut conn);
-
     orderbook_ws::Client::new(
         opts.orderbook,
         node_pubkey,
         node.node_key(),
         position_manager.clone(),
         health_tx.orderbook,
+        own_orders_tx,
     )
     .spawn_supervised_connection();
 
@@ -200,6 +229,7 @@ async fn main() -> Result<()> {
         node,
         exporter,
         position_manager,
+        pnl_tracker,
         health,
         announcement_addresses.clone(),
         node_alias,
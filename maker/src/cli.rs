@@ -1,10 +1,18 @@
+use crate::position::hedging::HedgingConfig;
+use crate::position::hedging::HedgingLimits;
+use crate::position::hedging::StrategyConfig;
+use crate::position::hedging::LOT_SIZE;
+use crate::trading::quoting::QuotingConfig;
+use anyhow::ensure;
 use anyhow::Result;
 use clap::Parser;
 use ln_dlc_node::node::OracleInfo;
 use reqwest::Url;
+use rust_decimal::Decimal;
 use std::env::current_dir;
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Parser)]
 pub struct Opts {
@@ -42,9 +50,35 @@ pub struct Opts {
     #[clap(short, long)]
     pub json: bool,
 
-    /// Amount of concurrent orders (buy,sell) that the maker will create at a time.
+    /// The distance of the best bid and ask from the mid price of the hedging venue, in basis
+    /// points.
+    #[clap(long, default_value = "10")]
+    quote_spread_bps: u32,
+
+    /// The distance between two consecutive price levels of the same side, in basis points.
+    #[clap(long, default_value = "5")]
+    quote_level_spacing_bps: u32,
+
+    /// The quantity of every price level quoted on each side, starting with the best level.
+    #[clap(
+        long,
+        value_delimiter = ',',
+        default_value = "5000,5000,5000,5000,5000"
+    )]
+    quote_level_sizes: Vec<Decimal>,
+
+    /// The maximum shift of the quotes against the net 10101 position, in basis points.
+    #[clap(long, default_value = "20")]
+    quote_max_skew_bps: u32,
+
+    /// The net 10101 position, in contracts, from which the quotes are shifted by the maximum
+    /// skew.
+    #[clap(long, default_value = "100000")]
+    quote_max_skew_contracts: Decimal,
+
+    /// The minimum price change of a level, in basis points, for its order to be replaced.
     #[clap(long, default_value = "5")]
-    pub concurrent_orders: usize,
+    requote_threshold_bps: u32,
 
     /// Orders created by maker will be valid for this number of seconds.
     #[clap(long, default_value = "60")]
@@ -72,6 +106,66 @@ pub struct Opts {
     /// RGS server URL.
     #[clap(long)]
     pub rgs_server_url: Option<String>,
+
+    /// Where to hedge the 10101 position.
+    #[clap(long, value_enum, default_value = "bitmex")]
+    pub hedging_venue: HedgingVenue,
+
+    /// How to hedge the 10101 position.
+    #[clap(long, value_enum, default_value = "band")]
+    hedging_strategy: HedgingStrategy,
+
+    /// The number of unhedged contracts at which the band strategy hedges.
+    #[clap(long, default_value = "100")]
+    hedging_band_contracts: u32,
+
+    /// The share of the 10101 position hedged by the delta strategy, between 0 and 1.
+    #[clap(long, default_value = "1")]
+    hedging_ratio: Decimal,
+
+    /// The maximum number of contracts traded per hedging round by the delta strategy.
+    #[clap(long, default_value = "1000")]
+    hedging_clip_contracts: u32,
+
+    /// The number of hedging rounds over which the TWAP strategy spreads its orders.
+    #[clap(long, default_value = "5")]
+    hedging_twap_slices: u32,
+
+    /// The number of seconds between two hedging rounds.
+    #[clap(long, default_value = "60")]
+    hedging_interval_seconds: u64,
+
+    /// The maximum number of contracts of a single hedging order, at least one lot of 100
+    /// contracts.
+    #[clap(long, default_value = "10000")]
+    hedging_max_order_contracts: u32,
+
+    /// The maximum deviation of the execution price of a hedging order from the mid price, in
+    /// basis points.
+    #[clap(long, default_value = "50")]
+    hedging_max_slippage_bps: u32,
+
+    /// The minimum number of seconds between two hedging orders.
+    #[clap(long, default_value = "0")]
+    hedging_cool_down_seconds: u64,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum HedgingVenue {
+    /// Trade on BitMEX, using the BitMEX API credentials.
+    Bitmex,
+    /// Trade against BitMEX quotes in-process, without placing any real orders.
+    Paper,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum HedgingStrategy {
+    /// Hedge the whole difference once it reaches a threshold.
+    Band,
+    /// Hedge a share of the 10101 position in clips.
+    Delta,
+    /// Spread the hedging orders evenly over several rounds.
+    Twap,
 }
 
 #[derive(Debug, Clone, Copy, clap::ValueEnum)]
@@ -113,6 +207,53 @@ impl Opts {
         Ok(data_dir)
     }
 
+    pub fn hedging_config(&self) -> Result<HedgingConfig> {
+        ensure!(
+            (Decimal::ZERO..=Decimal::ONE).contains(&self.hedging_ratio),
+            "The hedging ratio must be between 0 and 1, got {}",
+            self.hedging_ratio
+        );
+        ensure!(
+            self.hedging_max_order_contracts >= LOT_SIZE as u32,
+            "The maximum hedging order size must be at least {LOT_SIZE} contracts, got {}",
+            self.hedging_max_order_contracts
+        );
+
+        let strategy = match self.hedging_strategy {
+            HedgingStrategy::Band => StrategyConfig::Band {
+                threshold: self.hedging_band_contracts,
+            },
+            HedgingStrategy::Delta => StrategyConfig::Delta {
+                hedge_ratio: self.hedging_ratio,
+                clip_size: self.hedging_clip_contracts,
+            },
+            HedgingStrategy::Twap => StrategyConfig::Twap {
+                slices: self.hedging_twap_slices,
+            },
+        };
+
+        Ok(HedgingConfig {
+            strategy,
+            limits: HedgingLimits {
+                max_order_size: self.hedging_max_order_contracts,
+                max_slippage_bps: self.hedging_max_slippage_bps,
+                cool_down: Duration::from_secs(self.hedging_cool_down_seconds),
+            },
+            interval: Duration::from_secs(self.hedging_interval_seconds),
+        })
+    }
+
+    pub fn quoting_config(&self) -> QuotingConfig {
+        QuotingConfig {
+            spread_bps: self.quote_spread_bps,
+            level_spacing_bps: self.quote_level_spacing_bps,
+            level_sizes: self.quote_level_sizes.clone(),
+            max_skew_bps: self.quote_max_skew_bps,
+            max_skew_position: self.quote_max_skew_contracts,
+            requote_threshold_bps: self.requote_threshold_bps,
+        }
+    }
+
     pub fn get_oracle_info(&self) -> OracleInfo {
         OracleInfo {
             endpoint: self.oracle_endpoint.clone(),
@@ -0,0 +1,75 @@
+use crate::db::fills::Book;
+use crate::db::fills::ContractSymbol;
+use crate::db::fills::FillKind;
+use crate::schema::sql_types::BookType;
+use crate::schema::sql_types::ContractSymbolType;
+use crate::schema::sql_types::FillKindType;
+use diesel::deserialize;
+use diesel::deserialize::FromSql;
+use diesel::pg::Pg;
+use diesel::pg::PgValue;
+use diesel::serialize;
+use diesel::serialize::IsNull;
+use diesel::serialize::Output;
+use diesel::serialize::ToSql;
+use std::io::Write;
+
+impl ToSql<BookType, Pg> for Book {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        match *self {
+            Book::TenTenOne => out.write_all(b"TenTenOne")?,
+            Book::Hedge => out.write_all(b"Hedge")?,
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<BookType, Pg> for Book {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"TenTenOne" => Ok(Book::TenTenOne),
+            b"Hedge" => Ok(Book::Hedge),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+impl ToSql<ContractSymbolType, Pg> for ContractSymbol {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        match *self {
+            ContractSymbol::BtcUsd => out.write_all(b"BtcUsd")?,
+            ContractSymbol::EthUsd => out.write_all(b"EthUsd")?,
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<ContractSymbolType, Pg> for ContractSymbol {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"BtcUsd" => Ok(ContractSymbol::BtcUsd),
+            b"EthUsd" => Ok(ContractSymbol::EthUsd),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+impl ToSql<FillKindType, Pg> for FillKind {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        match *self {
+            FillKind::Trade => out.write_all(b"Trade")?,
+            FillKind::Funding => out.write_all(b"Funding")?,
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<FillKindType, Pg> for FillKind {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"Trade" => Ok(FillKind::Trade),
+            b"Funding" => Ok(FillKind::Funding),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use crate::pnl;
+use crate::schema::fills;
+use crate::schema::sql_types::BookType;
+use crate::schema::sql_types::ContractSymbolType;
+use crate::schema::sql_types::FillKindType;
+use diesel::prelude::*;
+use diesel::query_builder::QueryId;
+use diesel::AsExpression;
+use diesel::FromSqlRow;
+use rust_decimal::Decimal;
+use std::any::TypeId;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, FromSqlRow, AsExpression)]
+#[diesel(sql_type = BookType)]
+pub enum Book {
+    TenTenOne,
+    Hedge,
+}
+
+impl QueryId for BookType {
+    type QueryId = BookType;
+    const HAS_STATIC_QUERY_ID: bool = false;
+
+    fn query_id() -> Option<TypeId> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, FromSqlRow, AsExpression)]
+#[diesel(sql_type = ContractSymbolType)]
+pub enum ContractSymbol {
+    BtcUsd,
+    EthUsd,
+}
+
+impl QueryId for ContractSymbolType {
+    type QueryId = ContractSymbolType;
+    const HAS_STATIC_QUERY_ID: bool = false;
+
+    fn query_id() -> Option<TypeId> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, FromSqlRow, AsExpression)]
+#[diesel(sql_type = FillKindType)]
+pub enum FillKind {
+    Trade,
+    Funding,
+}
+
+impl QueryId for FillKindType {
+    type QueryId = FillKindType;
+    const HAS_STATIC_QUERY_ID: bool = false;
+
+    fn query_id() -> Option<TypeId> {
+        None
+    }
+}
+
+#[derive(Insertable, Queryable, Debug, Clone)]
+#[diesel(table_name = fills)]
+struct Fill {
+    id: Uuid,
+    book: Book,
+    contract_symbol: ContractSymbol,
+    kind: FillKind,
+    contracts: Decimal,
+    price: Decimal,
+    fee_sats: i64,
+    timestamp: OffsetDateTime,
+}
+
+/// Inserts the fill unless a fill with the same id exists already.
+///
+/// Returns whether the fill was inserted.
+pub fn insert(conn: &mut PgConnection, fill: &pnl::Fill) -> QueryResult<bool> {
+    let inserted = diesel::insert_into(fills::table)
+        .values(Fill::from(fill))
+        .on_conflict_do_nothing()
+        .execute(conn)?;
+
+    Ok(inserted > 0)
+}
+
+/// Returns the fills before `to`, or all fills if not set, oldest first.
+pub fn get(conn: &mut PgConnection, to: Option<OffsetDateTime>) -> QueryResult<Vec<pnl::Fill>> {
+    let mut query = fills::table
+        .select((
+            fills::id,
+            fills::book,
+            fills::contract_symbol,
+            fills::kind,
+            fills::contracts,
+            fills::price,
+            fills::fee_sats,
+            fills::timestamp,
+        ))
+        .into_boxed();
+
+    if let Some(to) = to {
+        query = query.filter(fills::timestamp.lt(to));
+    }
+
+    let fills = query
+        .order_by(fills::timestamp.asc())
+        .then_order_by(fills::id.asc())
+        .load::<Fill>(conn)?;
+
+    Ok(fills.into_iter().map(pnl::Fill::from).collect())
+}
+
+/// Returns the time of the most recent fill of the book.
+pub fn get_latest_timestamp(
+    conn: &mut PgConnection,
+    book: pnl::Book,
+) -> QueryResult<Option<OffsetDateTime>> {
+    fills::table
+        .filter(fills::book.eq(Book::from(book)))
+        .select(diesel::dsl::max(fills::timestamp))
+        .first::<Option<OffsetDateTime>>(conn)
+}
+
+impl From<&pnl::Fill> for Fill {
+    fn from(value: &pnl::Fill) -> Self {
+        Fill {
+            id: value.id,
+            book: value.book.into(),
+            contract_symbol: value.contract_symbol.into(),
+            kind: value.kind.into(),
+            contracts: value.contracts,
+            price: value.price,
+            fee_sats: value.fee_sats,
+            timestamp: value.timestamp,
+        }
+    }
+}
+
+impl From<Fill> for pnl::Fill {
+    fn from(value: Fill) -> Self {
+        pnl::Fill {
+            id: value.id,
+            book: value.book.into(),
+            contract_symbol: value.contract_symbol.into(),
+            kind: value.kind.into(),
+            contracts: value.contracts,
+            price: value.price,
+            fee_sats: value.fee_sats,
+            timestamp: value.timestamp,
+        }
+    }
+}
+
+impl From<pnl::Book> for Book {
+    fn from(value: pnl::Book) -> Self {
+        match value {
+            pnl::Book::TenTenOne => Book::TenTenOne,
+            pnl::Book::Hedge => Book::Hedge,
+        }
+    }
+}
+
+impl From<Book> for pnl::Book {
+    fn from(value: Book) -> Self {
+        match value {
+            Book::TenTenOne => pnl::Book::TenTenOne,
+            Book::Hedge => pnl::Book::Hedge,
+        }
+    }
+}
+
+impl From<trade::ContractSymbol> for ContractSymbol {
+    fn from(value: trade::ContractSymbol) -> Self {
+        match value {
+            trade::ContractSymbol::BtcUsd => ContractSymbol::BtcUsd,
+            trade::ContractSymbol::EthUsd => ContractSymbol::EthUsd,
+        }
+    }
+}
+
+impl From<ContractSymbol> for trade::ContractSymbol {
+    fn from(value: ContractSymbol) -> Self {
+        match value {
+            ContractSymbol::BtcUsd => trade::ContractSymbol::BtcUsd,
+            ContractSymbol::EthUsd => trade::ContractSymbol::EthUsd,
+        }
+    }
+}
+
+impl From<pnl::FillKind> for FillKind {
+    fn from(value: pnl::FillKind) -> Self {
+        match value {
+            pnl::FillKind::Trade => FillKind::Trade,
+            pnl::FillKind::Funding => FillKind::Funding,
+        }
+    }
+}
+
+impl From<FillKind> for pnl::FillKind {
+    fn from(value: FillKind) -> Self {
+        match value {
+            FillKind::Trade => pnl::FillKind::Trade,
+            FillKind::Funding => pnl::FillKind::Funding,
+        }
+    }
+}
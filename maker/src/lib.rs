@@ -10,17 +10,20 @@ use std::time::Duration;
 mod tests;
 
 pub mod cli;
+pub mod db;
 pub mod health;
 pub mod ln;
 pub mod logger;
 pub mod metrics;
 pub mod orderbook_ws;
+pub mod pnl;
 pub mod position;
 pub mod probing;
 pub mod routes;
 pub mod schema;
 pub mod storage;
 pub mod trading;
+pub mod venue;
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
 
@@ -1,5 +1,6 @@
 use crate::health::Health;
 use crate::health::ServiceStatus;
+use crate::pnl;
 use crate::storage::MakerTenTenOneStorage;
 use lazy_static::lazy_static;
 use lightning::ln::channelmanager::ChannelDetails;
@@ -15,6 +16,7 @@ use opentelemetry::sdk::metrics::selectors;
 use opentelemetry::Context;
 use opentelemetry::KeyValue;
 use opentelemetry_prometheus::PrometheusExporter;
+use rust_decimal::prelude::ToPrimitive;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -61,6 +63,32 @@ lazy_static! {
         .u64_observable_gauge("node_balance_satoshi")
         .with_description("Node balance in satoshi")
         .init();
+
+    // pnl metrics
+    pub static ref REALIZED_PNL_SATOSHI: ObservableGauge<i64> = METER
+        .i64_observable_gauge("realized_pnl_satoshi")
+        .with_description("Realized PnL in satoshi")
+        .init();
+    pub static ref UNREALIZED_PNL_SATOSHI: ObservableGauge<i64> = METER
+        .i64_observable_gauge("unrealized_pnl_satoshi")
+        .with_description("Unrealized PnL in satoshi at the mid price of the hedging venue")
+        .init();
+    pub static ref FEES_PAID_SATOSHI: ObservableGauge<i64> = METER
+        .i64_observable_gauge("fees_paid_satoshi")
+        .with_description("Fees paid in satoshi")
+        .init();
+    pub static ref FUNDING_PAID_SATOSHI: ObservableGauge<i64> = METER
+        .i64_observable_gauge("funding_paid_satoshi")
+        .with_description("Funding paid in satoshi")
+        .init();
+    pub static ref POSITION_CONTRACTS: ObservableGauge<f64> = METER
+        .f64_observable_gauge("position_contracts")
+        .with_description("Contracts held per book, negative if short")
+        .init();
+    pub static ref NET_EXPOSURE_CONTRACTS: ObservableGauge<f64> = METER
+        .f64_observable_gauge("net_exposure_contracts")
+        .with_description("Contracts held over all books, negative if short")
+        .init();
 }
 
 pub fn init_meter() -> PrometheusExporter {
@@ -74,13 +102,53 @@ pub fn init_meter() -> PrometheusExporter {
     opentelemetry_prometheus::exporter(controller).init()
 }
 
-pub fn collect(node: Arc<Node<MakerTenTenOneStorage, InMemoryStore>>, health: Health) {
+pub fn collect(
+    node: Arc<Node<MakerTenTenOneStorage, InMemoryStore>>,
+    health: Health,
+    pnl_tracker: pnl::Tracker,
+) {
     let cx = opentelemetry::Context::current();
 
     let channels = node.channel_manager.list_channels();
     channel_metrics(&cx, channels);
     node_metrics(&cx, node);
     health_metrics(&cx, &health);
+    pnl_metrics(&cx, &pnl_tracker);
+}
+
+fn pnl_metrics(cx: &Context, pnl_tracker: &pnl::Tracker) {
+    let report = match pnl_tracker.report(None) {
+        Ok(report) => report,
+        Err(e) => {
+            tracing::error!("Could not retrieve PnL for metrics {e:#}");
+            return;
+        }
+    };
+
+    for book in report.books {
+        let key_values = [
+            KeyValue::new("book", book.book.to_string()),
+            KeyValue::new("contract_symbol", book.contract_symbol.label()),
+        ];
+        REALIZED_PNL_SATOSHI.observe(cx, book.realized_pnl_sats, &key_values);
+        if let Some(unrealized_pnl_sats) = book.unrealized_pnl_sats {
+            UNREALIZED_PNL_SATOSHI.observe(cx, unrealized_pnl_sats, &key_values);
+        }
+        FEES_PAID_SATOSHI.observe(cx, book.fees_sats, &key_values);
+        FUNDING_PAID_SATOSHI.observe(cx, book.funding_sats, &key_values);
+        POSITION_CONTRACTS.observe(cx, book.contracts.to_f64().unwrap_or_default(), &key_values);
+    }
+
+    for exposure in report.exposure {
+        NET_EXPOSURE_CONTRACTS.observe(
+            cx,
+            exposure.net.to_f64().unwrap_or_default(),
+            &[KeyValue::new(
+                "contract_symbol",
+                exposure.contract_symbol.label(),
+            )],
+        );
+    }
 }
 
 fn health_metrics(cx: &Context, health: &Health) {
@@ -2,6 +2,7 @@ use crate::health::ServiceStatus;
 use crate::position;
 use crate::position::OrderTenTenOne;
 use crate::position::PositionUpdateTenTenOne;
+use crate::trading::OwnOrderUpdate;
 use anyhow::ensure;
 use anyhow::Context;
 use anyhow::Result;
@@ -10,12 +11,14 @@ use bitcoin::secp256k1::SecretKey;
 use bitcoin::secp256k1::SECP256K1;
 use commons::FilledWith;
 use commons::Message;
+use commons::OrderState;
 use commons::OrderbookRequest;
 use futures::FutureExt;
 use futures::SinkExt;
 use futures::TryStreamExt;
 use reqwest::Url;
 use std::time::Duration;
+use tokio::sync::mpsc;
 use tokio::sync::watch;
 use tokio_tungstenite::tungstenite;
 
@@ -35,6 +38,8 @@ pub struct Client {
     position_manager: xtra::Address<position::Manager>,
     /// Where to send the current status of the orderbook (for system health)
     orderbook_status: watch::Sender<ServiceStatus>,
+    /// Where to forward updates of the maker's own orders.
+    own_orders: mpsc::UnboundedSender<OwnOrderUpdate>,
 }
 
 impl Client {
@@ -44,6 +49,7 @@ impl Client {
         auth_sk: SecretKey,
         position_manager: xtra::Address<position::Manager>,
         orderbook_status: watch::Sender<ServiceStatus>,
+        own_orders: mpsc::UnboundedSender<OwnOrderUpdate>,
     ) -> Self {
         endpoint
             .set_scheme("ws")
@@ -57,12 +63,14 @@ impl Client {
             auth_sk,
             position_manager,
             orderbook_status,
+            own_orders,
         }
     }
 
     /// Spawn a task which subscribes to the orderbook's WebSocket API.
     ///
-    /// The maker uses this to learn about the orders which resulted in a match.
+    /// The maker uses this to learn about the orders which resulted in a match and to keep track of
+    /// its open orders.
     ///
     /// The task will attempt to reconnect to the WebSocket API if it encounters any errors.
     pub fn spawn_supervised_connection(self) {
@@ -71,6 +79,7 @@ impl Client {
         let url = self.url.clone();
         let position_manager = self.position_manager;
         let orderbook_status = self.orderbook_status;
+        let own_orders = self.own_orders;
 
         tokio::spawn(async move {
             let auth_pk = auth_sk.public_key(SECP256K1);
@@ -118,6 +127,7 @@ impl Client {
                                 &position_manager,
                                 &trader_id,
                                 &orderbook_status,
+                                &own_orders,
                             )
                             .await
                             {
@@ -148,6 +158,7 @@ async fn process_message(
     position_manager: &xtra::Address<position::Manager>,
     maker_trader_id: &PublicKey,
     orderbook_status: &watch::Sender<ServiceStatus>,
+    own_orders: &mpsc::UnboundedSender<OwnOrderUpdate>,
 ) -> Result<()> {
     tracing::trace!(%msg, "New message from orderbook");
 
@@ -190,13 +201,34 @@ async fn process_message(
         Message::InvalidAuthentication(e) => {
             tracing::error!("Orderbook authentication failed: {e}");
         }
-        Message::AllOrders(_)
-        | Message::NewOrder(_)
-        | Message::DeleteOrder(_)
+        Message::AllOrders(orders) => {
+            let open_orders = orders
+                .into_iter()
+                .filter(|order| {
+                    order.trader_id == *maker_trader_id && order.order_state == OrderState::Open
+                })
+                .collect();
+
+            let _ = own_orders.send(OwnOrderUpdate::Snapshot(open_orders));
+        }
+        Message::Update(order) if order.trader_id == *maker_trader_id => {
+            let _ = own_orders.send(OwnOrderUpdate::Update(order));
+        }
+        Message::DeleteOrder(order_id) => {
+            let _ = own_orders.send(OwnOrderUpdate::Delete(order_id));
+        }
+        Message::NewOrder(_)
         | Message::Update(_)
         | Message::AsyncMatch { .. }
         | Message::Rollover { .. }
-        | Message::CollaborativeRevert { .. } => {
+        | Message::CollaborativeRevert { .. }
+        | Message::Trade(_)
+        | Message::Candle(_)
+        | Message::OrderbookSnapshot { .. }
+        | Message::OrderbookDelta { .. }
+        | Message::TopOfBook { .. }
+        | Message::Depth(_)
+        | Message::Subscribed { .. } => {
             // Nothing to do.
         }
     }
@@ -0,0 +1,1063 @@
+use crate::db;
+use crate::trading::orderbook_http_client::OrderbookClient;
+use crate::venue::Event;
+use crate::venue::Execution;
+use crate::venue::ExecutionKind;
+use crate::venue::HedgingVenue;
+use anyhow::Result;
+use bitcoin::secp256k1::SecretKey;
+use commons::HistoryFilter;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::PgConnection;
+use futures::StreamExt;
+use reqwest::Url;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::task::spawn_blocking;
+use trade::ContractKind;
+use trade::ContractSymbol;
+use trade::Direction;
+use uuid::Uuid;
+
+const SATS_PER_BTC: Decimal = dec!(100_000_000);
+
+/// The books in which the maker holds positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Book {
+    /// The positions taken against the traders of 10101.
+    TenTenOne,
+    /// The positions taken on the hedging venue.
+    Hedge,
+}
+
+impl Book {
+    pub const ALL: [Book; 2] = [Book::TenTenOne, Book::Hedge];
+}
+
+impl fmt::Display for Book {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Book::TenTenOne => "tentenone".fmt(f),
+            Book::Hedge => "hedge".fmt(f),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillKind {
+    Trade,
+    Funding,
+}
+
+/// A change to the position or to the balance of one of the maker's books.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fill {
+    /// The id of the 10101 match or of the execution on the hedging venue.
+    pub id: Uuid,
+    pub book: Book,
+    pub contract_symbol: ContractSymbol,
+    pub kind: FillKind,
+    /// The number of contracts bought, or sold if negative. Always zero for funding.
+    pub contracts: Decimal,
+    pub price: Decimal,
+    /// The fee, or the funding, paid by the maker. Negative if the maker received it instead.
+    pub fee_sats: i64,
+    pub timestamp: OffsetDateTime,
+}
+
+/// Tracks the PnL of the maker by recording the fills of its books in the database.
+#[derive(Clone)]
+pub struct Tracker {
+    pool: Pool<ConnectionManager<PgConnection>>,
+    /// The latest mid price of every contract symbol on the hedging venue, used to mark the
+    /// positions of both books.
+    marks: Arc<Mutex<HashMap<ContractSymbol, Decimal>>>,
+    /// The recorded fills, loaded from the database for the first report and kept up-to-date
+    /// while recording fills. Unset if it has to be loaded again.
+    journal: Arc<Mutex<Option<Journal>>>,
+}
+
+impl Tracker {
+    pub fn new(pool: Pool<ConnectionManager<PgConnection>>) -> Self {
+        Self {
+            pool,
+            marks: Arc::new(Mutex::new(HashMap::new())),
+            journal: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Records the executions of the hedging venue and follows its quotes to mark the positions.
+    ///
+    /// In case the stream is closed, it is resubscribed to after `reconnect_after`. Once
+    /// resubscribed, the executions missed in the meantime are recorded as well.
+    pub async fn follow_venue(self, venue: Arc<dyn HedgingVenue>, reconnect_after: Duration) {
+        loop {
+            let mut stream = venue.stream();
+            let mut caught_up = false;
+            while let Some(event) = stream.next().await {
+                // Only catching up after the first event ensures that we are subscribed, so that
+                // no execution is missed in between.
+                if !caught_up {
+                    if let Err(e) = self.catch_up(venue.as_ref()).await {
+                        tracing::error!("Failed to catch up on executions of hedging venue: {e:#}");
+                    }
+                    caught_up = true;
+                }
+
+                match event {
+                    Ok(Event::Quote(quote)) => {
+                        let mid = (quote.bid() + quote.ask()) / Decimal::TWO;
+                        self.marks
+                            .lock()
+                            .expect("marks to be available")
+                            .insert(quote.contract_symbol, mid);
+                    }
+                    Ok(Event::Execution(execution)) => {
+                        let fill = Fill::from(execution);
+                        match self.record(vec![fill]).await {
+                            Ok(0) => tracing::debug!(id = %fill.id, "Fill was recorded already"),
+                            Ok(_) => tracing::info!(?fill, "Recorded fill"),
+                            Err(e) => tracing::error!(?fill, "Failed to record fill: {e:#}"),
+                        }
+                    }
+                    Ok(Event::Position(_)) => {}
+                    Err(e) => {
+                        tracing::error!("Failed to receive executions of hedging venue: {e:#}");
+                        break;
+                    }
+                }
+            }
+
+            tokio::time::sleep(reconnect_after).await;
+        }
+    }
+
+    /// Records the executions of the hedging venue since the latest recorded one.
+    ///
+    /// Without any recorded execution there is nothing to catch up on, as fills are only tracked
+    /// from the first time the maker follows the venue.
+    async fn catch_up(&self, venue: &dyn HedgingVenue) -> Result<()> {
+        let from = match self.latest_timestamp(Book::Hedge).await? {
+            Some(from) => from,
+            None => return Ok(()),
+        };
+
+        let fills = venue
+            .executions(from)
+            .await?
+            .into_iter()
+            .map(Fill::from)
+            .collect();
+        let inserted = self.record(fills).await?;
+
+        if inserted > 0 {
+            tracing::info!(inserted, "Recorded missed executions of hedging venue");
+        }
+
+        Ok(())
+    }
+
+    /// Downloads the fills of the maker's 10101 orders from the coordinator every `interval`.
+    pub async fn sync_tentenone_fills(
+        self,
+        orderbook_url: Url,
+        auth_sk: SecretKey,
+        interval: Duration,
+    ) {
+        let client = OrderbookClient::new(auth_sk);
+        loop {
+            if let Err(e) = self
+                .sync_tentenone_fills_once(&client, &orderbook_url)
+                .await
+            {
+                tracing::error!("Failed to sync 10101 fills: {e:#}");
+            }
+
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    async fn sync_tentenone_fills_once(
+        &self,
+        client: &OrderbookClient,
+        orderbook_url: &Url,
+    ) -> Result<()> {
+        // Fills at the time of the latest known fill are downloaded again, as more than one fill
+        // may have happened at that time. Already known fills are ignored when inserting.
+        let from = self.latest_timestamp(Book::TenTenOne).await?;
+        // Fixing the end of the range keeps the pages stable while new fills come in.
+        let to = OffsetDateTime::now_utc();
+
+        let mut fills = vec![];
+        let mut offset = 0;
+        loop {
+            let filter = HistoryFilter {
+                from,
+                to: Some(to),
+                limit: Some(HistoryFilter::MAX_LIMIT),
+                offset: Some(offset),
+                ..HistoryFilter::default()
+            };
+
+            let page = client.get_fills(orderbook_url, &filter).await?;
+            let is_last_page = page.len() < HistoryFilter::MAX_LIMIT as usize;
+            fills.extend(page.iter().map(Fill::from));
+
+            if is_last_page {
+                break;
+            }
+            offset += HistoryFilter::MAX_LIMIT;
+        }
+
+        fills.sort_by_key(|fill| fill.timestamp);
+        let inserted = self.record(fills).await?;
+
+        if inserted > 0 {
+            tracing::info!(inserted, "Recorded new 10101 fills");
+        }
+
+        Ok(())
+    }
+
+    /// Records the fills, which have to be ordered by time, returning the number of fills which
+    /// had not been recorded yet.
+    async fn record(&self, fills: Vec<Fill>) -> Result<usize> {
+        let tracker = self.clone();
+        spawn_blocking(move || tracker.insert(&fills)).await?
+    }
+
+    /// Inserts the fills into the database, applying the inserted ones to the journal.
+    ///
+    /// The journal is locked while inserting, so that it cannot be loaded from the database in the
+    /// meantime and miss or duplicate any of the fills.
+    fn insert(&self, fills: &[Fill]) -> Result<usize> {
+        let mut conn = self.pool.get()?;
+        let mut journal = self.journal.lock().expect("journal to be available");
+
+        let mut inserted = 0;
+        for fill in fills {
+            if !db::fills::insert(&mut conn, fill)? {
+                continue;
+            }
+            inserted += 1;
+
+            if let Some(loaded) = journal.as_mut() {
+                if !loaded.append(fill) {
+                    tracing::debug!(?fill, "Reloading journal after recording an older fill");
+                    *journal = None;
+                }
+            }
+        }
+
+        Ok(inserted)
+    }
+
+    async fn latest_timestamp(&self, book: Book) -> Result<Option<OffsetDateTime>> {
+        let pool = self.pool.clone();
+        spawn_blocking(move || -> Result<_> {
+            let mut conn = pool.get()?;
+            let timestamp = db::fills::get_latest_timestamp(&mut conn, book)?;
+
+            Ok(timestamp)
+        })
+        .await?
+    }
+
+    /// The PnL of the maker's books, with the realized PnL, fees and funding accumulated since
+    /// `from`, or since the first fill if not set.
+    pub fn report(&self, from: Option<OffsetDateTime>) -> Result<Report> {
+        let marks = self.marks.lock().expect("marks to be available").clone();
+
+        self.with_journal(|journal| journal.report(from, &marks, OffsetDateTime::now_utc()))
+    }
+
+    /// The PnL of the maker's books per UTC day between `from` and `to`.
+    pub fn daily_report(&self, from: OffsetDateTime, to: OffsetDateTime) -> Result<Vec<Daily>> {
+        self.with_journal(|journal| journal.daily_report(from, to))
+    }
+
+    /// Calls `f` with the journal, loading it from the database if necessary.
+    fn with_journal<T>(&self, f: impl FnOnce(&Journal) -> T) -> Result<T> {
+        let mut guard = self.journal.lock().expect("journal to be available");
+        let journal = match guard.take() {
+            Some(journal) => journal,
+            None => {
+                let mut conn = self.pool.get()?;
+                Journal::new(&db::fills::get(&mut conn, None)?)
+            }
+        };
+
+        let result = f(&journal);
+        *guard = Some(journal);
+
+        Ok(result)
+    }
+}
+
+/// Average cost accounting of the positions of the maker's books.
+#[derive(Debug, Default)]
+pub struct Ledger {
+    positions: HashMap<(Book, ContractSymbol), OpenPosition>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct OpenPosition {
+    /// The number of contracts, with the sign representing the direction: positive long;
+    /// negative short.
+    pub contracts: Decimal,
+    /// Zero without contracts.
+    pub average_entry_price: Decimal,
+}
+
+impl Ledger {
+    /// Applies the fill to the position of its book, returning the PnL realized by it in sats.
+    pub fn apply(&mut self, fill: &Fill) -> i64 {
+        if fill.kind == FillKind::Funding || fill.contracts.is_zero() {
+            return 0;
+        }
+
+        if fill.price <= Decimal::ZERO {
+            tracing::warn!(?fill, "Ignoring fill without price");
+            return 0;
+        }
+
+        self.positions
+            .entry((fill.book, fill.contract_symbol))
+            .or_default()
+            .apply(fill.contract_symbol, fill.contracts, fill.price)
+    }
+
+    pub fn position(&self, book: Book, contract_symbol: ContractSymbol) -> OpenPosition {
+        self.positions
+            .get(&(book, contract_symbol))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+impl OpenPosition {
+    fn apply(
+        &mut self,
+        contract_symbol: ContractSymbol,
+        contracts: Decimal,
+        price: Decimal,
+    ) -> i64 {
+        let kind = contract_symbol.metadata().kind;
+
+        // Opening or increasing the position.
+        if self.contracts.is_zero()
+            || self.contracts.is_sign_positive() == contracts.is_sign_positive()
+        {
+            self.average_entry_price = average_price(
+                kind,
+                self.contracts.abs(),
+                self.average_entry_price,
+                contracts.abs(),
+                price,
+            );
+            self.contracts += contracts;
+
+            return 0;
+        }
+
+        // Reducing, closing or flipping the position.
+        let closed = contracts.abs().min(self.contracts.abs());
+        let closed = if self.contracts.is_sign_positive() {
+            closed
+        } else {
+            -closed
+        };
+        let realized = pnl_sats(kind, closed, self.average_entry_price, price);
+
+        self.contracts += contracts;
+        if self.contracts.is_zero() {
+            self.contracts = Decimal::ZERO;
+            self.average_entry_price = Decimal::ZERO;
+        } else if self.contracts.is_sign_positive() != closed.is_sign_positive() {
+            self.average_entry_price = price;
+        }
+
+        to_sats(realized)
+    }
+
+    /// The PnL in sats if the position was closed at `mark_price`.
+    pub fn unrealized_pnl_sats(&self, contract_symbol: ContractSymbol, mark_price: Decimal) -> i64 {
+        if self.contracts.is_zero() || mark_price <= Decimal::ZERO {
+            return 0;
+        }
+
+        let kind = contract_symbol.metadata().kind;
+        to_sats(pnl_sats(
+            kind,
+            self.contracts,
+            self.average_entry_price,
+            mark_price,
+        ))
+    }
+}
+
+/// The PnL of the maker's books.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    /// The realized PnL, fees and funding are accumulated since then, or since the first fill if
+    /// not set.
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub from: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+    pub books: Vec<BookReport>,
+    pub exposure: Vec<Exposure>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct BookReport {
+    pub book: Book,
+    pub contract_symbol: ContractSymbol,
+    pub contracts: Decimal,
+    pub average_entry_price: Option<Decimal>,
+    pub mark_price: Option<Decimal>,
+    pub realized_pnl_sats: i64,
+    /// Unknown until the hedging venue has quoted the contract symbol.
+    pub unrealized_pnl_sats: Option<i64>,
+    pub fees_sats: i64,
+    pub funding_sats: i64,
+}
+
+/// The contracts held per book and in total.
+///
+/// The maker hedges by taking the opposite position on the hedging venue, so a net exposure of
+/// zero is fully hedged.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Exposure {
+    pub contract_symbol: ContractSymbol,
+    pub tentenone: Decimal,
+    pub hedge: Decimal,
+    pub net: Decimal,
+}
+
+/// The PnL of a book for one UTC day.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Daily {
+    /// The start of the day.
+    #[serde(with = "time::serde::rfc3339")]
+    pub day: OffsetDateTime,
+    pub book: Book,
+    pub contract_symbol: ContractSymbol,
+    /// The contracts held at the end of the day.
+    pub contracts: Decimal,
+    pub realized_pnl_sats: i64,
+    pub fees_sats: i64,
+    pub funding_sats: i64,
+    pub fills: usize,
+}
+
+/// The PnL accumulated by fills.
+#[derive(Debug, Default, Clone, Copy)]
+struct Accrual {
+    realized_pnl_sats: i64,
+    fees_sats: i64,
+    funding_sats: i64,
+    fills: usize,
+}
+
+impl Accrual {
+    fn add(&mut self, fill: &Fill, realized_pnl_sats: i64) {
+        self.realized_pnl_sats += realized_pnl_sats;
+        match fill.kind {
+            FillKind::Trade => self.fees_sats += fill.fee_sats,
+            FillKind::Funding => self.funding_sats += fill.fee_sats,
+        }
+        self.fills += 1;
+    }
+}
+
+/// The fills of the maker's books applied to the [`Ledger`], so that reports do not have to replay
+/// all fills.
+#[derive(Debug, Default)]
+pub struct Journal {
+    ledger: Ledger,
+    /// Ordered by time.
+    entries: Vec<Entry>,
+    /// The PnL accumulated by all fills.
+    totals: HashMap<(Book, ContractSymbol), Accrual>,
+    /// The time of the latest fill of every position.
+    latest: HashMap<(Book, ContractSymbol), OffsetDateTime>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    fill: Fill,
+    realized_pnl_sats: i64,
+    /// The contracts of the position after the fill.
+    contracts: Decimal,
+}
+
+impl Journal {
+    /// Applies the fills, which have to be ordered by time.
+    pub fn new(fills: &[Fill]) -> Self {
+        let mut journal = Self::default();
+        for fill in fills {
+            let applied = journal.append(fill);
+            debug_assert!(applied, "fills to be ordered by time");
+        }
+
+        journal
+    }
+
+    /// Applies the fill, unless it is older than the latest fill of its position, returning
+    /// whether it was applied.
+    ///
+    /// The realized PnL depends on the order in which the fills of a position are applied, so the
+    /// journal has to be rebuilt from all fills to include an older one.
+    #[must_use]
+    pub fn append(&mut self, fill: &Fill) -> bool {
+        let key = (fill.book, fill.contract_symbol);
+        if self
+            .latest
+            .get(&key)
+            .map_or(false, |latest| fill.timestamp < *latest)
+        {
+            return false;
+        }
+        self.latest.insert(key, fill.timestamp);
+
+        let realized_pnl_sats = self.ledger.apply(fill);
+        self.totals
+            .entry(key)
+            .or_default()
+            .add(fill, realized_pnl_sats);
+
+        // The fills of the other book may be newer, e.g. if the 10101 fills are synced after they
+        // have been hedged.
+        let i = self
+            .entries
+            .partition_point(|entry| entry.fill.timestamp <= fill.timestamp);
+        self.entries.insert(
+            i,
+            Entry {
+                fill: *fill,
+                realized_pnl_sats,
+                contracts: self
+                    .ledger
+                    .position(fill.book, fill.contract_symbol)
+                    .contracts,
+            },
+        );
+
+        true
+    }
+
+    /// Builds the [`Report`] with the realized PnL, fees and funding accumulated since `from`, or
+    /// since the first fill if not set.
+    pub fn report(
+        &self,
+        from: Option<OffsetDateTime>,
+        marks: &HashMap<ContractSymbol, Decimal>,
+        timestamp: OffsetDateTime,
+    ) -> Report {
+        let accruals = match from {
+            Some(from) => {
+                let first = self
+                    .entries
+                    .partition_point(|entry| entry.fill.timestamp < from);
+
+                let mut accruals = HashMap::<(Book, ContractSymbol), Accrual>::new();
+                for entry in &self.entries[first..] {
+                    accruals
+                        .entry((entry.fill.book, entry.fill.contract_symbol))
+                        .or_default()
+                        .add(&entry.fill, entry.realized_pnl_sats);
+                }
+
+                accruals
+            }
+            None => self.totals.clone(),
+        };
+
+        let mut books = vec![];
+        for book in Book::ALL {
+            for contract_symbol in ContractSymbol::ALL {
+                let position = self.ledger.position(book, contract_symbol);
+                let accrual = accruals
+                    .get(&(book, contract_symbol))
+                    .copied()
+                    .unwrap_or_default();
+                let mark_price = marks.get(&contract_symbol).copied();
+
+                books.push(BookReport {
+                    book,
+                    contract_symbol,
+                    contracts: position.contracts,
+                    average_entry_price: (!position.contracts.is_zero())
+                        .then_some(position.average_entry_price),
+                    mark_price,
+                    realized_pnl_sats: accrual.realized_pnl_sats,
+                    unrealized_pnl_sats: mark_price.map(|mark_price| {
+                        position.unrealized_pnl_sats(contract_symbol, mark_price)
+                    }),
+                    fees_sats: accrual.fees_sats,
+                    funding_sats: accrual.funding_sats,
+                });
+            }
+        }
+
+        let exposure = ContractSymbol::ALL
+            .into_iter()
+            .map(|contract_symbol| {
+                let tentenone = self
+                    .ledger
+                    .position(Book::TenTenOne, contract_symbol)
+                    .contracts;
+                let hedge = self.ledger.position(Book::Hedge, contract_symbol).contracts;
+
+                Exposure {
+                    contract_symbol,
+                    tentenone,
+                    hedge,
+                    net: tentenone + hedge,
+                }
+            })
+            .collect();
+
+        Report {
+            from,
+            timestamp,
+            books,
+            exposure,
+        }
+    }
+
+    /// Builds the [`Daily`] reports of the fills before `to`, starting with the day of `from`.
+    ///
+    /// Only days with fills are reported.
+    pub fn daily_report(&self, from: OffsetDateTime, to: OffsetDateTime) -> Vec<Daily> {
+        let start = from
+            .to_offset(time::UtcOffset::UTC)
+            .replace_time(time::Time::MIDNIGHT);
+        let first = self
+            .entries
+            .partition_point(|entry| entry.fill.timestamp < start);
+        let last = self
+            .entries
+            .partition_point(|entry| entry.fill.timestamp < to);
+
+        let mut days = Vec::<Daily>::new();
+        let mut index = HashMap::new();
+        for entry in self.entries.get(first..last).unwrap_or_default() {
+            let fill = &entry.fill;
+            let day = fill
+                .timestamp
+                .to_offset(time::UtcOffset::UTC)
+                .replace_time(time::Time::MIDNIGHT);
+            let i = *index
+                .entry((day, fill.book, fill.contract_symbol))
+                .or_insert_with(|| {
+                    days.push(Daily {
+                        day,
+                        book: fill.book,
+                        contract_symbol: fill.contract_symbol,
+                        contracts: Decimal::ZERO,
+                        realized_pnl_sats: 0,
+                        fees_sats: 0,
+                        funding_sats: 0,
+                        fills: 0,
+                    });
+                    days.len() - 1
+                });
+
+            let mut accrual = Accrual::default();
+            accrual.add(fill, entry.realized_pnl_sats);
+
+            let daily = &mut days[i];
+            daily.contracts = entry.contracts;
+            daily.realized_pnl_sats += accrual.realized_pnl_sats;
+            daily.fees_sats += accrual.fees_sats;
+            daily.funding_sats += accrual.funding_sats;
+            daily.fills += accrual.fills;
+        }
+
+        days
+    }
+}
+
+/// The PnL in sats of `contracts` opened at `opening_price` and closed at `closing_price`, with
+/// the sign of `contracts` representing the direction: positive long; negative short.
+fn pnl_sats(
+    kind: ContractKind,
+    contracts: Decimal,
+    opening_price: Decimal,
+    closing_price: Decimal,
+) -> Decimal {
+    let pnl = match kind {
+        ContractKind::Inverse => contracts / opening_price - contracts / closing_price,
+        ContractKind::Quanto { multiplier } => {
+            contracts * (closing_price - opening_price) * multiplier
+        }
+    };
+
+    pnl * SATS_PER_BTC
+}
+
+/// The price at which the combined contracts have the same PnL as the individual contracts.
+fn average_price(
+    kind: ContractKind,
+    contracts: Decimal,
+    price: Decimal,
+    added_contracts: Decimal,
+    added_price: Decimal,
+) -> Decimal {
+    if contracts.is_zero() {
+        return added_price;
+    }
+
+    match kind {
+        // The value of inverse contracts is linear in the inverse of the price.
+        ContractKind::Inverse => {
+            (contracts + added_contracts) / (contracts / price + added_contracts / added_price)
+        }
+        ContractKind::Quanto { .. } => {
+            (contracts * price + added_contracts * added_price) / (contracts + added_contracts)
+        }
+    }
+}
+
+fn to_sats(amount: Decimal) -> i64 {
+    amount.round().to_i64().expect("sats to fit into i64")
+}
+
+impl From<Execution> for Fill {
+    fn from(value: Execution) -> Self {
+        Fill {
+            id: value.execution_id,
+            book: Book::Hedge,
+            contract_symbol: value.contract_symbol,
+            kind: match value.kind {
+                ExecutionKind::Trade => FillKind::Trade,
+                ExecutionKind::Funding => FillKind::Funding,
+            },
+            contracts: Decimal::from(value.contracts),
+            price: value.price,
+            fee_sats: value.fee_sats,
+            timestamp: value.timestamp,
+        }
+    }
+}
+
+/// The maker does not pay fees for its 10101 orders, as only the taker is charged.
+impl From<&commons::Fill> for Fill {
+    fn from(value: &commons::Fill) -> Self {
+        let contracts = match value.direction {
+            Direction::Long => value.quantity,
+            Direction::Short => -value.quantity,
+        };
+
+        Fill {
+            id: value.match_id,
+            book: Book::TenTenOne,
+            contract_symbol: value.contract_symbol,
+            kind: FillKind::Trade,
+            contracts,
+            price: value.price,
+            fee_sats: 0,
+            timestamp: value.timestamp,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn fill(
+        book: Book,
+        contract_symbol: ContractSymbol,
+        contracts: Decimal,
+        price: Decimal,
+        timestamp: OffsetDateTime,
+    ) -> Fill {
+        Fill {
+            id: Uuid::new_v4(),
+            book,
+            contract_symbol,
+            kind: FillKind::Trade,
+            contracts,
+            price,
+            fee_sats: 0,
+            timestamp,
+        }
+    }
+
+    fn funding(fee_sats: i64, timestamp: OffsetDateTime) -> Fill {
+        Fill {
+            kind: FillKind::Funding,
+            fee_sats,
+            ..fill(
+                Book::Hedge,
+                ContractSymbol::BtcUsd,
+                Decimal::ZERO,
+                dec!(40_000),
+                timestamp,
+            )
+        }
+    }
+
+    #[test]
+    fn closing_inverse_long_realizes_pnl() {
+        let mut ledger = Ledger::default();
+        let t = OffsetDateTime::UNIX_EPOCH;
+
+        let opened = ledger.apply(&fill(
+            Book::TenTenOne,
+            ContractSymbol::BtcUsd,
+            dec!(100),
+            dec!(40_000),
+            t,
+        ));
+        let closed = ledger.apply(&fill(
+            Book::TenTenOne,
+            ContractSymbol::BtcUsd,
+            dec!(-100),
+            dec!(44_000),
+            t,
+        ));
+
+        assert_eq!(opened, 0);
+        // 100 / 40_000 - 100 / 44_000 BTC
+        assert_eq!(closed, 22_727);
+        assert_eq!(
+            ledger.position(Book::TenTenOne, ContractSymbol::BtcUsd),
+            OpenPosition::default()
+        );
+    }
+
+    #[test]
+    fn inverse_entry_price_is_averaged_harmonically() {
+        let mut ledger = Ledger::default();
+        let t = OffsetDateTime::UNIX_EPOCH;
+
+        ledger.apply(&fill(
+            Book::Hedge,
+            ContractSymbol::BtcUsd,
+            dec!(-100),
+            dec!(50_000),
+            t,
+        ));
+        ledger.apply(&fill(
+            Book::Hedge,
+            ContractSymbol::BtcUsd,
+            dec!(-100),
+            dec!(200_000),
+            t,
+        ));
+
+        let position = ledger.position(Book::Hedge, ContractSymbol::BtcUsd);
+        assert_eq!(position.contracts, dec!(-200));
+        // 200 / (100 / 50_000 + 100 / 200_000)
+        assert_eq!(position.average_entry_price, dec!(80_000));
+    }
+
+    #[test]
+    fn flipping_position_opens_remainder_at_fill_price() {
+        let mut ledger = Ledger::default();
+        let t = OffsetDateTime::UNIX_EPOCH;
+
+        ledger.apply(&fill(
+            Book::TenTenOne,
+            ContractSymbol::EthUsd,
+            dec!(10),
+            dec!(2_000),
+            t,
+        ));
+        let realized = ledger.apply(&fill(
+            Book::TenTenOne,
+            ContractSymbol::EthUsd,
+            dec!(-30),
+            dec!(2_100),
+            t,
+        ));
+
+        // 10 * (2_100 - 2_000) * 0.000001 BTC
+        assert_eq!(realized, 100_000);
+        let position = ledger.position(Book::TenTenOne, ContractSymbol::EthUsd);
+        assert_eq!(position.contracts, dec!(-20));
+        assert_eq!(position.average_entry_price, dec!(2_100));
+        // 20 short contracts lose 20 * 50 * 0.000001 BTC if the price rises by 50.
+        assert_eq!(
+            position.unrealized_pnl_sats(ContractSymbol::EthUsd, dec!(2_150)),
+            -100_000
+        );
+    }
+
+    #[test]
+    fn report_accumulates_fees_and_funding_since_from() {
+        let fills = [
+            Fill {
+                fee_sats: 25,
+                ..fill(
+                    Book::Hedge,
+                    ContractSymbol::BtcUsd,
+                    dec!(-100),
+                    dec!(40_000),
+                    datetime!(2024-01-01 10:00 UTC),
+                )
+            },
+            funding(3, datetime!(2024-01-01 12:00 UTC)),
+            funding(-1, datetime!(2024-01-02 04:00 UTC)),
+            fill(
+                Book::TenTenOne,
+                ContractSymbol::BtcUsd,
+                dec!(100),
+                dec!(40_000),
+                datetime!(2024-01-02 05:00 UTC),
+            ),
+        ];
+        let marks = HashMap::from([(ContractSymbol::BtcUsd, dec!(50_000))]);
+
+        let report = Journal::new(&fills).report(
+            Some(datetime!(2024-01-02 00:00 UTC)),
+            &marks,
+            datetime!(2024-01-03 00:00 UTC),
+        );
+
+        let hedge = report
+            .books
+            .iter()
+            .find(|b| b.book == Book::Hedge && b.contract_symbol == ContractSymbol::BtcUsd)
+            .unwrap();
+        assert_eq!(hedge.contracts, dec!(-100));
+        assert_eq!(hedge.fees_sats, 0);
+        assert_eq!(hedge.funding_sats, -1);
+        // -(100 / 40_000 - 100 / 50_000) BTC
+        assert_eq!(hedge.unrealized_pnl_sats, Some(-50_000));
+
+        let tentenone = report
+            .books
+            .iter()
+            .find(|b| b.book == Book::TenTenOne && b.contract_symbol == ContractSymbol::BtcUsd)
+            .unwrap();
+        assert_eq!(tentenone.unrealized_pnl_sats, Some(50_000));
+
+        let eth = report
+            .books
+            .iter()
+            .find(|b| b.book == Book::Hedge && b.contract_symbol == ContractSymbol::EthUsd)
+            .unwrap();
+        assert_eq!(eth.average_entry_price, None);
+        assert_eq!(eth.unrealized_pnl_sats, None);
+
+        assert!(report.exposure.contains(&Exposure {
+            contract_symbol: ContractSymbol::BtcUsd,
+            tentenone: dec!(100),
+            hedge: dec!(-100),
+            net: dec!(0),
+        }));
+    }
+
+    #[test]
+    fn daily_report_groups_fills_by_utc_day() {
+        let fills = [
+            fill(
+                Book::TenTenOne,
+                ContractSymbol::BtcUsd,
+                dec!(100),
+                dec!(40_000),
+                datetime!(2024-01-01 23:00 -02:00),
+            ),
+            fill(
+                Book::TenTenOne,
+                ContractSymbol::BtcUsd,
+                dec!(-50),
+                dec!(44_000),
+                datetime!(2024-01-02 12:00 UTC),
+            ),
+            funding(3, datetime!(2024-01-02 20:00 UTC)),
+        ];
+
+        let days = Journal::new(&fills).daily_report(
+            datetime!(2024-01-02 08:00 UTC),
+            datetime!(2024-01-03 00:00 UTC),
+        );
+
+        assert_eq!(
+            days,
+            vec![
+                Daily {
+                    day: datetime!(2024-01-02 00:00 UTC),
+                    book: Book::TenTenOne,
+                    contract_symbol: ContractSymbol::BtcUsd,
+                    contracts: dec!(50),
+                    realized_pnl_sats: 11_364,
+                    fees_sats: 0,
+                    funding_sats: 0,
+                    fills: 2,
+                },
+                Daily {
+                    day: datetime!(2024-01-02 00:00 UTC),
+                    book: Book::Hedge,
+                    contract_symbol: ContractSymbol::BtcUsd,
+                    contracts: dec!(0),
+                    realized_pnl_sats: 0,
+                    fees_sats: 0,
+                    funding_sats: 3,
+                    fills: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn journal_only_appends_fills_in_order_of_their_position() {
+        let mut journal = Journal::new(&[fill(
+            Book::Hedge,
+            ContractSymbol::BtcUsd,
+            dec!(-100),
+            dec!(40_000),
+            datetime!(2024-01-01 12:00 UTC),
+        )]);
+
+        assert!(journal.append(&fill(
+            Book::TenTenOne,
+            ContractSymbol::BtcUsd,
+            dec!(100),
+            dec!(40_000),
+            datetime!(2024-01-01 10:00 UTC),
+        )));
+        assert!(!journal.append(&fill(
+            Book::Hedge,
+            ContractSymbol::BtcUsd,
+            dec!(100),
+            dec!(44_000),
+            datetime!(2024-01-01 11:00 UTC),
+        )));
+
+        let days = journal.daily_report(
+            datetime!(2024-01-01 00:00 UTC),
+            datetime!(2024-01-02 00:00 UTC),
+        );
+        assert_eq!(
+            days.iter()
+                .map(|daily| (daily.book, daily.contracts))
+                .collect::<Vec<_>>(),
+            vec![(Book::TenTenOne, dec!(100)), (Book::Hedge, dec!(-100))]
+        );
+
+        let report = journal.report(None, &HashMap::new(), datetime!(2024-01-02 00:00 UTC));
+        assert!(report.exposure.contains(&Exposure {
+            contract_symbol: ContractSymbol::BtcUsd,
+            tentenone: dec!(100),
+            hedge: dec!(-100),
+            net: dec!(0),
+        }));
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use hedging::derive_hedging_action;
+use hedging::Hedger;
+use hedging::Quote;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use rust_decimal::RoundingStrategy;
@@ -10,17 +11,19 @@ use std::collections::HashSet;
 use std::fmt::Display;
 use std::hash::Hash;
 use std::ops::Add;
-use std::time::Duration;
+use std::time::Instant;
 use uuid::Uuid;
 use xtra::Mailbox;
 
-mod bitmex;
-mod hedging;
+pub mod hedging;
+
+mod hedge;
 mod tentenone;
 
 pub struct Manager {
     position: Position,
-    bitmex_http_client: bitmex_client::client::Client,
+    quotes: HashMap<ContractSymbol, Quote>,
+    hedger: Hedger,
 }
 
 #[async_trait]
@@ -30,11 +33,12 @@ impl xtra::Actor for Manager {
     async fn started(&mut self, mailbox: &mut Mailbox<Self>) -> Result<(), Self::Stop> {
         tokio::spawn({
             let mailbox = mailbox.clone();
+            let interval = self.hedger.interval();
             async move {
                 loop {
-                    // We sleep first to allow the 10101 and BitMEX positions to be up-to-date
+                    // We sleep first to allow the 10101 and hedge positions to be up-to-date
                     // before we start hedging.
-                    tokio::time::sleep(Duration::from_secs(60)).await;
+                    tokio::time::sleep(interval).await;
 
                     let _ = mailbox.address().send(Hedge).await;
                 }
@@ -48,16 +52,17 @@ impl xtra::Actor for Manager {
 }
 
 impl Manager {
-    pub fn new(bitmex_http_client: bitmex_client::client::Client) -> Self {
+    pub fn new(hedger: Hedger) -> Self {
         Self {
             position: Position::new(),
-            bitmex_http_client,
+            quotes: HashMap::new(),
+            hedger,
         }
     }
 
-    /// Adjust hedging on _BitMEX_ based on the balance between the [`bitmex::Position`] and the
-    /// [`tentenone::Position`].
-    async fn hedge(&self, contract_symbol: &ContractSymbol) {
+    /// Adjust hedging on the hedging venue based on the balance between the [`hedge::Position`]
+    /// and the [`tentenone::Position`].
+    async fn hedge(&mut self, contract_symbol: &ContractSymbol) {
         let tentenone = self.position.get_tentenone(contract_symbol);
 
         // For the purposes of hedging we have to round to the number of 10101 contracts to the
@@ -67,44 +72,35 @@ impl Manager {
             .to_i32()
             .expect("10101 position to fit in i32");
 
-        let bitmex = self.position.get_bitmex(contract_symbol);
+        // The position streamed by the venue may be stale, e.g. after a reconnect, hence we ask
+        // the venue before every hedging round.
+        match self
+            .hedger
+            .venue()
+            .position((*contract_symbol).into())
+            .await
+        {
+            Ok(contracts) => self.position.update_hedge(*contract_symbol, contracts),
+            Err(e) => tracing::warn!(
+                ?contract_symbol,
+                "Failed to get the position on the hedging venue, using the last known one: {e:#}"
+            ),
+        }
 
-        let action = derive_hedging_action(tentenone, bitmex);
+        let hedge = self.position.get_hedge(contract_symbol);
+        let quote = self.quotes.get(contract_symbol).copied();
 
-        if let Err(e) = self.create_bitmex_order(&action, contract_symbol).await {
+        if let Err(e) = self
+            .hedger
+            .hedge(*contract_symbol, tentenone, hedge, quote, Instant::now())
+            .await
+        {
             tracing::error!(
-                ?action,
-                "Failed to create order on BitMEX based on required hedging action: {e:#}"
+                ?contract_symbol,
+                "Failed to hedge the 10101 position on the hedging venue: {e:#}"
             )
         }
     }
-
-    async fn create_bitmex_order(
-        &self,
-        action: &hedging::Action,
-        contract_symbol: &ContractSymbol,
-    ) -> Result<()> {
-        let (contracts, side) = match action.contracts() {
-            0 => return Ok(()),
-            n @ 1..=i32::MAX => (n.abs(), bitmex_client::models::Side::Buy),
-            n @ i32::MIN..=-1 => (n.abs(), bitmex_client::models::Side::Sell),
-        };
-
-        tracing::info!(
-            ?action,
-            "Creating BitMEX order based on required hedging action"
-        );
-
-        let contract_symbol = match contract_symbol {
-            ContractSymbol::BtcUsd => bitmex_client::models::ContractSymbol::XbtUsd,
-        };
-
-        self.bitmex_http_client
-            .create_order(contract_symbol, contracts, side, None)
-            .await?;
-
-        Ok(())
-    }
 }
 
 pub struct PositionUpdateTenTenOne(pub Vec<OrderTenTenOne>);
@@ -129,11 +125,17 @@ impl OrderTenTenOne {
     }
 }
 
-pub struct PositionUpdateBitmex {
+pub struct PositionUpdateVenue {
     pub contract_symbol: ContractSymbol,
     pub contracts: i32,
 }
 
+pub struct QuoteUpdateVenue {
+    pub contract_symbol: ContractSymbol,
+    pub bid: Decimal,
+    pub ask: Decimal,
+}
+
 pub struct GetPosition;
 
 pub struct GetPositionResponse {
@@ -164,16 +166,35 @@ impl xtra::Handler<PositionUpdateTenTenOne> for Manager {
 }
 
 #[async_trait]
-impl xtra::Handler<PositionUpdateBitmex> for Manager {
+impl xtra::Handler<PositionUpdateVenue> for Manager {
     type Return = ();
 
     async fn handle(
         &mut self,
-        update: PositionUpdateBitmex,
+        update: PositionUpdateVenue,
         _: &mut xtra::Context<Self>,
     ) -> Self::Return {
         self.position
-            .update_bitmex(update.contract_symbol, update.contracts);
+            .update_hedge(update.contract_symbol, update.contracts);
+    }
+}
+
+#[async_trait]
+impl xtra::Handler<QuoteUpdateVenue> for Manager {
+    type Return = ();
+
+    async fn handle(
+        &mut self,
+        update: QuoteUpdateVenue,
+        _: &mut xtra::Context<Self>,
+    ) -> Self::Return {
+        self.quotes.insert(
+            update.contract_symbol,
+            Quote {
+                bid: update.bid,
+                ask: update.ask,
+            },
+        );
     }
 }
 
@@ -208,14 +229,14 @@ impl xtra::Handler<Hedge> for Manager {
 #[derive(Debug)]
 struct Position {
     tentenone: HashSet<tentenone::Position>,
-    bitmex: HashSet<bitmex::Position>,
+    hedge: HashSet<hedge::Position>,
 }
 
 impl Position {
     pub fn new() -> Self {
         Self {
             tentenone: HashSet::from_iter([tentenone::Position::new(ContractSymbol::BtcUsd)]),
-            bitmex: HashSet::from_iter([bitmex::Position::new(ContractSymbol::BtcUsd)]),
+            hedge: HashSet::from_iter([hedge::Position::new(ContractSymbol::BtcUsd)]),
         }
     }
 
@@ -229,23 +250,23 @@ impl Position {
             .tentenone
             .get(&contract_symbol)
             .cloned()
-            .unwrap_or(tentenone::Position::new(ContractSymbol::BtcUsd));
+            .unwrap_or(tentenone::Position::new(contract_symbol));
 
         position.update(order_id, contracts);
 
         self.tentenone.replace(position);
     }
 
-    fn update_bitmex(&mut self, contract_symbol: ContractSymbol, contracts: i32) {
+    fn update_hedge(&mut self, contract_symbol: ContractSymbol, contracts: i32) {
         let mut position = self
-            .bitmex
+            .hedge
             .get(&contract_symbol)
             .cloned()
-            .unwrap_or(bitmex::Position::new(ContractSymbol::BtcUsd));
+            .unwrap_or(hedge::Position::new(contract_symbol));
 
         position.update(contracts);
 
-        self.bitmex.replace(position);
+        self.hedge.replace(position);
     }
 
     fn get_tentenone(&self, contract_symbol: &ContractSymbol) -> Decimal {
@@ -259,8 +280,8 @@ impl Position {
         }
     }
 
-    fn get_bitmex(&self, contract_symbol: &ContractSymbol) -> i32 {
-        match self.bitmex.get(contract_symbol) {
+    fn get_hedge(&self, contract_symbol: &ContractSymbol) -> i32 {
+        match self.hedge.get(contract_symbol) {
             Some(position) => position.contracts(),
             None => 0,
         }
@@ -270,12 +291,23 @@ impl Position {
 #[derive(Clone, Copy, Hash, PartialEq, Eq, Debug, Serialize)]
 pub enum ContractSymbol {
     BtcUsd,
+    EthUsd,
 }
 
 impl From<trade::ContractSymbol> for ContractSymbol {
     fn from(value: trade::ContractSymbol) -> Self {
         match value {
             trade::ContractSymbol::BtcUsd => Self::BtcUsd,
+            trade::ContractSymbol::EthUsd => Self::EthUsd,
+        }
+    }
+}
+
+impl From<ContractSymbol> for trade::ContractSymbol {
+    fn from(value: ContractSymbol) -> Self {
+        match value {
+            ContractSymbol::BtcUsd => Self::BtcUsd,
+            ContractSymbol::EthUsd => Self::EthUsd,
         }
     }
 }
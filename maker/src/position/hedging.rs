@@ -1,60 +1,238 @@
+use crate::position::ContractSymbol;
+use crate::venue::HedgingVenue;
+use crate::venue::OrderRequest;
+use crate::venue::Side;
+use crate::venue::TimeInForce;
+use anyhow::Context;
+use anyhow::Result;
+use rust_decimal::Decimal;
+use rust_decimal::RoundingStrategy;
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::num::NonZeroU32;
-use std::ops::Add;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 
-#[derive(Debug, PartialEq)]
-pub enum Action {
-    StandPat,
-    Buy { hundreds_of_contracts: NonZeroU32 },
-    Sell { hundreds_of_contracts: NonZeroU32 },
+pub use strategy::Band;
+pub use strategy::Delta;
+pub use strategy::Twap;
+
+mod strategy;
+
+/// Hedging orders are placed in multiples of this number of contracts, as BitMEX does not accept
+/// anything else.
+pub const LOT_SIZE: i32 = 100;
+
+/// Decides how to trade on the hedging venue to hedge the 10101 position of the maker.
+///
+/// A strategy is consulted once per hedging round and contract symbol, hence it may keep track of
+/// what it decided in previous rounds.
+pub trait HedgingStrategy: Send {
+    /// The action to take to move the `hedge` towards the `tentenone` position, both in contracts.
+    fn action(&mut self, tentenone: i32, hedge: i32) -> Action;
 }
 
-pub fn derive_hedging_action(tentenone: i32, bitmex: i32) -> Action {
-    fn derive_hedging_action_rec(tentenone: i32, bitmex: i32, action_acc: Action) -> Action {
-        let diff = tentenone - bitmex;
-        let diff_hundreds = diff / 100;
+#[derive(Debug, Clone, Copy)]
+pub struct HedgingConfig {
+    pub strategy: StrategyConfig,
+    pub limits: HedgingLimits,
+    /// The time between two hedging rounds.
+    pub interval: Duration,
+}
 
-        if 1 <= diff_hundreds {
-            derive_hedging_action_rec(
-                tentenone,
-                bitmex + 100,
-                action_acc + Action::buy_one_hundred(),
-            )
-        } else if diff_hundreds <= -1 {
-            derive_hedging_action_rec(
-                tentenone,
-                bitmex - 100,
-                action_acc + Action::sell_one_hundred(),
-            )
-        } else {
-            action_acc
+#[derive(Debug, Clone, Copy)]
+pub enum StrategyConfig {
+    /// See [`Band`].
+    Band { threshold: u32 },
+    /// See [`Delta`].
+    Delta {
+        hedge_ratio: Decimal,
+        clip_size: u32,
+    },
+    /// See [`Twap`].
+    Twap { slices: u32 },
+}
+
+impl StrategyConfig {
+    fn build(&self) -> Box<dyn HedgingStrategy> {
+        match *self {
+            StrategyConfig::Band { threshold } => Box::new(Band::new(threshold)),
+            StrategyConfig::Delta {
+                hedge_ratio,
+                clip_size,
+            } => Box::new(Delta::new(hedge_ratio, clip_size)),
+            StrategyConfig::Twap { slices } => Box::new(Twap::new(slices)),
         }
     }
+}
+
+/// Limits applied to every hedging order, whatever the strategy.
+#[derive(Debug, Clone, Copy)]
+pub struct HedgingLimits {
+    /// The maximum number of contracts of a single hedging order.
+    pub max_order_size: u32,
+    /// The maximum deviation of the execution price from the mid price of the latest quote, in
+    /// basis points.
+    pub max_slippage_bps: u32,
+    /// The minimum time between two hedging orders of the same contract symbol.
+    pub cool_down: Duration,
+}
+
+/// The latest quote of a contract symbol on the hedging venue.
+#[derive(Debug, Clone, Copy)]
+pub struct Quote {
+    pub bid: Decimal,
+    pub ask: Decimal,
+}
+
+impl Quote {
+    fn mid(&self) -> Decimal {
+        (self.bid + self.ask) / Decimal::TWO
+    }
+}
 
-    derive_hedging_action_rec(tentenone, bitmex, Action::StandPat)
+/// Runs the hedging strategy per contract symbol and executes its actions within the
+/// [`HedgingLimits`].
+pub struct Hedger {
+    config: HedgingConfig,
+    strategies: HashMap<ContractSymbol, Box<dyn HedgingStrategy>>,
+    last_order: HashMap<ContractSymbol, Instant>,
+    venue: Arc<dyn HedgingVenue>,
+}
+
+impl Hedger {
+    pub fn new(config: HedgingConfig, venue: Arc<dyn HedgingVenue>) -> Self {
+        Self {
+            config,
+            strategies: HashMap::new(),
+            last_order: HashMap::new(),
+            venue,
+        }
+    }
+
+    pub fn venue(&self) -> &dyn HedgingVenue {
+        self.venue.as_ref()
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.config.interval
+    }
+
+    /// Runs a hedging round for the contract symbol, returning the action which has been executed.
+    pub async fn hedge(
+        &mut self,
+        contract_symbol: ContractSymbol,
+        tentenone: i32,
+        hedge: i32,
+        quote: Option<Quote>,
+        now: Instant,
+    ) -> Result<Action> {
+        let limits = self.config.limits;
+
+        if let Some(last_order) = self.last_order.get(&contract_symbol) {
+            if now.duration_since(*last_order) < limits.cool_down {
+                tracing::debug!(?contract_symbol, "Not hedging during cool-down");
+                return Ok(Action::StandPat);
+            }
+        }
+
+        let strategy_config = self.config.strategy;
+        let strategy = self
+            .strategies
+            .entry(contract_symbol)
+            .or_insert_with(|| strategy_config.build());
+
+        let action = strategy
+            .action(tentenone, hedge)
+            .clip(limits.max_order_size);
+
+        let (contracts, side) = match action.contracts() {
+            0 => return Ok(Action::StandPat),
+            n @ 1..=i32::MAX => (n.unsigned_abs(), Side::Buy),
+            n @ i32::MIN..=-1 => (n.unsigned_abs(), Side::Sell),
+        };
+
+        let quote = quote.context("Cannot hedge without a quote to limit the slippage")?;
+        let limit_price = limit_price(quote, side, limits.max_slippage_bps);
+
+        tracing::info!(
+            ?contract_symbol,
+            ?action,
+            %limit_price,
+            "Creating hedging order based on required hedging action"
+        );
+
+        self.venue
+            .place_order(OrderRequest {
+                contract_symbol: contract_symbol.into(),
+                side,
+                contracts,
+                limit_price,
+                time_in_force: TimeInForce::ImmediateOrCancel,
+            })
+            .await?;
+        self.last_order.insert(contract_symbol, now);
+
+        Ok(action)
+    }
+}
+
+/// The worst price at which we are willing to trade, rounded to a whole number to be a valid
+/// price for every contract symbol.
+fn limit_price(quote: Quote, side: Side, max_slippage_bps: u32) -> Decimal {
+    let slippage = quote.mid() * Decimal::from(max_slippage_bps) / Decimal::from(10_000);
+
+    match side {
+        Side::Sell => {
+            (quote.mid() - slippage).round_dp_with_strategy(0, RoundingStrategy::ToPositiveInfinity)
+        }
+        Side::Buy => {
+            (quote.mid() + slippage).round_dp_with_strategy(0, RoundingStrategy::ToNegativeInfinity)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Action {
+    StandPat,
+    Buy { hundreds_of_contracts: NonZeroU32 },
+    Sell { hundreds_of_contracts: NonZeroU32 },
 }
 
 impl Action {
+    /// Buys or sells the contracts, rounded towards zero to the [`LOT_SIZE`].
+    pub fn from_contracts(contracts: i32) -> Self {
+        Self::new(contracts / LOT_SIZE)
+    }
+
     pub fn contracts(&self) -> i32 {
-        self.to_int() * 100
+        self.to_int() * LOT_SIZE
+    }
+
+    /// Reduces the action to at most `max_contracts`.
+    fn clip(self, max_contracts: u32) -> Self {
+        let max_lots = (max_contracts / LOT_SIZE as u32) as i32;
+        Self::new(self.to_int().clamp(-max_lots, max_lots))
     }
 
+    #[cfg(test)]
     fn buy_hundreds(n: u32) -> Self {
         Self::new(n as i32)
     }
 
+    #[cfg(test)]
     fn sell_hundreds(n: u32) -> Self {
         Self::new(-(n as i32))
     }
 
     fn new(n: i32) -> Self {
-        let hundreds_of_contracts = NonZeroU32::new(n.unsigned_abs()).expect("not zero");
         match n.cmp(&0) {
             Ordering::Greater => Self::Buy {
-                hundreds_of_contracts,
+                hundreds_of_contracts: NonZeroU32::new(n.unsigned_abs()).expect("not zero"),
             },
             Ordering::Less => Self::Sell {
-                hundreds_of_contracts,
+                hundreds_of_contracts: NonZeroU32::new(n.unsigned_abs()).expect("not zero"),
             },
             Ordering::Equal => Self::StandPat,
         }
@@ -71,61 +249,310 @@ impl Action {
             } => -(hundreds_of_contracts.get() as i32),
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::venue::Event;
+    use crate::venue::Execution;
+    use crate::venue::Paper;
+    use anyhow::anyhow;
+    use anyhow::bail;
+    use anyhow::ensure;
+    use async_trait::async_trait;
+    use futures::stream;
+    use futures::stream::BoxStream;
+    use futures::StreamExt;
+    use rust_decimal_macros::dec;
+    use std::sync::Mutex;
+    use time::OffsetDateTime;
+    use uuid::Uuid;
+
+    #[derive(Default)]
+    struct MockVenue {
+        orders: Mutex<Vec<OrderRequest>>,
+        reject_orders: bool,
+    }
+
+    #[async_trait]
+    impl HedgingVenue for MockVenue {
+        async fn place_order(&self, order: OrderRequest) -> Result<Uuid> {
+            ensure!(!self.reject_orders, "Order rejected");
+
+            self.orders.lock().unwrap().push(order);
+            Ok(Uuid::new_v4())
+        }
+
+        async fn cancel_order(&self, _: Uuid) -> Result<()> {
+            bail!("MockVenue does not cancel orders")
+        }
+
+        async fn position(&self, _: trade::ContractSymbol) -> Result<i32> {
+            bail!("MockVenue does not track positions")
+        }
+
+        async fn executions(&self, _: OffsetDateTime) -> Result<Vec<Execution>> {
+            bail!("MockVenue does not track executions")
+        }
+
+        fn stream(&self) -> BoxStream<'static, Result<Event>> {
+            stream::once(async { Err(anyhow!("MockVenue does not stream events")) }).boxed()
+        }
+    }
 
-    fn buy_one_hundred() -> Action {
-        Action::buy_hundreds(1)
+    fn config(strategy: StrategyConfig) -> HedgingConfig {
+        HedgingConfig {
+            strategy,
+            limits: HedgingLimits {
+                max_order_size: 1_000,
+                max_slippage_bps: 50,
+                cool_down: Duration::from_secs(60),
+            },
+            interval: Duration::from_secs(60),
+        }
     }
 
-    fn sell_one_hundred() -> Action {
-        Action::sell_hundreds(1)
+    fn quote() -> Quote {
+        Quote {
+            bid: dec!(39_990),
+            ask: dec!(40_010),
+        }
     }
-}
 
-impl Add for Action {
-    type Output = Action;
+    #[tokio::test]
+    async fn hedging_order_is_limited_by_slippage() {
+        let client = Arc::new(MockVenue::default());
+        let mut hedger = Hedger::new(
+            config(StrategyConfig::Band { threshold: 100 }),
+            client.clone(),
+        );
 
-    fn add(self, rhs: Self) -> Self::Output {
-        let lhs = self.to_int();
-        let rhs = rhs.to_int();
+        hedger
+            .hedge(
+                ContractSymbol::BtcUsd,
+                250,
+                0,
+                Some(quote()),
+                Instant::now(),
+            )
+            .await
+            .unwrap();
+        hedger
+            .hedge(
+                ContractSymbol::EthUsd,
+                -300,
+                0,
+                Some(quote()),
+                Instant::now(),
+            )
+            .await
+            .unwrap();
 
-        Action::new(lhs + rhs)
+        assert_eq!(
+            *client.orders.lock().unwrap(),
+            vec![
+                OrderRequest {
+                    contract_symbol: trade::ContractSymbol::BtcUsd,
+                    side: Side::Buy,
+                    contracts: 200,
+                    limit_price: dec!(40_200),
+                    time_in_force: TimeInForce::ImmediateOrCancel,
+                },
+                OrderRequest {
+                    contract_symbol: trade::ContractSymbol::EthUsd,
+                    side: Side::Sell,
+                    contracts: 300,
+                    limit_price: dec!(39_800),
+                    time_in_force: TimeInForce::ImmediateOrCancel,
+                },
+            ]
+        );
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[tokio::test]
+    async fn hedging_order_is_clipped_to_max_order_size() {
+        let client = Arc::new(MockVenue::default());
+        let mut hedger = Hedger::new(
+            config(StrategyConfig::Band { threshold: 100 }),
+            client.clone(),
+        );
+
+        let action = hedger
+            .hedge(
+                ContractSymbol::BtcUsd,
+                0,
+                5_000,
+                Some(quote()),
+                Instant::now(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(action, Action::sell_hundreds(10));
+        assert_eq!(client.orders.lock().unwrap()[0].contracts, 1_000);
+    }
 
-    #[test]
-    fn derive_hedging_action_singleton() {
-        check(0, 0, Action::StandPat);
+    #[tokio::test]
+    async fn no_hedging_order_during_cool_down() {
+        let client = Arc::new(MockVenue::default());
+        let mut hedger = Hedger::new(
+            config(StrategyConfig::Band { threshold: 100 }),
+            client.clone(),
+        );
+        let now = Instant::now();
 
-        check(99, 0, Action::StandPat);
-        check(100, 0, Action::buy_one_hundred());
-        check(101, 0, Action::buy_one_hundred());
+        hedger
+            .hedge(ContractSymbol::BtcUsd, 100, 0, Some(quote()), now)
+            .await
+            .unwrap();
+        let action = hedger
+            .hedge(
+                ContractSymbol::BtcUsd,
+                200,
+                0,
+                Some(quote()),
+                now + Duration::from_secs(30),
+            )
+            .await
+            .unwrap();
+        assert_eq!(action, Action::StandPat);
 
-        check(-99, 0, Action::StandPat);
-        check(-100, 0, Action::sell_one_hundred());
-        check(-101, 0, Action::sell_one_hundred());
+        let action = hedger
+            .hedge(
+                ContractSymbol::BtcUsd,
+                200,
+                100,
+                Some(quote()),
+                now + Duration::from_secs(60),
+            )
+            .await
+            .unwrap();
+        assert_eq!(action, Action::buy_hundreds(1));
+        assert_eq!(client.orders.lock().unwrap().len(), 2);
+    }
 
-        check(0, -99, Action::StandPat);
-        check(0, -100, Action::buy_one_hundred());
-        check(0, -101, Action::buy_one_hundred());
+    #[tokio::test]
+    async fn failed_hedging_order_does_not_start_cool_down() {
+        let client = Arc::new(MockVenue {
+            reject_orders: true,
+            ..MockVenue::default()
+        });
+        let mut hedger = Hedger::new(
+            config(StrategyConfig::Band { threshold: 100 }),
+            client.clone(),
+        );
+        let now = Instant::now();
 
-        check(0, 99, Action::StandPat);
-        check(0, 100, Action::sell_one_hundred());
-        check(0, 101, Action::sell_one_hundred());
+        let result = hedger
+            .hedge(ContractSymbol::BtcUsd, 100, 0, Some(quote()), now)
+            .await;
+        assert!(result.is_err());
 
-        check(550, 300, Action::buy_hundreds(2));
-        check(-330, 200, Action::sell_hundreds(5));
+        let result = hedger
+            .hedge(
+                ContractSymbol::BtcUsd,
+                100,
+                0,
+                Some(quote()),
+                now + Duration::from_secs(30),
+            )
+            .await;
+        assert!(result.is_err(), "Expected another attempt to hedge");
     }
 
-    // TODO(lucas): Verify that executing a single `Action` and then calling
-    // `derive_hedging_action_singleton` again always leads to `Action::StandPat`.
+    #[tokio::test]
+    async fn no_hedging_order_without_quote() {
+        let client = Arc::new(MockVenue::default());
+        let mut hedger = Hedger::new(
+            config(StrategyConfig::Band { threshold: 100 }),
+            client.clone(),
+        );
+
+        let result = hedger
+            .hedge(ContractSymbol::BtcUsd, 100, 0, None, Instant::now())
+            .await;
+
+        assert!(result.is_err());
+        assert!(client.orders.lock().unwrap().is_empty());
+    }
 
-    #[track_caller]
-    fn check(tentenone: i32, bitmex: i32, expected: Action) {
-        let actual = derive_hedging_action(tentenone, bitmex);
-        assert_eq!(expected, actual);
+    #[tokio::test]
+    async fn twap_is_executed_over_several_rounds() {
+        let client = Arc::new(MockVenue::default());
+        let mut hedger = Hedger::new(config(StrategyConfig::Twap { slices: 3 }), client.clone());
+        let now = Instant::now();
+
+        let mut hedge = 0;
+        for round in 0..4 {
+            let action = hedger
+                .hedge(
+                    ContractSymbol::BtcUsd,
+                    900,
+                    hedge,
+                    Some(quote()),
+                    now + Duration::from_secs(60 * round),
+                )
+                .await
+                .unwrap();
+            hedge += action.contracts();
+        }
+
+        let contracts = client
+            .orders
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|order| order.contracts)
+            .collect::<Vec<_>>();
+        assert_eq!(contracts, vec![300, 300, 300]);
+        assert_eq!(hedge, 900);
+    }
+
+    #[tokio::test]
+    async fn hedging_orders_move_the_paper_position() {
+        let paper = Arc::new(Paper::new());
+        let mut hedger = Hedger::new(
+            config(StrategyConfig::Band { threshold: 100 }),
+            paper.clone(),
+        );
+        let now = Instant::now();
+
+        paper.set_quote(venue_quote(dec!(39_990), dec!(40_010)));
+        hedger
+            .hedge(ContractSymbol::BtcUsd, 500, 0, Some(quote()), now)
+            .await
+            .unwrap();
+
+        let position = paper.position(trade::ContractSymbol::BtcUsd).await.unwrap();
+        assert_eq!(position, 500);
+
+        // The spread has widened beyond the slippage limit, hence the order is not filled.
+        paper.set_quote(venue_quote(dec!(39_000), dec!(41_000)));
+        hedger
+            .hedge(
+                ContractSymbol::BtcUsd,
+                0,
+                position,
+                Some(Quote {
+                    bid: dec!(39_000),
+                    ask: dec!(41_000),
+                }),
+                now + Duration::from_secs(60),
+            )
+            .await
+            .unwrap();
+
+        let position = paper.position(trade::ContractSymbol::BtcUsd).await.unwrap();
+        assert_eq!(position, 500);
+    }
+
+    fn venue_quote(bid: Decimal, ask: Decimal) -> crate::venue::Quote {
+        crate::venue::Quote {
+            contract_symbol: trade::ContractSymbol::BtcUsd,
+            bid,
+            ask,
+            timestamp: OffsetDateTime::now_utc(),
+        }
     }
 }
@@ -0,0 +1,207 @@
+use crate::position::hedging::Action;
+use crate::position::hedging::HedgingStrategy;
+use crate::position::hedging::LOT_SIZE;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal::RoundingStrategy;
+
+/// Hedges the whole difference between the 10101 position and the hedge once it reaches the
+/// `threshold`, leaving smaller differences unhedged.
+pub struct Band {
+    threshold: i32,
+}
+
+impl Band {
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            threshold: threshold.max(LOT_SIZE as u32) as i32,
+        }
+    }
+}
+
+impl HedgingStrategy for Band {
+    fn action(&mut self, tentenone: i32, hedge: i32) -> Action {
+        let diff = tentenone - hedge;
+        if diff.abs() < self.threshold {
+            return Action::StandPat;
+        }
+
+        Action::from_contracts(diff)
+    }
+}
+
+/// Hedges `hedge_ratio` of the 10101 position, trading at most `clip_size` contracts per hedging
+/// round.
+///
+/// With a ratio below one the maker keeps part of the exposure of its 10101 position, and the clip
+/// size spreads large adjustments over several rounds.
+pub struct Delta {
+    hedge_ratio: Decimal,
+    clip_size: i32,
+}
+
+impl Delta {
+    pub fn new(hedge_ratio: Decimal, clip_size: u32) -> Self {
+        Self {
+            hedge_ratio,
+            clip_size: clip_size.max(LOT_SIZE as u32) as i32,
+        }
+    }
+}
+
+impl HedgingStrategy for Delta {
+    fn action(&mut self, tentenone: i32, hedge: i32) -> Action {
+        let target = (Decimal::from(tentenone) * self.hedge_ratio)
+            .round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero);
+
+        // Clipping before converting back keeps the difference within `i32` whatever the ratio.
+        let clip_size = Decimal::from(self.clip_size);
+        let diff = (target - Decimal::from(hedge))
+            .clamp(-clip_size, clip_size)
+            .to_i32()
+            .expect("clipped difference to fit in i32");
+
+        Action::from_contracts(diff)
+    }
+}
+
+/// Spreads the hedging of a change in the 10101 position evenly over `slices` hedging rounds.
+///
+/// If the 10101 position changes again before all slices have been executed, the remaining
+/// difference is spread over a new set of slices.
+pub struct Twap {
+    slices: u32,
+    schedule: Option<Schedule>,
+}
+
+struct Schedule {
+    /// The 10101 position the schedule is hedging.
+    tentenone: i32,
+    slices_left: u32,
+}
+
+impl Twap {
+    pub fn new(slices: u32) -> Self {
+        Self {
+            slices: slices.max(1),
+            schedule: None,
+        }
+    }
+}
+
+impl HedgingStrategy for Twap {
+    fn action(&mut self, tentenone: i32, hedge: i32) -> Action {
+        let diff = tentenone - hedge;
+        if diff.abs() < LOT_SIZE {
+            self.schedule = None;
+            return Action::StandPat;
+        }
+
+        let slices_left = match &self.schedule {
+            Some(schedule) if schedule.tentenone == tentenone => schedule.slices_left,
+            _ => self.slices,
+        };
+
+        self.schedule = Some(Schedule {
+            tentenone,
+            slices_left: (slices_left - 1).max(1),
+        });
+
+        // Trade at least one lot per slice, so that the schedule does not stall on differences
+        // smaller than a lot per slice.
+        let slice = diff / slices_left as i32;
+        let slice = if slice.abs() < LOT_SIZE {
+            LOT_SIZE * diff.signum()
+        } else {
+            slice
+        };
+
+        Action::from_contracts(slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn band_with_a_threshold_of_one_lot() {
+        let mut band = Band::new(100);
+
+        check(&mut band, 0, 0, Action::StandPat);
+
+        check(&mut band, 99, 0, Action::StandPat);
+        check(&mut band, 100, 0, Action::buy_hundreds(1));
+        check(&mut band, 101, 0, Action::buy_hundreds(1));
+
+        check(&mut band, -99, 0, Action::StandPat);
+        check(&mut band, -100, 0, Action::sell_hundreds(1));
+        check(&mut band, -101, 0, Action::sell_hundreds(1));
+
+        check(&mut band, 0, -99, Action::StandPat);
+        check(&mut band, 0, -100, Action::buy_hundreds(1));
+        check(&mut band, 0, -101, Action::buy_hundreds(1));
+
+        check(&mut band, 0, 99, Action::StandPat);
+        check(&mut band, 0, 100, Action::sell_hundreds(1));
+        check(&mut band, 0, 101, Action::sell_hundreds(1));
+
+        check(&mut band, 550, 300, Action::buy_hundreds(2));
+        check(&mut band, -330, 200, Action::sell_hundreds(5));
+    }
+
+    #[test]
+    fn band_leaves_differences_within_the_threshold_unhedged() {
+        let mut band = Band::new(1_000);
+
+        check(&mut band, 999, 0, Action::StandPat);
+        check(&mut band, 1_050, 0, Action::buy_hundreds(10));
+        check(&mut band, -500, 500, Action::sell_hundreds(10));
+    }
+
+    #[test]
+    fn delta_hedges_the_ratio_in_clips() {
+        let mut delta = Delta::new(dec!(0.5), 300);
+
+        check(&mut delta, 400, 0, Action::buy_hundreds(2));
+        check(&mut delta, 2_000, 0, Action::buy_hundreds(3));
+        check(&mut delta, 2_000, 900, Action::buy_hundreds(1));
+        check(&mut delta, 2_000, 1_000, Action::StandPat);
+        check(&mut delta, -2_000, 0, Action::sell_hundreds(3));
+    }
+
+    #[test]
+    fn delta_clips_the_difference_of_a_large_ratio() {
+        let mut delta = Delta::new(dec!(1_000_000), 300);
+
+        check(&mut delta, i32::MAX, 0, Action::buy_hundreds(3));
+        check(&mut delta, i32::MIN, 0, Action::sell_hundreds(3));
+    }
+
+    #[test]
+    fn twap_spreads_the_difference_over_the_slices() {
+        let mut twap = Twap::new(4);
+
+        check(&mut twap, 1_000, 0, Action::buy_hundreds(2));
+        check(&mut twap, 1_000, 200, Action::buy_hundreds(2));
+        check(&mut twap, 1_000, 400, Action::buy_hundreds(3));
+        check(&mut twap, 1_000, 700, Action::buy_hundreds(3));
+        check(&mut twap, 1_000, 1_000, Action::StandPat);
+    }
+
+    #[test]
+    fn twap_restarts_when_the_position_changes() {
+        let mut twap = Twap::new(2);
+
+        check(&mut twap, 400, 0, Action::buy_hundreds(2));
+        check(&mut twap, -400, 200, Action::sell_hundreds(3));
+        check(&mut twap, -400, -100, Action::sell_hundreds(3));
+    }
+
+    #[track_caller]
+    fn check(strategy: &mut dyn HedgingStrategy, tentenone: i32, hedge: i32, expected: Action) {
+        let actual = strategy.action(tentenone, hedge);
+        assert_eq!(expected, actual);
+    }
+}
@@ -1,10 +1,12 @@
 use crate::health::Health;
 use crate::health::OverallMakerHealth;
+use crate::pnl;
 use crate::position;
 use crate::position::ContractSymbol;
 use crate::position::GetPosition;
 use crate::storage::MakerTenTenOneStorage;
 use axum::extract::Path;
+use axum::extract::Query;
 use axum::extract::State;
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
@@ -33,12 +35,14 @@ use std::hash::Hash;
 use std::hash::Hasher;
 use std::str::FromStr;
 use std::sync::Arc;
+use time::OffsetDateTime;
 use tokio::task::spawn_blocking;
 
 pub struct AppState {
     node: Arc<Node<MakerTenTenOneStorage, InMemoryStore>>,
     exporter: PrometheusExporter,
     position_manager: xtra::Address<position::Manager>,
+    pnl_tracker: pnl::Tracker,
     announcement_addresses: Vec<SocketAddress>,
     node_alias: String,
     health: Health,
@@ -48,6 +52,7 @@ pub fn router(
     node: Arc<Node<MakerTenTenOneStorage, InMemoryStore>>,
     exporter: PrometheusExporter,
     position_manager: xtra::Address<position::Manager>,
+    pnl_tracker: pnl::Tracker,
     health: Health,
     announcement_addresses: Vec<SocketAddress>,
     node_alias: &str,
@@ -56,6 +61,7 @@ pub fn router(
         node,
         exporter,
         position_manager,
+        pnl_tracker,
         health,
         announcement_addresses,
         node_alias: node_alias.to_string(),
@@ -71,6 +77,8 @@ pub fn router(
         .route("/api/pay-invoice/:invoice", post(pay_invoice))
         .route("/api/sync", post(sync))
         .route("/api/position", get(get_position))
+        .route("/api/pnl", get(get_pnl))
+        .route("/api/pnl/daily", get(get_daily_pnl))
         .route("/api/node", get(get_node_info))
         .route("/metrics", get(get_metrics))
         .route("/health", get(get_health))
@@ -317,6 +325,55 @@ pub async fn get_position(State(state): State<Arc<AppState>>) -> Result<Json<Pos
     }))
 }
 
+#[derive(Deserialize)]
+pub struct PnlParams {
+    /// Only accumulate the realized PnL, fees and funding since this time.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    from: Option<OffsetDateTime>,
+}
+
+pub async fn get_pnl(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PnlParams>,
+) -> Result<Json<pnl::Report>, AppError> {
+    let report = spawn_blocking(move || state.pnl_tracker.report(params.from))
+        .await
+        .expect("task to complete")
+        .map_err(|e| AppError::InternalServerError(format!("Failed to get PnL: {e:#}")))?;
+
+    Ok(Json(report))
+}
+
+#[derive(Deserialize)]
+pub struct DailyPnlParams {
+    /// Defaults to the first fill.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    from: Option<OffsetDateTime>,
+    /// Defaults to now.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    to: Option<OffsetDateTime>,
+}
+
+pub async fn get_daily_pnl(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<DailyPnlParams>,
+) -> Result<Json<Vec<pnl::Daily>>, AppError> {
+    let from = params.from.unwrap_or(OffsetDateTime::UNIX_EPOCH);
+    let to = params.to.unwrap_or_else(OffsetDateTime::now_utc);
+    if from >= to {
+        return Err(AppError::BadRequest(
+            "The start of the range has to be before its end".to_string(),
+        ));
+    }
+
+    let report = spawn_blocking(move || state.pnl_tracker.daily_report(from, to))
+        .await
+        .expect("task to complete")
+        .map_err(|e| AppError::InternalServerError(format!("Failed to get daily PnL: {e:#}")))?;
+
+    Ok(Json(report))
+}
+
 pub async fn get_metrics(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     let exporter = state.exporter.clone();
     let encoder = TextEncoder::new();
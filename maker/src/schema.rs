@@ -1 +1,34 @@
 // @generated automatically by Diesel CLI.
+
+pub mod sql_types {
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "Book_Type"))]
+    pub struct BookType;
+
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "ContractSymbol_Type"))]
+    pub struct ContractSymbolType;
+
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "FillKind_Type"))]
+    pub struct FillKindType;
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::BookType;
+    use super::sql_types::ContractSymbolType;
+    use super::sql_types::FillKindType;
+
+    fills (id) {
+        id -> Uuid,
+        book -> BookType,
+        contract_symbol -> ContractSymbolType,
+        kind -> FillKindType,
+        contracts -> Numeric,
+        price -> Numeric,
+        fee_sats -> Int8,
+        timestamp -> Timestamptz,
+        created_at -> Timestamptz,
+    }
+}
@@ -1,132 +1,205 @@
 use crate::health::ServiceStatus;
 use crate::position;
-use crate::position::PositionUpdateBitmex;
-use crate::trading::bitmex_ws_client::Event;
+use crate::position::GetPosition;
+use crate::position::PositionUpdateVenue;
+use crate::position::QuoteUpdateVenue;
+use crate::venue::Event;
+use crate::venue::HedgingVenue;
 use bitcoin::secp256k1::PublicKey;
-use bitcoin::Network;
-use bitmex_stream::Credentials;
+use bitcoin::secp256k1::SecretKey;
+use bitcoin::secp256k1::SECP256K1;
 use commons::NewOrder;
+use commons::Order;
 use commons::OrderResponse;
+use commons::OrderState;
 use commons::OrderType;
+use commons::TimeInForce;
 use futures::TryStreamExt;
 use orderbook_http_client::OrderbookClient;
+use quoting::Level;
+use quoting::Quoter;
+use quoting::QuotingConfig;
+use quoting::Requote;
 use reqwest::Url;
 use rust_decimal::Decimal;
-use rust_decimal_macros::dec;
+use std::sync::Arc;
 use std::time::Duration;
 use time::OffsetDateTime;
+use tokio::sync::mpsc;
 use tokio::sync::watch;
-use trade::ContractSymbol;
-use trade::Direction;
 use uuid::Uuid;
 
-mod bitmex_ws_client;
-mod orderbook_http_client;
+pub mod orderbook_http_client;
+pub mod quoting;
 
-/// Perform trading related actions based on a subscription to BitMEX's WebSocket API. Specifically:
+/// Perform trading related actions based on a subscription to the hedging venue. Specifically:
 ///
-/// - Create orders based on relevant price updates from BitMEX.
-/// - Forward updates about all BitMEX positions.
+/// - Quote in the 10101 orderbook around the prices of the venue, see [`Quoter`].
+/// - Forward updates about all the venue's quotes and positions.
+/// - Keep track of the orders quoting the levels, reconciling them with the [`OwnOrderUpdate`]s.
 ///
 /// In the unlikely event that the stream is closed, the function will continue to try to reconnect
 /// after the [`Duration`] specified by `reconnect_after`.
 #[allow(clippy::too_many_arguments)]
 pub async fn run(
     orderbook_url: &Url,
-    maker_id: PublicKey,
-    network: Network,
-    concurrent_orders: usize,
+    auth_sk: SecretKey,
+    venue: Arc<dyn HedgingVenue>,
+    quoting: QuotingConfig,
     order_expiry_after: time::Duration,
     bitmex_pricefeed_tx: watch::Sender<ServiceStatus>,
     position_manager: xtra::Address<position::Manager>,
-    bitmex_api_key: Option<String>,
-    bitmex_api_secret: Option<String>,
+    mut own_orders: mpsc::UnboundedReceiver<OwnOrderUpdate>,
     reconnect_after: Duration,
 ) {
-    let network = match network {
-        Network::Bitcoin => bitmex_stream::Network::Mainnet,
-        _ => bitmex_stream::Network::Testnet,
-    };
-
-    let orderbook_client = OrderbookClient::new();
-
-    let mut orders: Vec<OrderResponse> = Vec::new();
+    let maker_id = auth_sk.public_key(SECP256K1);
+    let orderbook_client = OrderbookClient::new(auth_sk);
 
-    // Closure to avoid repeating the same code
-    let add_new_10101_order = |price, direction| {
-        add_10101_order(
-            &orderbook_client,
-            orderbook_url,
-            price,
-            direction,
-            maker_id,
-            dec!(5000),
-            OffsetDateTime::now_utc() + order_expiry_after,
-        )
-    };
-
-    let credentials = match (bitmex_api_key, bitmex_api_secret) {
-        (Some(api_key), Some(secret)) => Some(Credentials { api_key, secret }),
-        _ => None,
-    };
+    let mut quoter = Quoter::new(quoting);
 
     loop {
-        let mut stream = bitmex_ws_client::stream(network, credentials.clone()).await;
+        let mut stream = venue.stream();
         loop {
-            match stream.try_next().await {
-                Ok(Some(Event::Quote(quote))) => {
-                    let _ = bitmex_pricefeed_tx.send(ServiceStatus::Online);
-                    tracing::debug!("Received new quote {quote:?}");
+            tokio::select! {
+                event = stream.try_next() => match event {
+                    Ok(Some(Event::Quote(quote))) => {
+                        let _ = bitmex_pricefeed_tx.send(ServiceStatus::Online);
+                        tracing::debug!("Received new quote {quote:?}");
 
-                    orders.clear();
+                        let _ = position_manager
+                            .send(QuoteUpdateVenue {
+                                contract_symbol: quote.contract_symbol.into(),
+                                bid: quote.bid(),
+                                ask: quote.ask(),
+                            })
+                            .await;
 
-                    for _i in 0..concurrent_orders {
-                        if let Some(order) = add_new_10101_order(quote.bid(), Direction::Long).await
-                        {
-                            orders.push(order)
+                        let position = match position_manager.send(GetPosition).await {
+                            Ok(position) => position
+                                .tentenone
+                                .get(&position::ContractSymbol::from(quote.contract_symbol))
+                                .copied()
+                                .unwrap_or_default(),
+                            Err(e) => {
+                                tracing::error!("Not quoting without the 10101 position: {e:#}");
+                                continue;
+                            }
                         };
 
-                        if let Some(order) =
-                            add_new_10101_order(quote.ask(), Direction::Short).await
-                        {
-                            orders.push(order)
-                        };
+                        let reference_price = (quote.bid() + quote.ask()) / Decimal::TWO;
+                        let levels =
+                            quoter.levels(quote.contract_symbol, reference_price, position);
+
+                        // Orders are refreshed once half of their validity has passed, so that the
+                        // levels are not left empty until the next quote.
+                        let now = OffsetDateTime::now_utc();
+                        let expiry = now + order_expiry_after;
+                        for requote in quoter.requotes(levels, now + order_expiry_after / 2) {
+                            match requote {
+                                Requote::Place(level) => {
+                                    if let Some(order) = add_10101_order(
+                                        &orderbook_client,
+                                        orderbook_url,
+                                        &level,
+                                        maker_id,
+                                        expiry,
+                                    )
+                                    .await
+                                    {
+                                        quoter.quoted(&level, order.id, expiry);
+                                    }
+                                }
+                                Requote::Amend { order_id, level } => {
+                                    match orderbook_client
+                                        .amend_order(
+                                            orderbook_url,
+                                            order_id,
+                                            level.price,
+                                            level.quantity,
+                                            expiry,
+                                        )
+                                        .await
+                                    {
+                                        Ok(_) => quoter.quoted(&level, order_id, expiry),
+                                        Err(e) => {
+                                            handle_failed_amendment(
+                                                &orderbook_client,
+                                                orderbook_url,
+                                                &mut quoter,
+                                                order_id,
+                                                e,
+                                            )
+                                            .await
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    Ok(Some(Event::Position(position))) => {
+                        let _ = position_manager
+                            .send(PositionUpdateVenue {
+                                contract_symbol: position.contract_symbol.into(),
+                                contracts: position.contracts,
+                            })
+                            .await;
                     }
-                }
-                Ok(Some(Event::Position(position))) => {
-                    let _ = position_manager
-                        .send(PositionUpdateBitmex {
-                            contract_symbol: position.contract_symbol.into(),
-                            contracts: position.contracts,
-                        })
-                        .await;
-                }
-                Err(e) => {
-                    tracing::error!("Closing BitMEX WS after encountering error: {e:#}");
-                    break;
-                }
-                Ok(None) => {
-                    tracing::error!("BitMEX WS closed");
-                    break;
-                }
+                    // Executions are recorded by the PnL tracker, see `crate::pnl`.
+                    Ok(Some(Event::Execution(_))) => {}
+                    Err(e) => {
+                        tracing::error!(
+                            "Closing hedging venue stream after encountering error: {e:#}"
+                        );
+                        break;
+                    }
+                    Ok(None) => {
+                        tracing::error!("Hedging venue stream closed");
+                        break;
+                    }
+                },
+                Some(update) = own_orders.recv() => match update {
+                    OwnOrderUpdate::Snapshot(open_orders) => {
+                        for order_id in quoter.reconcile(&open_orders) {
+                            tracing::info!(%order_id, "Cancelling untracked order");
+                            cancel_10101_order(&orderbook_client, orderbook_url, order_id).await;
+                        }
+                    }
+                    OwnOrderUpdate::Update(order) => quoter.update(&order),
+                    OwnOrderUpdate::Delete(order_id) => quoter.forget(order_id),
+                },
             }
         }
 
         let _ = bitmex_pricefeed_tx.send(ServiceStatus::Offline);
 
-        tracing::error!(timeout = ?reconnect_after, "Reconnecting to BitMEX WS after timeout");
+        // Without prices from the hedging venue we cannot keep our quotes up-to-date.
+        for order_id in quoter.drain() {
+            cancel_10101_order(&orderbook_client, orderbook_url, order_id).await;
+        }
+
+        tracing::error!(
+            timeout = ?reconnect_after,
+            "Reconnecting to hedging venue stream after timeout"
+        );
 
         tokio::time::sleep(reconnect_after).await;
     }
 }
 
+/// An update of the maker's own orders, received from the orderbook WebSocket API.
+#[derive(Debug)]
+pub enum OwnOrderUpdate {
+    /// All open orders of the maker, received whenever connecting to the orderbook.
+    Snapshot(Vec<Order>),
+    Update(Order),
+    Delete(Uuid),
+}
+
 async fn add_10101_order(
     orderbook_client: &OrderbookClient,
     orderbook_url: &Url,
-    price: Decimal,
-    direction: Direction,
+    level: &Level,
     maker_id: PublicKey,
-    quantity: Decimal,
     expiry: OffsetDateTime,
 ) -> Option<OrderResponse> {
     orderbook_client
@@ -134,15 +207,16 @@ async fn add_10101_order(
             orderbook_url,
             NewOrder {
                 id: Uuid::new_v4(),
-                contract_symbol: ContractSymbol::BtcUsd,
-                price,
-                quantity,
+                contract_symbol: level.contract_symbol,
+                price: level.price,
+                quantity: level.quantity,
                 trader_id: maker_id,
-                direction,
+                direction: level.direction,
                 leverage: 1.0,
                 order_type: OrderType::Limit,
                 expiry,
                 stable: false,
+                time_in_force: TimeInForce::GoodTillDate,
             },
         )
         .await
@@ -152,3 +226,51 @@ async fn add_10101_order(
         })
         .ok()
 }
+
+/// Cancels the order if it is still open after its amendment failed, e.g. because the amendment
+/// has been rejected. An order which is not open anymore, because it has been filled or cancelled,
+/// is only forgotten.
+///
+/// If the state of the order is unknown, it remains tracked so that the amendment is retried with
+/// the next quote.
+async fn handle_failed_amendment(
+    orderbook_client: &OrderbookClient,
+    orderbook_url: &Url,
+    quoter: &mut Quoter,
+    order_id: Uuid,
+    error: anyhow::Error,
+) {
+    match orderbook_client.get_order(orderbook_url, order_id).await {
+        Ok(order) if order.order_state == OrderState::Open => {
+            tracing::warn!(%order_id, "Failed to amend order, cancelling it: {error:#}");
+
+            quoter.forget(order_id);
+            cancel_10101_order(orderbook_client, orderbook_url, order_id).await;
+        }
+        Ok(order) => {
+            tracing::info!(
+                %order_id,
+                order_state = ?order.order_state,
+                "Failed to amend order which is not open anymore: {error:#}"
+            );
+
+            quoter.forget(order_id);
+        }
+        Err(e) => {
+            tracing::warn!(
+                %order_id,
+                "Failed to amend order and to get its state: {error:#}, {e:#}"
+            );
+        }
+    }
+}
+
+async fn cancel_10101_order(
+    orderbook_client: &OrderbookClient,
+    orderbook_url: &Url,
+    order_id: Uuid,
+) {
+    if let Err(e) = orderbook_client.cancel_order(orderbook_url, order_id).await {
+        tracing::error!(%order_id, "Failed to cancel order: {e:#}");
+    }
+}
@@ -1,34 +1,143 @@
 use anyhow::bail;
 use anyhow::Result;
+use bitcoin::secp256k1::SecretKey;
+use bitcoin::secp256k1::SECP256K1;
+use commons::Fill;
+use commons::HistoryFilter;
 use commons::NewOrder;
+use commons::Order;
 use commons::OrderResponse;
+use commons::RequestSignature;
+use commons::UpdateOrder;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::Method;
 use reqwest::Url;
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
 
 pub struct OrderbookClient {
     client: reqwest::Client,
+    /// The key signing our requests, i.e. the key of our node id.
+    auth_sk: SecretKey,
 }
 
 impl OrderbookClient {
-    pub fn new() -> Self {
+    pub fn new(auth_sk: SecretKey) -> Self {
         Self {
             client: reqwest::Client::builder()
                 .timeout(std::time::Duration::from_secs(30))
                 .build()
                 .expect("to build client from static config"),
+            auth_sk,
         }
     }
 
     pub async fn post_new_order(&self, url: &Url, order: NewOrder) -> Result<OrderResponse> {
         let url = url.join("/api/orderbook/orders")?;
 
-        let response = self.client.post(url).json(&order).send().await?;
+        match self.send_signed(Method::POST, url, &order).await {
+            Ok(response) => Ok(response),
+            Err(e) => {
+                tracing::error!("Could not create new order");
+                bail!("Could not create new order: {e:#}")
+            }
+        }
+    }
+
+    /// Changes the price, quantity and expiry of an open order, keeping its id.
+    pub async fn amend_order(
+        &self,
+        url: &Url,
+        order_id: Uuid,
+        price: Decimal,
+        quantity: Decimal,
+        expiry: OffsetDateTime,
+    ) -> Result<Order> {
+        self.update_order(
+            url,
+            order_id,
+            UpdateOrder {
+                taken: false,
+                price: Some(price),
+                quantity: Some(quantity),
+                expiry: Some(expiry),
+            },
+        )
+        .await
+    }
+
+    /// Takes the order out of the orderbook.
+    pub async fn cancel_order(&self, url: &Url, order_id: Uuid) -> Result<Order> {
+        self.update_order(url, order_id, UpdateOrder::cancel())
+            .await
+    }
+
+    pub async fn get_order(&self, url: &Url, order_id: Uuid) -> Result<Order> {
+        let url = url.join(&format!("/api/orderbook/orders/{order_id}"))?;
+
+        match self.send_signed(Method::GET, url, &()).await {
+            Ok(order) => Ok(order),
+            Err(e) => bail!("Could not get order {order_id}: {e:#}"),
+        }
+    }
+
+    /// The fills of our orders matching the filter, most recent first.
+    pub async fn get_fills(&self, url: &Url, filter: &HistoryFilter) -> Result<Vec<Fill>> {
+        let trader_id = self.auth_sk.public_key(SECP256K1);
+        let url = url.join(&format!("/api/orderbook/history/{trader_id}/fills"))?;
+        let url = self.client.get(url).query(filter).build()?.url().clone();
+
+        match self.send_signed(Method::GET, url, &()).await {
+            Ok(fills) => Ok(fills),
+            Err(e) => bail!("Could not get fills: {e:#}"),
+        }
+    }
+
+    async fn update_order(&self, url: &Url, order_id: Uuid, update: UpdateOrder) -> Result<Order> {
+        let url = url.join(&format!("/api/orderbook/orders/{order_id}"))?;
+
+        match self.send_signed(Method::PUT, url, &update).await {
+            Ok(order) => Ok(order),
+            Err(e) => bail!("Could not update order {order_id}: {e:#}"),
+        }
+    }
+
+    /// Sends the JSON `body` to `url`, signed with our node key.
+    async fn send_signed<B, R>(&self, method: Method, url: Url, body: &B) -> Result<R>
+    where
+        B: Serialize,
+        R: DeserializeOwned,
+    {
+        let body = serde_json::to_vec(body)?;
+
+        // The signature covers the query as well.
+        let path = match url.query() {
+            Some(query) => format!("{}?{query}", url.path()),
+            None => url.path().to_string(),
+        };
+        let signature = RequestSignature::new(&self.auth_sk, method.as_str(), &path, &body);
+
+        let mut request = self
+            .client
+            .request(method, url)
+            .header(CONTENT_TYPE, "application/json")
+            .body(body);
+        for (name, value) in signature.headers() {
+            request = request.header(name, value);
+        }
+
+        let response = request.send().await?;
 
         if response.status().as_u16() == 200 {
             let response = response.json().await?;
             Ok(response)
         } else {
-            tracing::error!("Could not create new order");
-            bail!("Could not create new order ")
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            bail!("Request failed with status {status}: {text}")
         }
     }
 }
@@ -0,0 +1,428 @@
+use commons::Order;
+use commons::OrderState;
+use rust_decimal::Decimal;
+use rust_decimal::RoundingStrategy;
+use std::collections::HashMap;
+use time::OffsetDateTime;
+use trade::ContractSymbol;
+use trade::Direction;
+use uuid::Uuid;
+
+/// How the maker quotes in the 10101 orderbook.
+#[derive(Debug, Clone)]
+pub struct QuotingConfig {
+    /// The distance of the best bid and ask from the reference price, in basis points.
+    pub spread_bps: u32,
+    /// The distance between two consecutive price levels of the same side, in basis points.
+    pub level_spacing_bps: u32,
+    /// The quantity quoted at every price level of each side, starting with the best level.
+    pub level_sizes: Vec<Decimal>,
+    /// The maximum shift of the quotes against the net 10101 position of the maker, in basis
+    /// points.
+    pub max_skew_bps: u32,
+    /// The net 10101 position, in contracts, from which the quotes are shifted by
+    /// `max_skew_bps`.
+    pub max_skew_position: Decimal,
+    /// The minimum price change of a level, in basis points, for its order to be replaced.
+    pub requote_threshold_bps: u32,
+}
+
+/// A price level to quote in the 10101 orderbook.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Level {
+    pub contract_symbol: ContractSymbol,
+    pub direction: Direction,
+    /// The position of the level within its side, zero being the best level.
+    pub index: usize,
+    pub price: Decimal,
+    pub quantity: Decimal,
+}
+
+impl Level {
+    fn key(&self) -> (ContractSymbol, Direction, usize) {
+        (self.contract_symbol, self.direction, self.index)
+    }
+}
+
+/// How to bring the orders of the maker in line with a [`Level`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Requote {
+    /// Place a new order for the level.
+    Place(Level),
+    /// Amend the order currently quoting the level.
+    Amend { order_id: Uuid, level: Level },
+}
+
+/// An open order of the maker quoting a level.
+struct LiveOrder {
+    order_id: Uuid,
+    price: Decimal,
+    quantity: Decimal,
+    expiry: OffsetDateTime,
+}
+
+/// Computes the price levels of the maker and keeps track of the orders quoting them.
+pub struct Quoter {
+    config: QuotingConfig,
+    live_orders: HashMap<(ContractSymbol, Direction, usize), LiveOrder>,
+}
+
+impl Quoter {
+    pub fn new(config: QuotingConfig) -> Self {
+        Self {
+            config,
+            live_orders: HashMap::new(),
+        }
+    }
+
+    /// The levels to quote around the `reference` price, given the net 10101 `position` of the
+    /// maker in contracts.
+    ///
+    /// If the maker is long, the quotes are shifted down to attract buyers rather than sellers, and
+    /// vice versa. Bids are rounded down and asks up to whole numbers, to be valid prices for every
+    /// contract symbol.
+    pub fn levels(
+        &self,
+        contract_symbol: ContractSymbol,
+        reference: Decimal,
+        position: Decimal,
+    ) -> Vec<Level> {
+        let mid = reference * (Decimal::ONE - self.skew(position));
+
+        self.config
+            .level_sizes
+            .iter()
+            .enumerate()
+            .flat_map(|(index, quantity)| {
+                let distance = bps(self.config.spread_bps)
+                    + bps(self.config.level_spacing_bps) * Decimal::from(index);
+
+                let bid = (mid * (Decimal::ONE - distance))
+                    .round_dp_with_strategy(0, RoundingStrategy::ToNegativeInfinity);
+                let ask = (mid * (Decimal::ONE + distance))
+                    .round_dp_with_strategy(0, RoundingStrategy::ToPositiveInfinity);
+
+                [(Direction::Long, bid), (Direction::Short, ask)].map(|(direction, price)| Level {
+                    contract_symbol,
+                    direction,
+                    index,
+                    price,
+                    quantity: *quantity,
+                })
+            })
+            .collect()
+    }
+
+    /// How to quote the levels: a new order is placed for the levels without an order, and the
+    /// order of a level is amended if its price moved beyond the requote threshold, if its quantity
+    /// changed, e.g. after a partial fill, or if it expires before `refresh_before`.
+    pub fn requotes(&self, levels: Vec<Level>, refresh_before: OffsetDateTime) -> Vec<Requote> {
+        levels
+            .into_iter()
+            .filter_map(|level| match self.live_orders.get(&level.key()) {
+                Some(order) => {
+                    let moved = (level.price - order.price).abs() * Decimal::from(10_000)
+                        > order.price * Decimal::from(self.config.requote_threshold_bps);
+
+                    (moved || order.quantity != level.quantity || order.expiry < refresh_before)
+                        .then_some(Requote::Amend {
+                            order_id: order.order_id,
+                            level,
+                        })
+                }
+                None => Some(Requote::Place(level)),
+            })
+            .collect()
+    }
+
+    /// Records the order quoting the level, after it has been placed or amended.
+    pub fn quoted(&mut self, level: &Level, order_id: Uuid, expiry: OffsetDateTime) {
+        self.live_orders.insert(
+            level.key(),
+            LiveOrder {
+                order_id,
+                price: level.price,
+                quantity: level.quantity,
+                expiry,
+            },
+        );
+    }
+
+    /// Stops tracking the order, e.g. because it has been filled or cancelled, so that its level
+    /// is quoted with a new order.
+    pub fn forget(&mut self, order_id: Uuid) {
+        self.live_orders
+            .retain(|_, order| order.order_id != order_id);
+    }
+
+    /// Applies an update of one of the maker's orders received from the orderbook.
+    pub fn update(&mut self, order: &Order) {
+        if order.order_state != OrderState::Open {
+            self.forget(order.id);
+            return;
+        }
+
+        if let Some(live_order) = self
+            .live_orders
+            .values_mut()
+            .find(|live_order| live_order.order_id == order.id)
+        {
+            live_order.price = order.price;
+            live_order.quantity = order.quantity;
+            live_order.expiry = order.expiry;
+        }
+    }
+
+    /// Reconciles the tracked orders with all open orders of the maker in the orderbook, e.g.
+    /// after reconnecting to the orderbook.
+    ///
+    /// Tracked orders which are no longer open are forgotten. Returns the ids of the open orders
+    /// which are not tracked, e.g. because they have been placed before a restart, and which should
+    /// hence be cancelled.
+    pub fn reconcile(&mut self, open_orders: &[Order]) -> Vec<Uuid> {
+        self.live_orders.retain(|_, live_order| {
+            open_orders
+                .iter()
+                .any(|order| order.id == live_order.order_id)
+        });
+
+        let mut untracked = Vec::new();
+        for order in open_orders {
+            if self
+                .live_orders
+                .values()
+                .any(|live_order| live_order.order_id == order.id)
+            {
+                self.update(order);
+            } else {
+                untracked.push(order.id);
+            }
+        }
+
+        untracked
+    }
+
+    /// Stops tracking all orders, returning their ids.
+    pub fn drain(&mut self) -> Vec<Uuid> {
+        self.live_orders
+            .drain()
+            .map(|(_, order)| order.order_id)
+            .collect()
+    }
+
+    /// The relative shift of the quotes for the net 10101 `position`.
+    fn skew(&self, position: Decimal) -> Decimal {
+        if self.config.max_skew_position <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+
+        let ratio = (position / self.config.max_skew_position).clamp(-Decimal::ONE, Decimal::ONE);
+
+        ratio * bps(self.config.max_skew_bps)
+    }
+}
+
+fn bps(bps: u32) -> Decimal {
+    Decimal::from(bps) / Decimal::from(10_000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use time::Duration;
+
+    fn config() -> QuotingConfig {
+        QuotingConfig {
+            spread_bps: 10,
+            level_spacing_bps: 5,
+            level_sizes: vec![dec!(1_000), dec!(2_000)],
+            max_skew_bps: 20,
+            max_skew_position: dec!(10_000),
+            requote_threshold_bps: 5,
+        }
+    }
+
+    fn prices(levels: &[Level]) -> Vec<(Direction, Decimal)> {
+        levels
+            .iter()
+            .map(|level| (level.direction, level.price))
+            .collect()
+    }
+
+    #[test]
+    fn levels_are_spread_around_the_reference_price() {
+        let quoter = Quoter::new(config());
+
+        let levels = quoter.levels(ContractSymbol::BtcUsd, dec!(40_000), Decimal::ZERO);
+
+        assert_eq!(
+            prices(&levels),
+            vec![
+                (Direction::Long, dec!(39_960)),
+                (Direction::Short, dec!(40_040)),
+                (Direction::Long, dec!(39_940)),
+                (Direction::Short, dec!(40_060)),
+            ]
+        );
+        assert_eq!(levels[2].quantity, dec!(2_000));
+    }
+
+    #[test]
+    fn quotes_are_skewed_against_the_position() {
+        let quoter = Quoter::new(config());
+
+        let long = quoter.levels(ContractSymbol::BtcUsd, dec!(40_000), dec!(5_000));
+        assert_eq!(
+            prices(&long[..2]),
+            vec![
+                (Direction::Long, dec!(39_920)),
+                (Direction::Short, dec!(40_000)),
+            ]
+        );
+
+        let very_short = quoter.levels(ContractSymbol::BtcUsd, dec!(40_000), dec!(-50_000));
+        assert_eq!(
+            prices(&very_short[..2]),
+            vec![
+                (Direction::Long, dec!(40_039)),
+                (Direction::Short, dec!(40_121)),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_levels_moved_beyond_the_threshold_are_amended() {
+        let mut quoter = Quoter::new(config());
+        let now = OffsetDateTime::now_utc();
+
+        let levels = quoter.levels(ContractSymbol::BtcUsd, dec!(40_000), Decimal::ZERO);
+        let requotes = quoter.requotes(levels.clone(), now);
+        assert_eq!(requotes.len(), 4);
+        assert!(requotes
+            .iter()
+            .all(|requote| matches!(requote, Requote::Place(_))));
+        quote_all(&mut quoter, &levels, now + Duration::minutes(5));
+
+        let levels = quoter.levels(ContractSymbol::BtcUsd, dec!(40_010), Decimal::ZERO);
+        assert!(quoter.requotes(levels, now).is_empty());
+
+        let levels = quoter.levels(ContractSymbol::BtcUsd, dec!(40_100), Decimal::ZERO);
+        let requotes = quoter.requotes(levels.clone(), now);
+        assert_eq!(
+            requotes[0],
+            Requote::Amend {
+                order_id: Uuid::from_u128(0),
+                level: levels[0],
+            }
+        );
+        assert_eq!(requotes.len(), 4);
+    }
+
+    #[test]
+    fn expiring_orders_are_amended() {
+        let mut quoter = Quoter::new(config());
+        let now = OffsetDateTime::now_utc();
+
+        let levels = quoter.levels(ContractSymbol::BtcUsd, dec!(40_000), Decimal::ZERO);
+        quoter.quoted(&levels[0], Uuid::from_u128(0), now + Duration::seconds(10));
+        quoter.quoted(&levels[1], Uuid::from_u128(1), now + Duration::minutes(5));
+
+        let requotes = quoter.requotes(levels.clone(), now + Duration::seconds(30));
+
+        assert_eq!(
+            requotes,
+            vec![
+                Requote::Amend {
+                    order_id: Uuid::from_u128(0),
+                    level: levels[0],
+                },
+                Requote::Place(levels[2]),
+                Requote::Place(levels[3]),
+            ]
+        );
+    }
+
+    #[test]
+    fn partially_filled_order_is_amended() {
+        let mut quoter = Quoter::new(config());
+        let now = OffsetDateTime::now_utc();
+
+        let levels = quoter.levels(ContractSymbol::BtcUsd, dec!(40_000), Decimal::ZERO);
+        quote_all(&mut quoter, &levels, now + Duration::minutes(5));
+
+        quoter.update(&order(&levels[1], 1, dec!(400), OrderState::Open, now));
+        quoter.update(&order(&levels[2], 2, dec!(2_000), OrderState::Taken, now));
+
+        assert_eq!(
+            quoter.requotes(levels.clone(), now),
+            vec![
+                Requote::Amend {
+                    order_id: Uuid::from_u128(1),
+                    level: levels[1],
+                },
+                Requote::Place(levels[2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn reconciling_forgets_closed_orders_and_returns_untracked_orders() {
+        let mut quoter = Quoter::new(config());
+        let now = OffsetDateTime::now_utc();
+
+        let levels = quoter.levels(ContractSymbol::BtcUsd, dec!(40_000), Decimal::ZERO);
+        quote_all(&mut quoter, &levels, now + Duration::minutes(5));
+
+        let open_orders = [
+            order(&levels[0], 0, dec!(1_000), OrderState::Open, now),
+            order(&levels[1], 1, dec!(1_000), OrderState::Open, now),
+            order(&levels[2], 2, dec!(2_000), OrderState::Open, now),
+            order(&levels[2], 42, dec!(2_000), OrderState::Open, now),
+        ];
+        let untracked = quoter.reconcile(&open_orders);
+
+        assert_eq!(untracked, vec![Uuid::from_u128(42)]);
+        assert_eq!(
+            quoter.requotes(levels.clone(), now),
+            vec![Requote::Place(levels[3])]
+        );
+
+        let mut drained = quoter.drain();
+        drained.sort();
+        assert_eq!(drained, (0..3).map(Uuid::from_u128).collect::<Vec<_>>());
+    }
+
+    fn quote_all(quoter: &mut Quoter, levels: &[Level], expiry: OffsetDateTime) {
+        for (i, level) in levels.iter().enumerate() {
+            quoter.quoted(level, Uuid::from_u128(i as u128), expiry);
+        }
+    }
+
+    fn order(
+        level: &Level,
+        id: u128,
+        quantity: Decimal,
+        order_state: OrderState,
+        now: OffsetDateTime,
+    ) -> Order {
+        Order {
+            id: Uuid::from_u128(id),
+            price: level.price,
+            leverage: 1.0,
+            contract_symbol: level.contract_symbol,
+            trader_id: "027f31ebc5462c1fdce1b737ecff52d37d75dea43ce11c74d25aa297165faa2007"
+                .parse()
+                .unwrap(),
+            direction: level.direction,
+            quantity,
+            order_type: commons::OrderType::Limit,
+            timestamp: now,
+            expiry: now + Duration::minutes(5),
+            order_state,
+            order_reason: commons::OrderReason::Manual,
+            stable: false,
+            time_in_force: commons::TimeInForce::GoodTillDate,
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use rust_decimal::Decimal;
+use std::fmt;
+use time::OffsetDateTime;
+use trade::ContractSymbol;
+use uuid::Uuid;
+
+pub use bitmex::Bitmex;
+pub use paper::Paper;
+
+mod bitmex;
+mod paper;
+
+/// A venue on which the maker hedges its 10101 position.
+#[async_trait]
+pub trait HedgingVenue: Send + Sync {
+    /// Places the order, returning the id assigned to it by the venue.
+    async fn place_order(&self, order: OrderRequest) -> Result<Uuid>;
+
+    /// Cancels an order which has not been filled yet.
+    async fn cancel_order(&self, order_id: Uuid) -> Result<()>;
+
+    /// The number of contracts held on the venue, with the sign representing the direction:
+    /// positive long; negative short.
+    async fn position(&self, contract_symbol: ContractSymbol) -> Result<i32>;
+
+    /// The executions since `from`, oldest first, to catch up on the executions missed while
+    /// not subscribed to the [`HedgingVenue::stream`].
+    async fn executions(&self, from: OffsetDateTime) -> Result<Vec<Execution>>;
+
+    /// Subscribes to the quotes, position updates and executions of the venue.
+    ///
+    /// The stream ends, or yields an error, if the connection to the venue is lost. It is up to
+    /// the caller to subscribe again.
+    fn stream(&self) -> BoxStream<'static, Result<Event>>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeInForce {
+    /// Fill whatever can be filled immediately and cancel the rest.
+    ImmediateOrCancel,
+    /// Rest on the venue until filled or cancelled.
+    GoodTillCancel,
+}
+
+/// A limit order to be placed on a [`HedgingVenue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderRequest {
+    pub contract_symbol: ContractSymbol,
+    pub side: Side,
+    pub contracts: u32,
+    /// The worst price at which the order may be filled.
+    pub limit_price: Decimal,
+    pub time_in_force: TimeInForce,
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Quote(Quote),
+    Position(Position),
+    Execution(Execution),
+}
+
+#[derive(Clone, Copy)]
+pub struct Quote {
+    pub contract_symbol: ContractSymbol,
+    pub bid: Decimal,
+    pub ask: Decimal,
+    pub timestamp: OffsetDateTime,
+}
+
+impl fmt::Debug for Quote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rfc3339_timestamp = self
+            .timestamp
+            .format(&time::format_description::well_known::Rfc3339)
+            .expect("Timestamp to be formatted");
+
+        f.debug_struct("Quote")
+            .field("timestamp", &rfc3339_timestamp)
+            .field("bid", &self.bid)
+            .field("ask", &self.ask)
+            .field("contract_symbol", &self.contract_symbol)
+            .finish()
+    }
+}
+
+impl Quote {
+    pub fn bid(&self) -> Decimal {
+        self.bid
+    }
+
+    pub fn ask(&self) -> Decimal {
+        self.ask
+    }
+
+    #[allow(dead_code)]
+    pub fn is_older_than(&self, duration: time::Duration) -> bool {
+        let required_quote_timestamp = (OffsetDateTime::now_utc() - duration).unix_timestamp();
+
+        self.timestamp.unix_timestamp() < required_quote_timestamp
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Position {
+    pub contract_symbol: ContractSymbol,
+    pub contracts: i32,
+    pub timestamp: OffsetDateTime,
+}
+
+impl fmt::Debug for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rfc3339_timestamp = self
+            .timestamp
+            .format(&time::format_description::well_known::Rfc3339)
+            .expect("Timestamp to be formatted");
+
+        f.debug_struct("Position")
+            .field("contract_symbol", &self.contract_symbol)
+            .field("contracts", &self.contracts)
+            .field("timestamp", &rfc3339_timestamp)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionKind {
+    /// An order of the maker was (partially) filled.
+    Trade,
+    /// Funding was exchanged for the position held on the venue.
+    Funding,
+}
+
+/// Something the venue executed for the maker, changing its position or its balance.
+#[derive(Debug, Clone, Copy)]
+pub struct Execution {
+    /// The id of the execution assigned by the venue, unique per execution.
+    pub execution_id: Uuid,
+    pub contract_symbol: ContractSymbol,
+    pub kind: ExecutionKind,
+    /// The number of contracts bought, or sold if negative. Always zero for funding.
+    pub contracts: i32,
+    pub price: Decimal,
+    /// The fee, or the funding, paid for the execution. Negative if it was received instead,
+    /// e.g. for a rebate.
+    pub fee_sats: i64,
+    pub timestamp: OffsetDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use time::ext::NumericalDuration;
+
+    #[test]
+    fn quote_from_now_is_not_old() {
+        let quote = dummy_quote_at(OffsetDateTime::now_utc());
+
+        let is_older = quote.is_older_than(1.minutes());
+
+        assert!(!is_older)
+    }
+
+    #[test]
+    fn quote_from_one_hour_ago_is_old() {
+        let quote = dummy_quote_at(OffsetDateTime::now_utc() - 1.hours());
+
+        let is_older = quote.is_older_than(1.minutes());
+
+        assert!(is_older)
+    }
+
+    fn dummy_quote_at(timestamp: OffsetDateTime) -> Quote {
+        Quote {
+            timestamp,
+            bid: dec!(10),
+            ask: dec!(10),
+            contract_symbol: ContractSymbol::BtcUsd,
+        }
+    }
+}
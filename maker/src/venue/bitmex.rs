@@ -0,0 +1,179 @@
+use crate::venue::Event;
+use crate::venue::Execution;
+use crate::venue::ExecutionKind;
+use crate::venue::HedgingVenue;
+use crate::venue::OrderRequest;
+use crate::venue::Side;
+use crate::venue::TimeInForce;
+use anyhow::Context;
+use anyhow::Result;
+use async_trait::async_trait;
+use bitcoin::Network;
+use bitmex_client::client::Client;
+use bitmex_stream::Credentials;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use time::OffsetDateTime;
+use trade::ContractSymbol;
+use uuid::Uuid;
+
+mod ws;
+
+/// Hedges on BitMEX, through its REST API and WebSocket API.
+pub struct Bitmex {
+    client: Client,
+    network: bitmex_stream::Network,
+    credentials: Option<Credentials>,
+}
+
+impl Bitmex {
+    /// Connects to BitMEX mainnet for [`Network::Bitcoin`] and to BitMEX testnet otherwise.
+    ///
+    /// Without credentials only the public quotes are available.
+    pub fn new(network: Network, api_key: Option<String>, api_secret: Option<String>) -> Self {
+        let (client, network) = match network {
+            Network::Bitcoin => (
+                Client::new(bitmex_client::models::Network::Mainnet),
+                bitmex_stream::Network::Mainnet,
+            ),
+            _ => (
+                Client::new(bitmex_client::models::Network::Testnet),
+                bitmex_stream::Network::Testnet,
+            ),
+        };
+
+        let (client, credentials) = match (api_key, api_secret) {
+            (Some(api_key), Some(secret)) => {
+                tracing::info!("BitMEX credentials provided");
+                (
+                    client.with_credentials(api_key.clone(), secret.clone()),
+                    Some(Credentials { api_key, secret }),
+                )
+            }
+            _ => {
+                tracing::info!("BitMEX credentials not provided");
+                (client, None)
+            }
+        };
+
+        Self {
+            client,
+            network,
+            credentials,
+        }
+    }
+}
+
+#[async_trait]
+impl HedgingVenue for Bitmex {
+    async fn place_order(&self, order: OrderRequest) -> Result<Uuid> {
+        let side = match order.side {
+            Side::Buy => bitmex_client::models::Side::Buy,
+            Side::Sell => bitmex_client::models::Side::Sell,
+        };
+        let time_in_force = match order.time_in_force {
+            TimeInForce::ImmediateOrCancel => bitmex_client::models::TimeInForce::ImmediateOrCancel,
+            TimeInForce::GoodTillCancel => bitmex_client::models::TimeInForce::GoodTillCancel,
+        };
+        let contracts = i32::try_from(order.contracts).context("Too many contracts")?;
+        let limit_price = order
+            .limit_price
+            .to_f64()
+            .context("Limit price does not fit into f64")?;
+
+        let order = self
+            .client
+            .create_limit_order(
+                bitmex_symbol(order.contract_symbol),
+                contracts,
+                side,
+                limit_price,
+                time_in_force,
+                None,
+            )
+            .await?;
+
+        Ok(order.order_id)
+    }
+
+    async fn cancel_order(&self, order_id: Uuid) -> Result<()> {
+        self.client.cancel_order(order_id).await?;
+
+        Ok(())
+    }
+
+    async fn position(&self, contract_symbol: ContractSymbol) -> Result<i32> {
+        let symbol = bitmex_symbol(contract_symbol);
+
+        let contracts = self
+            .client
+            .positions()
+            .await?
+            .into_iter()
+            .find(|position| position.symbol == symbol)
+            .and_then(|position| position.current_qty)
+            .unwrap_or_default();
+
+        i32::try_from(contracts).context("BitMEX position does not fit into i32")
+    }
+
+    /// Only the executions of XBTUSD are considered, like in the [`HedgingVenue::stream`].
+    async fn executions(&self, from: OffsetDateTime) -> Result<Vec<Execution>> {
+        let executions = self
+            .client
+            .trade_history(bitmex_client::models::ContractSymbol::XbtUsd, from)
+            .await?
+            .into_iter()
+            .filter_map(execution)
+            .collect();
+
+        Ok(executions)
+    }
+
+    fn stream(&self) -> BoxStream<'static, Result<Event>> {
+        ws::stream(self.network, self.credentials.clone()).boxed()
+    }
+}
+
+fn bitmex_symbol(contract_symbol: ContractSymbol) -> bitmex_client::models::ContractSymbol {
+    match contract_symbol {
+        ContractSymbol::BtcUsd => bitmex_client::models::ContractSymbol::XbtUsd,
+        ContractSymbol::EthUsd => bitmex_client::models::ContractSymbol::EthUsd,
+    }
+}
+
+/// Converts the execution of the trade history into an [`Execution`], like the executions
+/// received through the WebSocket API.
+fn execution(execution: bitmex_client::models::Execution) -> Option<Execution> {
+    let kind = match execution.exec_type {
+        bitmex_client::models::ExecType::Trade => ExecutionKind::Trade,
+        bitmex_client::models::ExecType::Funding => ExecutionKind::Funding,
+        bitmex_client::models::ExecType::Unknown => return None,
+    };
+
+    let contracts = match (kind, execution.side) {
+        (ExecutionKind::Funding, _) => 0,
+        (ExecutionKind::Trade, Some(bitmex_client::models::Side::Buy)) => execution.last_qty?,
+        (ExecutionKind::Trade, Some(bitmex_client::models::Side::Sell)) => -execution.last_qty?,
+        (ExecutionKind::Trade, _) => {
+            tracing::warn!(?execution, "Ignoring trade without side");
+            return None;
+        }
+    };
+
+    Some(Execution {
+        execution_id: execution.exec_id,
+        contract_symbol: match execution.symbol {
+            bitmex_client::models::ContractSymbol::XbtUsd => ContractSymbol::BtcUsd,
+            bitmex_client::models::ContractSymbol::EthUsd => ContractSymbol::EthUsd,
+        },
+        kind,
+        contracts: i32::try_from(contracts).ok()?,
+        price: Decimal::from_f64(execution.last_px?)?,
+        fee_sats: execution.exec_comm.unwrap_or_default(),
+        timestamp: execution.timestamp,
+    })
+}
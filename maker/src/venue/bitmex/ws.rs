@@ -1,3 +1,8 @@
+use crate::venue::Event;
+use crate::venue::Execution;
+use crate::venue::ExecutionKind;
+use crate::venue::Position;
+use crate::venue::Quote;
 use anyhow::anyhow;
 use anyhow::Error;
 use anyhow::Result;
@@ -7,12 +12,8 @@ use bitmex_stream::Network;
 use futures::Stream;
 use futures::StreamExt;
 use futures::TryStreamExt;
-use rust_decimal::Decimal;
-use std::fmt;
-use time::OffsetDateTime;
-use trade::ContractSymbol;
 
-pub async fn stream(
+pub fn stream(
     network: Network,
     credentials: Option<Credentials>,
 ) -> impl Stream<Item = Result<Event, Error>> + Unpin {
@@ -20,7 +21,11 @@ pub async fn stream(
         let mut stream = match credentials {
             Some(credentials) => {
                 bitmex_stream::subscribe_with_credentials(
-                    ["quoteBin1m:XBTUSD".to_owned(), "position:XBTUSD".to_owned()],
+                    [
+                        "quoteBin1m:XBTUSD".to_owned(),
+                        "position:XBTUSD".to_owned(),
+                        "execution:XBTUSD".to_owned(),
+                    ],
                     network,
                     credentials
                 ).boxed()
@@ -38,12 +43,11 @@ pub async fn stream(
                 Ok(Some(text)) => {
                     match serde_json::from_str::<wire::TableUpdate>(&text) {
                         Ok(update) => {
-                            let event = Event::from(update);
-
-                            tracing::debug!(?event, "Received new event");
-
-                            yield Ok(event);
+                            for event in events(update) {
+                                tracing::debug!(?event, "Received new event");
 
+                                yield Ok(event);
+                            }
                         }
                         Err(_) => {
                             tracing::debug!("Unexpected table update: {text}");
@@ -63,91 +67,57 @@ pub async fn stream(
     stream.boxed()
 }
 
-#[derive(Debug, Clone)]
-pub enum Event {
-    Quote(Quote),
-    Position(Position),
-}
-
-impl From<wire::TableUpdate> for Event {
-    fn from(value: wire::TableUpdate) -> Self {
-        match value {
-            wire::TableUpdate::QuoteBin1m(quote) => Self::Quote(Quote {
-                contract_symbol: quote.symbol.into(),
-                bid: quote.bid_price,
-                ask: quote.ask_price,
-                timestamp: quote.timestamp,
-            }),
-            wire::TableUpdate::Position(position) => Self::Position(Position {
-                contract_symbol: position.symbol.into(),
-                contracts: position.contracts,
-                timestamp: position.timestamp,
-            }),
-        }
-    }
-}
-
-#[derive(Clone, Copy)]
-pub struct Quote {
-    pub contract_symbol: ContractSymbol,
-    pub bid: Decimal,
-    pub ask: Decimal,
-    pub timestamp: OffsetDateTime,
-}
-
-impl fmt::Debug for Quote {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let rfc3339_timestamp = self
-            .timestamp
-            .format(&time::format_description::well_known::Rfc3339)
-            .expect("Timestamp to be formatted");
-
-        f.debug_struct("Quote")
-            .field("timestamp", &rfc3339_timestamp)
-            .field("bid", &self.bid)
-            .field("ask", &self.ask)
-            .field("contract_symbol", &self.contract_symbol)
-            .finish()
+fn events(update: wire::TableUpdate) -> Vec<Event> {
+    match update {
+        wire::TableUpdate::QuoteBin1m(quote) => vec![Event::Quote(Quote {
+            contract_symbol: quote.symbol.into(),
+            bid: quote.bid_price,
+            ask: quote.ask_price,
+            timestamp: quote.timestamp,
+        })],
+        wire::TableUpdate::Position(position) => vec![Event::Position(Position {
+            contract_symbol: position.symbol.into(),
+            contracts: position.contracts,
+            timestamp: position.timestamp,
+        })],
+        wire::TableUpdate::Execution(executions) => executions
+            .into_iter()
+            .filter_map(execution)
+            .map(Event::Execution)
+            .collect(),
     }
 }
 
-impl Quote {
-    pub fn bid(&self) -> Decimal {
-        self.bid
-    }
-
-    pub fn ask(&self) -> Decimal {
-        self.ask
-    }
-
-    #[allow(dead_code)]
-    pub fn is_older_than(&self, duration: time::Duration) -> bool {
-        let required_quote_timestamp = (OffsetDateTime::now_utc() - duration).unix_timestamp();
-
-        self.timestamp.unix_timestamp() < required_quote_timestamp
-    }
-}
+/// Converts the execution into an [`Execution`], if it is a trade or funding.
+///
+/// BitMEX also reports the placement, amendment and cancellation of orders as executions, which
+/// neither change the position nor the balance.
+fn execution(execution: wire::ExecutionData) -> Option<Execution> {
+    let kind = match execution.exec_type.as_str() {
+        "Trade" => ExecutionKind::Trade,
+        "Funding" => ExecutionKind::Funding,
+        _ => return None,
+    };
 
-#[derive(Clone, Copy)]
-pub struct Position {
-    pub contract_symbol: ContractSymbol,
-    pub contracts: i32,
-    pub timestamp: OffsetDateTime,
-}
+    let contracts = match (kind, execution.side.as_deref()) {
+        (ExecutionKind::Funding, _) => 0,
+        (ExecutionKind::Trade, Some("Buy")) => execution.last_qty?,
+        (ExecutionKind::Trade, Some("Sell")) => -execution.last_qty?,
+        (ExecutionKind::Trade, _) => {
+            tracing::warn!(?execution, "Ignoring trade without side");
+            return None;
+        }
+    };
 
-impl fmt::Debug for Position {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let rfc3339_timestamp = self
-            .timestamp
-            .format(&time::format_description::well_known::Rfc3339)
-            .expect("Timestamp to be formatted");
-
-        f.debug_struct("Quote")
-            .field("contract_symbol", &self.contract_symbol)
-            .field("contracts", &self.contracts)
-            .field("timestamp", &rfc3339_timestamp)
-            .finish()
-    }
+    Some(Execution {
+        execution_id: execution.exec_id,
+        contract_symbol: execution.symbol.into(),
+        kind,
+        contracts,
+        price: execution.last_px?,
+        fee_sats: execution.exec_comm.unwrap_or_default(),
+        timestamp: execution.timestamp,
+    })
 }
 
 mod wire {
@@ -156,11 +126,13 @@ mod wire {
     use serde::Deserialize;
     use serde::Deserializer;
     use time::OffsetDateTime;
+    use uuid::Uuid;
 
     #[derive(Debug)]
     pub enum TableUpdate {
         QuoteBin1m(QuoteData),
         Position(PositionData),
+        Execution(Vec<ExecutionData>),
     }
 
     #[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
@@ -186,16 +158,38 @@ mod wire {
         pub timestamp: OffsetDateTime,
     }
 
+    #[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ExecutionData {
+        #[serde(rename = "execID")]
+        pub exec_id: Uuid,
+        pub symbol: ContractSymbol,
+        /// Either `Buy` or `Sell`, but empty for funding.
+        pub side: Option<String>,
+        pub last_qty: Option<i32>,
+        #[serde(default, with = "rust_decimal::serde::float_option")]
+        pub last_px: Option<Decimal>,
+        pub exec_type: String,
+        /// The commission paid in satoshi, negative for rebates. For funding executions it is the
+        /// funding paid.
+        pub exec_comm: Option<i64>,
+        #[serde(with = "time::serde::rfc3339")]
+        pub timestamp: OffsetDateTime,
+    }
+
     #[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
     pub enum ContractSymbol {
         #[serde(rename = "XBTUSD")]
         XbtUsd,
+        #[serde(rename = "ETHUSD")]
+        EthUsd,
     }
 
     impl From<ContractSymbol> for trade::ContractSymbol {
         fn from(value: ContractSymbol) -> Self {
             match value {
                 ContractSymbol::XbtUsd => Self::BtcUsd,
+                ContractSymbol::EthUsd => Self::EthUsd,
             }
         }
     }
@@ -211,7 +205,9 @@ mod wire {
                 type Value = TableUpdate;
 
                 fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                    formatter.write_str("either a `QuoteBin1m` or a `Position` table update")
+                    formatter.write_str(
+                        "either a `QuoteBin1m`, a `Position` or an `Execution` table update",
+                    )
                 }
 
                 fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
@@ -222,6 +218,7 @@ mod wire {
                     enum TableUpdateKind {
                         QuoteBin1m,
                         Position,
+                        Execution,
                     }
 
                     let mut table = None;
@@ -237,6 +234,7 @@ mod wire {
                                 let value = match map.next_value()? {
                                     "quoteBin1m" => TableUpdateKind::QuoteBin1m,
                                     "position" => TableUpdateKind::Position,
+                                    "execution" => TableUpdateKind::Execution,
                                     _ => return Err(serde::de::Error::custom("unexpected table")),
                                 };
 
@@ -280,6 +278,15 @@ mod wire {
                             })?[0]
                                 .clone(),
                         ),
+                        TableUpdateKind::Execution => TableUpdate::Execution(
+                            serde_json::from_str::<Vec<ExecutionData>>(data.get()).map_err(
+                                |e| {
+                                    serde::de::Error::custom(format!(
+                                        "could not deserialize execution data: {e}"
+                                    ))
+                                },
+                            )?,
+                        ),
                     };
 
                     Ok(value)
@@ -295,7 +302,6 @@ mod wire {
 mod tests {
     use super::*;
     use rust_decimal_macros::dec;
-    use time::ext::NumericalDuration;
 
     #[test]
     fn can_deserialize_quote_update() {
@@ -340,29 +346,27 @@ mod tests {
     }
 
     #[test]
-    fn quote_from_now_is_not_old() {
-        let quote = dummy_quote_at(OffsetDateTime::now_utc());
-
-        let is_older = quote.is_older_than(1.minutes());
-
-        assert!(!is_older)
-    }
-
-    #[test]
-    fn quote_from_one_hour_ago_is_old() {
-        let quote = dummy_quote_at(OffsetDateTime::now_utc() - 1.hours());
-
-        let is_older = quote.is_older_than(1.minutes());
-
-        assert!(is_older)
-    }
-
-    fn dummy_quote_at(timestamp: OffsetDateTime) -> Quote {
-        Quote {
-            timestamp,
-            bid: dec!(10),
-            ask: dec!(10),
-            contract_symbol: trade::ContractSymbol::BtcUsd,
-        }
+    fn can_deserialize_trade_and_funding_executions() {
+        let table_update = serde_json::from_str(r#"{"table":"execution","action":"insert","data":[{"execID":"0d4c5e2e-7c4b-4d6f-9d1c-0f3a5d1c9e11","orderID":"3b6d1f8e-2a6c-4f8b-8a0e-4c5d2e7f9a10","symbol":"XBTUSD","side":"Sell","lastQty":100,"lastPx":42640.5,"execType":"Trade","ordStatus":"Filled","execComm":18,"timestamp":"2023-10-05T17:36:45.781Z"},{"execID":"7e1f0a9b-5c3d-4e2f-8b1a-6d4c3b2a1f00","orderID":"00000000-0000-0000-0000-000000000000","symbol":"XBTUSD","side":"","lastQty":-100,"lastPx":42700,"execType":"Funding","execComm":-2,"timestamp":"2023-10-05T20:00:00.000Z"},{"execID":"9a8b7c6d-5e4f-4a3b-2c1d-0e9f8a7b6c5d","orderID":"3b6d1f8e-2a6c-4f8b-8a0e-4c5d2e7f9a10","symbol":"XBTUSD","side":"Sell","lastQty":null,"lastPx":null,"execType":"New","ordStatus":"New","execComm":null,"timestamp":"2023-10-05T17:36:45.700Z"}]}"#).unwrap();
+
+        let executions = events(table_update)
+            .into_iter()
+            .map(|event| match event {
+                Event::Execution(execution) => execution,
+                event => panic!("Unexpected event {event:?}"),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(executions.len(), 2);
+
+        assert_eq!(executions[0].kind, ExecutionKind::Trade);
+        assert_eq!(executions[0].contracts, -100);
+        assert_eq!(executions[0].price, dec!(42640.5));
+        assert_eq!(executions[0].fee_sats, 18);
+
+        assert_eq!(executions[1].kind, ExecutionKind::Funding);
+        assert_eq!(executions[1].contracts, 0);
+        assert_eq!(executions[1].fee_sats, -2);
+        assert_eq!(executions[1].timestamp.unix_timestamp(), 1696536000);
     }
 }
@@ -0,0 +1,393 @@
+use crate::venue::Event;
+use crate::venue::Execution;
+use crate::venue::ExecutionKind;
+use crate::venue::HedgingVenue;
+use crate::venue::OrderRequest;
+use crate::venue::Position;
+use crate::venue::Quote;
+use crate::venue::Side;
+use crate::venue::TimeInForce;
+use anyhow::bail;
+use anyhow::Result;
+use async_stream::stream;
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use trade::ContractSymbol;
+use uuid::Uuid;
+
+/// The number of events buffered for slow subscribers before they start missing events.
+const EVENT_BUFFER: usize = 100;
+
+/// An in-process venue for paper trading, filling orders against the quotes it is fed.
+///
+/// Buy orders are filled at the ask and sell orders at the bid, as long as that price is within
+/// the limit of the order. Order ids are assigned sequentially, so that a sequence of quotes and
+/// orders always leads to the same fills. Execution ids are random instead, as the recorded fills
+/// have to remain unique across restarts.
+pub struct Paper {
+    state: Mutex<State>,
+    events: broadcast::Sender<Event>,
+}
+
+#[derive(Default)]
+struct State {
+    quotes: HashMap<ContractSymbol, Quote>,
+    positions: HashMap<ContractSymbol, i32>,
+    /// The orders waiting for the quotes to reach their limit price, by order id.
+    resting_orders: BTreeMap<Uuid, OrderRequest>,
+    /// All executions since the venue was started, oldest first.
+    executions: Vec<Execution>,
+    order_count: u128,
+}
+
+impl Default for Paper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Paper {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+
+        Self {
+            state: Mutex::new(State::default()),
+            events,
+        }
+    }
+
+    /// Updates the quote of the contract symbol, filling the resting orders it crosses.
+    pub fn set_quote(&self, quote: Quote) {
+        let fills = {
+            let mut state = self.state.lock().expect("paper state to be available");
+            state.quotes.insert(quote.contract_symbol, quote);
+
+            let crossed = state
+                .resting_orders
+                .iter()
+                .filter(|(_, order)| {
+                    order.contract_symbol == quote.contract_symbol && crosses(order, &quote)
+                })
+                .map(|(order_id, _)| *order_id)
+                .collect::<Vec<_>>();
+
+            let mut fills = Vec::new();
+            for order_id in crossed {
+                if let Some(order) = state.resting_orders.remove(&order_id) {
+                    fills.push(state.fill(order, quote));
+                }
+            }
+
+            fills
+        };
+
+        let _ = self.events.send(Event::Quote(quote));
+        for fill in fills {
+            self.publish(fill);
+        }
+    }
+
+    fn publish(&self, (position, execution): (Position, Execution)) {
+        let _ = self.events.send(Event::Position(position));
+        let _ = self.events.send(Event::Execution(execution));
+    }
+
+    /// Feeds the quotes streamed by another venue into this one, e.g. to paper trade against
+    /// the quotes of BitMEX.
+    ///
+    /// In case the stream is closed, it is resubscribed to after `reconnect_after`.
+    pub async fn follow_quotes(
+        self: Arc<Self>,
+        source: Arc<dyn HedgingVenue>,
+        reconnect_after: Duration,
+    ) {
+        loop {
+            let mut stream = source.stream();
+            while let Some(event) = stream.next().await {
+                match event {
+                    Ok(Event::Quote(quote)) => self.set_quote(quote),
+                    Ok(Event::Position(_) | Event::Execution(_)) => {}
+                    Err(e) => {
+                        tracing::error!("Failed to receive quotes for paper trading: {e:#}");
+                        break;
+                    }
+                }
+            }
+
+            tokio::time::sleep(reconnect_after).await;
+        }
+    }
+}
+
+impl State {
+    fn next_order_id(&mut self) -> Uuid {
+        self.order_count += 1;
+        Uuid::from_u128(self.order_count)
+    }
+
+    /// Fills the whole order at the price of the quote, returning the updated position and the
+    /// execution of the order.
+    ///
+    /// Paper trading is free of fees.
+    fn fill(&mut self, order: OrderRequest, quote: Quote) -> (Position, Execution) {
+        let contracts = order.contracts as i32;
+        let (contracts, price) = match order.side {
+            Side::Buy => (contracts, quote.ask),
+            Side::Sell => (-contracts, quote.bid),
+        };
+
+        let position = self.positions.entry(order.contract_symbol).or_default();
+        *position += contracts;
+
+        tracing::info!(?order, position = *position, "Filled paper order");
+
+        let position = Position {
+            contract_symbol: order.contract_symbol,
+            contracts: *position,
+            timestamp: quote.timestamp,
+        };
+        let execution = Execution {
+            execution_id: Uuid::new_v4(),
+            contract_symbol: order.contract_symbol,
+            kind: ExecutionKind::Trade,
+            contracts,
+            price,
+            fee_sats: 0,
+            timestamp: quote.timestamp,
+        };
+        self.executions.push(execution);
+
+        (position, execution)
+    }
+}
+
+#[async_trait]
+impl HedgingVenue for Paper {
+    async fn place_order(&self, order: OrderRequest) -> Result<Uuid> {
+        let (order_id, fill) = {
+            let mut state = self.state.lock().expect("paper state to be available");
+            let order_id = state.next_order_id();
+
+            let quote = state
+                .quotes
+                .get(&order.contract_symbol)
+                .copied()
+                .filter(|quote| crosses(&order, quote));
+
+            match (quote, order.time_in_force) {
+                (Some(quote), _) => (order_id, Some(state.fill(order, quote))),
+                (None, TimeInForce::GoodTillCancel) => {
+                    state.resting_orders.insert(order_id, order);
+                    (order_id, None)
+                }
+                (None, TimeInForce::ImmediateOrCancel) => {
+                    tracing::info!(?order, "Cancelled paper order which cannot be filled");
+                    (order_id, None)
+                }
+            }
+        };
+
+        if let Some(fill) = fill {
+            self.publish(fill);
+        }
+
+        Ok(order_id)
+    }
+
+    async fn cancel_order(&self, order_id: Uuid) -> Result<()> {
+        let mut state = self.state.lock().expect("paper state to be available");
+        if state.resting_orders.remove(&order_id).is_none() {
+            bail!("No open paper order {order_id}");
+        }
+
+        Ok(())
+    }
+
+    async fn position(&self, contract_symbol: ContractSymbol) -> Result<i32> {
+        let state = self.state.lock().expect("paper state to be available");
+        let contracts = state
+            .positions
+            .get(&contract_symbol)
+            .copied()
+            .unwrap_or_default();
+
+        Ok(contracts)
+    }
+
+    async fn executions(&self, from: OffsetDateTime) -> Result<Vec<Execution>> {
+        let state = self.state.lock().expect("paper state to be available");
+        let executions = state
+            .executions
+            .iter()
+            .filter(|execution| execution.timestamp >= from)
+            .copied()
+            .collect();
+
+        Ok(executions)
+    }
+
+    fn stream(&self) -> BoxStream<'static, Result<Event>> {
+        let mut events = self.events.subscribe();
+
+        let stream = stream! {
+            loop {
+                match events.recv().await {
+                    Ok(event) => yield Ok(event),
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped, "Missed paper trading events");
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        };
+
+        stream.boxed()
+    }
+}
+
+/// Whether the order can be filled at the quote without exceeding its limit price.
+fn crosses(order: &OrderRequest, quote: &Quote) -> bool {
+    match order.side {
+        Side::Buy => quote.ask <= order.limit_price,
+        Side::Sell => quote.bid >= order.limit_price,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    fn quote(bid: Decimal, ask: Decimal) -> Quote {
+        Quote {
+            contract_symbol: ContractSymbol::BtcUsd,
+            bid,
+            ask,
+            timestamp: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+
+    fn order(side: Side, limit_price: Decimal, time_in_force: TimeInForce) -> OrderRequest {
+        OrderRequest {
+            contract_symbol: ContractSymbol::BtcUsd,
+            side,
+            contracts: 100,
+            limit_price,
+            time_in_force,
+        }
+    }
+
+    #[tokio::test]
+    async fn crossing_order_is_filled_immediately() {
+        let paper = Paper::new();
+        paper.set_quote(quote(dec!(39_990), dec!(40_010)));
+
+        paper
+            .place_order(order(
+                Side::Buy,
+                dec!(40_010),
+                TimeInForce::ImmediateOrCancel,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(paper.position(ContractSymbol::BtcUsd).await.unwrap(), 100);
+
+        paper
+            .place_order(order(
+                Side::Sell,
+                dec!(39_900),
+                TimeInForce::ImmediateOrCancel,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(paper.position(ContractSymbol::BtcUsd).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn immediate_or_cancel_order_beyond_the_quote_is_cancelled() {
+        let paper = Paper::new();
+        paper.set_quote(quote(dec!(39_990), dec!(40_010)));
+
+        let order_id = paper
+            .place_order(order(
+                Side::Buy,
+                dec!(40_000),
+                TimeInForce::ImmediateOrCancel,
+            ))
+            .await
+            .unwrap();
+        paper.set_quote(quote(dec!(39_980), dec!(40_000)));
+
+        assert_eq!(paper.position(ContractSymbol::BtcUsd).await.unwrap(), 0);
+        assert!(paper.cancel_order(order_id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn resting_order_is_filled_once_the_quote_crosses_it() {
+        let paper = Paper::new();
+        paper.set_quote(quote(dec!(39_990), dec!(40_010)));
+        let mut events = paper.stream();
+
+        paper
+            .place_order(order(Side::Sell, dec!(40_100), TimeInForce::GoodTillCancel))
+            .await
+            .unwrap();
+        assert_eq!(paper.position(ContractSymbol::BtcUsd).await.unwrap(), 0);
+
+        paper.set_quote(quote(dec!(40_100), dec!(40_120)));
+        assert_eq!(paper.position(ContractSymbol::BtcUsd).await.unwrap(), -100);
+
+        assert!(matches!(events.next().await, Some(Ok(Event::Quote(_)))));
+        assert!(matches!(
+            events.next().await,
+            Some(Ok(Event::Position(Position {
+                contracts: -100,
+                ..
+            })))
+        ));
+        let execution = match events.next().await {
+            Some(Ok(Event::Execution(execution))) => execution,
+            event => panic!("Expected an execution, got {event:?}"),
+        };
+        assert_eq!(execution.contracts, -100);
+        assert_eq!(execution.price, dec!(40_100));
+        assert_eq!(execution.fee_sats, 0);
+
+        let executions = paper.executions(OffsetDateTime::UNIX_EPOCH).await.unwrap();
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].execution_id, execution.execution_id);
+    }
+
+    #[tokio::test]
+    async fn cancelled_order_is_not_filled() {
+        let paper = Paper::new();
+
+        let order_id = paper
+            .place_order(order(Side::Buy, dec!(40_000), TimeInForce::GoodTillCancel))
+            .await
+            .unwrap();
+        paper.cancel_order(order_id).await.unwrap();
+        paper.set_quote(quote(dec!(39_000), dec!(39_010)));
+
+        assert_eq!(paper.position(ContractSymbol::BtcUsd).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn order_ids_are_deterministic() {
+        let paper = Paper::new();
+        let order = order(Side::Buy, dec!(40_000), TimeInForce::GoodTillCancel);
+
+        assert_eq!(paper.place_order(order).await.unwrap(), Uuid::from_u128(1));
+        assert_eq!(paper.place_order(order).await.unwrap(), Uuid::from_u128(2));
+    }
+}
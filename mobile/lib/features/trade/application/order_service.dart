@@ -14,7 +14,8 @@ class OrderService {
         contractSymbol: contractSymbol.toApi(),
         direction: direction.toApi(),
         orderType: const rust.OrderType.market(),
-        stable: stable);
+        stable: stable,
+        timeInForce: const rust.TimeInForce.fillOrKill());
 
     return await rust.api.submitOrder(order: order);
   }
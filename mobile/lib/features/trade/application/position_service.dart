@@ -20,6 +20,7 @@ class PositionService {
       ask: price.ask!,
     );
     return rust.api.calculatePnl(
+        contractSymbol: position.contractSymbol.toApi(),
         openingPrice: position.averageEntryPrice,
         closingPrice: closingPrice,
         quantity: position.quantity.asDouble(),
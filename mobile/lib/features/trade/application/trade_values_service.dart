@@ -1,4 +1,5 @@
 import 'package:get_10101/common/domain/model.dart';
+import 'package:get_10101/features/trade/domain/contract_symbol.dart';
 import 'package:get_10101/features/trade/domain/direction.dart';
 import 'package:get_10101/features/trade/domain/leverage.dart';
 import 'package:get_10101/ffi.dart' as rust;
@@ -8,22 +9,33 @@ class TradeValuesService {
       {required double? price,
       required Amount? quantity,
       required Leverage leverage,
+      ContractSymbol contractSymbol = ContractSymbol.btcusd,
       dynamic hint}) {
     if (price == null || quantity == null) {
       return null;
     } else {
       return Amount(rust.api.calculateMargin(
-          price: price, quantity: quantity.asDouble(), leverage: leverage.leverage));
+          contractSymbol: contractSymbol.toApi(),
+          price: price,
+          quantity: quantity.asDouble(),
+          leverage: leverage.leverage));
     }
   }
 
   Amount? calculateQuantity(
-      {required double? price, required Amount? margin, required Leverage leverage, dynamic hint}) {
+      {required double? price,
+      required Amount? margin,
+      required Leverage leverage,
+      ContractSymbol contractSymbol = ContractSymbol.btcusd,
+      dynamic hint}) {
     if (price == null || margin == null) {
       return null;
     } else {
-      final quantity = rust.api
-          .calculateQuantity(price: price, margin: margin.sats, leverage: leverage.leverage);
+      final quantity = rust.api.calculateQuantity(
+          contractSymbol: contractSymbol.toApi(),
+          price: price,
+          margin: margin.sats,
+          leverage: leverage.leverage);
       return Amount(quantity.ceil());
     }
   }
@@ -32,12 +44,16 @@ class TradeValuesService {
       {required double? price,
       required Leverage leverage,
       required Direction direction,
+      ContractSymbol contractSymbol = ContractSymbol.btcusd,
       dynamic hint}) {
     if (price == null) {
       return null;
     } else {
       return rust.api.calculateLiquidationPrice(
-          price: price, leverage: leverage.leverage, direction: direction.toApi());
+          contractSymbol: contractSymbol.toApi(),
+          price: price,
+          leverage: leverage.leverage,
+          direction: direction.toApi());
     }
   }
 
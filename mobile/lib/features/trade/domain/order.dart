@@ -6,7 +6,10 @@ import 'package:get_10101/features/trade/domain/leverage.dart';
 
 enum OrderReason {
   manual,
-  expired;
+  expired,
+  stopLoss,
+  takeProfit,
+  liquidated;
 
   static OrderReason fromApi(bridge.OrderReason orderReason) {
     switch (orderReason) {
@@ -14,6 +17,12 @@ enum OrderReason {
         return OrderReason.manual;
       case bridge.OrderReason.Expired:
         return OrderReason.expired;
+      case bridge.OrderReason.StopLoss:
+        return OrderReason.stopLoss;
+      case bridge.OrderReason.TakeProfit:
+        return OrderReason.takeProfit;
+      case bridge.OrderReason.Liquidated:
+        return OrderReason.liquidated;
     }
   }
 
@@ -94,7 +94,7 @@ class PositionChangeNotifier extends ChangeNotifier implements Subscriber {
     if (event is bridge.Event_PositionUpdateNotification) {
       Position position = Position.fromApi(event.field0);
 
-      if (price != null) {
+      if (price != null && position.contractSymbol == ContractSymbol.btcusd) {
         final pnl = _positionService.calculatePnl(position, price!);
         position.unrealizedPnl = pnl != null ? Amount(pnl) : null;
       } else {
@@ -119,7 +119,8 @@ class PositionChangeNotifier extends ChangeNotifier implements Subscriber {
     } else if (event is bridge.Event_PriceUpdateNotification) {
       price = Price.fromApi(event.field0);
       for (ContractSymbol symbol in positions.keys) {
-        if (price != null) {
+        // the price update only contains the BTCUSD price
+        if (price != null && symbol == ContractSymbol.btcusd) {
           if (positions[symbol] != null) {
             final pnl = _positionService.calculatePnl(positions[symbol]!, price!);
             positions[symbol]!.unrealizedPnl = pnl != null ? Amount(pnl) : null;
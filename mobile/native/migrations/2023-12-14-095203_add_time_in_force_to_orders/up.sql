@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE
+    orders
+    ADD
+        COLUMN "time_in_force" TEXT NOT NULL DEFAULT 'GoodTillDate';
@@ -1,3 +1,4 @@
+pub use crate::backup::target::BackupTarget;
 use crate::calculations;
 use crate::commons::api::ChannelInfo;
 use crate::commons::api::Price;
@@ -144,12 +145,32 @@ pub enum Status {
     Failed,
 }
 
-pub fn calculate_margin(price: f32, quantity: f32, leverage: f32) -> SyncReturn<u64> {
-    SyncReturn(calculations::calculate_margin(price, quantity, leverage))
+pub fn calculate_margin(
+    contract_symbol: ContractSymbol,
+    price: f32,
+    quantity: f32,
+    leverage: f32,
+) -> SyncReturn<u64> {
+    SyncReturn(calculations::calculate_margin(
+        contract_symbol,
+        price,
+        quantity,
+        leverage,
+    ))
 }
 
-pub fn calculate_quantity(price: f32, margin: u64, leverage: f32) -> SyncReturn<f32> {
-    SyncReturn(calculations::calculate_quantity(price, margin, leverage))
+pub fn calculate_quantity(
+    contract_symbol: ContractSymbol,
+    price: f32,
+    margin: u64,
+    leverage: f32,
+) -> SyncReturn<f32> {
+    SyncReturn(calculations::calculate_quantity(
+        contract_symbol,
+        price,
+        margin,
+        leverage,
+    ))
 }
 
 #[allow(dead_code)]
@@ -157,6 +178,7 @@ pub fn calculate_quantity(price: f32, margin: u64, leverage: f32) -> SyncReturn<
 #[derive(Debug, Clone, Copy)]
 pub enum _ContractSymbol {
     BtcUsd,
+    EthUsd,
 }
 
 #[allow(dead_code)]
@@ -167,17 +189,40 @@ pub enum _Direction {
     Short,
 }
 
+#[allow(dead_code)]
+#[frb(mirror(BackupTarget))]
+#[derive(Debug, Clone)]
+pub enum _BackupTarget {
+    Coordinator,
+    Directory {
+        path: String,
+    },
+    WebDav {
+        url: String,
+        username: Option<String>,
+        password: Option<String>,
+    },
+    BackupServer {
+        url: String,
+    },
+}
+
 pub fn calculate_liquidation_price(
+    contract_symbol: ContractSymbol,
     price: f32,
     leverage: f32,
     direction: Direction,
 ) -> SyncReturn<f32> {
     SyncReturn(calculations::calculate_liquidation_price(
-        price, leverage, direction,
+        contract_symbol,
+        price,
+        leverage,
+        direction,
     ))
 }
 
 pub fn calculate_pnl(
+    contract_symbol: ContractSymbol,
     opening_price: f32,
     closing_price: Price,
     quantity: f32,
@@ -188,6 +233,7 @@ pub fn calculate_pnl(
 
     SyncReturn(
         calculations::calculate_pnl(
+            contract_symbol,
             opening_price,
             closing_price.into(),
             quantity,
@@ -213,7 +259,7 @@ pub fn order_matching_fee(quantity: f32, price: f32) -> SyncReturn<u64> {
 
 #[tokio::main(flavor = "current_thread")]
 pub async fn submit_order(order: NewOrder) -> Result<String> {
-    order::handler::submit_order(order.into())
+    order::handler::submit_order(order.try_into()?)
         .await
         .map(|id| id.to_string())
 }
@@ -246,6 +292,12 @@ pub async fn get_positions() -> Result<Vec<Position>> {
     Ok(positions)
 }
 
+/// Sets the stop loss and take profit of the open position. Passing `None` removes the trigger.
+#[tokio::main(flavor = "current_thread")]
+pub async fn set_position_triggers(stop_loss: Option<f32>, take_profit: Option<f32>) -> Result<()> {
+    position::handler::set_position_triggers(stop_loss, take_profit).await
+}
+
 pub fn delete_network_graph() -> Result<()> {
     crate::state::get_storage()
         .ln_storage
@@ -382,6 +434,28 @@ pub fn force_close_channel() -> Result<()> {
     ln_dlc::close_channel(true)
 }
 
+/// Exports the static channel backup, which allows to recover the channel funds even if all other
+/// backups are lost.
+///
+/// The backup is encrypted with the node key and only needs to be exported again after a channel
+/// has been opened.
+pub fn export_static_channel_backup() -> Result<String> {
+    ln_dlc::export_static_channel_backup()
+}
+
+/// Sweeps the channel funds of the given static channel backup into the on-chain wallet once the
+/// coordinator has closed the channels.
+///
+/// Note, the channels are not closed on request, the recovery relies on the coordinator
+/// force-closing them on its own after failing to re-establish them.
+///
+/// Only use this if the channel state has been lost, e.g. after restoring from the seed phrase
+/// without a backup.
+pub fn recover_from_static_channel_backup(backup: String) -> Result<()> {
+    let runtime = crate::state::get_or_create_tokio_runtime()?;
+    runtime.block_on(async { ln_dlc::recover_from_static_channel_backup(backup).await })
+}
+
 /// Returns channel info if we have a channel available already
 ///
 /// If no channel is established with the coordinator `None` is returned.
@@ -523,10 +597,39 @@ pub async fn restore_from_seed_phrase(
 ) -> Result<()> {
     let file_path = PathBuf::from(target_seed_file_path);
     tracing::info!("Restoring seed from phrase to {:?}", file_path);
-    ln_dlc::restore_from_mnemonic(&seed_phrase, file_path.as_path()).await?;
+    ln_dlc::restore_from_mnemonic(&seed_phrase, file_path.as_path(), None).await?;
+    Ok(())
+}
+
+/// Restores the wallet from the seed phrase and the most recent complete backup of the given
+/// targets.
+///
+/// The targets are kept for all future backups.
+#[tokio::main(flavor = "current_thread")]
+pub async fn restore_from_seed_phrase_with_backup_targets(
+    seed_phrase: String,
+    target_seed_file_path: String,
+    backup_targets: Vec<BackupTarget>,
+) -> Result<()> {
+    let file_path = PathBuf::from(target_seed_file_path);
+    tracing::info!("Restoring seed from phrase to {:?}", file_path);
+    ln_dlc::restore_from_mnemonic(&seed_phrase, file_path.as_path(), Some(backup_targets)).await?;
     Ok(())
 }
 
+/// Returns the targets the backups are uploaded to.
+pub fn get_backup_targets() -> Vec<BackupTarget> {
+    get_storage().client.targets()
+}
+
+/// Replaces the targets the backups are uploaded to.
+///
+/// Existing backups are not copied to new targets, hence consider running a [`full_backup`]
+/// afterwards.
+pub fn set_backup_targets(backup_targets: Vec<BackupTarget>) -> Result<()> {
+    get_storage().set_backup_targets(backup_targets)
+}
+
 pub fn init_new_mnemonic(target_seed_file_path: String) -> Result<()> {
     let file_path = PathBuf::from(target_seed_file_path);
     tracing::info!("Creating a new seed in {:?}", file_path);
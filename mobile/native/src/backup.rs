@@ -1,3 +1,8 @@
+use crate::backup::manifest::split_manifest;
+use crate::backup::manifest::BackupManifest;
+use crate::backup::manifest::MANIFEST_BACKUP_KEY;
+use crate::backup::manifest::MANIFEST_BACKUP_NAME;
+use crate::backup::target::BackupTarget;
 use crate::cipher::AesCipher;
 use crate::config;
 use crate::db;
@@ -6,7 +11,9 @@ use crate::event::EventInternal;
 use crate::event::EventType;
 use anyhow::bail;
 use anyhow::ensure;
+use anyhow::Context;
 use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
 use commons::Backup;
 use commons::DeleteBackup;
 use commons::Restore;
@@ -14,14 +21,19 @@ use futures::future::RemoteHandle;
 use futures::FutureExt;
 use ln_dlc_storage::sled::SledStorageProvider;
 use ln_dlc_storage::DlcStoreProvider;
+use parking_lot::RwLock;
 use reqwest::Client;
-use reqwest::StatusCode;
 use std::fs;
 use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::Mutex;
 use tokio::task::spawn_blocking;
 
+pub mod manifest;
+pub mod target;
+
 const BLACKLIST: [&str; 1] = ["ln/network_graph"];
 
 pub const DB_BACKUP_KEY: &str = "10101";
@@ -29,6 +41,9 @@ pub const LN_BACKUP_KEY: &str = "ln";
 pub const DLC_BACKUP_KEY: &str = "dlc";
 pub const DB_BACKUP_NAME: &str = "db";
 
+/// The file in the data dir of the network holding the manifest of the latest backup.
+const MANIFEST_FILE: &str = "backup_manifest.json";
+
 #[derive(Clone)]
 pub struct DBBackupSubscriber {
     client: RemoteBackupClient,
@@ -82,49 +97,79 @@ pub struct RemoteBackupClient {
     inner: Client,
     endpoint: String,
     cipher: AesCipher,
+    targets: Arc<RwLock<Vec<BackupTarget>>>,
+    /// The manifest of the latest backup, kept up-to-date with every backed up key.
+    ///
+    /// The manifest is `None` until the first full backup.
+    manifest: Arc<Mutex<Option<BackupManifest>>>,
+    manifest_path: PathBuf,
 }
 
 impl RemoteBackupClient {
-    pub fn new(cipher: AesCipher) -> RemoteBackupClient {
+    /// Creates the client, loading the manifest of the latest backup from the data dir.
+    pub fn new(
+        cipher: AesCipher,
+        targets: Vec<BackupTarget>,
+        data_dir: &Path,
+    ) -> RemoteBackupClient {
         let inner = Client::builder()
             .timeout(Duration::from_secs(30))
             .build()
             .expect("Could not build reqwest client");
 
+        let manifest_path = data_dir.join(MANIFEST_FILE);
+        let manifest = BackupManifest::load(&manifest_path).unwrap_or_else(|e| {
+            tracing::error!("Failed to load backup manifest. {e:#}");
+            None
+        });
+
         Self {
             inner,
             endpoint: format!("http://{}/api", config::get_http_endpoint()),
             cipher,
+            targets: Arc::new(RwLock::new(targets)),
+            manifest: Arc::new(Mutex::new(manifest)),
+            manifest_path,
         }
     }
+
+    pub fn cipher(&self) -> &AesCipher {
+        &self.cipher
+    }
+
+    pub fn targets(&self) -> Vec<BackupTarget> {
+        self.targets.read().clone()
+    }
+
+    pub fn set_targets(&self, targets: Vec<BackupTarget>) {
+        tracing::info!(
+            targets = targets
+                .iter()
+                .map(|target| target.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            "Updating backup targets"
+        );
+        *self.targets.write() = targets;
+    }
 }
 
 impl RemoteBackupClient {
     pub fn delete(&self, key: String) -> RemoteHandle<()> {
         let (fut, remote_handle) = {
-            let client = self.inner.clone();
-            let node_id = self.cipher.public_key();
-            let endpoint = format!("{}/backup/{}", self.endpoint.clone(), node_id);
-            let cipher = self.cipher.clone();
-            let message = node_id.to_string().as_bytes().to_vec();
+            let client = self.clone();
             async move {
-                let signature = match cipher.sign(message) {
-                    Ok(signature) => signature,
-                    Err(e) => {
-                        tracing::error!(%key, "{e:#}");
-                        return;
-                    }
-                };
-
-                let backup = DeleteBackup {
-                    key: key.clone(),
-                    signature,
-                };
+                client.delete_from_targets(&key).await;
 
-                if let Err(e) = client.delete(endpoint).json(&backup).send().await {
-                    tracing::error!("Failed to delete backup of {key}. {e:#}")
-                } else {
-                    tracing::debug!("Successfully deleted backup of {key}");
+                if let Err(e) = client
+                    .update_manifest(|manifest| {
+                        if let Some(manifest) = manifest {
+                            manifest.remove(&key);
+                        }
+                    })
+                    .await
+                {
+                    tracing::error!("Failed to update backup manifest after deleting {key}. {e:#}");
                 }
             }
         }
@@ -137,55 +182,62 @@ impl RemoteBackupClient {
         remote_handle
     }
 
+    /// Whether a manifest has been created, i.e. whether there has been a full backup.
+    pub async fn has_manifest(&self) -> bool {
+        self.manifest.lock().await.is_some()
+    }
+
+    /// Backs up the key and updates the manifest accordingly.
     pub fn backup(&self, key: String, value: Vec<u8>) -> RemoteHandle<()> {
+        self.spawn_backup(key, value, true)
+    }
+
+    /// Backs up all keys of the full backup, followed by its manifest.
+    ///
+    /// The manifest is uploaded last so that it only describes data which has already been backed
+    /// up.
+    pub async fn full_backup(&self, backup: &[(String, Vec<u8>)]) -> Result<()> {
+        let handles = backup
+            .iter()
+            .map(|(key, value)| self.spawn_backup(key.clone(), value.clone(), false))
+            .collect::<Vec<_>>();
+        futures::future::join_all(handles).await;
+
+        let manifest = BackupManifest::new(backup);
+        self.update_manifest(|latest| *latest = Some(manifest))
+            .await
+    }
+
+    fn spawn_backup(&self, key: String, value: Vec<u8>, update_manifest: bool) -> RemoteHandle<()> {
         tracing::trace!("Creating backup for {key}");
         let (fut, remote_handle) = {
-            let client = self.inner.clone();
-            let cipher = self.cipher.clone();
-            let node_id = cipher.public_key();
-            let endpoint = format!("{}/backup/{}", self.endpoint.clone(), node_id);
+            let client = self.clone();
             async move {
                 if BLACKLIST.contains(&key.as_str()) {
                     tracing::debug!(key, "Skipping blacklisted backup");
                     return;
                 }
 
-                let encrypted_value = match cipher.encrypt(value) {
-                    Ok(encrypted_value) => encrypted_value,
-                    Err(e) => {
-                        tracing::error!(%key, "{e:#}");
-                        return;
-                    }
-                };
-                let signature = match cipher.sign(encrypted_value.clone()) {
-                    Ok(signature) => signature,
-                    Err(e) => {
-                        tracing::error!(%key, "{e:#}");
-                        return;
-                    }
-                };
-
-                let backup = Backup {
-                    key: key.clone(),
-                    value: encrypted_value,
-                    signature,
-                };
-
-                match client.post(endpoint).json(&backup).send().await {
-                    Ok(response) => {
-                        tracing::debug!("Response status code {}", response.status());
-                        if response.status() != StatusCode::OK {
-                            match response.text().await {
-                                Ok(response) => {
-                                    tracing::error!("Failed to upload backup. {response}")
-                                }
-                                Err(e) => tracing::error!("Failed to upload backup. {e}"),
-                            }
-                        } else {
-                            tracing::debug!("Successfully uploaded backup of {key}.");
+                if let Err(e) = client.upload(&key, value.clone()).await {
+                    tracing::error!(%key, "{e:#}");
+                    return;
+                }
+
+                if !update_manifest {
+                    return;
+                }
+
+                if let Err(e) = client
+                    .update_manifest(|manifest| {
+                        if let Some(manifest) = manifest {
+                            manifest.insert(&key, &value);
                         }
-                    }
-                    Err(e) => tracing::error!("Failed to create a backup of {key}. {e:#}"),
+                    })
+                    .await
+                {
+                    tracing::error!(
+                        "Failed to update backup manifest after backing up {key}. {e:#}"
+                    );
                 }
             }
         }
@@ -198,6 +250,103 @@ impl RemoteBackupClient {
         remote_handle
     }
 
+    /// Encrypts, signs and uploads the value to all targets.
+    ///
+    /// Fails only if the value cannot be encrypted or signed, failed uploads are logged.
+    async fn upload(&self, key: &str, value: Vec<u8>) -> Result<()> {
+        let node_id = self.cipher.public_key();
+        let encrypted_value = self.cipher.encrypt(value)?;
+        let signature = self.cipher.sign(encrypted_value.clone())?;
+
+        let backup = Backup {
+            key: key.to_string(),
+            value: encrypted_value,
+            signature,
+        };
+
+        for target in self.targets().iter() {
+            match target
+                .upload(&self.inner, &self.endpoint, node_id, &backup)
+                .await
+            {
+                Ok(()) => {
+                    tracing::debug!(%target, "Successfully uploaded backup of {key}.")
+                }
+                Err(e) => {
+                    tracing::error!(%target, "Failed to create a backup of {key}. {e:#}")
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn delete_from_targets(&self, key: &str) {
+        let node_id = self.cipher.public_key();
+        let message = node_id.to_string().as_bytes().to_vec();
+        let signature = match self.cipher.sign(message) {
+            Ok(signature) => signature,
+            Err(e) => {
+                tracing::error!(%key, "{e:#}");
+                return;
+            }
+        };
+
+        let backup = DeleteBackup {
+            key: key.to_string(),
+            signature,
+        };
+
+        for target in self.targets().iter() {
+            if let Err(e) = target
+                .delete(&self.inner, &self.endpoint, node_id, &backup)
+                .await
+            {
+                tracing::error!(%target, "Failed to delete backup of {key}. {e:#}")
+            } else {
+                tracing::debug!(%target, "Successfully deleted backup of {key}");
+            }
+        }
+    }
+
+    /// Applies the update to the manifest, then persists and uploads it, unless there is no
+    /// manifest yet.
+    ///
+    /// The manifest is locked until it has been uploaded, so that no target receives an outdated
+    /// manifest after a newer one.
+    async fn update_manifest(
+        &self,
+        update: impl FnOnce(&mut Option<BackupManifest>),
+    ) -> Result<()> {
+        let mut manifest = self.manifest.lock().await;
+        update(&mut manifest);
+
+        let manifest = match manifest.as_ref() {
+            Some(manifest) => manifest,
+            None => {
+                tracing::debug!("Not uploading backup manifest before the first full backup");
+                return Ok(());
+            }
+        };
+
+        manifest.save(&self.manifest_path)?;
+
+        let value = serde_json::to_vec(&manifest.sign(&self.cipher)?)?;
+        self.upload(
+            &format!("{MANIFEST_BACKUP_KEY}/{MANIFEST_BACKUP_NAME}"),
+            value,
+        )
+        .await
+    }
+
+    /// Restores the backup from one of the configured targets, see [`target::select_snapshot`].
+    ///
+    /// Targets which fail to provide their backup are skipped, as long as at least one target
+    /// succeeds.
+    ///
+    /// If the backup contains a manifest, the restored data is verified against it and nothing is
+    /// written if the restored data could lead to a loss of funds, e.g. an outdated channel
+    /// monitor.
     pub async fn restore(&self, dlc_storage: Arc<SledStorageProvider>) -> Result<()> {
         let runtime = crate::state::get_or_create_tokio_runtime()?;
         runtime
@@ -205,83 +354,157 @@ impl RemoteBackupClient {
                 let client = self.inner.clone();
                 let cipher = self.cipher.clone();
                 let node_id = cipher.public_key();
-                let endpoint = format!("{}/restore/{}", self.endpoint.clone(), node_id);
+                let endpoint = self.endpoint.clone();
+                let targets = self.targets();
+                let manifest = self.manifest.clone();
+                let manifest_path = self.manifest_path.clone();
                 let data_dir = config::get_data_dir();
                 let network = config::get_network();
-                let message = node_id.to_string().as_bytes().to_vec();
                 async move {
-                    let signature = cipher.sign(message)?;
-
-                    match client.get(endpoint).json(&signature).send().await {
-                        Ok(response) => {
-                            tracing::debug!("Response status code {}", response.status());
-                            if response.status() != StatusCode::OK {
-                                let response = response.text().await?;
-                                bail!("Failed to download backup. {response}");
+                    ensure!(!targets.is_empty(), "No backup target configured");
+
+                    let mut snapshots = vec![];
+                    for target in targets.iter() {
+                        let snapshot = match target.download(&client, &endpoint, node_id).await {
+                            Ok(backup) => backup
+                                .into_iter()
+                                .map(|restore| Ok((restore.key, cipher.decrypt(restore.value)?)))
+                                .collect::<Result<Vec<_>>>(),
+                            Err(e) => Err(e),
+                        };
+
+                        match snapshot {
+                            Ok(snapshot) => {
+                                tracing::debug!(%target, "Successfully downloaded backup.");
+                                snapshots.push(snapshot);
+                            }
+                            Err(e) => {
+                                tracing::warn!(%target, "Failed to download backup. {e:#}")
+                            }
+                        }
+                    }
+
+                    if snapshots.is_empty() {
+                        bail!("Failed to download backup from any target");
+                    }
+
+                    let restored = target::select_snapshot(node_id, snapshots)?;
+
+                    let mut latest_manifest = manifest.lock().await;
+                    let restored_manifest =
+                        verify_manifest(node_id, &restored, latest_manifest.as_ref())?;
+
+                    for (restore_key, decrypted_value) in restored.into_iter() {
+                        let keys = restore_key
+                            .split('/')
+                            .map(|key| key.to_string())
+                            .collect::<Vec<String>>();
+                        let (backup_key, key) = keys.split_first().expect("keys to be long enough");
+                        let key = key.join("/");
+
+                        let backup_key = backup_key.as_str();
+
+                        match backup_key {
+                            x if x == LN_BACKUP_KEY => {
+                                tracing::debug!("Restoring {}", key);
+                                let dest_file = Path::new(&data_dir)
+                                    .join(network.to_string())
+                                    .join(key.clone());
+
+                                fs::create_dir_all(dest_file.parent().expect("parent"))?;
+                                fs::write(dest_file.as_path(), decrypted_value)?;
                             }
+                            x if x == DLC_BACKUP_KEY => {
+                                tracing::debug!("Restoring {}", key);
+                                let keys = key.split('/').collect::<Vec<&str>>();
+                                ensure!(keys.len() == 2, "dlc key is too short");
+
+                                let kind = *hex::decode(keys.first().expect("to exist"))?
+                                    .first()
+                                    .expect("to exist");
+
+                                let key = hex::decode(keys.get(1).expect("to exist"))?;
 
-                            let backup: Vec<Restore> = response.json().await?;
-                            tracing::debug!("Successfully downloaded backup.");
-
-                            for restore in backup.into_iter() {
-                                let decrypted_value = cipher.decrypt(restore.value)?;
-
-                                let keys = restore
-                                    .key
-                                    .split('/')
-                                    .map(|key| key.to_string())
-                                    .collect::<Vec<String>>();
-                                let (backup_key, key) =
-                                    keys.split_first().expect("keys to be long enough");
-                                let key = key.join("/");
-
-                                let backup_key = backup_key.as_str();
-
-                                match backup_key {
-                                    x if x == LN_BACKUP_KEY => {
-                                        tracing::debug!("Restoring {}", key);
-                                        let dest_file = Path::new(&data_dir)
-                                            .join(network.to_string())
-                                            .join(key.clone());
-
-                                        fs::create_dir_all(dest_file.parent().expect("parent"))?;
-                                        fs::write(dest_file.as_path(), decrypted_value)?;
-                                    }
-                                    x if x == DLC_BACKUP_KEY => {
-                                        tracing::debug!("Restoring {}", key);
-                                        let keys = key.split('/').collect::<Vec<&str>>();
-                                        ensure!(keys.len() == 2, "dlc key is too short");
-
-                                        let kind = *hex::decode(keys.first().expect("to exist"))?
-                                            .first()
-                                            .expect("to exist");
-
-                                        let key = hex::decode(keys.get(1).expect("to exist"))?;
-
-                                        dlc_storage.write(kind, key, decrypted_value)?;
-                                    }
-                                    x if x == DB_BACKUP_KEY => {
-                                        let data_dir = Path::new(&data_dir);
-                                        let db_file =
-                                            data_dir.join(format!("trades-{}.sqlite", network));
-                                        tracing::debug!(
-                                            "Restoring 10101 database backup into {}",
-                                            db_file.to_string_lossy().to_string()
-                                        );
-                                        fs::write(db_file.as_path(), decrypted_value)?;
-                                    }
-                                    _ => {
-                                        tracing::warn!(backup_key, "Received unknown backup key")
-                                    }
-                                }
+                                dlc_storage.write(kind, key, decrypted_value)?;
+                            }
+                            x if x == DB_BACKUP_KEY => {
+                                let data_dir = Path::new(&data_dir);
+                                let db_file = data_dir.join(format!("trades-{}.sqlite", network));
+                                tracing::debug!(
+                                    "Restoring 10101 database backup into {}",
+                                    db_file.to_string_lossy().to_string()
+                                );
+                                fs::write(db_file.as_path(), decrypted_value)?;
+                            }
+                            x if x == MANIFEST_BACKUP_KEY => {
+                                tracing::debug!("Skipping backup manifest");
+                            }
+                            _ => {
+                                tracing::warn!(backup_key, "Received unknown backup key")
                             }
-                            tracing::info!("Successfully restored 10101 from backup!");
                         }
-                        Err(e) => bail!("Failed to download backup. {e:#}"),
                     }
+
+                    if let Some(restored_manifest) = restored_manifest {
+                        restored_manifest.save(&manifest_path)?;
+                        *latest_manifest = Some(restored_manifest);
+                    }
+
+                    tracing::info!("Successfully restored 10101 from backup!");
                     Ok(())
                 }
             })
             .await?
     }
 }
+
+/// Verifies the decrypted backup against its manifest, failing if the backup must not be restored.
+///
+/// A backup with an invalid manifest or with a manifest older than the latest one known to this
+/// device is rejected, as it could contain revoked channel state. A backup without a manifest,
+/// e.g. one created before manifests had been introduced, can't be verified and is only restored
+/// if this device doesn't know of a manifest either. Returns the manifest of the backup, if any.
+fn verify_manifest(
+    node_id: PublicKey,
+    restored: &[(String, Vec<u8>)],
+    latest: Option<&BackupManifest>,
+) -> Result<Option<BackupManifest>> {
+    let (manifest, backup) = split_manifest(restored)?;
+
+    let manifest = match (manifest, latest) {
+        (Some(manifest), _) => manifest,
+        (None, Some(latest)) => bail!(
+            "Refusing to restore backup without a manifest, the latest known backup is from {}",
+            latest.created_at
+        ),
+        (None, None) => {
+            tracing::warn!("Restoring backup without a manifest, the backup can't be verified");
+            return Ok(None);
+        }
+    };
+
+    let manifest = manifest
+        .open(node_id)?
+        .context("Refusing to restore backup: invalid manifest signature")?;
+
+    if let Some(latest) = latest {
+        ensure!(
+            manifest.created_at >= latest.created_at,
+            "Refusing to restore backup from {} older than the latest known backup from {}",
+            manifest.created_at,
+            latest.created_at
+        );
+    }
+
+    let issues = manifest.verify(&BackupManifest::new(&backup));
+
+    for issue in issues.iter() {
+        tracing::warn!("Backup does not match its manifest: {issue}");
+    }
+
+    if let Some(issue) = issues.iter().find(|issue| issue.is_critical()) {
+        bail!("Refusing to restore backup: {issue}");
+    }
+
+    Ok(Some(manifest))
+}
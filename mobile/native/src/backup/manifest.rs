@@ -0,0 +1,471 @@
+use crate::backup::DLC_BACKUP_KEY;
+use crate::cipher::AesCipher;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::hashes::sha256;
+use bitcoin::hashes::Hash;
+use bitcoin::secp256k1::ecdsa::Signature;
+use bitcoin::secp256k1::PublicKey;
+use bitcoin::secp256k1::SECP256K1;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use time::OffsetDateTime;
+
+pub const MANIFEST_BACKUP_KEY: &str = "manifest";
+pub const MANIFEST_BACKUP_NAME: &str = "latest";
+
+/// The prefix of the backup keys of the LN channel monitors.
+const CHANNEL_MONITORS_PREFIX: &str = "ln/monitors/";
+
+/// The backup key of the LN channel manager.
+const CHANNEL_MANAGER_KEY: &str = "ln/manager";
+
+/// Describes the content of the latest backup, allowing to verify a restored backup.
+///
+/// The manifest is created with every full backup and updated with every backed up key.
+///
+/// Note, a manifest is downloaded together with the backup it describes, hence it can't detect a
+/// target which has been rolled back as a whole, i.e. an older backup with its matching older
+/// manifest. Such a rollback is only detected while the manifest of the latest backup is still
+/// known to this device, which is not the case when restoring on a new device. The risk is reduced
+/// by restoring the backup with the newest manifest of all configured targets, see
+/// [`select_snapshot`](crate::backup::target::select_snapshot).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// The unix timestamp in seconds at which the manifest has last been updated.
+    pub created_at: i64,
+    pub entries: Vec<ManifestEntry>,
+    pub dlc_channels: Vec<DlcChannelState>,
+    pub channel_monitors: Vec<ChannelMonitorState>,
+}
+
+/// A backed up key with the hash of its unencrypted value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub key: String,
+    pub sha256: String,
+}
+
+/// The state of a DLC channel or sub-channel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DlcChannelState {
+    /// The backup key of the channel.
+    pub key: String,
+    pub state: String,
+}
+
+/// The latest update of an LN channel monitor.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelMonitorState {
+    /// The backup key of the channel monitor.
+    pub key: String,
+    pub latest_update_id: u64,
+}
+
+/// The manifest as it is backed up, signed by the node key.
+///
+/// The manifest is kept as the exact JSON that has been signed.
+#[derive(Serialize, Deserialize)]
+pub struct SignedManifest {
+    manifest: String,
+    signature: Signature,
+}
+
+/// A problem detected when verifying a restored backup against its manifest.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RestoreIssue {
+    /// The signature of the manifest is not valid for the node key.
+    InvalidSignature,
+    /// A key of the manifest is not part of the restored backup.
+    MissingKey(String),
+    /// The value of a key has changed since the manifest has been created.
+    ChangedValue(String),
+    /// The restored channel monitor is older than the one known to the manifest. Starting the
+    /// node with it could broadcast a revoked state and lose all funds of the channel.
+    StaleChannelMonitor {
+        key: String,
+        restored_update_id: u64,
+        manifest_update_id: u64,
+    },
+    /// The state of a DLC channel differs from the one known to the manifest.
+    ChangedDlcChannelState {
+        key: String,
+        manifest_state: String,
+        restored_state: String,
+    },
+}
+
+impl RestoreIssue {
+    /// Whether the restored backup must not be used to start the node.
+    pub fn is_critical(&self) -> bool {
+        match self {
+            RestoreIssue::InvalidSignature | RestoreIssue::StaleChannelMonitor { .. } => true,
+            RestoreIssue::MissingKey(key) => {
+                key == CHANNEL_MANAGER_KEY || key.starts_with(CHANNEL_MONITORS_PREFIX)
+            }
+            RestoreIssue::ChangedValue(_) | RestoreIssue::ChangedDlcChannelState { .. } => false,
+        }
+    }
+}
+
+impl fmt::Display for RestoreIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RestoreIssue::InvalidSignature => write!(f, "invalid manifest signature"),
+            RestoreIssue::MissingKey(key) => write!(f, "missing {key}"),
+            RestoreIssue::ChangedValue(key) => write!(f, "{key} changed since the full backup"),
+            RestoreIssue::StaleChannelMonitor {
+                key,
+                restored_update_id,
+                manifest_update_id,
+            } => write!(
+                f,
+                "stale channel monitor {key} at update {restored_update_id}, expected at least {manifest_update_id}"
+            ),
+            RestoreIssue::ChangedDlcChannelState {
+                key,
+                manifest_state,
+                restored_state,
+            } => write!(
+                f,
+                "DLC channel {key} is {restored_state}, but was {manifest_state} at the full backup"
+            ),
+        }
+    }
+}
+
+impl BackupManifest {
+    /// Creates the manifest of the given unencrypted backup.
+    pub fn new(backup: &[(String, Vec<u8>)]) -> Self {
+        let mut manifest = Self {
+            created_at: OffsetDateTime::now_utc().unix_timestamp(),
+            entries: vec![],
+            dlc_channels: vec![],
+            channel_monitors: vec![],
+        };
+
+        for (key, value) in backup {
+            manifest.add(key, value);
+        }
+
+        manifest
+    }
+
+    /// Records the backup of the key, replacing its previous value.
+    pub fn insert(&mut self, key: &str, value: &[u8]) {
+        self.remove_exact(key);
+        self.add(key, value);
+        self.touch();
+    }
+
+    /// Removes the key and all keys below it from the manifest.
+    pub fn remove(&mut self, key: &str) {
+        let prefix = format!("{key}/");
+        let is_removed = |k: &str| k == key || k.starts_with(&prefix);
+
+        self.entries.retain(|entry| !is_removed(&entry.key));
+        self.dlc_channels
+            .retain(|channel| !is_removed(&channel.key));
+        self.channel_monitors
+            .retain(|monitor| !is_removed(&monitor.key));
+        self.touch();
+    }
+
+    fn add(&mut self, key: &str, value: &[u8]) {
+        self.entries.push(ManifestEntry {
+            key: key.to_string(),
+            sha256: sha256::Hash::hash(value).to_string(),
+        });
+
+        if key.starts_with(CHANNEL_MONITORS_PREFIX) {
+            match latest_update_id(value) {
+                Some(latest_update_id) => self.channel_monitors.push(ChannelMonitorState {
+                    key: key.to_string(),
+                    latest_update_id,
+                }),
+                None => tracing::warn!(key, "Failed to read channel monitor update id"),
+            }
+        }
+
+        if let Some(state) = dlc_channel_state(key, value) {
+            self.dlc_channels.push(DlcChannelState {
+                key: key.to_string(),
+                state,
+            });
+        }
+    }
+
+    fn remove_exact(&mut self, key: &str) {
+        self.entries.retain(|entry| entry.key != key);
+        self.dlc_channels.retain(|channel| channel.key != key);
+        self.channel_monitors.retain(|monitor| monitor.key != key);
+    }
+
+    /// Bumps the update time, never going back in time.
+    fn touch(&mut self) {
+        self.created_at = self
+            .created_at
+            .max(OffsetDateTime::now_utc().unix_timestamp());
+    }
+
+    pub fn sign(&self, cipher: &AesCipher) -> Result<SignedManifest> {
+        let manifest = serde_json::to_string(self)?;
+        let signature = cipher.sign(manifest.as_bytes().to_vec())?;
+
+        Ok(SignedManifest {
+            manifest,
+            signature,
+        })
+    }
+
+    /// Verifies the restored backup against this manifest.
+    ///
+    /// Keys which have been backed up after the manifest had been created are not reported. This
+    /// does not tell whether the manifest itself is the latest one, see [`BackupManifest`].
+    pub fn verify(&self, restored: &BackupManifest) -> Vec<RestoreIssue> {
+        let restored_entries = restored
+            .entries
+            .iter()
+            .map(|entry| (entry.key.as_str(), entry.sha256.as_str()))
+            .collect::<HashMap<_, _>>();
+        let restored_monitors = restored
+            .channel_monitors
+            .iter()
+            .map(|monitor| (monitor.key.as_str(), monitor.latest_update_id))
+            .collect::<HashMap<_, _>>();
+        let restored_dlc_channels = restored
+            .dlc_channels
+            .iter()
+            .map(|channel| (channel.key.as_str(), channel.state.as_str()))
+            .collect::<HashMap<_, _>>();
+
+        let mut issues = vec![];
+        for entry in self.entries.iter() {
+            match restored_entries.get(entry.key.as_str()) {
+                None => issues.push(RestoreIssue::MissingKey(entry.key.clone())),
+                Some(sha256) if *sha256 != entry.sha256 => {
+                    issues.push(RestoreIssue::ChangedValue(entry.key.clone()))
+                }
+                Some(_) => {}
+            }
+        }
+
+        for monitor in self.channel_monitors.iter() {
+            if let Some(restored_update_id) = restored_monitors.get(monitor.key.as_str()) {
+                if *restored_update_id < monitor.latest_update_id {
+                    issues.push(RestoreIssue::StaleChannelMonitor {
+                        key: monitor.key.clone(),
+                        restored_update_id: *restored_update_id,
+                        manifest_update_id: monitor.latest_update_id,
+                    });
+                }
+            }
+        }
+
+        for channel in self.dlc_channels.iter() {
+            if let Some(restored_state) = restored_dlc_channels.get(channel.key.as_str()) {
+                if *restored_state != channel.state {
+                    issues.push(RestoreIssue::ChangedDlcChannelState {
+                        key: channel.key.clone(),
+                        manifest_state: channel.state.clone(),
+                        restored_state: restored_state.to_string(),
+                    });
+                }
+            }
+        }
+
+        issues
+    }
+
+    /// Loads the manifest stored at the path, if any.
+    pub fn load(path: &Path) -> Result<Option<BackupManifest>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let manifest = fs::read(path)?;
+        let manifest = serde_json::from_slice(&manifest).context("Invalid backup manifest")?;
+        Ok(Some(manifest))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+}
+
+impl SignedManifest {
+    /// Returns the manifest if it has been signed by the given node.
+    pub fn open(&self, node_id: PublicKey) -> Result<Option<BackupManifest>> {
+        let message = commons::create_sign_message(self.manifest.as_bytes().to_vec());
+        if SECP256K1
+            .verify_ecdsa(&message, &self.signature, &node_id)
+            .is_err()
+        {
+            return Ok(None);
+        }
+
+        let manifest = serde_json::from_str(&self.manifest).context("Invalid backup manifest")?;
+        Ok(Some(manifest))
+    }
+}
+
+/// Splits the decrypted backup into its manifest, if it contains one, and the remaining keys.
+pub fn split_manifest(
+    backup: &[(String, Vec<u8>)],
+) -> Result<(Option<SignedManifest>, Vec<(String, Vec<u8>)>)> {
+    let manifest_key = format!("{MANIFEST_BACKUP_KEY}/{MANIFEST_BACKUP_NAME}");
+    let (manifest, backup): (Vec<_>, Vec<_>) = backup
+        .iter()
+        .cloned()
+        .partition(|(key, _)| *key == manifest_key);
+
+    let manifest = match manifest.first() {
+        Some((_, manifest)) => Some(
+            serde_json::from_slice::<SignedManifest>(manifest)
+                .context("Invalid backup manifest")?,
+        ),
+        None => None,
+    };
+
+    Ok((manifest, backup))
+}
+
+/// Reads the id of the latest update from a serialized LN channel monitor.
+///
+/// A serialized channel monitor starts with the two bytes of its serialization version, followed
+/// by the big-endian id of its latest update.
+fn latest_update_id(monitor: &[u8]) -> Option<u64> {
+    let latest_update_id = monitor.get(2..10)?;
+    Some(u64::from_be_bytes(latest_update_id.try_into().ok()?))
+}
+
+/// Describes the state of a backed up DLC channel. Backup keys of the DLC storage have the format
+/// `dlc/{kind}/{key}` with the kind and key being hex encoded.
+fn dlc_channel_state(key: &str, value: &[u8]) -> Option<String> {
+    let mut parts = key.split('/');
+    if parts.next()? != DLC_BACKUP_KEY {
+        return None;
+    }
+
+    let kind = *hex::decode(parts.next()?).ok()?.first()?;
+    ln_dlc_storage::channel_state(kind, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backup::manifest::BackupManifest;
+    use crate::backup::manifest::RestoreIssue;
+    use crate::cipher::AesCipher;
+    use bitcoin::secp256k1::SecretKey;
+    use bitcoin::secp256k1::SECP256K1;
+
+    #[test]
+    fn unchanged_backup_has_no_issues() {
+        let backup = vec![
+            ("ln/manager".to_string(), b"manager".to_vec()),
+            ("ln/monitors/abc_0".to_string(), monitor(5)),
+        ];
+
+        let manifest = BackupManifest::new(&backup);
+
+        assert_eq!(manifest.channel_monitors[0].latest_update_id, 5);
+        assert!(manifest.verify(&BackupManifest::new(&backup)).is_empty());
+    }
+
+    #[test]
+    fn detect_stale_channel_monitor_and_missing_key() {
+        let manifest = BackupManifest::new(&[
+            ("ln/manager".to_string(), b"manager".to_vec()),
+            ("ln/monitors/abc_0".to_string(), monitor(5)),
+            ("10101/db".to_string(), b"db".to_vec()),
+        ]);
+
+        let restored = BackupManifest::new(&[
+            ("ln/manager".to_string(), b"manager".to_vec()),
+            ("ln/monitors/abc_0".to_string(), monitor(4)),
+        ]);
+
+        let issues = manifest.verify(&restored);
+
+        assert_eq!(
+            issues,
+            vec![
+                RestoreIssue::ChangedValue("ln/monitors/abc_0".to_string()),
+                RestoreIssue::MissingKey("10101/db".to_string()),
+                RestoreIssue::StaleChannelMonitor {
+                    key: "ln/monitors/abc_0".to_string(),
+                    restored_update_id: 4,
+                    manifest_update_id: 5,
+                },
+            ]
+        );
+        assert_eq!(
+            issues
+                .iter()
+                .filter(|issue| issue.is_critical())
+                .collect::<Vec<_>>(),
+            vec![&issues[2]]
+        );
+    }
+
+    #[test]
+    fn newer_channel_monitor_is_not_an_issue() {
+        let manifest = BackupManifest::new(&[("ln/monitors/abc_0".to_string(), monitor(5))]);
+        let restored = BackupManifest::new(&[("ln/monitors/abc_0".to_string(), monitor(6))]);
+
+        let issues = manifest.verify(&restored);
+
+        assert!(issues.iter().all(|issue| !issue.is_critical()));
+    }
+
+    #[test]
+    fn manifest_signed_by_other_node_is_rejected() {
+        let cipher = AesCipher::new(SecretKey::from_slice(&[1; 32]).unwrap());
+        let other_node = SecretKey::from_slice(&[2; 32])
+            .unwrap()
+            .public_key(SECP256K1);
+        let manifest = BackupManifest::new(&[("ln/manager".to_string(), b"manager".to_vec())]);
+
+        let signed = manifest.sign(&cipher).unwrap();
+
+        assert_eq!(signed.open(cipher.public_key()).unwrap(), Some(manifest));
+        assert_eq!(signed.open(other_node).unwrap(), None);
+    }
+
+    #[test]
+    fn incremental_backups_update_the_manifest() {
+        let mut manifest = BackupManifest::new(&[
+            ("ln/manager".to_string(), b"manager".to_vec()),
+            ("ln/monitors/abc_0".to_string(), monitor(5)),
+            ("dlc/01/aa".to_string(), b"channel".to_vec()),
+        ]);
+        let created_at = manifest.created_at;
+
+        manifest.insert("ln/monitors/abc_0", &monitor(6));
+        manifest.insert("10101/db", b"db");
+        manifest.remove("dlc/01");
+
+        let restored = BackupManifest::new(&[
+            ("ln/manager".to_string(), b"manager".to_vec()),
+            ("ln/monitors/abc_0".to_string(), monitor(6)),
+            ("10101/db".to_string(), b"db".to_vec()),
+        ]);
+
+        assert!(manifest.created_at >= created_at);
+        assert_eq!(manifest.channel_monitors[0].latest_update_id, 6);
+        assert_eq!(manifest.entries.len(), 3);
+        assert!(manifest.verify(&restored).is_empty());
+    }
+
+    fn monitor(latest_update_id: u64) -> Vec<u8> {
+        let mut monitor = vec![1, 1];
+        monitor.extend_from_slice(&latest_update_id.to_be_bytes());
+        monitor.extend_from_slice(b"rest of the monitor");
+        monitor
+    }
+}
@@ -0,0 +1,691 @@
+use crate::backup::manifest::split_manifest;
+use crate::backup::manifest::BackupManifest;
+use crate::backup::manifest::RestoreIssue;
+use crate::cipher::AesCipher;
+use crate::commons::signed_request;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use commons::Backup;
+use commons::DeleteBackup;
+use commons::Restore;
+use reqwest::Client;
+use reqwest::Method;
+use reqwest::RequestBuilder;
+use reqwest::StatusCode;
+use reqwest::Url;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+const BACKUP_TARGETS_FILE: &str = "backup_targets.json";
+
+/// A location the encrypted backups are uploaded to and can be restored from.
+///
+/// Every target only ever receives values encrypted with the [`crate::cipher::AesCipher`] of the
+/// wallet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BackupTarget {
+    /// The backup API of the coordinator the app is connected to.
+    Coordinator,
+    /// A local directory, e.g. a folder synced to a cloud storage.
+    Directory { path: String },
+    /// A WebDAV server, e.g. a Nextcloud instance.
+    WebDav {
+        url: String,
+        username: Option<String>,
+        password: Option<String>,
+    },
+    /// A server providing the same backup API as the coordinator, e.g. a self-hosted coordinator.
+    BackupServer { url: String },
+}
+
+impl fmt::Display for BackupTarget {
+    // Deliberately leaves out any credentials, as the target is used in logs.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackupTarget::Coordinator => write!(f, "coordinator"),
+            BackupTarget::Directory { path } => write!(f, "directory {path}"),
+            BackupTarget::WebDav { url, .. } => write!(f, "WebDAV {url}"),
+            BackupTarget::BackupServer { url } => write!(f, "backup server {url}"),
+        }
+    }
+}
+
+impl BackupTarget {
+    /// Uploads the encrypted and signed backup.
+    ///
+    /// `coordinator_endpoint` is the base URL of the coordinator API, only used by the
+    /// [`BackupTarget::Coordinator`].
+    pub async fn upload(
+        &self,
+        client: &Client,
+        coordinator_endpoint: &str,
+        node_id: PublicKey,
+        backup: &Backup,
+    ) -> Result<()> {
+        match self {
+            BackupTarget::Coordinator => {
+                let endpoint = format!("{coordinator_endpoint}/backup/{node_id}");
+                send(signed_request(
+                    client,
+                    Method::POST,
+                    Url::parse(&endpoint)?,
+                    backup,
+                )?)
+                .await?;
+            }
+            BackupTarget::BackupServer { url } => {
+                let endpoint = format!("{}/backup/{node_id}", url.trim_end_matches('/'));
+                send(signed_request(
+                    client,
+                    Method::POST,
+                    Url::parse(&endpoint)?,
+                    backup,
+                )?)
+                .await?;
+            }
+            BackupTarget::Directory { path } => {
+                let directory = Path::new(path).join(node_id.to_string());
+                fs::create_dir_all(&directory)?;
+
+                // Write to a temporary file first, so that an interrupted backup never replaces
+                // a previous backup with a partially written one.
+                let file = directory.join(file_name(&backup.key));
+                let temporary_file = file.with_extension("tmp");
+                fs::write(&temporary_file, &backup.value)?;
+                fs::rename(&temporary_file, &file)?;
+            }
+            BackupTarget::WebDav { url, .. } => {
+                let collection = webdav_collection(url, node_id);
+
+                // Creating an existing collection fails, which is expected after the first
+                // backup.
+                let response = self
+                    .webdav_request(client, Method::from_bytes(b"MKCOL")?, &collection)
+                    .send()
+                    .await?;
+                let status = response.status();
+                if !status.is_success() && status != StatusCode::METHOD_NOT_ALLOWED {
+                    bail!("Failed to create WebDAV collection. Status {status}");
+                }
+
+                let file = format!("{collection}{}", file_name(&backup.key));
+                send(
+                    self.webdav_request(client, Method::PUT, &file)
+                        .body(backup.value.clone()),
+                )
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn delete(
+        &self,
+        client: &Client,
+        coordinator_endpoint: &str,
+        node_id: PublicKey,
+        backup: &DeleteBackup,
+    ) -> Result<()> {
+        match self {
+            BackupTarget::Coordinator => {
+                let endpoint = format!("{coordinator_endpoint}/backup/{node_id}");
+                send(signed_request(
+                    client,
+                    Method::DELETE,
+                    Url::parse(&endpoint)?,
+                    backup,
+                )?)
+                .await?;
+            }
+            BackupTarget::BackupServer { url } => {
+                let endpoint = format!("{}/backup/{node_id}", url.trim_end_matches('/'));
+                send(signed_request(
+                    client,
+                    Method::DELETE,
+                    Url::parse(&endpoint)?,
+                    backup,
+                )?)
+                .await?;
+            }
+            BackupTarget::Directory { path } => {
+                let file = Path::new(path)
+                    .join(node_id.to_string())
+                    .join(file_name(&backup.key));
+                match fs::remove_file(file) {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == ErrorKind::NotFound => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            BackupTarget::WebDav { url, .. } => {
+                let file = format!(
+                    "{}{}",
+                    webdav_collection(url, node_id),
+                    file_name(&backup.key)
+                );
+                let response = self
+                    .webdav_request(client, Method::DELETE, &file)
+                    .send()
+                    .await?;
+                let status = response.status();
+                if !status.is_success() && status != StatusCode::NOT_FOUND {
+                    bail!("Failed to delete WebDAV file. Status {status}");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Downloads all encrypted backups of the node.
+    pub async fn download(
+        &self,
+        client: &Client,
+        coordinator_endpoint: &str,
+        node_id: PublicKey,
+    ) -> Result<Vec<Restore>> {
+        let backup = match self {
+            BackupTarget::Coordinator => {
+                let endpoint = format!("{coordinator_endpoint}/restore/{node_id}");
+                send(signed_request(
+                    client,
+                    Method::GET,
+                    Url::parse(&endpoint)?,
+                    &(),
+                )?)
+                .await?
+                .json()
+                .await?
+            }
+            BackupTarget::BackupServer { url } => {
+                let endpoint = format!("{}/restore/{node_id}", url.trim_end_matches('/'));
+                send(signed_request(
+                    client,
+                    Method::GET,
+                    Url::parse(&endpoint)?,
+                    &(),
+                )?)
+                .await?
+                .json()
+                .await?
+            }
+            BackupTarget::Directory { path } => {
+                read_directory(&Path::new(path).join(node_id.to_string()))?
+            }
+            BackupTarget::WebDav { url, .. } => {
+                let collection = webdav_collection(url, node_id);
+                let response = self
+                    .webdav_request(client, Method::from_bytes(b"PROPFIND")?, &collection)
+                    .header("Depth", "1")
+                    .send()
+                    .await?;
+                if response.status() == StatusCode::NOT_FOUND {
+                    return Ok(vec![]);
+                }
+                let response = check_status(response).await?;
+
+                let mut backup = vec![];
+                for name in webdav_file_names(&response.text().await?) {
+                    let key = match key_from_file_name(&name) {
+                        Some(key) => key,
+                        None => continue,
+                    };
+
+                    let file = format!("{collection}{name}");
+                    let value = send(self.webdav_request(client, Method::GET, &file))
+                        .await?
+                        .bytes()
+                        .await?;
+
+                    backup.push(Restore {
+                        key,
+                        value: value.to_vec(),
+                    });
+                }
+
+                backup
+            }
+        };
+
+        Ok(backup)
+    }
+
+    fn webdav_request(&self, client: &Client, method: Method, url: &str) -> RequestBuilder {
+        let request = client.request(method, url);
+        match self {
+            BackupTarget::WebDav {
+                username: Some(username),
+                password,
+                ..
+            } => request.basic_auth(username, password.as_ref()),
+            _ => request,
+        }
+    }
+}
+
+/// Picks the decrypted backup of a single target to restore.
+///
+/// Combining the keys of several targets could mix up e.g. a channel manager and channel monitors
+/// of different states, hence the whole backup of one target is restored: the backup with the
+/// newest complete manifest, i.e. a manifest signed by the node whose keys are all part of the
+/// backup. If no target has a complete manifest, the backup of the first target is restored.
+///
+/// The backups are expected in the order of the configured targets.
+pub fn select_snapshot(
+    node_id: PublicKey,
+    snapshots: Vec<Vec<(String, Vec<u8>)>>,
+) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut newest: Option<(i64, usize)> = None;
+    for (i, snapshot) in snapshots.iter().enumerate() {
+        let manifest = match complete_manifest(node_id, snapshot) {
+            Ok(Some(manifest)) => manifest,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::warn!("Ignoring invalid backup manifest. {e:#}");
+                continue;
+            }
+        };
+
+        if newest.map_or(true, |(created_at, _)| manifest.created_at > created_at) {
+            newest = Some((manifest.created_at, i));
+        }
+    }
+
+    let index = newest.map_or(0, |(_, index)| index);
+    snapshots
+        .into_iter()
+        .nth(index)
+        .context("No backup to restore")
+}
+
+/// Returns the manifest of the backup if it has been signed by the node and all its keys are
+/// part of the backup.
+fn complete_manifest(
+    node_id: PublicKey,
+    snapshot: &[(String, Vec<u8>)],
+) -> Result<Option<BackupManifest>> {
+    let (manifest, backup) = split_manifest(snapshot)?;
+    let manifest = match manifest {
+        Some(manifest) => manifest.open(node_id)?,
+        None => None,
+    };
+
+    Ok(manifest.filter(|manifest| {
+        manifest
+            .verify(&BackupManifest::new(&backup))
+            .iter()
+            .all(|issue| !matches!(issue, RestoreIssue::MissingKey(_)))
+    }))
+}
+
+/// Reads the configured backup targets from the data dir.
+///
+/// Defaults to the coordinator if no targets have been configured.
+pub fn load_backup_targets(data_dir: &Path, cipher: &AesCipher) -> Result<Vec<BackupTarget>> {
+    let path = data_dir.join(BACKUP_TARGETS_FILE);
+    let targets = match fs::read(&path) {
+        Ok(data) => serde_json::from_slice::<Vec<StoredBackupTarget>>(&data)
+            .with_context(|| format!("Failed to parse backup targets at {path:?}"))?,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![BackupTarget::Coordinator]),
+        Err(e) => return Err(e.into()),
+    };
+
+    targets
+        .into_iter()
+        .map(|target| target.decrypt(cipher))
+        .collect()
+}
+
+/// Writes the backup targets to the data dir, encrypting their credentials.
+pub fn save_backup_targets(
+    data_dir: &Path,
+    targets: &[BackupTarget],
+    cipher: &AesCipher,
+) -> Result<()> {
+    let targets = targets
+        .iter()
+        .map(|target| StoredBackupTarget::encrypt(target, cipher))
+        .collect::<Result<Vec<_>>>()?;
+
+    let path = data_dir.join(BACKUP_TARGETS_FILE);
+    fs::write(&path, serde_json::to_vec_pretty(&targets)?)
+        .with_context(|| format!("Failed to write backup targets to {path:?}"))
+}
+
+/// A [`BackupTarget`] as it is written to the data dir, with the WebDAV password encrypted by the
+/// [`AesCipher`] of the wallet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StoredBackupTarget {
+    Coordinator,
+    Directory {
+        path: String,
+    },
+    WebDav {
+        url: String,
+        username: Option<String>,
+        /// The hex encoded encrypted password.
+        encrypted_password: Option<String>,
+    },
+    BackupServer {
+        url: String,
+    },
+}
+
+impl StoredBackupTarget {
+    fn encrypt(target: &BackupTarget, cipher: &AesCipher) -> Result<Self> {
+        let target = match target.clone() {
+            BackupTarget::Coordinator => StoredBackupTarget::Coordinator,
+            BackupTarget::Directory { path } => StoredBackupTarget::Directory { path },
+            BackupTarget::WebDav {
+                url,
+                username,
+                password,
+            } => StoredBackupTarget::WebDav {
+                url,
+                username,
+                encrypted_password: password
+                    .map(|password| cipher.encrypt(password.into_bytes()).map(hex::encode))
+                    .transpose()?,
+            },
+            BackupTarget::BackupServer { url } => StoredBackupTarget::BackupServer { url },
+        };
+
+        Ok(target)
+    }
+
+    fn decrypt(self, cipher: &AesCipher) -> Result<BackupTarget> {
+        let target = match self {
+            StoredBackupTarget::Coordinator => BackupTarget::Coordinator,
+            StoredBackupTarget::Directory { path } => BackupTarget::Directory { path },
+            StoredBackupTarget::WebDav {
+                url,
+                username,
+                encrypted_password,
+            } => {
+                let password = match encrypted_password {
+                    Some(encrypted_password) => {
+                        let password = cipher.decrypt(hex::decode(encrypted_password)?)?;
+                        Some(String::from_utf8(password)?)
+                    }
+                    None => None,
+                };
+
+                BackupTarget::WebDav {
+                    url,
+                    username,
+                    password,
+                }
+            }
+            StoredBackupTarget::BackupServer { url } => BackupTarget::BackupServer { url },
+        };
+
+        Ok(target)
+    }
+}
+
+async fn send(request: RequestBuilder) -> Result<reqwest::Response> {
+    check_status(request.send().await?).await
+}
+
+async fn check_status(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    tracing::debug!("Response status code {status}");
+    if !status.is_success() {
+        let response = response.text().await.unwrap_or_default();
+        bail!("Request failed with status {status}. {response}");
+    }
+
+    Ok(response)
+}
+
+/// Backup keys contain `/`, hence they are hex encoded to be used as file names.
+fn file_name(key: &str) -> String {
+    hex::encode(key)
+}
+
+fn key_from_file_name(name: &str) -> Option<String> {
+    let key = hex::decode(name).ok()?;
+    String::from_utf8(key).ok()
+}
+
+fn read_directory(directory: &Path) -> Result<Vec<Restore>> {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut backup = vec![];
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        // Skips temporary files of interrupted backups.
+        let key = match key_from_file_name(&name) {
+            Some(key) => key,
+            None => continue,
+        };
+
+        backup.push(Restore {
+            key,
+            value: fs::read(entry.path())?,
+        });
+    }
+
+    Ok(backup)
+}
+
+fn webdav_collection(url: &str, node_id: PublicKey) -> String {
+    format!("{}/{node_id}/", url.trim_end_matches('/'))
+}
+
+/// Extracts the names of the files listed in the response to a `PROPFIND` request.
+///
+/// The collection itself is listed as well, which is skipped as its href ends with a `/`.
+fn webdav_file_names(xml: &str) -> Vec<String> {
+    xml.split('<')
+        .filter_map(|element| {
+            let (tag, text) = element.split_once('>')?;
+            // The namespace prefix differs between servers, e.g. `d:href` or `D:href`.
+            if tag.starts_with('/') || tag.rsplit(':').next()? != "href" {
+                return None;
+            }
+
+            let text = text.trim();
+            if text.ends_with('/') {
+                return None;
+            }
+
+            text.rsplit('/')
+                .next()
+                .filter(|name| !name.is_empty())
+                .map(|name| name.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backup::manifest::BackupManifest;
+    use crate::backup::manifest::MANIFEST_BACKUP_KEY;
+    use crate::backup::manifest::MANIFEST_BACKUP_NAME;
+    use crate::backup::target::load_backup_targets;
+    use crate::backup::target::save_backup_targets;
+    use crate::backup::target::select_snapshot;
+    use crate::backup::target::webdav_file_names;
+    use crate::backup::target::BackupTarget;
+    use crate::backup::target::StoredBackupTarget;
+    use crate::backup::target::BACKUP_TARGETS_FILE;
+    use crate::cipher::AesCipher;
+    use bitcoin::secp256k1::ecdsa::Signature;
+    use bitcoin::secp256k1::SecretKey;
+    use bitcoin::secp256k1::SECP256K1;
+    use commons::Backup;
+    use commons::DeleteBackup;
+    use commons::Restore;
+    use reqwest::Client;
+
+    #[tokio::test]
+    async fn back_up_to_directory_and_restore() {
+        let path = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let target = BackupTarget::Directory {
+            path: path.to_string_lossy().to_string(),
+        };
+        let node_id = SecretKey::from_slice(&[1; 32])
+            .unwrap()
+            .public_key(SECP256K1);
+        let signature = dummy_signature();
+        let client = Client::new();
+
+        for (key, value) in [("ln/manager", b"1"), ("dlc/01/ff", b"2")] {
+            let backup = Backup {
+                key: key.to_string(),
+                value: value.to_vec(),
+                signature,
+            };
+            target
+                .upload(&client, "unused", node_id, &backup)
+                .await
+                .unwrap();
+        }
+        target
+            .delete(
+                &client,
+                "unused",
+                node_id,
+                &DeleteBackup {
+                    key: "dlc/01/ff".to_string(),
+                    signature,
+                },
+            )
+            .await
+            .unwrap();
+
+        let restored = target.download(&client, "unused", node_id).await.unwrap();
+
+        assert_eq!(
+            restored,
+            vec![Restore {
+                key: "ln/manager".to_string(),
+                value: b"1".to_vec()
+            }]
+        );
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn newest_complete_snapshot_is_selected() {
+        let cipher = AesCipher::new(SecretKey::from_slice(&[1; 32]).unwrap());
+
+        let old = snapshot(&cipher, &[("ln/manager", "old"), ("10101/db", "old")], 1);
+        let mut incomplete = snapshot(&cipher, &[("ln/manager", "new"), ("10101/db", "new")], 3);
+        incomplete.retain(|(key, _)| key != "10101/db");
+        let new = snapshot(&cipher, &[("ln/manager", "new")], 2);
+        let without_manifest = vec![("ln/manager".to_string(), b"newest".to_vec())];
+
+        let selected = select_snapshot(
+            cipher.public_key(),
+            vec![without_manifest.clone(), old, incomplete, new.clone()],
+        )
+        .unwrap();
+        assert_eq!(selected, new);
+
+        let selected =
+            select_snapshot(cipher.public_key(), vec![without_manifest.clone()]).unwrap();
+        assert_eq!(selected, without_manifest);
+    }
+
+    #[test]
+    fn webdav_password_is_stored_encrypted() {
+        let data_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&data_dir).unwrap();
+        let cipher = AesCipher::new(SecretKey::from_slice(&[1; 32]).unwrap());
+        let targets = vec![
+            BackupTarget::Coordinator,
+            BackupTarget::WebDav {
+                url: "https://dav.example.com".to_string(),
+                username: Some("satoshi".to_string()),
+                password: Some("hunter2".to_string()),
+            },
+        ];
+
+        save_backup_targets(&data_dir, &targets, &cipher).unwrap();
+
+        let stored = std::fs::read_to_string(data_dir.join(BACKUP_TARGETS_FILE)).unwrap();
+        assert!(!stored.contains("hunter2"));
+        assert_eq!(load_backup_targets(&data_dir, &cipher).unwrap(), targets);
+
+        std::fs::remove_dir_all(data_dir).unwrap();
+    }
+
+    #[test]
+    fn parse_propfind_response() {
+        let xml = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:">
+  <d:response><d:href>/remote.php/dav/files/satoshi/02abc/</d:href></d:response>
+  <d:response><d:href>/remote.php/dav/files/satoshi/02abc/6c6e2f6d616e61676572</d:href></d:response>
+</d:multistatus>"#;
+
+        assert_eq!(
+            webdav_file_names(xml),
+            vec!["6c6e2f6d616e61676572".to_string()]
+        );
+    }
+
+    #[test]
+    fn deserialize_backup_targets() {
+        let targets: Vec<StoredBackupTarget> = serde_json::from_str(
+            r#"[{"type":"coordinator"},{"type":"web_dav","url":"https://dav.example.com","username":"satoshi","encrypted_password":null}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            targets,
+            vec![
+                StoredBackupTarget::Coordinator,
+                StoredBackupTarget::WebDav {
+                    url: "https://dav.example.com".to_string(),
+                    username: Some("satoshi".to_string()),
+                    encrypted_password: None,
+                }
+            ]
+        );
+    }
+
+    /// A decrypted backup with a manifest created at the given time.
+    fn snapshot(
+        cipher: &AesCipher,
+        backup: &[(&str, &str)],
+        created_at: i64,
+    ) -> Vec<(String, Vec<u8>)> {
+        let mut snapshot = backup
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.as_bytes().to_vec()))
+            .collect::<Vec<_>>();
+
+        let mut manifest = BackupManifest::new(&snapshot);
+        manifest.created_at = created_at;
+        snapshot.push((
+            format!("{MANIFEST_BACKUP_KEY}/{MANIFEST_BACKUP_NAME}"),
+            serde_json::to_vec(&manifest.sign(cipher).unwrap()).unwrap(),
+        ));
+
+        snapshot
+    }
+
+    fn dummy_signature() -> Signature {
+        SecretKey::from_slice(&[1; 32])
+            .unwrap()
+            .sign_ecdsa(commons::create_sign_message(b"10101".to_vec()))
+    }
+}
@@ -1,3 +1,12 @@
+use crate::ln_dlc;
+use anyhow::Result;
+use commons::RequestSignature;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::Method;
+use reqwest::RequestBuilder;
+use reqwest::Url;
+use serde::Serialize;
+
 pub mod api;
 
 /// Provide a reqwest client with a specified 10 seconds timeout.
@@ -9,3 +18,30 @@ pub fn reqwest_client() -> reqwest::Client {
         .build()
         .expect("Failed to build reqwest client")
 }
+
+/// Builds a request to the coordinator with the JSON body, signed with our node key so that the
+/// coordinator (or a backup server) can authenticate us.
+pub fn signed_request<T: Serialize + ?Sized>(
+    client: &reqwest::Client,
+    method: Method,
+    url: Url,
+    body: &T,
+) -> Result<RequestBuilder> {
+    let body = serde_json::to_vec(body)?;
+
+    let path = match url.query() {
+        Some(query) => format!("{}?{query}", url.path()),
+        None => url.path().to_string(),
+    };
+    let signature = RequestSignature::new(&ln_dlc::get_node_key(), method.as_str(), &path, &body);
+
+    let mut request = client
+        .request(method, url)
+        .header(CONTENT_TYPE, "application/json")
+        .body(body);
+    for (name, value) in signature.headers() {
+        request = request.header(name, value);
+    }
+
+    Ok(request)
+}
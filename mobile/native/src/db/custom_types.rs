@@ -8,6 +8,7 @@ use crate::db::models::OrderReason;
 use crate::db::models::OrderState;
 use crate::db::models::OrderType;
 use crate::db::models::PositionState;
+use crate::db::models::TimeInForce;
 use diesel::backend;
 use diesel::deserialize;
 use diesel::deserialize::FromSql;
@@ -46,6 +47,9 @@ impl ToSql<Text, Sqlite> for OrderReason {
         let text = match *self {
             OrderReason::Manual => "Manual".to_string(),
             OrderReason::Expired => "Expired".to_string(),
+            OrderReason::StopLoss => "StopLoss".to_string(),
+            OrderReason::TakeProfit => "TakeProfit".to_string(),
+            OrderReason::Liquidated => "Liquidated".to_string(),
         };
         out.set_value(text);
         Ok(IsNull::No)
@@ -59,6 +63,36 @@ impl FromSql<Text, Sqlite> for OrderReason {
         return match string.as_str() {
             "Manual" => Ok(OrderReason::Manual),
             "Expired" => Ok(OrderReason::Expired),
+            "StopLoss" => Ok(OrderReason::StopLoss),
+            "TakeProfit" => Ok(OrderReason::TakeProfit),
+            "Liquidated" => Ok(OrderReason::Liquidated),
+            _ => Err("Unrecognized enum variant".into()),
+        };
+    }
+}
+
+impl ToSql<Text, Sqlite> for TimeInForce {
+    fn to_sql(&self, out: &mut Output<Sqlite>) -> serialize::Result {
+        let text = match *self {
+            TimeInForce::ImmediateOrCancel => "ImmediateOrCancel".to_string(),
+            TimeInForce::FillOrKill => "FillOrKill".to_string(),
+            TimeInForce::GoodTillCancelled => "GoodTillCancelled".to_string(),
+            TimeInForce::GoodTillDate => "GoodTillDate".to_string(),
+        };
+        out.set_value(text);
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Text, Sqlite> for TimeInForce {
+    fn from_sql(bytes: backend::RawValue<Sqlite>) -> deserialize::Result<Self> {
+        let string = <String as FromSql<Text, Sqlite>>::from_sql(bytes)?;
+
+        return match string.as_str() {
+            "ImmediateOrCancel" => Ok(TimeInForce::ImmediateOrCancel),
+            "FillOrKill" => Ok(TimeInForce::FillOrKill),
+            "GoodTillCancelled" => Ok(TimeInForce::GoodTillCancelled),
+            "GoodTillDate" => Ok(TimeInForce::GoodTillDate),
             _ => Err("Unrecognized enum variant".into()),
         };
     }
@@ -99,6 +133,7 @@ impl ToSql<Text, Sqlite> for ContractSymbol {
     fn to_sql(&self, out: &mut Output<Sqlite>) -> serialize::Result {
         let text = match *self {
             ContractSymbol::BtcUsd => "BtcUsd",
+            ContractSymbol::EthUsd => "EthUsd",
         };
         out.set_value(text);
         Ok(IsNull::No)
@@ -111,6 +146,7 @@ impl FromSql<Text, Sqlite> for ContractSymbol {
 
         return match string.as_str() {
             "BtcUsd" => Ok(ContractSymbol::BtcUsd),
+            "EthUsd" => Ok(ContractSymbol::EthUsd),
             _ => Err("Unrecognized enum variant".into()),
         };
     }
@@ -150,6 +186,7 @@ impl ToSql<Text, Sqlite> for FailureReason {
             FailureReason::FailedToSetToFilling => "FailedToSetToFilling",
             FailureReason::OrderNotAcceptable => "OrderNotAcceptable",
             FailureReason::TimedOut => "TimedOut",
+            FailureReason::Cancelled => "Cancelled",
             FailureReason::SubchannelOfferOutdated => "SubchannelOfferOutdated",
             FailureReason::SubchannelOfferDateUndetermined => "SubchannelOfferDateUndetermined",
         };
@@ -171,6 +208,7 @@ impl FromSql<Text, Sqlite> for FailureReason {
             "FailedToSetToFilling" => Ok(FailureReason::FailedToSetToFilling),
             "OrderNotAcceptable" => Ok(FailureReason::OrderNotAcceptable),
             "TimedOut" => Ok(FailureReason::TimedOut),
+            "Cancelled" => Ok(FailureReason::Cancelled),
             "SubchannelOfferOutdated" => Ok(FailureReason::SubchannelOfferOutdated),
             "SubchannelOfferDateUndetermined" => Ok(FailureReason::SubchannelOfferDateUndetermined),
             _ => Err("Unrecognized enum variant".into()),
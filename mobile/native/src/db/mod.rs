@@ -170,6 +170,26 @@ pub fn get_order(order_id: Uuid) -> Result<trade::order::Order> {
     Ok(order.try_into()?)
 }
 
+pub fn maybe_get_order(order_id: Uuid) -> Result<Option<trade::order::Order>> {
+    let mut db = connection()?;
+    let order = Order::get(order_id.to_string(), &mut db).optional()?;
+
+    let order = match order {
+        Some(order) => Some(order.try_into()?),
+        None => None,
+    };
+
+    Ok(order)
+}
+
+pub fn update_order_quantity(order_id: Uuid, quantity: f32) -> Result<trade::order::Order> {
+    let mut db = connection()?;
+    let order = Order::update_quantity(order_id.to_string(), quantity, &mut db)
+        .context("Failed to update order quantity")?;
+
+    Ok(order.try_into()?)
+}
+
 pub fn get_orders_for_ui() -> Result<Vec<trade::order::Order>> {
     let mut db = connection()?;
     let orders = Order::get_without_rejected_and_initial(&mut db)?;
@@ -65,6 +65,7 @@ pub(crate) struct Order {
     pub order_expiry_timestamp: i64,
     pub reason: OrderReason,
     pub stable: bool,
+    pub time_in_force: TimeInForce,
 }
 
 impl Order {
@@ -142,6 +143,20 @@ impl Order {
         })
     }
 
+    /// Updates the quantity of the given order, e.g. if a limit order has been partially filled.
+    pub fn update_quantity(
+        order_id: String,
+        quantity: f32,
+        conn: &mut SqliteConnection,
+    ) -> QueryResult<Order> {
+        diesel::update(orders::table)
+            .filter(schema::orders::id.eq(order_id.clone()))
+            .set(schema::orders::quantity.eq(quantity))
+            .execute(conn)?;
+
+        Self::get(order_id, conn)
+    }
+
     pub fn get(order_id: String, conn: &mut SqliteConnection) -> QueryResult<Order> {
         orders::table
             .filter(schema::orders::id.eq(order_id))
@@ -162,13 +177,19 @@ impl Order {
     }
 
     /// Gets any async order in the database. An async order is defined by any order which has been
-    /// generated by the orderbook. e.g. if the position expired.
+    /// generated by the orderbook. e.g. if the position expired, a stop loss or take profit got
+    /// triggered or the position got liquidated.
     pub fn get_async_order(conn: &mut SqliteConnection) -> QueryResult<Option<Order>> {
         orders::table
             .filter(
                 orders::state
                     .eq(OrderState::Filling)
-                    .and(orders::reason.eq(OrderReason::Expired)),
+                    .and(orders::reason.eq_any([
+                        OrderReason::Expired,
+                        OrderReason::StopLoss,
+                        OrderReason::TakeProfit,
+                        OrderReason::Liquidated,
+                    ])),
             )
             .first(conn)
             .optional()
@@ -211,6 +232,7 @@ impl From<crate::trade::order::Order> for Order {
             order_expiry_timestamp: value.order_expiry_timestamp.unix_timestamp(),
             reason: value.reason.into(),
             stable: value.stable,
+            time_in_force: value.time_in_force.into(),
         }
     }
 }
@@ -220,6 +242,9 @@ impl From<crate::trade::order::OrderReason> for OrderReason {
         match value {
             crate::trade::order::OrderReason::Manual => OrderReason::Manual,
             crate::trade::order::OrderReason::Expired => OrderReason::Expired,
+            crate::trade::order::OrderReason::StopLoss => OrderReason::StopLoss,
+            crate::trade::order::OrderReason::TakeProfit => OrderReason::TakeProfit,
+            crate::trade::order::OrderReason::Liquidated => OrderReason::Liquidated,
         }
     }
 }
@@ -229,6 +254,31 @@ impl From<OrderReason> for crate::trade::order::OrderReason {
         match value {
             OrderReason::Manual => crate::trade::order::OrderReason::Manual,
             OrderReason::Expired => crate::trade::order::OrderReason::Expired,
+            OrderReason::StopLoss => crate::trade::order::OrderReason::StopLoss,
+            OrderReason::TakeProfit => crate::trade::order::OrderReason::TakeProfit,
+            OrderReason::Liquidated => crate::trade::order::OrderReason::Liquidated,
+        }
+    }
+}
+
+impl From<crate::trade::order::TimeInForce> for TimeInForce {
+    fn from(value: crate::trade::order::TimeInForce) -> Self {
+        match value {
+            crate::trade::order::TimeInForce::ImmediateOrCancel => TimeInForce::ImmediateOrCancel,
+            crate::trade::order::TimeInForce::FillOrKill => TimeInForce::FillOrKill,
+            crate::trade::order::TimeInForce::GoodTillCancelled => TimeInForce::GoodTillCancelled,
+            crate::trade::order::TimeInForce::GoodTillDate => TimeInForce::GoodTillDate,
+        }
+    }
+}
+
+impl From<TimeInForce> for crate::trade::order::TimeInForce {
+    fn from(value: TimeInForce) -> Self {
+        match value {
+            TimeInForce::ImmediateOrCancel => crate::trade::order::TimeInForce::ImmediateOrCancel,
+            TimeInForce::FillOrKill => crate::trade::order::TimeInForce::FillOrKill,
+            TimeInForce::GoodTillCancelled => crate::trade::order::TimeInForce::GoodTillCancelled,
+            TimeInForce::GoodTillDate => crate::trade::order::TimeInForce::GoodTillDate,
         }
     }
 }
@@ -253,6 +303,7 @@ impl TryFrom<Order> for crate::trade::order::Order {
             .expect("unix timestamp to fit in itself"),
             reason: value.reason.into(),
             stable: value.stable,
+            time_in_force: value.time_in_force.into(),
         };
 
         Ok(order)
@@ -454,12 +505,14 @@ impl From<PositionState> for crate::trade::position::PositionState {
 #[diesel(sql_type = Text)]
 pub enum ContractSymbol {
     BtcUsd,
+    EthUsd,
 }
 
 impl From<trade::ContractSymbol> for ContractSymbol {
     fn from(value: trade::ContractSymbol) -> Self {
         match value {
             trade::ContractSymbol::BtcUsd => ContractSymbol::BtcUsd,
+            trade::ContractSymbol::EthUsd => ContractSymbol::EthUsd,
         }
     }
 }
@@ -468,6 +521,7 @@ impl From<ContractSymbol> for trade::ContractSymbol {
     fn from(value: ContractSymbol) -> Self {
         match value {
             ContractSymbol::BtcUsd => trade::ContractSymbol::BtcUsd,
+            ContractSymbol::EthUsd => trade::ContractSymbol::EthUsd,
         }
     }
 }
@@ -534,6 +588,18 @@ impl TryFrom<(OrderType, Option<f32>)> for crate::trade::order::OrderType {
 pub enum OrderReason {
     Manual,
     Expired,
+    StopLoss,
+    TakeProfit,
+    Liquidated,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, FromSqlRow, AsExpression)]
+#[diesel(sql_type = Text)]
+pub enum TimeInForce {
+    ImmediateOrCancel,
+    FillOrKill,
+    GoodTillCancelled,
+    GoodTillDate,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, FromSqlRow, AsExpression)]
@@ -638,6 +704,7 @@ pub enum FailureReason {
     ProposeDlcChannel,
     OrderNotAcceptable,
     TimedOut,
+    Cancelled,
     SubchannelOfferOutdated,
     SubchannelOfferDateUndetermined,
 }
@@ -659,6 +726,7 @@ impl From<FailureReason> for crate::trade::order::FailureReason {
                 crate::trade::order::FailureReason::OrderNotAcceptable
             }
             FailureReason::TimedOut => crate::trade::order::FailureReason::TimedOut,
+            FailureReason::Cancelled => crate::trade::order::FailureReason::Cancelled,
             FailureReason::SubchannelOfferOutdated => {
                 crate::trade::order::FailureReason::InvalidDlcOffer(
                     InvalidSubchannelOffer::Outdated,
@@ -690,6 +758,7 @@ impl From<crate::trade::order::FailureReason> for FailureReason {
                 FailureReason::OrderNotAcceptable
             }
             crate::trade::order::FailureReason::TimedOut => FailureReason::TimedOut,
+            crate::trade::order::FailureReason::Cancelled => FailureReason::Cancelled,
             crate::trade::order::FailureReason::InvalidDlcOffer(reason) => match reason {
                 InvalidSubchannelOffer::Outdated => FailureReason::SubchannelOfferOutdated,
                 InvalidSubchannelOffer::UndeterminedMaturityDate => {
@@ -1481,6 +1550,7 @@ pub mod test {
             order_expiry_timestamp: expiry_timestamp.unix_timestamp(),
             reason: OrderReason::Manual,
             stable: false,
+            time_in_force: TimeInForce::FillOrKill,
         };
 
         Order::insert(
@@ -1495,6 +1565,7 @@ pub mod test {
                 creation_timestamp,
                 order_expiry_timestamp: expiry_timestamp,
                 reason: crate::trade::order::OrderReason::Manual,
+                time_in_force: crate::trade::order::TimeInForce::FillOrKill,
                 stable: false,
             }
             .into(),
@@ -1515,6 +1586,7 @@ pub mod test {
                 creation_timestamp,
                 order_expiry_timestamp: expiry_timestamp,
                 reason: crate::trade::order::OrderReason::Manual,
+                time_in_force: crate::trade::order::TimeInForce::FillOrKill,
                 stable: false,
             }
             .into(),
@@ -1584,6 +1656,7 @@ pub mod test {
                 creation_timestamp,
                 order_expiry_timestamp,
                 reason: crate::trade::order::OrderReason::Manual,
+                time_in_force: crate::trade::order::TimeInForce::FillOrKill,
                 stable: false,
             }
             .into(),
@@ -1607,6 +1680,7 @@ pub mod test {
                 creation_timestamp,
                 order_expiry_timestamp,
                 reason: crate::trade::order::OrderReason::Manual,
+                time_in_force: crate::trade::order::TimeInForce::FillOrKill,
                 stable: false,
             }
             .into(),
@@ -0,0 +1,196 @@
+use crate::cipher::AesCipher;
+use crate::config;
+use crate::ln_dlc::node::Node;
+use anyhow::Context;
+use anyhow::Result;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use bitcoin::hashes::hex::ToHex;
+use ln_dlc_node::node::RecoveredChannel;
+use ln_dlc_node::node::StaticChannelBackup;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::task::spawn_blocking;
+
+/// The interval at which we look for the transactions closing the recovered channels.
+const SWEEP_RECOVERED_CHANNELS_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// The file in the data dir of the network holding the channels which are still being recovered.
+const PENDING_RECOVERY_FILE: &str = "pending_channel_recovery";
+
+impl Node {
+    /// Exports the static channel backup of all channels, encrypted with the node key.
+    ///
+    /// The backup only changes when a channel is opened or closed and can be used to recover the
+    /// channel funds with [`Node::recover_from_static_channel_backup`], even if all other backups
+    /// have been lost.
+    pub fn export_static_channel_backup(&self) -> Result<String> {
+        let backup = self.inner.static_channel_backup()?;
+        tracing::info!(
+            channels = backup.channels.len(),
+            "Exporting static channel backup"
+        );
+
+        let cipher = AesCipher::new(self.inner.node_key());
+        let encrypted = cipher.encrypt(backup.serialize())?;
+
+        Ok(BASE64.encode(encrypted))
+    }
+
+    /// Connects to the coordinator to have it close the channels of the static channel backup and
+    /// sweeps our outputs into the on-chain wallet once the channels have been closed.
+    ///
+    /// We don't request the coordinator to close the channels, the recovery relies on the
+    /// coordinator force-closing them on its own once it fails to re-establish them.
+    ///
+    /// The channels are persisted until they have been closed, so that the recovery is resumed
+    /// with [`Node::resume_channel_recovery`] after a restart.
+    pub async fn recover_from_static_channel_backup(&self, backup: String) -> Result<()> {
+        let encrypted = BASE64
+            .decode(backup)
+            .context("Invalid static channel backup encoding")?;
+        let cipher = AesCipher::new(self.inner.node_key());
+        let backup = cipher
+            .decrypt(encrypted)
+            .context("Could not decrypt static channel backup")?;
+        let backup = StaticChannelBackup::deserialize(&backup)?;
+
+        tracing::info!(
+            channels = backup.channels.len(),
+            "Recovering channels from static channel backup"
+        );
+
+        save_pending_recovery(&cipher, &backup)?;
+
+        // The channels might have been closed already, so we keep sweeping even if we cannot
+        // reach the counterparty.
+        let recovered = self
+            .inner
+            .recover_channels(&backup, &[config::get_coordinator_info()])
+            .await;
+
+        self.sweep_recovered_channels(backup)?;
+
+        recovered
+    }
+
+    /// Resumes the recovery of the channels which have not been swept before the last shutdown.
+    pub async fn resume_channel_recovery(&self) -> Result<()> {
+        let cipher = AesCipher::new(self.inner.node_key());
+        let backup = match load_pending_recovery(&cipher)? {
+            Some(backup) => backup,
+            None => return Ok(()),
+        };
+
+        tracing::info!(
+            channels = backup.channels.len(),
+            "Resuming recovery of channels from static channel backup"
+        );
+
+        // The channels might have been closed already, so we keep sweeping even if we cannot
+        // reach the counterparty.
+        let recovered = self
+            .inner
+            .recover_channels(&backup, &[config::get_coordinator_info()])
+            .await;
+
+        self.sweep_recovered_channels(backup)?;
+
+        recovered
+    }
+
+    fn sweep_recovered_channels(&self, mut backup: StaticChannelBackup) -> Result<()> {
+        let runtime = crate::state::get_or_create_tokio_runtime()?;
+        runtime.spawn({
+            let node = self.inner.clone();
+            async move {
+                let cipher = AesCipher::new(node.node_key());
+                while !backup.channels.is_empty() {
+                    let node = node.clone();
+                    let channels = backup.channels.clone();
+                    let pending = spawn_blocking(move || {
+                        channels
+                            .into_iter()
+                            .filter(|channel| match node.sweep_recovered_channel(channel) {
+                                Ok(RecoveredChannel::Open) => true,
+                                Ok(RecoveredChannel::Swept) => false,
+                                Ok(RecoveredChannel::ClosedWithoutOutput { closing_txid }) => {
+                                    tracing::warn!(
+                                        channel_id = %channel.channel_id.0.to_hex(),
+                                        %closing_txid,
+                                        "Recovered channel has been closed without an output to sweep, e.g. cooperatively into our on-chain wallet or with a dust balance"
+                                    );
+                                    false
+                                }
+                                Err(e) => {
+                                    tracing::error!(
+                                        channel_id = %channel.channel_id.0.to_hex(),
+                                        "Failed to sweep recovered channel: {e:#}"
+                                    );
+                                    true
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .await
+                    .expect("To spawn blocking task");
+
+                    if pending != backup.channels {
+                        backup.channels = pending;
+                        if let Err(e) = save_pending_recovery(&cipher, &backup) {
+                            tracing::error!("Failed to persist pending channel recovery: {e:#}");
+                        }
+                    }
+
+                    if !backup.channels.is_empty() {
+                        tracing::debug!(
+                            channels = backup.channels.len(),
+                            "Waiting for recovered channels to be closed"
+                        );
+                        tokio::time::sleep(SWEEP_RECOVERED_CHANNELS_INTERVAL).await;
+                    }
+                }
+
+                tracing::info!("Swept all recovered channels");
+            }
+        });
+
+        Ok(())
+    }
+}
+
+fn pending_recovery_path() -> PathBuf {
+    Path::new(&config::get_data_dir())
+        .join(config::get_network().to_string())
+        .join(PENDING_RECOVERY_FILE)
+}
+
+/// Persists the channels which have not been swept yet, encrypted with the node key.
+///
+/// Once all channels have been swept, the pending recovery is removed.
+fn save_pending_recovery(cipher: &AesCipher, backup: &StaticChannelBackup) -> Result<()> {
+    let path = pending_recovery_path();
+    if backup.channels.is_empty() {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+
+    fs::write(path, cipher.encrypt(backup.serialize())?)?;
+    Ok(())
+}
+
+fn load_pending_recovery(cipher: &AesCipher) -> Result<Option<StaticChannelBackup>> {
+    let path = pending_recovery_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let backup = cipher
+        .decrypt(fs::read(path)?)
+        .context("Could not decrypt pending channel recovery")?;
+    Ok(Some(StaticChannelBackup::deserialize(&backup)?))
+}
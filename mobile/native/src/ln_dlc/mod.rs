@@ -4,8 +4,10 @@ use crate::api::SendPayment;
 use crate::api::Status;
 use crate::api::WalletHistoryItem;
 use crate::api::WalletHistoryItemType;
+use crate::backup::target::BackupTarget;
 use crate::backup::DBBackupSubscriber;
 use crate::commons::reqwest_client;
+use crate::commons::signed_request;
 use crate::config;
 use crate::config::get_rgs_server_url;
 use crate::db;
@@ -22,6 +24,7 @@ use crate::trade::order::Order;
 use crate::trade::order::OrderReason;
 use crate::trade::order::OrderState;
 use crate::trade::order::OrderType;
+use crate::trade::order::TimeInForce;
 use crate::trade::position;
 use anyhow::anyhow;
 use anyhow::bail;
@@ -76,6 +79,8 @@ use ln_dlc_node::util;
 use ln_dlc_node::AppEventHandler;
 use ln_dlc_node::HTLCStatus;
 use ln_dlc_node::CONFIRMATION_TARGET;
+use reqwest::Method;
+use reqwest::Url;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use std::net::IpAddr;
@@ -91,8 +96,8 @@ use time::OffsetDateTime;
 use tokio::runtime::Runtime;
 use tokio::sync::watch;
 use tokio::task::spawn_blocking;
-use trade::ContractSymbol;
 
+mod channel_backup;
 mod lightning_subscriber;
 pub mod node;
 mod recover_rollover;
@@ -392,6 +397,21 @@ pub fn run(seed_dir: String, runtime: &Runtime) -> Result<()> {
 
         runtime.spawn(track_channel_status(node.clone()));
 
+        runtime.spawn({
+            let storage = storage.clone();
+            async move {
+                // A backup can only be verified when restoring it if it has a manifest, which is
+                // created with the first full backup.
+                if storage.client.has_manifest().await {
+                    return;
+                }
+
+                if let Err(e) = storage.full_backup().await {
+                    tracing::error!("Failed to create the first full backup. Error: {e:#}");
+                }
+            }
+        });
+
         if let Err(e) = node.sync_position_with_subchannel_state().await {
             tracing::error!("Failed to sync position with subchannel state. Error: {e:#}");
         }
@@ -402,6 +422,10 @@ pub fn run(seed_dir: String, runtime: &Runtime) -> Result<()> {
             );
         }
 
+        if let Err(e) = node.resume_channel_recovery().await {
+            tracing::error!("Failed to resume channel recovery. Error: {e:#}");
+        }
+
         state::set_node(node);
 
         event::publish(&EventInternal::Init("10101 is ready.".to_string()));
@@ -416,7 +440,15 @@ pub fn init_new_mnemonic(target_seed_file: &Path) -> Result<()> {
     Ok(())
 }
 
-pub async fn restore_from_mnemonic(seed_words: &str, target_seed_file: &Path) -> Result<()> {
+/// Restores the wallet from the mnemonic and the backups of the given targets.
+///
+/// If no targets are given, the previously configured targets are used, which default to the
+/// coordinator.
+pub async fn restore_from_mnemonic(
+    seed_words: &str,
+    target_seed_file: &Path,
+    backup_targets: Option<Vec<BackupTarget>>,
+) -> Result<()> {
     let seed = Bip39Seed::restore_from_mnemonic(seed_words, target_seed_file)?;
     state::set_seed(seed);
 
@@ -426,6 +458,9 @@ pub async fn restore_from_mnemonic(seed_words: &str, target_seed_file: &Path) ->
         get_node_key(),
     );
     tracing::info!("Initialized 10101 storage!");
+    if let Some(backup_targets) = backup_targets {
+        storage.set_backup_targets(backup_targets)?;
+    }
     state::set_storage(storage.clone());
     storage.client.restore(storage.dlc_storage).await
 }
@@ -624,6 +659,18 @@ pub fn close_channel(is_force_close: bool) -> Result<()> {
     Ok(())
 }
 
+/// Exports the encrypted static channel backup of all channels.
+pub fn export_static_channel_backup() -> Result<String> {
+    let node = state::try_get_node().context("failed to get ln dlc node")?;
+    node.export_static_channel_backup()
+}
+
+/// Recovers the funds of the channels of the given encrypted static channel backup.
+pub async fn recover_from_static_channel_backup(backup: String) -> Result<()> {
+    let node = state::try_get_node().context("failed to get ln dlc node")?;
+    node.recover_from_static_channel_backup(backup).await
+}
+
 pub fn collaborative_revert_channel(
     channel_id: ChannelId,
     coordinator_address: Address,
@@ -727,19 +774,16 @@ pub fn collaborative_revert_channel(
     };
 
     let client = reqwest_client();
+    let url = Url::parse(&format!(
+        "http://{}/api/channels/revertconfirm",
+        config::get_http_endpoint()
+    ))?;
+    let request = signed_request(&client, Method::POST, url, &data)?;
     let runtime = state::get_or_create_tokio_runtime()?;
     runtime.spawn({
         let subchannel = subchannel.clone();
         async move {
-            match client
-                .post(format!(
-                    "http://{}/api/channels/revertconfirm",
-                    config::get_http_endpoint(),
-                ))
-                .json(&data)
-                .send()
-                .await
-            {
+            match request.send().await {
                 Ok(response) => match response.text().await {
                     Ok(response) => {
                         tracing::info!(
@@ -812,6 +856,7 @@ fn update_state_after_collab_revert(
                 order_expiry_timestamp: OffsetDateTime::now_utc(),
                 reason: OrderReason::Expired,
                 stable: position.stable,
+                time_in_force: TimeInForce::FillOrKill,
             };
             db::insert_order(order)?;
             event::publish(&EventInternal::OrderUpdateNotification(order));
@@ -824,7 +869,7 @@ fn update_state_after_collab_revert(
     match db::delete_positions() {
         Ok(_) => {
             event::publish(&EventInternal::PositionCloseNotification(
-                ContractSymbol::BtcUsd,
+                position.contract_symbol,
             ));
         }
         Err(error) => {
@@ -1081,9 +1126,11 @@ pub async fn estimate_payment_fee_msat(payment: SendPayment) -> Result<u64> {
 
 pub async fn trade(trade_params: TradeParams) -> Result<(), (FailureReason, Error)> {
     let client = reqwest_client();
-    let response = client
-        .post(format!("http://{}/api/trade", config::get_http_endpoint()))
-        .json(&trade_params)
+    let url = Url::parse(&format!("http://{}/api/trade", config::get_http_endpoint()))
+        .context("Invalid trade url")
+        .map_err(|e| (FailureReason::TradeRequest, e))?;
+    let response = signed_request(&client, Method::POST, url, &trade_params)
+        .map_err(|e| (FailureReason::TradeRequest, e))?
         .send()
         .await
         .context("Failed to register with coordinator")
@@ -1155,12 +1202,12 @@ pub async fn rollover(contract_id: Option<String>) -> Result<()> {
     };
 
     let client = reqwest_client();
-    let response = client
-        .post(format!(
-            "http://{}/api/rollover/{}",
-            config::get_http_endpoint(),
-            dlc_channel_id.to_hex()
-        ))
+    let url = Url::parse(&format!(
+        "http://{}/api/rollover/{}",
+        config::get_http_endpoint(),
+        dlc_channel_id.to_hex()
+    ))?;
+    let response = signed_request(&client, Method::POST, url, &())?
         .send()
         .await
         .with_context(|| format!("Failed to rollover dlc with id {}", dlc_channel_id.to_hex()))?;
@@ -334,7 +334,15 @@ async fn handle_orderbook_mesage(
                 ));
             }
         }
-        msg @ Message::LimitOrderFilledMatches { .. } | msg @ Message::InvalidAuthentication(_) => {
+        msg @ Message::LimitOrderFilledMatches { .. }
+        | msg @ Message::InvalidAuthentication(_)
+        | msg @ Message::Trade(_)
+        | msg @ Message::Candle(_)
+        | msg @ Message::OrderbookSnapshot { .. }
+        | msg @ Message::OrderbookDelta { .. }
+        | msg @ Message::TopOfBook { .. }
+        | msg @ Message::Depth(_)
+        | msg @ Message::Subscribed { .. } => {
             tracing::debug!(?msg, "Skipping message from orderbook");
         }
     };
@@ -33,6 +33,7 @@ diesel::table! {
         order_expiry_timestamp -> BigInt,
         reason -> Text,
         stable -> Bool,
+        time_in_force -> Text,
     }
 }
 
@@ -1,3 +1,6 @@
+use crate::backup::target::load_backup_targets;
+use crate::backup::target::save_backup_targets;
+use crate::backup::target::BackupTarget;
 use crate::backup::RemoteBackupClient;
 use crate::backup::DB_BACKUP_KEY;
 use crate::backup::DB_BACKUP_NAME;
@@ -51,9 +54,16 @@ impl TenTenOneNodeStorage {
 
         let ln_storage = Arc::new(FilesystemStore::new(data_dir.clone()));
 
+        let cipher = AesCipher::new(secret_key);
+        let backup_targets = load_backup_targets(&data_dir, &cipher).unwrap_or_else(|e| {
+            tracing::error!("Failed to load backup targets, falling back to coordinator. {e:#}");
+            vec![BackupTarget::Coordinator]
+        });
+
+        let client = RemoteBackupClient::new(cipher, backup_targets, &data_dir);
+
         let data_dir = data_dir.to_string_lossy().to_string();
         let dlc_storage = Arc::new(SledStorageProvider::new(&data_dir));
-        let client = RemoteBackupClient::new(AesCipher::new(secret_key));
 
         TenTenOneNodeStorage {
             ln_storage,
@@ -65,24 +75,27 @@ impl TenTenOneNodeStorage {
         }
     }
 
+    /// Persists the targets the backups are uploaded to and uses them for all future backups.
+    ///
+    /// Note, existing backups are not copied to new targets, consider running a full backup.
+    pub fn set_backup_targets(&self, targets: Vec<BackupTarget>) -> Result<()> {
+        save_backup_targets(Path::new(&self.data_dir), &targets, self.client.cipher())?;
+        self.client.set_targets(targets);
+        Ok(())
+    }
+
     /// Creates a full backup of the lightning and dlc data.
     pub async fn full_backup(&self) -> Result<()> {
         tracing::info!("Running full backup");
-        let mut handles = vec![];
 
         let db_backup = db::back_up()?;
-        let value = fs::read(db_backup)?;
-        let handle = self
-            .client
-            .backup(format!("{DB_BACKUP_KEY}/{DB_BACKUP_NAME}"), value);
-        handles.push(handle);
-
-        for ln_backup in self.export()?.into_iter() {
-            let handle = self.client.backup(
-                [LN_BACKUP_KEY.to_string(), ln_backup.0].join("/"),
-                ln_backup.1,
-            );
-            handles.push(handle);
+        let mut backup = vec![(
+            format!("{DB_BACKUP_KEY}/{DB_BACKUP_NAME}"),
+            fs::read(db_backup)?,
+        )];
+
+        for (key, value) in self.export()?.into_iter() {
+            backup.push(([LN_BACKUP_KEY, &key].join("/"), value));
         }
 
         for dlc_backup in self.dlc_storage.export().into_iter() {
@@ -92,11 +105,10 @@ impl TenTenOneNodeStorage {
                 &hex::encode(dlc_backup.key),
             ]
             .join("/");
-            let handle = self.client.backup(key, dlc_backup.value);
-            handles.push(handle);
+            backup.push((key, dlc_backup.value));
         }
 
-        futures::future::join_all(handles).await;
+        self.client.full_backup(&backup).await?;
 
         tracing::info!("Successfully created a full backup!");
 
@@ -110,7 +122,7 @@ impl TenTenOneNodeStorage {
             CHANNEL_MANAGER_PERSISTENCE_SECONDARY_NAMESPACE,
             CHANNEL_MANAGER_PERSISTENCE_KEY,
         ) {
-            export.push(("ln/manager".to_string(), manager));
+            export.push(("manager".to_string(), manager));
         }
 
         let path = &format!("{}/monitors", self.data_dir);
@@ -119,7 +131,7 @@ impl TenTenOneNodeStorage {
             let monitor = monitor?;
             let value = fs::read(monitor.path())?;
             let key = monitor.file_name().to_string_lossy().to_string();
-            export.push((format!("ln/monitors/{key}"), value));
+            export.push((format!("monitors/{key}"), value));
         }
 
         Ok(export)
@@ -1,10 +1,19 @@
 use crate::trade::order;
+use anyhow::Context;
+use anyhow::Result;
 use flutter_rust_bridge::frb;
+use time::Duration;
 use time::OffsetDateTime;
 use trade::ContractSymbol;
 use trade::Direction;
 use uuid::Uuid;
 
+/// How long a market order is valid for in the orderbook.
+const MARKET_ORDER_EXPIRY: Duration = Duration::minutes(1);
+
+/// How long a limit order rests in the orderbook until it gets matched.
+const LIMIT_ORDER_EXPIRY: Duration = Duration::days(1);
+
 #[frb]
 #[derive(Debug, Clone, Copy)]
 pub enum OrderType {
@@ -28,6 +37,21 @@ pub enum OrderState {
 pub enum OrderReason {
     Manual,
     Expired,
+    StopLoss,
+    TakeProfit,
+    Liquidated,
+}
+
+/// How long an order remains active in the orderbook
+///
+/// Please refer to [`commons::TimeInForce`]
+#[frb]
+#[derive(Debug, Clone, Copy)]
+pub enum TimeInForce {
+    ImmediateOrCancel,
+    FillOrKill,
+    GoodTillCancelled,
+    GoodTillDate { expiry_timestamp: i64 },
 }
 
 #[frb]
@@ -47,6 +71,10 @@ pub struct NewOrder {
     pub order_type: Box<OrderType>,
     #[frb(non_final)]
     pub stable: bool,
+    // Box needed for complex enum, otherwise generated Rust code complains about Default impl
+    // missing
+    #[frb(non_final)]
+    pub time_in_force: Box<TimeInForce>,
 }
 
 #[frb]
@@ -102,6 +130,9 @@ impl From<OrderReason> for order::OrderReason {
         match value {
             OrderReason::Manual => order::OrderReason::Manual,
             OrderReason::Expired => order::OrderReason::Expired,
+            OrderReason::StopLoss => order::OrderReason::StopLoss,
+            OrderReason::TakeProfit => order::OrderReason::TakeProfit,
+            OrderReason::Liquidated => order::OrderReason::Liquidated,
         }
     }
 }
@@ -111,6 +142,9 @@ impl From<order::OrderReason> for OrderReason {
         match value {
             order::OrderReason::Manual => OrderReason::Manual,
             order::OrderReason::Expired => OrderReason::Expired,
+            order::OrderReason::StopLoss => OrderReason::StopLoss,
+            order::OrderReason::TakeProfit => OrderReason::TakeProfit,
+            order::OrderReason::Liquidated => OrderReason::Liquidated,
         }
     }
 }
@@ -142,9 +176,28 @@ impl From<order::OrderState> for OrderState {
     }
 }
 
-impl From<NewOrder> for order::Order {
-    fn from(value: NewOrder) -> Self {
-        order::Order {
+impl TryFrom<NewOrder> for order::Order {
+    type Error = anyhow::Error;
+
+    fn try_from(value: NewOrder) -> Result<Self> {
+        let (time_in_force, order_expiry_timestamp) =
+            match (*value.time_in_force, *value.order_type) {
+                (TimeInForce::GoodTillDate { expiry_timestamp }, _) => (
+                    order::TimeInForce::GoodTillDate,
+                    OffsetDateTime::from_unix_timestamp(expiry_timestamp)
+                        .context("Invalid order expiry timestamp")?,
+                ),
+                (time_in_force, OrderType::Market) => (
+                    time_in_force.into(),
+                    OffsetDateTime::now_utc() + MARKET_ORDER_EXPIRY,
+                ),
+                (time_in_force, OrderType::Limit { .. }) => (
+                    time_in_force.into(),
+                    OffsetDateTime::now_utc() + LIMIT_ORDER_EXPIRY,
+                ),
+            };
+
+        Ok(order::Order {
             id: Uuid::new_v4(),
             leverage: value.leverage,
             quantity: value.quantity,
@@ -153,10 +206,21 @@ impl From<NewOrder> for order::Order {
             order_type: (*value.order_type).into(),
             state: order::OrderState::Initial,
             creation_timestamp: OffsetDateTime::now_utc(),
-            // We do not support setting order expiry from the frontend for now
-            order_expiry_timestamp: OffsetDateTime::now_utc() + time::Duration::minutes(1),
+            order_expiry_timestamp,
             reason: order::OrderReason::Manual,
             stable: value.stable,
+            time_in_force,
+        })
+    }
+}
+
+impl From<TimeInForce> for order::TimeInForce {
+    fn from(value: TimeInForce) -> Self {
+        match value {
+            TimeInForce::ImmediateOrCancel => order::TimeInForce::ImmediateOrCancel,
+            TimeInForce::FillOrKill => order::TimeInForce::FillOrKill,
+            TimeInForce::GoodTillCancelled => order::TimeInForce::GoodTillCancelled,
+            TimeInForce::GoodTillDate { .. } => order::TimeInForce::GoodTillDate,
         }
     }
 }
@@ -7,6 +7,8 @@ use crate::trade::order::orderbook_client::OrderbookClient;
 use crate::trade::order::FailureReason;
 use crate::trade::order::Order;
 use crate::trade::order::OrderState;
+use crate::trade::order::OrderType;
+use crate::trade::order::TimeInForce;
 use crate::trade::position;
 use crate::trade::position::handler::update_position_after_order_submitted;
 use crate::trade::position::PositionState;
@@ -38,7 +40,12 @@ pub async fn submit_order(order: Order) -> Result<Uuid> {
     }
 
     update_order_state_in_db_and_ui(order.id, OrderState::Open)?;
-    update_position_after_order_submitted(&order)?;
+
+    // A limit order might rest in the orderbook for a long time, hence we only update the position
+    // once the order has been matched.
+    if order.order_type == OrderType::Market {
+        update_position_after_order_submitted(&order)?;
+    }
 
     Ok(order.id)
 }
@@ -136,12 +143,31 @@ pub fn check_open_orders() -> Result<()> {
     let now = OffsetDateTime::now_utc();
 
     for order_being_filled in orders_being_filled {
-        if order_being_filled.creation_timestamp + ORDER_OUTDATED_AFTER < now {
-            order_failed(
-                Some(order_being_filled.id),
-                FailureReason::TimedOut,
-                anyhow!("Order was not matched within {ORDER_OUTDATED_AFTER:?}"),
-            )?;
+        match (
+            order_being_filled.order_type,
+            order_being_filled.time_in_force,
+        ) {
+            // Good-till-cancelled limit orders rest in the orderbook until they are matched.
+            (OrderType::Limit { .. }, TimeInForce::GoodTillCancelled) => {}
+            // Good-till-date limit orders rest in the orderbook until they are matched or they
+            // expire.
+            (OrderType::Limit { .. }, TimeInForce::GoodTillDate) => {
+                if order_being_filled.order_expiry_timestamp < now {
+                    order_failed(
+                        Some(order_being_filled.id),
+                        FailureReason::TimedOut,
+                        anyhow!("Limit order was not matched before it expired"),
+                    )?;
+                }
+            }
+            _ if order_being_filled.creation_timestamp + ORDER_OUTDATED_AFTER < now => {
+                order_failed(
+                    Some(order_being_filled.id),
+                    FailureReason::TimedOut,
+                    anyhow!("Order was not matched within {ORDER_OUTDATED_AFTER:?}"),
+                )?;
+            }
+            _ => {}
         }
     }
 
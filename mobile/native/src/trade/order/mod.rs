@@ -31,6 +31,9 @@ pub enum FailureReason {
     /// MVP scope: Can only close the order, not reduce or extend
     OrderNotAcceptable,
     TimedOut,
+    /// The unfilled remainder of the order was cancelled by the orderbook, e.g. after an
+    /// immediate-or-cancel order was only filled partially.
+    Cancelled,
     InvalidDlcOffer(InvalidSubchannelOffer),
 }
 
@@ -109,6 +112,9 @@ pub enum OrderState {
 pub enum OrderReason {
     Manual,
     Expired,
+    StopLoss,
+    TakeProfit,
+    Liquidated,
 }
 
 impl From<OrderReason> for commons::OrderReason {
@@ -116,6 +122,9 @@ impl From<OrderReason> for commons::OrderReason {
         match value {
             OrderReason::Manual => commons::OrderReason::Manual,
             OrderReason::Expired => commons::OrderReason::Expired,
+            OrderReason::StopLoss => commons::OrderReason::StopLoss,
+            OrderReason::TakeProfit => commons::OrderReason::TakeProfit,
+            OrderReason::Liquidated => commons::OrderReason::Liquidated,
         }
     }
 }
@@ -125,6 +134,42 @@ impl From<commons::OrderReason> for OrderReason {
         match value {
             commons::OrderReason::Manual => OrderReason::Manual,
             commons::OrderReason::Expired => OrderReason::Expired,
+            commons::OrderReason::StopLoss => OrderReason::StopLoss,
+            commons::OrderReason::TakeProfit => OrderReason::TakeProfit,
+            commons::OrderReason::Liquidated => OrderReason::Liquidated,
+        }
+    }
+}
+
+/// How long an order remains active in the orderbook
+///
+/// Please refer to [`commons::TimeInForce`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeInForce {
+    ImmediateOrCancel,
+    FillOrKill,
+    GoodTillCancelled,
+    GoodTillDate,
+}
+
+impl From<TimeInForce> for commons::TimeInForce {
+    fn from(value: TimeInForce) -> Self {
+        match value {
+            TimeInForce::ImmediateOrCancel => commons::TimeInForce::ImmediateOrCancel,
+            TimeInForce::FillOrKill => commons::TimeInForce::FillOrKill,
+            TimeInForce::GoodTillCancelled => commons::TimeInForce::GoodTillCancelled,
+            TimeInForce::GoodTillDate => commons::TimeInForce::GoodTillDate,
+        }
+    }
+}
+
+impl From<commons::TimeInForce> for TimeInForce {
+    fn from(value: commons::TimeInForce) -> Self {
+        match value {
+            commons::TimeInForce::ImmediateOrCancel => TimeInForce::ImmediateOrCancel,
+            commons::TimeInForce::FillOrKill => TimeInForce::FillOrKill,
+            commons::TimeInForce::GoodTillCancelled => TimeInForce::GoodTillCancelled,
+            commons::TimeInForce::GoodTillDate => TimeInForce::GoodTillDate,
         }
     }
 }
@@ -142,6 +187,7 @@ pub struct Order {
     pub order_expiry_timestamp: OffsetDateTime,
     pub reason: OrderReason,
     pub stable: bool,
+    pub time_in_force: TimeInForce,
 }
 
 impl Order {
@@ -168,6 +214,7 @@ impl Order {
         let opening_price = self.execution_price()?;
 
         Some(calculate_margin(
+            self.contract_symbol,
             opening_price,
             self.quantity,
             self.leverage,
@@ -179,12 +226,16 @@ impl From<Order> for commons::NewOrder {
     fn from(order: Order) -> Self {
         let quantity = Decimal::try_from(order.quantity).expect("to parse into decimal");
         let trader_id = ln_dlc::get_node_pubkey();
+        let price = match order.order_type {
+            // todo: this is left out intentionally as market orders do not set a price. this field
+            // should either be an option or differently modelled for a market order.
+            OrderType::Market => Decimal::ZERO,
+            OrderType::Limit { price } => Decimal::try_from(price).expect("to parse into decimal"),
+        };
         commons::NewOrder {
             id: order.id,
             contract_symbol: order.contract_symbol,
-            // todo: this is left out intentionally as market orders do not set a price. this field
-            // should either be an option or differently modelled for a market order.
-            price: Decimal::ZERO,
+            price,
             quantity,
             trader_id,
             direction: order.direction,
@@ -192,6 +243,7 @@ impl From<Order> for commons::NewOrder {
             order_type: order.order_type.into(),
             expiry: order.order_expiry_timestamp,
             stable: order.stable,
+            time_in_force: order.time_in_force.into(),
         }
     }
 }
@@ -1,8 +1,10 @@
 use crate::commons::reqwest_client;
+use crate::commons::signed_request;
 use anyhow::bail;
 use anyhow::Result;
 use commons::NewOrder;
 use commons::OrderResponse;
+use reqwest::Method;
 use reqwest::Url;
 
 pub struct OrderbookClient {
@@ -18,7 +20,9 @@ impl OrderbookClient {
         let url = self.url.join("/api/orderbook/orders")?;
         let client = reqwest_client();
 
-        let response = client.post(url).json(&order).send().await?;
+        let response = signed_request(&client, Method::POST, url, &order)?
+            .send()
+            .await?;
 
         if response.status().as_u16() == 200 {
             let response = response.json().await?;
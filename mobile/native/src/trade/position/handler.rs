@@ -1,24 +1,31 @@
+use crate::commons::reqwest_client;
+use crate::commons::signed_request;
+use crate::config;
 use crate::db;
 use crate::event;
 use crate::event::EventInternal;
 use crate::ln_dlc;
 use crate::trade::order;
+use crate::trade::order::FailureReason;
 use crate::trade::order::Order;
 use crate::trade::order::OrderState;
 use crate::trade::order::OrderType;
 use crate::trade::position::compute_relative_contracts;
 use crate::trade::position::Position;
 use crate::trade::position::PositionState;
+use anyhow::anyhow;
 use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
 use commons::FilledWith;
+use commons::PositionTriggers;
 use commons::Prices;
 use commons::TradeParams;
+use reqwest::Method;
+use reqwest::Url;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use time::OffsetDateTime;
-use trade::ContractSymbol;
 
 /// Sets up a trade with the counterparty
 ///
@@ -77,7 +84,7 @@ pub async fn trade(filled: FilledWith) -> Result<()> {
 }
 
 /// Executes an async trade from the orderbook / coordinator. e.g. this will happen if the position
-/// expires.
+/// expires or if one of our limit orders got matched.
 pub async fn async_trade(order: commons::Order, filled_with: FilledWith) -> Result<()> {
     let order_type = match order.order_type {
         commons::OrderType::Market => OrderType::Market,
@@ -90,23 +97,69 @@ pub async fn async_trade(order: commons::Order, filled_with: FilledWith) -> Resu
         .average_execution_price()
         .to_f32()
         .expect("to fit into f32");
-    let order = Order {
-        id: order.id,
-        leverage: order.leverage,
-        quantity: order.quantity.to_f32().expect("to fit into f32"),
-        contract_symbol: order.contract_symbol,
-        direction: order.direction,
-        order_type,
-        state: OrderState::Filling { execution_price },
-        creation_timestamp: order.timestamp,
-        order_expiry_timestamp: order.expiry,
-        reason: order.order_reason.into(),
-        stable: order.stable,
-    };
+    let filled_quantity = filled_with
+        .matches
+        .iter()
+        .map(|m| m.quantity)
+        .sum::<Decimal>()
+        .to_f32()
+        .expect("to fit into f32");
 
-    db::insert_order(order)?;
+    if filled_with.order_id != order.id {
+        // Only a part of our order got filled, the remainder either stays in the orderbook or is
+        // cancelled by it.
+        if db::maybe_get_order(order.id)?.is_some() {
+            let mut remaining_order = db::update_order_quantity(
+                order.id,
+                order.quantity.to_f32().expect("to fit into f32"),
+            )?;
+
+            if !rests_in_orderbook(&order) {
+                remaining_order = db::update_order_state(
+                    order.id,
+                    OrderState::Failed {
+                        reason: FailureReason::Cancelled,
+                    },
+                )?;
+            }
+
+            event::publish(&EventInternal::OrderUpdateNotification(remaining_order));
+        }
+    }
+
+    let order = match db::maybe_get_order(filled_with.order_id)? {
+        Some(known_order) => {
+            order::handler::order_filling(known_order.id, execution_price)
+                .context("Could not update order to filling")?;
+
+            let order = db::get_order(known_order.id)?;
+            update_position_after_order_submitted(&order)?;
 
-    event::publish(&EventInternal::OrderUpdateNotification(order));
+            order
+        }
+        None => {
+            let order = Order {
+                id: filled_with.order_id,
+                leverage: order.leverage,
+                quantity: filled_quantity,
+                contract_symbol: order.contract_symbol,
+                direction: order.direction,
+                order_type,
+                state: OrderState::Filling { execution_price },
+                creation_timestamp: order.timestamp,
+                order_expiry_timestamp: order.expiry,
+                reason: order.order_reason.into(),
+                stable: order.stable,
+                time_in_force: order.time_in_force.into(),
+            };
+
+            db::insert_order(order)?;
+
+            event::publish(&EventInternal::OrderUpdateNotification(order));
+
+            order
+        }
+    };
 
     let trade_params = TradeParams {
         pubkey: ln_dlc::get_node_pubkey(),
@@ -125,11 +178,57 @@ pub async fn async_trade(order: commons::Order, filled_with: FilledWith) -> Resu
     Ok(())
 }
 
+/// Whether the unfilled part of the given `order` rests in the orderbook, like the coordinator
+/// decides when matching it.
+fn rests_in_orderbook(order: &commons::Order) -> bool {
+    order.order_type == commons::OrderType::Limit
+        && matches!(
+            order.time_in_force,
+            commons::TimeInForce::GoodTillCancelled | commons::TimeInForce::GoodTillDate
+        )
+}
+
 /// Rollover dlc to new expiry timestamp
 pub async fn rollover(contract_id: Option<String>) -> Result<()> {
     ln_dlc::rollover(contract_id).await
 }
 
+/// Sets the stop loss and take profit of the open position with the coordinator
+///
+/// The coordinator closes the position on our behalf once one of the triggers is hit.
+pub async fn set_position_triggers(stop_loss: Option<f32>, take_profit: Option<f32>) -> Result<()> {
+    let triggers = PositionTriggers {
+        pubkey: ln_dlc::get_node_pubkey(),
+        stop_loss,
+        take_profit,
+    };
+
+    let client = reqwest_client();
+    let url = Url::parse(&format!(
+        "http://{}/api/position/triggers",
+        config::get_http_endpoint()
+    ))?;
+    let response = signed_request(&client, Method::POST, url, &triggers)?
+        .send()
+        .await
+        .context("Failed to send position triggers to coordinator")?;
+
+    if !response.status().is_success() {
+        let response_text = match response.text().await {
+            Ok(text) => text,
+            Err(err) => {
+                format!("could not decode response {err:#}")
+            }
+        };
+        return Err(anyhow!(
+            "Could not set position triggers with coordinator: {response_text}"
+        ));
+    }
+
+    tracing::info!(?stop_loss, ?take_profit, "Set position triggers");
+    Ok(())
+}
+
 /// Fetch the positions from the database
 pub fn get_positions() -> Result<Vec<Position>> {
     db::get_positions()
@@ -262,6 +361,8 @@ pub fn update_position_after_dlc_closure(filled_order: Option<Order>) -> Result<
         }
     };
 
+    let contract_symbol = position.contract_symbol;
+
     if let Some(filled_order) = filled_order {
         tracing::debug!(
             ?position,
@@ -292,9 +393,7 @@ pub fn update_position_after_dlc_closure(filled_order: Option<Order>) -> Result<
 
     db::delete_positions()?;
 
-    event::publish(&EventInternal::PositionCloseNotification(
-        ContractSymbol::BtcUsd,
-    ));
+    event::publish(&EventInternal::PositionCloseNotification(contract_symbol));
 
     Ok(())
 }
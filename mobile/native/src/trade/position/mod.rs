@@ -15,6 +15,7 @@ use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use rust_decimal::RoundingStrategy;
 use time::OffsetDateTime;
+use trade::ContractKind;
 use trade::ContractSymbol;
 use trade::Direction;
 
@@ -85,8 +86,12 @@ impl Position {
 
         let average_entry_price = order.execution_price().expect("order to be filled");
 
-        let liquidation_price =
-            calculate_liquidation_price(average_entry_price, order.leverage, order.direction);
+        let liquidation_price = calculate_liquidation_price(
+            order.contract_symbol,
+            average_entry_price,
+            order.leverage,
+            order.direction,
+        );
 
         let contracts = decimal_from_f32(order.quantity);
 
@@ -94,7 +99,12 @@ impl Position {
             let leverage = decimal_from_f32(order.leverage);
             let average_entry_price = decimal_from_f32(average_entry_price);
 
-            let expected_collateral_btc = contracts / (leverage * average_entry_price);
+            let expected_collateral_btc = margin_btc(
+                order.contract_symbol,
+                contracts,
+                leverage,
+                average_entry_price,
+            );
             let expected_collateral_btc = expected_collateral_btc
                 .round_dp_with_strategy(8, RoundingStrategy::MidpointAwayFromZero)
                 .to_f64()
@@ -255,8 +265,12 @@ impl Position {
                 // The margin difference corresponds to the entire margin for the position being
                 // closed, as a negative number.
                 let margin_diff = {
-                    let margin_before_btc =
-                        starting_contracts / (starting_leverage * starting_average_execution_price);
+                    let margin_before_btc = margin_btc(
+                        self.contract_symbol,
+                        starting_contracts,
+                        starting_leverage,
+                        starting_average_execution_price,
+                    );
 
                     let margin_before_btc = margin_before_btc
                         .round_dp_with_strategy(8, RoundingStrategy::MidpointAwayFromZero)
@@ -272,6 +286,7 @@ impl Position {
 
                 let pnl = {
                     let pnl = calculate_pnl(
+                        self.contract_symbol,
                         self.average_entry_price,
                         trade::Price {
                             bid: order_execution_price,
@@ -323,8 +338,12 @@ impl Position {
                 // the number of contracts in the position.
 
                 let updated_collateral = {
-                    let updated_collateral_btc = total_contracts_relative
-                        / (starting_leverage * starting_average_execution_price);
+                    let updated_collateral_btc = margin_btc(
+                        self.contract_symbol,
+                        total_contracts_relative,
+                        starting_leverage,
+                        starting_average_execution_price,
+                    );
 
                     let updated_collateral_btc = updated_collateral_btc
                         .abs()
@@ -355,11 +374,19 @@ impl Position {
                 let fee = order_matching_fee_taker(order.quantity, order_execution_price);
 
                 let margin_diff = {
-                    let margin_before_btc = starting_contracts_relative.abs()
-                        / (starting_leverage * starting_average_execution_price);
-
-                    let margin_after_btc = total_contracts_relative.abs()
-                        / (starting_leverage * starting_average_execution_price);
+                    let margin_before_btc = margin_btc(
+                        self.contract_symbol,
+                        starting_contracts_relative.abs(),
+                        starting_leverage,
+                        starting_average_execution_price,
+                    );
+
+                    let margin_after_btc = margin_btc(
+                        self.contract_symbol,
+                        total_contracts_relative.abs(),
+                        starting_leverage,
+                        starting_average_execution_price,
+                    );
 
                     let margin_diff_btc = (margin_after_btc.abs() - margin_before_btc.abs())
                         .abs()
@@ -376,6 +403,7 @@ impl Position {
 
                 let pnl = {
                     let pnl = calculate_pnl(
+                        self.contract_symbol,
                         self.average_entry_price,
                         trade::Price {
                             bid: order_execution_price,
@@ -420,8 +448,12 @@ impl Position {
                 // The margin difference corresponds to the entire margin for the position being
                 // closed, as a negative number.
                 let margin_diff = {
-                    let margin_before_btc =
-                        starting_contracts / (starting_leverage * starting_average_execution_price);
+                    let margin_before_btc = margin_btc(
+                        self.contract_symbol,
+                        starting_contracts,
+                        starting_leverage,
+                        starting_average_execution_price,
+                    );
 
                     let margin_before_btc = margin_before_btc
                         .abs()
@@ -438,6 +470,7 @@ impl Position {
 
                 let pnl = {
                     let pnl = calculate_pnl(
+                        self.contract_symbol,
                         self.average_entry_price,
                         trade::Price {
                             bid: order_execution_price,
@@ -486,19 +519,28 @@ impl Position {
                 compute_relative_contracts(order.quantity, order.direction);
             let total_contracts_relative = starting_contracts_relative + order_contracts_relative;
 
-            let updated_average_execution_price = total_contracts_relative
-                / (starting_contracts_relative / starting_average_execution_price
-                    + order_contracts_relative / order_execution_price);
+            let updated_average_execution_price = average_execution_price(
+                self.contract_symbol,
+                starting_contracts_relative,
+                starting_average_execution_price,
+                order_contracts_relative,
+                order_execution_price,
+            );
 
             let updated_liquidation_price = calculate_liquidation_price(
+                self.contract_symbol,
                 f32_from_decimal(updated_average_execution_price),
                 f32_from_decimal(starting_leverage),
                 self.direction,
             );
 
             let updated_collateral = {
-                let updated_collateral_btc = total_contracts_relative
-                    / (starting_leverage * updated_average_execution_price);
+                let updated_collateral_btc = margin_btc(
+                    self.contract_symbol,
+                    total_contracts_relative,
+                    starting_leverage,
+                    updated_average_execution_price,
+                );
 
                 let updated_collateral_btc = updated_collateral_btc
                     .abs()
@@ -531,11 +573,19 @@ impl Position {
             let fee = order_matching_fee_taker(order.quantity, order_execution_price);
 
             let margin_diff = {
-                let margin_before_btc = starting_contracts_relative.abs()
-                    / (starting_leverage * starting_average_execution_price);
+                let margin_before_btc = margin_btc(
+                    self.contract_symbol,
+                    starting_contracts_relative.abs(),
+                    starting_leverage,
+                    starting_average_execution_price,
+                );
 
-                let margin_after_btc = total_contracts_relative.abs()
-                    / (starting_leverage * updated_average_execution_price);
+                let margin_after_btc = margin_btc(
+                    self.contract_symbol,
+                    total_contracts_relative.abs(),
+                    starting_leverage,
+                    updated_average_execution_price,
+                );
 
                 let margin_diff_btc = (margin_after_btc - margin_before_btc)
                     .round_dp_with_strategy(8, RoundingStrategy::MidpointAwayFromZero)
@@ -576,6 +626,43 @@ impl Position {
 /// The _cost_ of a trade is computed as the change in margin (positive if the margin _increases_),
 /// plus the PNL (positive if the PNL is a loss), plus the fee (always positive because fees are
 /// always a cost).
+/// The margin in BTC required for `contracts` at the given `leverage` and `price`.
+fn margin_btc(
+    contract_symbol: ContractSymbol,
+    contracts: Decimal,
+    leverage: Decimal,
+    price: Decimal,
+) -> Decimal {
+    match contract_symbol.metadata().kind {
+        ContractKind::Inverse => contracts / (leverage * price),
+        ContractKind::Quanto { multiplier } => contracts * price * multiplier / leverage,
+    }
+}
+
+/// The average execution price after adding `order_contracts_relative` at `order_price` to
+/// `starting_contracts_relative` at `starting_price`.
+fn average_execution_price(
+    contract_symbol: ContractSymbol,
+    starting_contracts_relative: Decimal,
+    starting_price: Decimal,
+    order_contracts_relative: Decimal,
+    order_price: Decimal,
+) -> Decimal {
+    let total_contracts_relative = starting_contracts_relative + order_contracts_relative;
+
+    match contract_symbol.metadata().kind {
+        ContractKind::Inverse => {
+            total_contracts_relative
+                / (starting_contracts_relative / starting_price
+                    + order_contracts_relative / order_price)
+        }
+        ContractKind::Quanto { .. } => {
+            (starting_contracts_relative * starting_price + order_contracts_relative * order_price)
+                / total_contracts_relative
+        }
+    }
+}
+
 fn trade_cost(margin_diff: SignedAmount, pnl: SignedAmount, fee: Amount) -> SignedAmount {
     let fee = fee.to_signed().expect("fee to fit into SignedAmount");
 
@@ -611,6 +698,7 @@ fn compute_relative_contracts(contracts: f32, direction: Direction) -> Decimal {
 mod tests {
     use super::*;
     use crate::trade::order::OrderReason;
+    use crate::trade::order::TimeInForce;
     use rust_decimal_macros::dec;
     use uuid::Uuid;
 
@@ -634,6 +722,7 @@ mod tests {
             order_expiry_timestamp: now,
             reason: OrderReason::Manual,
             stable: true,
+            time_in_force: TimeInForce::FillOrKill,
         };
 
         let (position, opening_trade) = Position::new_open(order, dlc_collateral, now);
@@ -694,6 +783,7 @@ mod tests {
             order_expiry_timestamp: now,
             reason: OrderReason::Manual,
             stable: false,
+            time_in_force: TimeInForce::FillOrKill,
         };
 
         // The DLC channel has been closed.
@@ -755,6 +845,7 @@ mod tests {
             order_expiry_timestamp: now,
             reason: OrderReason::Manual,
             stable: false,
+            time_in_force: TimeInForce::FillOrKill,
         };
 
         let dlc_collateral_after_resize = 20_578;
@@ -825,6 +916,7 @@ mod tests {
             order_expiry_timestamp: now,
             reason: OrderReason::Manual,
             stable: false,
+            time_in_force: TimeInForce::FillOrKill,
         };
 
         let dlc_collateral_after_resize = 6_855;
@@ -901,6 +993,7 @@ mod tests {
             order_expiry_timestamp: now,
             reason: OrderReason::Manual,
             stable: false,
+            time_in_force: TimeInForce::FillOrKill,
         };
 
         let dlc_collateral_after_resize = 13_736;
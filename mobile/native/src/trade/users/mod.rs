@@ -1,10 +1,13 @@
 use crate::commons::reqwest_client;
+use crate::commons::signed_request;
 use crate::config;
 use crate::ln_dlc;
 use anyhow::anyhow;
 use anyhow::Context;
 use anyhow::Result;
 use commons::RegisterParams;
+use reqwest::Method;
+use reqwest::Url;
 
 /// Enroll the user in the beta program
 pub async fn register_beta(email: String) -> Result<()> {
@@ -15,12 +18,11 @@ pub async fn register_beta(email: String) -> Result<()> {
     };
 
     let client = reqwest_client();
-    let response = client
-        .post(format!(
-            "http://{}/api/register",
-            config::get_http_endpoint()
-        ))
-        .json(&register)
+    let url = Url::parse(&format!(
+        "http://{}/api/register",
+        config::get_http_endpoint()
+    ))?;
+    let response = signed_request(&client, Method::POST, url, &register)?
         .send()
         .await
         .context("Failed to register beta program with coordinator")?;
@@ -6,4 +6,9 @@ void main() {
     const contractSymbol = ContractSymbol.btcusd;
     expect(contractSymbol.label, "BTC/USD");
   });
+
+  test('ETH contract symbol label correct', () {
+    const contractSymbol = ContractSymbol.ethusd;
+    expect(contractSymbol.label, "ETH/USD");
+  });
 }